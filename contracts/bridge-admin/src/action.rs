@@ -0,0 +1,90 @@
+//! Admin actions shared by both deployments
+
+use clap::Subcommand;
+
+/// 0.00125 NEAR, the NEP-145 registration cost of standard NEP-141 tokens
+pub const DEFAULT_TOKEN_STORAGE_DEPOSIT: u128 = 1_250_000_000_000_000_000_000;
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum Action {
+    /// Initialize the bridge with its validator set
+    Init {
+        /// Validator public key; repeat for each validator
+        #[arg(long = "validator", required = true)]
+        validators: Vec<String>,
+        #[arg(long)]
+        required_signatures: u8,
+        /// Bridge owner on NEAR, the signer by default. The Solana admin is
+        /// always the signer.
+        #[arg(long)]
+        owner: Option<String>,
+    },
+    /// Stop all locks and unlocks
+    Pause,
+    /// Resume locks and unlocks
+    Unpause,
+    AddValidator {
+        validator: String,
+    },
+    RemoveValidator {
+        validator: String,
+    },
+    /// Change the number of validator signatures an unlock requires
+    SetThreshold {
+        required_signatures: u8,
+    },
+    /// Transfer ownership of the bridge. On Solana the config admin is fixed
+    /// at initialization, so this hands the program upgrade authority to
+    /// `new_owner` as governance.
+    TransferOwnership {
+        new_owner: String,
+    },
+    /// Manage the tokens the bridge can hold
+    #[command(subcommand)]
+    Token(TokenAction),
+}
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum TokenAction {
+    /// Prepare the bridge to hold a token: NEP-141 storage registration on
+    /// NEAR, the vault token account on Solana
+    Register {
+        /// NEP-141 contract or SPL mint
+        token: String,
+        /// Storage deposit paid to the NEP-141 contract, in yoctoNEAR
+        #[arg(long, default_value_t = DEFAULT_TOKEN_STORAGE_DEPOSIT)]
+        deposit: u128,
+    },
+    /// Show whether the bridge is set up to hold a token
+    Status {
+        /// NEP-141 contract or SPL mint
+        token: String,
+    },
+}
+
+impl Action {
+    /// One-line summary recorded in the plan
+    pub fn describe(&self) -> String {
+        match self {
+            Action::Init {
+                validators,
+                required_signatures,
+                ..
+            } => format!(
+                "Initialize the bridge with {} validators, {} required",
+                validators.len(),
+                required_signatures
+            ),
+            Action::Pause => "Pause the bridge".to_string(),
+            Action::Unpause => "Unpause the bridge".to_string(),
+            Action::AddValidator { validator } => format!("Add validator {validator}"),
+            Action::RemoveValidator { validator } => format!("Remove validator {validator}"),
+            Action::SetThreshold {
+                required_signatures,
+            } => format!("Require {required_signatures} validator signatures"),
+            Action::TransferOwnership { new_owner } => format!("Transfer ownership to {new_owner}"),
+            Action::Token(TokenAction::Register { token, .. }) => format!("Register token {token}"),
+            Action::Token(TokenAction::Status { token }) => format!("Show token {token}"),
+        }
+    }
+}
@@ -0,0 +1,237 @@
+//! Administration CLI for the Articium NEAR and Solana bridge deployments
+//!
+//! Every change is first described as a JSON plan: the calls or instructions
+//! it makes and who signs them. `--dry-run` stops there, `--offline` signs
+//! the plan without network access, and `submit` broadcasts a signed plan
+//! from another machine.
+
+mod action;
+mod near;
+mod plan;
+mod solana;
+
+use std::process::ExitCode;
+
+use anyhow::{bail, Context, Result};
+use clap::{Args, Parser, Subcommand};
+use serde::Serialize;
+
+use action::{Action, TokenAction};
+use plan::Plan;
+
+#[derive(Parser)]
+#[command(version, about = "Administer the Articium NEAR and Solana bridges")]
+struct Cli {
+    #[command(flatten)]
+    rpc: Rpc,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Args)]
+struct Rpc {
+    #[arg(long, global = true, env = "NEAR_RPC_URL", default_value = near::DEFAULT_RPC_URL)]
+    near_rpc: String,
+    #[arg(long, global = true, env = "SOLANA_RPC_URL", default_value = solana::DEFAULT_RPC_URL)]
+    solana_rpc: String,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Administer the NEAR bridge contract
+    Near {
+        /// Bridge contract account
+        #[arg(long, env = "NEAR_BRIDGE_CONTRACT")]
+        bridge: String,
+        /// Account signing the transaction, the key file account by default
+        #[arg(long)]
+        signer: Option<String>,
+        #[command(flatten)]
+        signing: Signing,
+        #[command(subcommand)]
+        action: Action,
+    },
+    /// Administer the Solana bridge program
+    Solana {
+        /// Bridge admin, the keypair pubkey by default
+        #[arg(long)]
+        admin: Option<String>,
+        #[command(flatten)]
+        signing: Signing,
+        #[command(subcommand)]
+        action: Action,
+    },
+    /// Sign and broadcast a reviewed plan
+    Apply {
+        /// Plan file printed by `--dry-run`
+        plan: String,
+        #[command(flatten)]
+        signing: Signing,
+    },
+    /// Broadcast a plan signed with `--offline`
+    Submit {
+        /// Signed plan file
+        plan: String,
+    },
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct Signing {
+    /// NEAR credentials file or Solana keypair file
+    #[arg(long)]
+    pub keystore: Option<String>,
+    /// Print the plan without signing it
+    #[arg(long, conflicts_with = "offline")]
+    pub dry_run: bool,
+    /// Sign without network access and print the signed plan
+    #[arg(long)]
+    pub offline: bool,
+    /// Access key nonce of the NEAR signer, required with `--offline`
+    #[arg(long)]
+    pub nonce: Option<u64>,
+    /// Recent NEAR block hash or Solana blockhash, required with `--offline`
+    #[arg(long)]
+    pub block_hash: Option<String>,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(err) => {
+            eprintln!("error: {err}");
+            return ExitCode::from(2);
+        }
+    };
+    match runtime.block_on(run(cli)) {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("error: {err:#}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+async fn run(cli: Cli) -> Result<ExitCode> {
+    let rpc = &cli.rpc;
+    match cli.command {
+        Command::Near {
+            bridge,
+            signer,
+            signing,
+            action,
+        } => {
+            if let Action::Token(TokenAction::Status { token }) = &action {
+                print_json(&near::token_status(&rpc.near_rpc, &bridge, token).await?)?;
+                return Ok(ExitCode::SUCCESS);
+            }
+            let signer = match signer {
+                Some(signer) => signer,
+                None => near_key_account(&signing)?,
+            };
+            let plan = Plan::Near(near::plan(&bridge, &signer, &action)?);
+            execute(plan, &signing, rpc).await
+        }
+        Command::Solana {
+            admin,
+            signing,
+            action,
+        } => {
+            if let Action::Token(TokenAction::Status { token }) = &action {
+                print_json(&solana::token_status(&rpc.solana_rpc, token).await?)?;
+                return Ok(ExitCode::SUCCESS);
+            }
+            let admin = match admin {
+                Some(admin) => admin,
+                None => solana_keypair_pubkey(&signing)?,
+            };
+            let plan = Plan::Solana(solana::plan(&admin, &action)?);
+            execute(plan, &signing, rpc).await
+        }
+        Command::Apply { plan, signing } => {
+            let plan = read_plan(&plan)?;
+            if plan.is_signed() {
+                bail!("the plan is already signed, broadcast it with `submit`");
+            }
+            execute(plan, &signing, rpc).await
+        }
+        Command::Submit { plan } => {
+            let plan = read_plan(&plan)?;
+            if !plan.is_signed() {
+                bail!("the plan is not signed, sign it with `apply --offline`");
+            }
+            submit(&plan, rpc).await
+        }
+    }
+}
+
+/// Print, sign or broadcast `plan` as the signing flags ask
+async fn execute(mut plan: Plan, signing: &Signing, rpc: &Rpc) -> Result<ExitCode> {
+    if signing.dry_run {
+        print_json(&plan)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    match &mut plan {
+        Plan::Near(near_plan) => near::sign(near_plan, signing, &rpc.near_rpc).await?,
+        Plan::Solana(solana_plan) => solana::sign(solana_plan, signing, &rpc.solana_rpc).await?,
+    }
+    if signing.offline {
+        print_json(&plan)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    // Keep a record of what was broadcast next to its outcome
+    eprintln!("{}", serde_json::to_string_pretty(&plan)?);
+    submit(&plan, rpc).await
+}
+
+async fn submit(plan: &Plan, rpc: &Rpc) -> Result<ExitCode> {
+    match plan {
+        Plan::Near(plan) => {
+            let (outcome, succeeded) = near::submit(plan, &rpc.near_rpc).await?;
+            print_json(&outcome)?;
+            Ok(if succeeded {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            })
+        }
+        Plan::Solana(plan) => {
+            print_json(&solana::submit(plan, &rpc.solana_rpc).await?)?;
+            Ok(ExitCode::SUCCESS)
+        }
+    }
+}
+
+fn near_key_account(signing: &Signing) -> Result<String> {
+    let keystore = signing
+        .keystore
+        .as_deref()
+        .context("--signer or --keystore is required")?;
+    let signer = near_crypto::InMemorySigner::from_file(keystore.as_ref())
+        .with_context(|| format!("reading NEAR key file {keystore}"))?;
+    Ok(signer.account_id.to_string())
+}
+
+fn solana_keypair_pubkey(signing: &Signing) -> Result<String> {
+    use solana_sdk::signature::Signer;
+
+    let keystore = signing
+        .keystore
+        .as_deref()
+        .context("--admin or --keystore is required")?;
+    let keypair = solana_sdk::signature::read_keypair_file(keystore)
+        .map_err(|err| anyhow::anyhow!("reading Solana keypair {keystore}: {err}"))?;
+    Ok(keypair.pubkey().to_string())
+}
+
+fn read_plan(path: &str) -> Result<Plan> {
+    let plan = std::fs::read_to_string(path).with_context(|| format!("reading {path}"))?;
+    serde_json::from_str(&plan).with_context(|| format!("parsing {path}"))
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
@@ -0,0 +1,224 @@
+//! NEAR bridge contract administration
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use near_bridge_client::args::{
+    AddValidator, ChangeMethod, New, Pause, RemoveValidator, StorageBalanceOf, StorageDeposit,
+    TransferOwnership, Unpause, UpdateRequiredSignatures, ViewMethod,
+};
+use near_crypto::{InMemorySigner, Signer};
+use near_jsonrpc_client::methods::broadcast_tx_commit::RpcBroadcastTxCommitRequest;
+use near_jsonrpc_client::methods::query::RpcQueryRequest;
+use near_jsonrpc_client::JsonRpcClient;
+use near_jsonrpc_primitives::types::query::QueryResponseKind;
+use near_primitives::hash::CryptoHash;
+use near_primitives::transaction::{
+    Action as NearAction, FunctionCallAction, SignedTransaction, Transaction,
+};
+use near_primitives::types::{BlockReference, Finality, FunctionArgs};
+use near_primitives::views::{FinalExecutionStatus, QueryRequest};
+use near_sdk::json_types::U128;
+use near_sdk::AccountId;
+use serde_json::{json, Value};
+
+use crate::action::{Action, TokenAction};
+use crate::plan::{FunctionCall, NearPlan};
+use crate::Signing;
+
+pub const DEFAULT_RPC_URL: &str = "https://rpc.testnet.near.org";
+
+/// Gas attached to each admin call
+const ADMIN_GAS: u64 = 30_000_000_000_000;
+
+/// Plan `action` on the bridge deployed at `bridge`, signed by `signer_id`
+pub fn plan(bridge: &str, signer_id: &str, action: &Action) -> Result<NearPlan> {
+    let bridge_id = account_id(bridge)?;
+    let mut receiver_id = bridge.to_string();
+    let mut deposit = 0;
+
+    let (method_name, args) = match action {
+        Action::Init {
+            validators,
+            required_signatures,
+            owner,
+        } => call(&New {
+            owner: account_id(owner.as_deref().unwrap_or(signer_id))?,
+            validators: validators.iter().map(|key| public_key(key)).collect::<Result<_>>()?,
+            required_signatures: *required_signatures,
+        })?,
+        Action::Pause => call(&Pause {})?,
+        Action::Unpause => call(&Unpause {})?,
+        Action::AddValidator { validator } => call(&AddValidator {
+            validator: public_key(validator)?,
+        })?,
+        Action::RemoveValidator { validator } => call(&RemoveValidator {
+            validator: public_key(validator)?,
+        })?,
+        Action::SetThreshold {
+            required_signatures,
+        } => call(&UpdateRequiredSignatures {
+            required_signatures: *required_signatures,
+        })?,
+        Action::TransferOwnership { new_owner } => call(&TransferOwnership {
+            new_owner: account_id(new_owner)?,
+        })?,
+        Action::Token(TokenAction::Register {
+            token,
+            deposit: storage_deposit,
+        }) => {
+            // Registers the bridge with the token contract so it can receive
+            // and send the token
+            receiver_id = account_id(token)?.to_string();
+            deposit = *storage_deposit;
+            call(&StorageDeposit {
+                account_id: Some(bridge_id),
+                registration_only: Some(true),
+            })?
+        }
+        Action::Token(TokenAction::Status { .. }) => bail!("token status is read-only"),
+    };
+
+    Ok(NearPlan {
+        description: action.describe(),
+        signer_id: account_id(signer_id)?.to_string(),
+        receiver_id,
+        calls: vec![FunctionCall {
+            method_name,
+            args,
+            gas: ADMIN_GAS,
+            deposit: U128(deposit),
+        }],
+        signed_transaction: None,
+    })
+}
+
+/// Sign `plan` with the key in `signing.keystore`. The access key nonce and
+/// a recent block hash come from the flags, or from RPC when online.
+pub async fn sign(plan: &mut NearPlan, signing: &Signing, rpc_url: &str) -> Result<()> {
+    let keystore = signing.keystore.as_deref().context("--keystore is required to sign")?;
+    let signer = InMemorySigner::from_file(keystore.as_ref())
+        .with_context(|| format!("reading NEAR key file {keystore}"))?;
+    if signer.account_id.as_str() != plan.signer_id {
+        bail!("key file is for {}, the plan is signed by {}", signer.account_id, plan.signer_id);
+    }
+
+    let (nonce, block_hash) = match (signing.nonce, &signing.block_hash) {
+        (Some(nonce), Some(block_hash)) => {
+            let block_hash = block_hash
+                .parse::<CryptoHash>()
+                .map_err(|err| anyhow!("--block-hash: {err}"))?;
+            (nonce, block_hash)
+        }
+        _ if signing.offline => bail!("--nonce and --block-hash are required to sign offline"),
+        _ => {
+            let (access_key_nonce, block_hash) = access_key(rpc_url, &signer).await?;
+            (signing.nonce.unwrap_or(access_key_nonce + 1), block_hash)
+        }
+    };
+
+    let transaction = Transaction {
+        signer_id: signer.account_id.clone(),
+        public_key: signer.public_key(),
+        nonce,
+        receiver_id: plan.receiver_id.parse()?,
+        block_hash,
+        actions: plan
+            .calls
+            .iter()
+            .map(|call| {
+                Ok(NearAction::FunctionCall(Box::new(FunctionCallAction {
+                    method_name: call.method_name.clone(),
+                    args: serde_json::to_vec(&call.args)?,
+                    gas: call.gas,
+                    deposit: call.deposit.0,
+                })))
+            })
+            .collect::<Result<_>>()?,
+    };
+    let signature = signer.sign(transaction.get_hash_and_size().0.as_ref());
+    let signed = SignedTransaction::new(signature, transaction);
+
+    plan.signed_transaction = Some(STANDARD.encode(borsh::to_vec(&signed)?));
+    Ok(())
+}
+
+/// Broadcast the signed transaction of `plan` and wait for its outcome
+pub async fn submit(plan: &NearPlan, rpc_url: &str) -> Result<(Value, bool)> {
+    let encoded = plan.signed_transaction.as_deref().context("the plan is not signed")?;
+    let signed_transaction: SignedTransaction = borsh::from_slice(
+        &STANDARD.decode(encoded).context("signed_transaction is not base64")?,
+    )
+    .context("signed_transaction is not a NEAR transaction")?;
+
+    let outcome = JsonRpcClient::connect(rpc_url)
+        .call(RpcBroadcastTxCommitRequest { signed_transaction })
+        .await?;
+    let succeeded = matches!(outcome.status, FinalExecutionStatus::SuccessValue(_));
+    Ok((
+        json!({
+            "transaction": outcome.transaction.hash.to_string(),
+            "status": outcome.status,
+        }),
+        succeeded,
+    ))
+}
+
+/// Storage registration of `bridge` with the NEP-141 contract `token`
+pub async fn token_status(rpc_url: &str, bridge: &str, token: &str) -> Result<Value> {
+    let args = StorageBalanceOf {
+        account_id: account_id(bridge)?,
+    };
+    let response = JsonRpcClient::connect(rpc_url)
+        .call(RpcQueryRequest {
+            block_reference: BlockReference::Finality(Finality::Final),
+            request: QueryRequest::CallFunction {
+                account_id: token.parse()?,
+                method_name: StorageBalanceOf::NAME.to_string(),
+                args: FunctionArgs::from(args.args()),
+            },
+        })
+        .await?;
+    let QueryResponseKind::CallResult(result) = response.kind else {
+        bail!("unexpected response to {}", StorageBalanceOf::NAME);
+    };
+    let balance: <StorageBalanceOf as ViewMethod>::Output =
+        serde_json::from_slice(&result.result)?;
+
+    Ok(json!({
+        "token": token,
+        "bridge": bridge,
+        "registered": balance.is_some(),
+        "storage_balance": balance,
+    }))
+}
+
+async fn access_key(rpc_url: &str, signer: &InMemorySigner) -> Result<(u64, CryptoHash)> {
+    let response = JsonRpcClient::connect(rpc_url)
+        .call(RpcQueryRequest {
+            block_reference: BlockReference::Finality(Finality::Final),
+            request: QueryRequest::ViewAccessKey {
+                account_id: signer.account_id.clone(),
+                public_key: signer.public_key(),
+            },
+        })
+        .await
+        .with_context(|| format!("fetching the access key of {}", signer.account_id))?;
+    let QueryResponseKind::AccessKey(access_key) = response.kind else {
+        bail!("unexpected response to the access key query");
+    };
+    Ok((access_key.nonce, response.block_hash))
+}
+
+/// Method name and JSON arguments of a change method
+fn call<M: ChangeMethod>(method: &M) -> Result<(String, Value)> {
+    Ok((M::NAME.to_string(), serde_json::from_slice(&method.args())?))
+}
+
+fn account_id(id: &str) -> Result<AccountId> {
+    id.parse().with_context(|| format!("invalid NEAR account ID {id}"))
+}
+
+fn public_key(key: &str) -> Result<near_sdk::PublicKey> {
+    key.parse().map_err(|err| anyhow!("invalid public key {key}: {err:?}"))
+}
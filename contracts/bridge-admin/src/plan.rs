@@ -0,0 +1,123 @@
+//! Reviewable description of an admin transaction
+//!
+//! A plan is produced first, optionally signed, and broadcast last, so each
+//! step can run on a different machine: the plan is reviewed where it is
+//! built, signed on an offline machine holding the key, and submitted from
+//! anywhere with RPC access.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anyhow::{Context, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use near_sdk::json_types::U128;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "chain", rename_all = "snake_case")]
+pub enum Plan {
+    Near(NearPlan),
+    Solana(SolanaPlan),
+}
+
+impl Plan {
+    pub fn is_signed(&self) -> bool {
+        match self {
+            Plan::Near(plan) => plan.signed_transaction.is_some(),
+            Plan::Solana(plan) => plan.signed_transaction.is_some(),
+        }
+    }
+}
+
+/// Function calls from `signer_id` to `receiver_id` in one transaction
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NearPlan {
+    pub description: String,
+    pub signer_id: String,
+    pub receiver_id: String,
+    pub calls: Vec<FunctionCall>,
+    /// Base64 Borsh `SignedTransaction`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signed_transaction: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FunctionCall {
+    pub method_name: String,
+    pub args: Value,
+    pub gas: u64,
+    /// Attached deposit in yoctoNEAR
+    pub deposit: U128,
+}
+
+/// Instructions paid for and signed by `fee_payer`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SolanaPlan {
+    pub description: String,
+    pub fee_payer: String,
+    pub instructions: Vec<PlannedInstruction>,
+    /// Base64 bincode `Transaction`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signed_transaction: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PlannedInstruction {
+    pub program_id: String,
+    pub accounts: Vec<PlannedAccount>,
+    /// Base64 instruction data
+    pub data: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PlannedAccount {
+    pub pubkey: String,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl From<&Instruction> for PlannedInstruction {
+    fn from(instruction: &Instruction) -> Self {
+        PlannedInstruction {
+            program_id: instruction.program_id.to_string(),
+            accounts: instruction
+                .accounts
+                .iter()
+                .map(|account| PlannedAccount {
+                    pubkey: account.pubkey.to_string(),
+                    is_signer: account.is_signer,
+                    is_writable: account.is_writable,
+                })
+                .collect(),
+            data: STANDARD.encode(&instruction.data),
+        }
+    }
+}
+
+impl TryFrom<&PlannedInstruction> for Instruction {
+    type Error = anyhow::Error;
+
+    fn try_from(planned: &PlannedInstruction) -> Result<Self> {
+        let accounts = planned
+            .accounts
+            .iter()
+            .map(|account| {
+                Ok(AccountMeta {
+                    pubkey: parse_pubkey(&account.pubkey)?,
+                    is_signer: account.is_signer,
+                    is_writable: account.is_writable,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Instruction {
+            program_id: parse_pubkey(&planned.program_id)?,
+            accounts,
+            data: STANDARD.decode(&planned.data).context("instruction data is not base64")?,
+        })
+    }
+}
+
+pub fn parse_pubkey(key: &str) -> Result<Pubkey> {
+    key.parse().with_context(|| format!("invalid Solana address {key}"))
+}
@@ -0,0 +1,157 @@
+//! Solana bridge program administration
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::hash::Hash;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::AccountDeserialize;
+use anyhow::{anyhow, bail, Context, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde_json::{json, Value};
+use solana_bridge::state::TokenVault;
+use solana_bridge_client::instruction::create_vault_token_account;
+use solana_bridge_client::{pda, AdminInstruction, InitializeBridge};
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::signature::{read_keypair_file, Signer};
+use solana_sdk::transaction::Transaction;
+
+use crate::action::{Action, TokenAction};
+use crate::plan::{parse_pubkey, PlannedInstruction, SolanaPlan};
+use crate::Signing;
+
+pub const DEFAULT_RPC_URL: &str = "https://api.devnet.solana.com";
+
+/// Plan `action` on the bridge program, signed and paid for by `admin`
+pub fn plan(admin: &str, action: &Action) -> Result<SolanaPlan> {
+    let admin_key = parse_pubkey(admin)?;
+
+    let instruction = match action {
+        Action::Init {
+            validators,
+            required_signatures,
+            owner,
+        } => {
+            if owner.as_deref().is_some_and(|owner| owner != admin) {
+                bail!("the Solana bridge admin is the signer of `init`");
+            }
+            InitializeBridge {
+                admin: admin_key,
+                validators: validators.iter().map(|key| parse_pubkey(key)).collect::<Result<_>>()?,
+                required_signatures: *required_signatures,
+            }
+            .instruction()
+        }
+        Action::Pause => AdminInstruction::Pause.instruction(admin_key),
+        Action::Unpause => AdminInstruction::Unpause.instruction(admin_key),
+        Action::AddValidator { validator } => {
+            AdminInstruction::AddValidator(parse_pubkey(validator)?).instruction(admin_key)
+        }
+        Action::RemoveValidator { validator } => {
+            AdminInstruction::RemoveValidator(parse_pubkey(validator)?).instruction(admin_key)
+        }
+        Action::SetThreshold {
+            required_signatures,
+        } => {
+            AdminInstruction::UpdateRequiredSignatures(*required_signatures).instruction(admin_key)
+        }
+        Action::TransferOwnership { new_owner } => AdminInstruction::HandoverUpgradeAuthority {
+            governance: parse_pubkey(new_owner)?,
+        }
+        .instruction(admin_key),
+        Action::Token(TokenAction::Register { token, .. }) => {
+            create_vault_token_account(admin_key, parse_pubkey(token)?)
+        }
+        Action::Token(TokenAction::Status { .. }) => bail!("token status is read-only"),
+    };
+
+    Ok(SolanaPlan {
+        description: action.describe(),
+        fee_payer: admin.to_string(),
+        instructions: vec![PlannedInstruction::from(&instruction)],
+        signed_transaction: None,
+    })
+}
+
+/// Sign `plan` with the keypair in `signing.keystore` against the blockhash
+/// from the flags, or the latest one when online
+pub async fn sign(plan: &mut SolanaPlan, signing: &Signing, rpc_url: &str) -> Result<()> {
+    let keystore = signing.keystore.as_deref().context("--keystore is required to sign")?;
+    let keypair = read_keypair_file(keystore)
+        .map_err(|err| anyhow!("reading Solana keypair {keystore}: {err}"))?;
+    let fee_payer = parse_pubkey(&plan.fee_payer)?;
+    if keypair.pubkey() != fee_payer {
+        bail!("keypair is for {}, the plan is signed by {fee_payer}", keypair.pubkey());
+    }
+
+    let blockhash = match &signing.block_hash {
+        Some(blockhash) => blockhash
+            .parse::<Hash>()
+            .map_err(|err| anyhow!("--block-hash: {err}"))?,
+        None if signing.offline => bail!("--block-hash is required to sign offline"),
+        None => RpcClient::new(rpc_url.to_string())
+            .get_latest_blockhash()
+            .await
+            .context("fetching the latest blockhash")?,
+    };
+
+    let instructions = plan
+        .instructions
+        .iter()
+        .map(Instruction::try_from)
+        .collect::<Result<Vec<_>>>()?;
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&fee_payer));
+    transaction.try_sign(&[&keypair], blockhash)?;
+
+    plan.signed_transaction = Some(STANDARD.encode(bincode::serialize(&transaction)?));
+    Ok(())
+}
+
+/// Send the signed transaction of `plan` and wait for its confirmation
+pub async fn submit(plan: &SolanaPlan, rpc_url: &str) -> Result<Value> {
+    let encoded = plan.signed_transaction.as_deref().context("the plan is not signed")?;
+    let transaction: Transaction = bincode::deserialize(
+        &STANDARD.decode(encoded).context("signed_transaction is not base64")?,
+    )
+    .context("signed_transaction is not a Solana transaction")?;
+
+    let signature = RpcClient::new(rpc_url.to_string())
+        .send_and_confirm_transaction(&transaction)
+        .await?;
+    Ok(json!({ "signature": signature.to_string() }))
+}
+
+/// Vault record and vault token account balance of `token`
+pub async fn token_status(rpc_url: &str, token: &str) -> Result<Value> {
+    let mint = parse_pubkey(token)?;
+    let client = RpcClient::new(rpc_url.to_string());
+
+    let vault = pda::token_vault(&mint).0;
+    let total_locked = match fetch(&client, &vault).await? {
+        Some(data) => Some(TokenVault::try_deserialize(&mut data.as_slice())?.total_locked),
+        None => None,
+    };
+
+    let vault_token_account = pda::vault_token_account(&mint);
+    let balance = match fetch(&client, &vault_token_account).await? {
+        Some(_) => Some(client.get_token_account_balance(&vault_token_account).await?),
+        None => None,
+    };
+
+    Ok(json!({
+        "token": token,
+        "token_vault": vault.to_string(),
+        "total_locked": total_locked,
+        "vault_token_account": vault_token_account.to_string(),
+        "registered": balance.is_some(),
+        "balance": balance,
+    }))
+}
+
+/// Data of `address`, `None` if the account does not exist
+async fn fetch(client: &RpcClient, address: &Pubkey) -> Result<Option<Vec<u8>>> {
+    let account = client
+        .get_account_with_commitment(address, client.commitment())
+        .await
+        .with_context(|| format!("fetching {address}"))?;
+    Ok(account.value.map(|account| account.data))
+}
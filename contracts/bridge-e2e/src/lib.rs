@@ -0,0 +1,19 @@
+//! Cross-chain simulator for the Articium bridge
+//!
+//! Runs the NEAR contract in a `near-workspaces` sandbox and the Solana
+//! program in `solana-program-test`, both configured with the same
+//! [`MockValidators`]. A [`Relayer`] reads locks from one chain, has the
+//! validators attest them, and submits the signed unlock (or, when delivery
+//! fails, the signed refund) on the other. No live network is involved.
+//!
+//! Build the NEAR contracts first with `contracts/near/build.sh`.
+
+pub mod near;
+pub mod relayer;
+pub mod solana;
+pub mod validators;
+
+pub use near::NearChain;
+pub use relayer::{NearDelivery, Relayer, SolanaDelivery, Transfer};
+pub use solana::{SolanaChain, SolanaUser};
+pub use validators::MockValidators;
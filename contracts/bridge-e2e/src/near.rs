@@ -0,0 +1,186 @@
+//! The NEAR bridge and a reference NEP-141 token in a `near-workspaces` sandbox
+
+use bridge_types::chain;
+use near_bridge::types::LockRecord;
+use near_bridge_client::args::{
+    FtTransferCall, GetLockRecord, GetTotalLocked, GetTotalUnlocked, New, StorageDeposit,
+};
+use near_bridge_client::{ChangeMethod, FtLockMessage, MessageId, ViewMethod};
+use near_sdk::json_types::U128;
+use near_sdk::AccountId;
+use near_workspaces::network::Sandbox;
+use near_workspaces::result::ExecutionFinalResult;
+use near_workspaces::types::NearToken;
+use near_workspaces::{Account, Contract, Worker};
+use serde_json::json;
+use crate::validators::MockValidators;
+
+const BRIDGE_WASM: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../near/res/near_bridge.wasm");
+const TOKEN_WASM: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../near/res/near_test_token.wasm");
+
+/// Tokens minted to the token owner and handed out to users
+pub const TOTAL_SUPPLY: u128 = 1_000_000_000;
+pub const USER_TOKENS: u128 = 1_000_000;
+
+pub struct NearChain {
+    pub worker: Worker<Sandbox>,
+    pub bridge: Contract,
+    pub token: Contract,
+    pub owner: Account,
+}
+
+impl NearChain {
+    /// Deploy the bridge with every key of `validators`, `required_signatures`
+    /// of them required, and a token the bridge is registered with
+    pub async fn deploy(
+        validators: &MockValidators,
+        required_signatures: u8,
+    ) -> anyhow::Result<Self> {
+        let worker = near_workspaces::sandbox().await?;
+        let bridge = worker.dev_deploy(&read_wasm(BRIDGE_WASM)?).await?;
+        let token = worker.dev_deploy(&read_wasm(TOKEN_WASM)?).await?;
+        let owner = worker.dev_create_account().await?;
+
+        let indices: Vec<usize> = (0..validators.len()).collect();
+        let new = New {
+            owner: id(&owner),
+            validators: validators.near_keys(&indices),
+            required_signatures,
+        };
+        bridge.call(New::NAME).args(new.args()).transact().await?.into_result()?;
+
+        token
+            .call("new")
+            .args_json(json!({ "owner_id": owner.id(), "total_supply": TOTAL_SUPPLY.to_string() }))
+            .transact()
+            .await?
+            .into_result()?;
+
+        let chain = Self { worker, bridge, token, owner };
+        chain.register_with_token(chain.bridge.as_account()).await?;
+        Ok(chain)
+    }
+
+    pub fn bridge_id(&self) -> AccountId {
+        id(self.bridge.as_account())
+    }
+
+    pub fn token_id(&self) -> AccountId {
+        id(self.token.as_account())
+    }
+
+    /// New account holding `USER_TOKENS`, registered with the token and with
+    /// a bridge storage balance for its lock records
+    pub async fn user(&self) -> anyhow::Result<Account> {
+        let user = self.worker.dev_create_account().await?;
+        self.register_with_token(&user).await?;
+        self.owner
+            .call(self.token.id(), "ft_transfer")
+            .args_json(json!({ "receiver_id": user.id(), "amount": USER_TOKENS.to_string() }))
+            .deposit(NearToken::from_yoctonear(1))
+            .transact()
+            .await?
+            .into_result()?;
+        self.call(&user, &StorageDeposit::default(), NearToken::from_millinear(100))
+            .await?
+            .into_result()?;
+        Ok(user)
+    }
+
+    /// New account the token does not know, so transfers to it fail
+    pub async fn unregistered_user(&self) -> anyhow::Result<Account> {
+        Ok(self.worker.dev_create_account().await?)
+    }
+
+    pub async fn register_with_token(&self, account: &Account) -> anyhow::Result<()> {
+        account
+            .call(self.token.id(), "storage_deposit")
+            .args_json(json!({ "account_id": account.id(), "registration_only": true }))
+            .deposit(NearToken::from_millinear(10))
+            .transact()
+            .await?
+            .into_result()?;
+        Ok(())
+    }
+
+    /// Lock `amount` of the token with `ft_transfer_call` for delivery to
+    /// `destination_address` on Solana
+    pub async fn lock(
+        &self,
+        sender: &Account,
+        amount: u128,
+        destination_address: &str,
+    ) -> anyhow::Result<ExecutionFinalResult> {
+        let lock = FtLockMessage {
+            destination_chain: chain::SOLANA.to_string(),
+            destination_address: destination_address.to_string(),
+            call: None,
+        };
+        let transfer = FtTransferCall::lock(self.bridge_id(), U128(amount), &lock);
+        Ok(sender
+            .call(self.token.id(), FtTransferCall::NAME)
+            .args(transfer.args())
+            .deposit(NearToken::from_yoctonear(1))
+            .max_gas()
+            .transact()
+            .await?)
+    }
+
+    /// Call a bridge method as `account` with all the gas available
+    pub async fn call<M: ChangeMethod>(
+        &self,
+        account: &Account,
+        method: &M,
+        deposit: NearToken,
+    ) -> anyhow::Result<ExecutionFinalResult> {
+        Ok(account
+            .call(self.bridge.id(), M::NAME)
+            .args(method.args())
+            .deposit(deposit)
+            .max_gas()
+            .transact()
+            .await?)
+    }
+
+    /// Call an owner-only bridge method as the owner
+    pub async fn admin<M: ChangeMethod>(&self, method: &M) -> anyhow::Result<ExecutionFinalResult> {
+        self.call(&self.owner, method, NearToken::from_yoctonear(0)).await
+    }
+
+    pub async fn view<M: ViewMethod>(&self, method: &M) -> anyhow::Result<M::Output> {
+        Ok(self.bridge.view(M::NAME).args(method.args()).await?.json()?)
+    }
+
+    pub async fn lock_record(&self, message_id: MessageId) -> anyhow::Result<Option<LockRecord>> {
+        self.view(&GetLockRecord { message_id }).await
+    }
+
+    /// Amount of the token the bridge has taken in through locks
+    pub async fn total_locked(&self) -> anyhow::Result<u128> {
+        Ok(self.view(&GetTotalLocked { token_contract: self.token_id() }).await?.0)
+    }
+
+    /// Amount of the token the bridge has released through unlocks
+    pub async fn total_unlocked(&self) -> anyhow::Result<u128> {
+        Ok(self.view(&GetTotalUnlocked { token_contract: self.token_id() }).await?.0)
+    }
+
+    pub async fn ft_balance(&self, account: &AccountId) -> anyhow::Result<u128> {
+        let balance: String = self
+            .token
+            .view("ft_balance_of")
+            .args_json(json!({ "account_id": account }))
+            .await?
+            .json()?;
+        Ok(balance.parse()?)
+    }
+}
+
+/// SDK account ID of a sandbox account
+pub fn id(account: &Account) -> AccountId {
+    account.id().as_str().parse().expect("sandbox account IDs are valid")
+}
+
+fn read_wasm(path: &str) -> anyhow::Result<Vec<u8>> {
+    std::fs::read(path).map_err(|err| anyhow::anyhow!("{path}: {err}; run contracts/near/build.sh"))
+}
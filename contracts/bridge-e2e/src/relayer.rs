@@ -0,0 +1,388 @@
+//! Mini relayer: reads locks on one chain and delivers them to the other as
+//! validator-attested unlocks, or returns them to the sender with an attested
+//! refund when delivery is impossible
+
+use std::str::FromStr;
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::token::ID as TOKEN_PROGRAM_ID;
+use anyhow::{anyhow, ensure};
+use bridge_types::chain;
+use near_bridge::types::LockStatus as NearLockStatus;
+use near_bridge_client::args::{
+    AcknowledgeDelivery as NearAcknowledgeDelivery, IsTransferProcessed, Refund as NearRefund,
+    UnlockFt,
+};
+use near_bridge_client::{parse_logs, BridgeEvent, MessageId};
+use near_sdk::json_types::U128;
+use near_sdk::AccountId;
+use near_workspaces::result::ExecutionFinalResult;
+use near_workspaces::types::NearToken;
+use solana_bridge::state::{LockRecord, LockStatus as SolanaLockStatus, MessageRecord};
+use solana_bridge_client::{
+    pda, AcknowledgeDelivery as SolanaAcknowledgeDelivery, Refund as SolanaRefund, UnlockToken,
+    ValidatorSignature,
+};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::{Signature as SolanaSignature, Signer};
+use solana_sdk::transaction::TransactionError;
+use spl_associated_token_account::get_associated_token_address;
+use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use crate::near::NearChain;
+use crate::solana::SolanaChain;
+use crate::validators::MockValidators;
+
+/// A lock as read from its source chain
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Transfer {
+    pub message_id: MessageId,
+    pub source_chain: String,
+    /// Sequence of the lock on the source bridge
+    pub sequence: u64,
+    /// Sender in the source chain's encoding
+    pub sender: String,
+    /// Recipient in the destination chain's encoding
+    pub recipient: String,
+    pub amount: u128,
+    /// Nonce of the lock on the source chain
+    pub nonce: u64,
+}
+
+/// Unlock of a NEAR lock on Solana, with the signatures collected for it
+#[derive(Clone, Debug)]
+pub struct SolanaDelivery {
+    pub unlock: UnlockToken,
+    pub signatures: Vec<ValidatorSignature>,
+}
+
+impl SolanaDelivery {
+    /// Creation of the recipient's token account if needed, the Ed25519
+    /// precompile instruction and `unlock_token`
+    pub fn instructions(&self) -> anyhow::Result<Vec<Instruction>> {
+        let mut instructions = vec![create_associated_token_account_idempotent(
+            &self.unlock.payer,
+            &self.unlock.recipient,
+            &self.unlock.token_mint,
+            &TOKEN_PROGRAM_ID,
+        )];
+        instructions.extend(self.unlock.instructions(&self.signatures)?);
+        Ok(instructions)
+    }
+}
+
+/// Unlock of a Solana lock on NEAR, signatures included
+#[derive(Clone, Debug)]
+pub struct NearDelivery {
+    pub unlock: UnlockFt,
+}
+
+/// Relays transfers between the NEAR token and the Solana mint. The
+/// validators at `signers` attest every transfer the relayer delivers, once
+/// they have checked it against both chains.
+pub struct Relayer {
+    pub validators: MockValidators,
+    pub signers: Vec<usize>,
+}
+
+impl Relayer {
+    pub fn new(validators: MockValidators, signers: Vec<usize>) -> Self {
+        Self { validators, signers }
+    }
+
+    /// Transfers to Solana locked by a NEAR transaction, read from its
+    /// `token_locked` events
+    pub fn near_locks(outcome: &ExecutionFinalResult) -> anyhow::Result<Vec<Transfer>> {
+        let mut transfers = Vec::new();
+        for event in parse_logs(&outcome.logs())? {
+            let BridgeEvent::TokenLocked(locked) = event else {
+                continue;
+            };
+            if locked.destination_chain != chain::SOLANA {
+                continue;
+            }
+            transfers.push(Transfer {
+                message_id: message_id(&locked.message_id)?,
+                source_chain: chain::NEAR.to_string(),
+                sequence: locked.nonce,
+                sender: locked.sender.to_string(),
+                recipient: locked.recipient,
+                amount: locked.amount.0,
+                nonce: locked.nonce,
+            });
+        }
+        Ok(transfers)
+    }
+
+    /// Transfer to NEAR stored in the Solana lock record at `lock_record`.
+    /// Natively run programs do not log their events, so the record is read
+    /// instead.
+    pub async fn solana_lock(
+        solana: &mut SolanaChain,
+        lock_record: Pubkey,
+    ) -> anyhow::Result<Transfer> {
+        let record: LockRecord =
+            solana.fetch(lock_record).await.ok_or_else(|| anyhow!("no lock at {lock_record}"))?;
+        ensure!(record.destination_chain == chain::NEAR, "lock is not bound for NEAR");
+        Ok(Transfer {
+            message_id: record.message_id,
+            source_chain: chain::SOLANA.to_string(),
+            sequence: record.sequence,
+            sender: record.sender.to_string(),
+            recipient: record.destination_address,
+            amount: u128::from(record.amount),
+            nonce: record.nonce,
+        })
+    }
+
+    /// Have the validators attest the unlock of a NEAR lock on Solana. They
+    /// only sign transfers whose lock is still pending on NEAR.
+    pub async fn attest_to_solana(
+        &self,
+        near: &NearChain,
+        solana: &SolanaChain,
+        transfer: &Transfer,
+    ) -> anyhow::Result<SolanaDelivery> {
+        let record = near
+            .lock_record(transfer.message_id)
+            .await?
+            .ok_or_else(|| anyhow!("no NEAR lock {}", hex(&transfer.message_id)))?;
+        ensure!(record.status == NearLockStatus::Pending, "NEAR lock is not pending");
+        ensure!(record.amount == transfer.amount, "amount differs from the NEAR lock");
+
+        let recipient = Pubkey::from_str(&transfer.recipient)
+            .map_err(|_| anyhow!("`{}` is not a Solana address", transfer.recipient))?;
+        let amount = u64::try_from(transfer.amount)
+            .map_err(|_| anyhow!("{} does not fit a Solana amount", transfer.amount))?;
+        let unlock = UnlockToken {
+            payer: solana.payer().pubkey(),
+            recipient,
+            recipient_token_account: get_associated_token_address(&recipient, &solana.mint),
+            token_mint: solana.mint,
+            message_id: transfer.message_id,
+            source_chain: transfer.source_chain.clone(),
+            sequence: transfer.sequence,
+            sender: transfer.sender.clone(),
+            amount,
+        };
+        let signatures = self.validators.sign_solana(&unlock.message_hash(), &self.signers);
+        Ok(SolanaDelivery { unlock, signatures })
+    }
+
+    /// Submit an attested unlock to Solana, returning the signature of the
+    /// delivering transaction
+    pub async fn deliver_to_solana(
+        &self,
+        solana: &mut SolanaChain,
+        delivery: &SolanaDelivery,
+    ) -> anyhow::Result<std::result::Result<SolanaSignature, TransactionError>> {
+        Ok(solana.send(&delivery.instructions()?, &[]).await)
+    }
+
+    /// Attest a NEAR lock, deliver it to Solana and mark it delivered on NEAR
+    pub async fn relay_to_solana(
+        &self,
+        near: &NearChain,
+        solana: &mut SolanaChain,
+        transfer: &Transfer,
+    ) -> anyhow::Result<SolanaSignature> {
+        let delivery = self.attest_to_solana(near, solana, transfer).await?;
+        let signature = self.deliver_to_solana(solana, &delivery).await??;
+        self.acknowledge_on_near(near, solana, transfer, &signature.to_string())
+            .await?
+            .into_result()?;
+        Ok(signature)
+    }
+
+    /// Mark a NEAR lock as delivered by `destination_tx`. The validators only
+    /// attest the delivery if Solana processed the transfer.
+    pub async fn acknowledge_on_near(
+        &self,
+        near: &NearChain,
+        solana: &mut SolanaChain,
+        transfer: &Transfer,
+        destination_tx: &str,
+    ) -> anyhow::Result<ExecutionFinalResult> {
+        let delivered: Option<MessageRecord> =
+            solana.fetch(pda::message_record(&transfer.message_id).0).await;
+        let message = hex(&transfer.message_id);
+        ensure!(delivered.is_some(), "transfer {message} was not delivered to Solana");
+        let record = near
+            .lock_record(transfer.message_id)
+            .await?
+            .ok_or_else(|| anyhow!("no NEAR lock {message}"))?;
+
+        let hash =
+            NearAcknowledgeDelivery::message_hash(&near.bridge_id(), &record, destination_tx);
+        let acknowledge = NearAcknowledgeDelivery {
+            message_id: transfer.message_id,
+            destination_tx: destination_tx.to_string(),
+            signatures: self.validators.sign_near(&hash, &self.signers),
+            aggregate_signature: None,
+        };
+        near.call(&near.owner, &acknowledge, NearToken::from_yoctonear(0)).await
+    }
+
+    /// Have the validators attest the unlock of a Solana lock on NEAR. They
+    /// only sign transfers whose lock is still pending on Solana.
+    pub async fn attest_to_near(
+        &self,
+        near: &NearChain,
+        solana: &mut SolanaChain,
+        transfer: &Transfer,
+    ) -> anyhow::Result<NearDelivery> {
+        let (_, record) = Self::solana_lock_record(solana, transfer).await?;
+        ensure!(record.status == SolanaLockStatus::Pending, "Solana lock is not pending");
+        ensure!(u128::from(record.amount) == transfer.amount, "amount differs from the lock");
+
+        let recipient: AccountId = transfer
+            .recipient
+            .parse()
+            .map_err(|_| anyhow!("`{}` is not a NEAR account", transfer.recipient))?;
+        let unlock = UnlockFt {
+            message_id: transfer.message_id,
+            source_chain: transfer.source_chain.clone(),
+            sequence: transfer.sequence,
+            sender_address: transfer.sender.clone(),
+            recipient,
+            token_contract: near.token_id(),
+            amount: U128(transfer.amount),
+            signatures: Vec::new(),
+            aggregate_signature: None,
+        };
+        let hash = unlock.message_hash(&near.bridge_id());
+        let signatures = self.validators.sign_near(&hash, &self.signers);
+        Ok(NearDelivery { unlock: UnlockFt { signatures, ..unlock } })
+    }
+
+    /// Submit an attested unlock to NEAR from the owner account. The outcome
+    /// is returned as is: an unlock whose token transfer fails still
+    /// completes, with a failed receipt.
+    pub async fn deliver_to_near(
+        &self,
+        near: &NearChain,
+        delivery: &NearDelivery,
+    ) -> anyhow::Result<ExecutionFinalResult> {
+        near.call(&near.owner, &delivery.unlock, NearToken::from_yoctonear(0)).await
+    }
+
+    /// Attest a Solana lock, deliver it to NEAR and, if the tokens reached
+    /// the recipient, mark it delivered on Solana. The outcome of the unlock
+    /// is returned as is.
+    pub async fn relay_to_near(
+        &self,
+        near: &NearChain,
+        solana: &mut SolanaChain,
+        transfer: &Transfer,
+    ) -> anyhow::Result<ExecutionFinalResult> {
+        let delivery = self.attest_to_near(near, solana, transfer).await?;
+        let outcome = self.deliver_to_near(near, &delivery).await?;
+        let processed = near.view(&Self::is_transfer_processed(transfer)).await?;
+        if outcome.is_success() && processed {
+            let destination_tx = outcome.outcome().transaction_hash.to_string();
+            self.acknowledge_on_solana(near, solana, transfer, &destination_tx).await??;
+        }
+        Ok(outcome)
+    }
+
+    /// Mark a Solana lock as delivered by `destination_tx`. The validators
+    /// only attest the delivery if NEAR processed the transfer.
+    pub async fn acknowledge_on_solana(
+        &self,
+        near: &NearChain,
+        solana: &mut SolanaChain,
+        transfer: &Transfer,
+        destination_tx: &str,
+    ) -> anyhow::Result<std::result::Result<(), TransactionError>> {
+        let processed: bool = near.view(&Self::is_transfer_processed(transfer)).await?;
+        let message = hex(&transfer.message_id);
+        ensure!(processed, "transfer {message} was not delivered to NEAR");
+        let (lock_record, record) = Self::solana_lock_record(solana, transfer).await?;
+
+        let acknowledge =
+            SolanaAcknowledgeDelivery::new(lock_record, &record, destination_tx.to_string());
+        let signatures = self.validators.sign_solana(&acknowledge.message_hash(), &self.signers);
+        Ok(solana.send(&acknowledge.instructions(&signatures)?, &[]).await.map(drop))
+    }
+
+    /// Return a NEAR lock that could not be delivered to Solana. The
+    /// validators only attest the refund if Solana never processed it.
+    pub async fn refund_on_near(
+        &self,
+        near: &NearChain,
+        solana: &mut SolanaChain,
+        transfer: &Transfer,
+    ) -> anyhow::Result<ExecutionFinalResult> {
+        let delivered: Option<MessageRecord> =
+            solana.fetch(pda::message_record(&transfer.message_id).0).await;
+        let message = hex(&transfer.message_id);
+        ensure!(delivered.is_none(), "transfer {message} was delivered to Solana");
+        let record = near
+            .lock_record(transfer.message_id)
+            .await?
+            .ok_or_else(|| anyhow!("no NEAR lock {message}"))?;
+
+        let hash = NearRefund::message_hash(&near.bridge_id(), &record);
+        let refund = NearRefund {
+            message_id: transfer.message_id,
+            signatures: self.validators.sign_near(&hash, &self.signers),
+            aggregate_signature: None,
+        };
+        near.call(&near.owner, &refund, NearToken::from_yoctonear(0)).await
+    }
+
+    /// Return a Solana lock that could not be delivered to NEAR. The
+    /// validators only attest the refund if NEAR has no record of it.
+    pub async fn refund_on_solana(
+        &self,
+        near: &NearChain,
+        solana: &mut SolanaChain,
+        transfer: &Transfer,
+    ) -> anyhow::Result<std::result::Result<(), TransactionError>> {
+        let processed: bool = near.view(&Self::is_transfer_processed(transfer)).await?;
+        ensure!(!processed, "transfer {} was delivered to NEAR", hex(&transfer.message_id));
+
+        let (lock_record, record) = Self::solana_lock_record(solana, transfer).await?;
+        let sender_token_account = get_associated_token_address(&record.sender, &record.token_mint);
+
+        let refund = SolanaRefund::new(lock_record, &record, sender_token_account);
+        let signatures = self.validators.sign_solana(&refund.message_hash(), &self.signers);
+        Ok(solana.send(&refund.instructions(&signatures)?, &[]).await.map(drop))
+    }
+
+    /// Address and contents of the Solana lock record of `transfer`
+    async fn solana_lock_record(
+        solana: &mut SolanaChain,
+        transfer: &Transfer,
+    ) -> anyhow::Result<(Pubkey, LockRecord)> {
+        let sender = Pubkey::from_str(&transfer.sender)?;
+        let lock_record = pda::lock_record(&sender, transfer.nonce).0;
+        let record: LockRecord = solana
+            .fetch(lock_record)
+            .await
+            .ok_or_else(|| anyhow!("no Solana lock {}", hex(&transfer.message_id)))?;
+        ensure!(record.message_id == transfer.message_id, "message ID differs from the lock");
+        Ok((lock_record, record))
+    }
+
+    /// View telling whether NEAR released `transfer`
+    fn is_transfer_processed(transfer: &Transfer) -> IsTransferProcessed {
+        IsTransferProcessed {
+            source_chain: transfer.source_chain.clone(),
+            sequence: transfer.sequence,
+        }
+    }
+}
+
+/// Message ID from the hex of an event
+fn message_id(encoded: &str) -> anyhow::Result<MessageId> {
+    ensure!(encoded.len() == 64 && encoded.is_ascii(), "malformed message ID `{encoded}`");
+    let mut message_id = [0u8; 32];
+    for (byte, pair) in message_id.iter_mut().zip(encoded.as_bytes().chunks(2)) {
+        let pair = std::str::from_utf8(pair)?;
+        *byte = u8::from_str_radix(pair, 16)?;
+    }
+    Ok(message_id)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    near_bridge_client::hash::hex(bytes)
+}
@@ -0,0 +1,221 @@
+//! The Solana bridge program and an SPL mint in `solana-program-test`
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::AccountDeserialize;
+use anchor_spl::token::spl_token;
+use bridge_types::chain;
+use solana_bridge::state::{BridgeConfig, TokenVault};
+use solana_bridge_client::instruction::create_vault_token_account;
+use solana_bridge_client::{pda, AdminInstruction, InitializeBridge, LockToken};
+use solana_program_test::{processor, ProgramTest, ProgramTestContext};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::{Transaction, TransactionError};
+use spl_associated_token_account::get_associated_token_address;
+use spl_associated_token_account::instruction::create_associated_token_account;
+use crate::validators::MockValidators;
+
+fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    // The Anchor entrypoint wants accounts that outlive the call
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    solana_bridge::entry(program_id, accounts, data)
+}
+
+/// A wallet and its token account for the bridged mint
+pub struct SolanaUser {
+    pub keypair: Keypair,
+    pub token_account: Pubkey,
+}
+
+impl SolanaUser {
+    pub fn pubkey(&self) -> Pubkey {
+        self.keypair.pubkey()
+    }
+}
+
+pub struct SolanaChain {
+    pub context: ProgramTestContext,
+    /// Mint bridged to the NEAR token
+    pub mint: Pubkey,
+    next_nonce: u64,
+}
+
+impl SolanaChain {
+    /// Start a bank with the bridge initialized with every key of
+    /// `validators`, `required_signatures` of them required. The payer is
+    /// the bridge admin and the mint authority.
+    pub async fn start(validators: &MockValidators, required_signatures: u8) -> Self {
+        let test =
+            ProgramTest::new("solana_bridge", solana_bridge::ID, processor!(process_instruction));
+        let context = test.start_with_context().await;
+        let mut chain = Self { context, mint: Pubkey::default(), next_nonce: 0 };
+        let payer = chain.payer();
+
+        let indices: Vec<usize> = (0..validators.len()).collect();
+        let initialize = InitializeBridge {
+            admin: payer.pubkey(),
+            validators: validators.solana_keys(&indices),
+            required_signatures,
+        };
+        chain.send(&[initialize.instruction()], &[]).await.expect("initialize");
+
+        let mint = Keypair::new();
+        let rent = Rent::default().minimum_balance(spl_token::state::Mint::LEN);
+        chain
+            .send(
+                &[
+                    system_instruction::create_account(
+                        &payer.pubkey(),
+                        &mint.pubkey(),
+                        rent,
+                        spl_token::state::Mint::LEN as u64,
+                        &spl_token::ID,
+                    ),
+                    spl_token::instruction::initialize_mint(
+                        &spl_token::ID,
+                        &mint.pubkey(),
+                        &payer.pubkey(),
+                        None,
+                        6,
+                    )
+                    .expect("valid mint"),
+                    create_vault_token_account(payer.pubkey(), mint.pubkey()),
+                ],
+                &[&mint],
+            )
+            .await
+            .expect("create mint");
+        chain.mint = mint.pubkey();
+        chain
+    }
+
+    pub fn payer(&self) -> Keypair {
+        self.context.payer.insecure_clone()
+    }
+
+    /// New funded wallet holding `tokens` of the mint
+    pub async fn user(&mut self, tokens: u64) -> SolanaUser {
+        let keypair = Keypair::new();
+        let payer = self.payer();
+        let token_account = get_associated_token_address(&keypair.pubkey(), &self.mint);
+        let mut instructions = vec![
+            system_instruction::transfer(&payer.pubkey(), &keypair.pubkey(), 1_000_000_000),
+            create_associated_token_account(
+                &payer.pubkey(),
+                &keypair.pubkey(),
+                &self.mint,
+                &spl_token::ID,
+            ),
+        ];
+        if tokens > 0 {
+            instructions.push(
+                spl_token::instruction::mint_to(
+                    &spl_token::ID,
+                    &self.mint,
+                    &token_account,
+                    &payer.pubkey(),
+                    &[],
+                    tokens,
+                )
+                .expect("valid mint_to"),
+            );
+        }
+        self.send(&instructions, &[]).await.expect("create user");
+        SolanaUser { keypair, token_account }
+    }
+
+    /// Lock `amount` for delivery to `destination_address` on NEAR, returning
+    /// the lock record the relayer reads
+    pub async fn lock(
+        &mut self,
+        sender: &SolanaUser,
+        amount: u64,
+        destination_address: &str,
+    ) -> std::result::Result<Pubkey, TransactionError> {
+        let lock = LockToken {
+            sender: sender.pubkey(),
+            sender_token_account: sender.token_account,
+            token_mint: self.mint,
+            amount,
+            destination_chain: chain::NEAR.to_string(),
+            destination_address: destination_address.to_string(),
+            nonce: self.next_nonce,
+        };
+        self.next_nonce += 1;
+        self.send(&[lock.instruction()], &[&sender.keypair]).await?;
+        Ok(lock.lock_record())
+    }
+
+    /// Admin instruction signed by the admin
+    pub async fn admin(
+        &mut self,
+        instruction: AdminInstruction,
+    ) -> std::result::Result<(), TransactionError> {
+        let admin = self.payer().pubkey();
+        self.send(&[instruction.instruction(admin)], &[]).await?;
+        Ok(())
+    }
+
+    /// Send `instructions` signed by the payer and `signers` in a fresh
+    /// transaction, so identical retries are not deduplicated. Returns the
+    /// transaction signature.
+    pub async fn send(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> std::result::Result<Signature, TransactionError> {
+        let clock: Clock = self.context.banks_client.get_sysvar().await.expect("clock sysvar");
+        self.context.warp_to_slot(clock.slot + 2).expect("warp");
+
+        let payer = self.payer();
+        let mut all_signers = vec![&payer];
+        all_signers.extend_from_slice(signers);
+        let blockhash = self.context.banks_client.get_latest_blockhash().await.expect("blockhash");
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&payer.pubkey()),
+            &all_signers,
+            blockhash,
+        );
+        let signature = transaction.signatures[0];
+        self.context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .map_err(|err| err.unwrap())?;
+        Ok(signature)
+    }
+
+    pub async fn fetch<T: AccountDeserialize>(&mut self, address: Pubkey) -> Option<T> {
+        let account = self.context.banks_client.get_account(address).await.expect("bank")?;
+        Some(T::try_deserialize(&mut &account.data[..]).expect("account of the expected type"))
+    }
+
+    pub async fn balance(&mut self, token_account: Pubkey) -> u64 {
+        let account = self
+            .context
+            .banks_client
+            .get_account(token_account)
+            .await
+            .expect("bank")
+            .expect("token account exists");
+        spl_token::state::Account::unpack(&account.data).expect("token account").amount
+    }
+
+    pub async fn config(&mut self) -> BridgeConfig {
+        self.fetch(pda::bridge_config().0).await.expect("bridge config exists")
+    }
+
+    /// Amount of the mint the vault holds for pending transfers
+    pub async fn vault_locked(&mut self) -> u64 {
+        let vault: Option<TokenVault> = self.fetch(pda::token_vault(&self.mint).0).await;
+        vault.map_or(0, |vault| vault.total_locked)
+    }
+}
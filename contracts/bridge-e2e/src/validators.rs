@@ -0,0 +1,81 @@
+//! Validator keys shared by both chains
+
+use anchor_lang::prelude::Pubkey;
+use ed25519_dalek::{Keypair, PublicKey, SecretKey, Signer};
+use near_bridge_client::hash::{ed25519_public_key, ed25519_signature};
+use near_bridge_client::Signature;
+use solana_bridge_client::ValidatorSignature;
+
+/// Ed25519 validator keys. Each key is a validator on both chains: its raw
+/// public key is both a NEAR `ed25519:` key and a Solana address.
+pub struct MockValidators {
+    keys: Vec<Keypair>,
+}
+
+impl MockValidators {
+    /// `count` validators with deterministic keys
+    pub fn new(count: usize) -> Self {
+        let mut validators = Self { keys: Vec::new() };
+        for _ in 0..count {
+            validators.add();
+        }
+        validators
+    }
+
+    /// Generate another validator key, returning its index. The chains only
+    /// accept its signatures once it is added to their validator sets.
+    pub fn add(&mut self) -> usize {
+        let seed = u8::try_from(self.keys.len() + 1).expect("at most 255 validators");
+        let secret = SecretKey::from_bytes(&[seed; 32]).expect("32 bytes is a valid secret key");
+        self.keys.push(Keypair { public: PublicKey::from(&secret), secret });
+        self.keys.len() - 1
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn near_key(&self, index: usize) -> near_sdk::PublicKey {
+        ed25519_public_key(self.keys[index].public.to_bytes())
+    }
+
+    pub fn solana_key(&self, index: usize) -> Pubkey {
+        Pubkey::new_from_array(self.keys[index].public.to_bytes())
+    }
+
+    pub fn near_keys(&self, indices: &[usize]) -> Vec<near_sdk::PublicKey> {
+        indices.iter().map(|&index| self.near_key(index)).collect()
+    }
+
+    pub fn solana_keys(&self, indices: &[usize]) -> Vec<Pubkey> {
+        indices.iter().map(|&index| self.solana_key(index)).collect()
+    }
+
+    /// Signatures of the validators at `signers` over `hash`, as the NEAR
+    /// contract takes them
+    pub fn sign_near(&self, hash: &[u8; 32], signers: &[usize]) -> Vec<Signature> {
+        signers
+            .iter()
+            .map(|&index| {
+                let key = &self.keys[index];
+                ed25519_signature(key.public.to_bytes(), key.sign(hash).to_bytes())
+            })
+            .collect()
+    }
+
+    /// Signatures of the validators at `signers` over `hash`, for the Ed25519
+    /// precompile instruction of a Solana transaction
+    pub fn sign_solana(&self, hash: &[u8; 32], signers: &[usize]) -> Vec<ValidatorSignature> {
+        signers
+            .iter()
+            .map(|&index| ValidatorSignature {
+                public_key: self.solana_key(index),
+                signature: self.keys[index].sign(hash).to_bytes(),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,93 @@
+//! Both chains with the same three validators, two signatures required
+
+#![allow(dead_code)]
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::InstructionError;
+use bridge_e2e::{MockValidators, NearChain, Relayer, SolanaChain, SolanaUser, Transfer};
+use near_workspaces::result::ExecutionFinalResult;
+use near_workspaces::Account;
+use solana_bridge::error::BridgeError;
+use solana_sdk::transaction::TransactionError;
+
+pub const VALIDATORS: usize = 3;
+pub const REQUIRED_SIGNATURES: u8 = 2;
+
+/// Escrowed on each chain by locks that are never relayed, so transfers in
+/// either direction have tokens to release
+pub const LIQUIDITY: u64 = 100_000;
+
+pub struct Bridges {
+    pub near: NearChain,
+    pub solana: SolanaChain,
+    pub relayer: Relayer,
+}
+
+impl Bridges {
+    /// Both chains with liquidity escrowed, relayed by validators 0 and 1
+    pub async fn start() -> anyhow::Result<Self> {
+        let validators = MockValidators::new(VALIDATORS);
+        let near = NearChain::deploy(&validators, REQUIRED_SIGNATURES).await?;
+        let mut solana = SolanaChain::start(&validators, REQUIRED_SIGNATURES).await;
+
+        let provider = near.user().await?;
+        let destination = Pubkey::new_unique().to_string();
+        near.lock(&provider, u128::from(LIQUIDITY), &destination).await?.into_result()?;
+        let provider = solana.user(LIQUIDITY).await;
+        solana.lock(&provider, LIQUIDITY, "liquidity.near").await?;
+
+        Ok(Self { near, solana, relayer: Relayer::new(validators, vec![0, 1]) })
+    }
+
+    /// Lock `amount` from `sender` on NEAR to `recipient` on Solana
+    pub async fn lock_on_near(
+        &self,
+        sender: &Account,
+        amount: u64,
+        recipient: &SolanaUser,
+    ) -> anyhow::Result<Transfer> {
+        let outcome =
+            self.near.lock(sender, u128::from(amount), &recipient.pubkey().to_string()).await?;
+        anyhow::ensure!(outcome.is_success(), "{outcome:?}");
+        let mut transfers = Relayer::near_locks(&outcome)?;
+        anyhow::ensure!(transfers.len() == 1, "expected a single lock, got {transfers:?}");
+        Ok(transfers.remove(0))
+    }
+
+    /// Lock `amount` from `sender` on Solana to `recipient` on NEAR
+    pub async fn lock_on_solana(
+        &mut self,
+        sender: &SolanaUser,
+        amount: u64,
+        recipient: &Account,
+    ) -> anyhow::Result<Transfer> {
+        let lock_record = self.solana.lock(sender, amount, recipient.id().as_str()).await?;
+        Relayer::solana_lock(&mut self.solana, lock_record).await
+    }
+}
+
+pub fn assert_failure(outcome: ExecutionFinalResult, message: &str) {
+    match outcome.into_result() {
+        Ok(_) => panic!("expected the call to fail with `{message}`"),
+        Err(failure) => {
+            let failure = failure.to_string();
+            assert!(failure.contains(message), "expected `{message}`, got `{failure}`");
+        }
+    }
+}
+
+/// Failure of instruction `index` with a bridge program error
+pub fn bridge_error(index: u8, error: BridgeError) -> TransactionError {
+    TransactionError::InstructionError(index, InstructionError::Custom(error.into()))
+}
+
+/// Failure of instruction `index` to create an account that already exists,
+/// such as the message record of a processed unlock
+pub fn already_in_use(index: u8) -> TransactionError {
+    TransactionError::InstructionError(
+        index,
+        InstructionError::Custom(
+            solana_sdk::system_instruction::SystemError::AccountAlreadyInUse as u32,
+        ),
+    )
+}
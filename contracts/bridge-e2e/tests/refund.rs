@@ -0,0 +1,141 @@
+//! Transfers that cannot be delivered, returned to their sender
+
+mod common;
+
+use bridge_e2e::near::{id, USER_TOKENS};
+use bridge_e2e::{Relayer, Transfer};
+use common::{assert_failure, bridge_error, Bridges, LIQUIDITY};
+use near_bridge::types::LockStatus as NearLockStatus;
+use near_bridge_client::args::Refund;
+use near_workspaces::types::NearToken;
+use solana_bridge::error::BridgeError;
+use solana_bridge::state::{LockRecord, LockStatus as SolanaLockStatus};
+use solana_bridge_client::pda;
+
+#[tokio::test]
+async fn near_lock_to_invalid_solana_address() -> anyhow::Result<()> {
+    let mut bridges = Bridges::start().await?;
+    let alice = bridges.near.user().await?;
+    let outcome = bridges.near.lock(&alice, 1_000, "not-a-solana-address").await?;
+    assert!(outcome.is_success(), "{outcome:?}");
+    let transfer = Relayer::near_locks(&outcome)?.remove(0);
+
+    let err = bridges
+        .relayer
+        .relay_to_solana(&bridges.near, &mut bridges.solana, &transfer)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("not a Solana address"), "{err}");
+
+    let outcome =
+        bridges.relayer.refund_on_near(&bridges.near, &mut bridges.solana, &transfer).await?;
+    assert!(outcome.receipt_failures().is_empty(), "{outcome:?}");
+    assert_eq!(bridges.near.ft_balance(&id(&alice)).await?, USER_TOKENS);
+    let record = bridges.near.lock_record(transfer.message_id).await?.unwrap();
+    assert_eq!(record.status, NearLockStatus::Refunded);
+
+    // A second refund finds the lock settled
+    let outcome =
+        bridges.relayer.refund_on_near(&bridges.near, &mut bridges.solana, &transfer).await?;
+    assert_failure(outcome, "Lock is not pending");
+    assert_eq!(bridges.near.ft_balance(&id(&alice)).await?, USER_TOKENS);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn solana_lock_to_unregistered_near_account() -> anyhow::Result<()> {
+    let mut bridges = Bridges::start().await?;
+    let bob = bridges.solana.user(1_000).await;
+    let alice = bridges.near.unregistered_user().await?;
+    let transfer = bridges.lock_on_solana(&bob, 1_000, &alice).await?;
+    assert_eq!(bridges.solana.balance(bob.token_account).await, 0);
+
+    // The unlock goes through but the token refuses the transfer, so NEAR
+    // reopens it and Solana is not told it was delivered
+    let outcome =
+        bridges.relayer.relay_to_near(&bridges.near, &mut bridges.solana, &transfer).await?;
+    assert!(!outcome.receipt_failures().is_empty(), "{outcome:?}");
+    assert!(!outcome.json::<bool>()?);
+    assert_eq!(bridges.near.total_unlocked().await?, 0);
+
+    bridges.relayer.refund_on_solana(&bridges.near, &mut bridges.solana, &transfer).await??;
+    assert_eq!(bridges.solana.balance(bob.token_account).await, 1_000);
+    assert_eq!(bridges.solana.vault_locked().await, LIQUIDITY);
+    assert_eq!(
+        lock_status(&mut bridges, &bob.pubkey(), &transfer).await,
+        SolanaLockStatus::Refunded
+    );
+
+    // The refunded lock can no longer be attested for delivery
+    let err = bridges
+        .relayer
+        .relay_to_near(&bridges.near, &mut bridges.solana, &transfer)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("not pending"), "{err}");
+
+    // Nor refunded twice
+    let result =
+        bridges.relayer.refund_on_solana(&bridges.near, &mut bridges.solana, &transfer).await?;
+    assert_eq!(result.unwrap_err(), bridge_error(1, BridgeError::LockNotPending));
+    assert_eq!(bridges.solana.balance(bob.token_account).await, 1_000);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn delivered_transfers_are_not_refunded() -> anyhow::Result<()> {
+    let mut bridges = Bridges::start().await?;
+    let alice = bridges.near.user().await?;
+    let bob = bridges.solana.user(500).await;
+
+    let outbound = bridges.lock_on_near(&alice, 1_000, &bob).await?;
+    bridges.relayer.relay_to_solana(&bridges.near, &mut bridges.solana, &outbound).await?;
+    let inbound = bridges.lock_on_solana(&bob, 500, &alice).await?;
+    bridges
+        .relayer
+        .relay_to_near(&bridges.near, &mut bridges.solana, &inbound)
+        .await?
+        .into_result()?;
+
+    // The validators refuse to attest the refunds
+    let err = bridges
+        .relayer
+        .refund_on_near(&bridges.near, &mut bridges.solana, &outbound)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("was delivered to Solana"), "{err}");
+    let err = bridges
+        .relayer
+        .refund_on_solana(&bridges.near, &mut bridges.solana, &inbound)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("was delivered to NEAR"), "{err}");
+
+    // A refund attested anyway finds the lock marked delivered
+    let record = bridges.near.lock_record(outbound.message_id).await?.unwrap();
+    let hash = Refund::message_hash(&bridges.near.bridge_id(), &record);
+    let refund = Refund {
+        message_id: outbound.message_id,
+        signatures: bridges.relayer.validators.sign_near(&hash, &[0, 1]),
+        aggregate_signature: None,
+    };
+    let outcome = bridges.near.call(&alice, &refund, NearToken::from_yoctonear(0)).await?;
+    assert_failure(outcome, "Lock is not pending");
+
+    assert_eq!(bridges.near.ft_balance(&id(&alice)).await?, USER_TOKENS - 1_000 + 500);
+    assert_eq!(bridges.solana.balance(bob.token_account).await, 1_000);
+
+    Ok(())
+}
+
+async fn lock_status(
+    bridges: &mut Bridges,
+    sender: &anchor_lang::prelude::Pubkey,
+    transfer: &Transfer,
+) -> SolanaLockStatus {
+    let record: LockRecord =
+        bridges.solana.fetch(pda::lock_record(sender, transfer.nonce).0).await.unwrap();
+    record.status
+}
@@ -0,0 +1,127 @@
+//! Attested transfers delivered more than once, or on the wrong chain
+
+mod common;
+
+use bridge_e2e::near::{id, USER_TOKENS};
+use common::{already_in_use, assert_failure, Bridges, LIQUIDITY};
+use near_bridge_client::args::UnlockFt;
+use near_bridge_client::hash::ed25519_signature;
+use near_workspaces::types::NearToken;
+
+#[tokio::test]
+async fn solana_unlock_is_delivered_once() -> anyhow::Result<()> {
+    let mut bridges = Bridges::start().await?;
+    let alice = bridges.near.user().await?;
+    let bob = bridges.solana.user(0).await;
+    let transfer = bridges.lock_on_near(&alice, 1_000, &bob).await?;
+
+    let delivery =
+        bridges.relayer.attest_to_solana(&bridges.near, &bridges.solana, &transfer).await?;
+    bridges.relayer.deliver_to_solana(&mut bridges.solana, &delivery).await??;
+
+    // The same attestation in a new transaction
+    let replay = bridges.relayer.deliver_to_solana(&mut bridges.solana, &delivery).await?;
+    assert_eq!(replay.unwrap_err(), already_in_use(2));
+
+    // The same transfer attested by other validators
+    bridges.relayer.signers = vec![1, 2];
+    let reattested =
+        bridges.relayer.attest_to_solana(&bridges.near, &bridges.solana, &transfer).await?;
+    let replay = bridges.relayer.deliver_to_solana(&mut bridges.solana, &reattested).await?;
+    assert_eq!(replay.unwrap_err(), already_in_use(2));
+
+    assert_eq!(bridges.solana.balance(bob.token_account).await, 1_000);
+    assert_eq!(bridges.solana.vault_locked().await, LIQUIDITY - 1_000);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn near_unlock_is_delivered_once() -> anyhow::Result<()> {
+    let mut bridges = Bridges::start().await?;
+    let alice = bridges.near.user().await?;
+    let bob = bridges.solana.user(1_000).await;
+    let transfer = bridges.lock_on_solana(&bob, 1_000, &alice).await?;
+
+    let delivery =
+        bridges.relayer.attest_to_near(&bridges.near, &mut bridges.solana, &transfer).await?;
+    bridges.relayer.deliver_to_near(&bridges.near, &delivery).await?.into_result()?;
+
+    let replay = bridges.relayer.deliver_to_near(&bridges.near, &delivery).await?;
+    assert_failure(replay, "Message already processed");
+
+    assert_eq!(bridges.near.ft_balance(&id(&alice)).await?, USER_TOKENS + 1_000);
+    assert_eq!(bridges.near.total_unlocked().await?, 1_000);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn delivered_lock_is_not_attested_again() -> anyhow::Result<()> {
+    let mut bridges = Bridges::start().await?;
+    let alice = bridges.near.user().await?;
+    let bob = bridges.solana.user(1_000).await;
+
+    let outbound = bridges.lock_on_near(&alice, 500, &bob).await?;
+    bridges.relayer.relay_to_solana(&bridges.near, &mut bridges.solana, &outbound).await?;
+    let err = bridges
+        .relayer
+        .attest_to_solana(&bridges.near, &bridges.solana, &outbound)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("not pending"), "{err}");
+
+    let inbound = bridges.lock_on_solana(&bob, 500, &alice).await?;
+    bridges
+        .relayer
+        .relay_to_near(&bridges.near, &mut bridges.solana, &inbound)
+        .await?
+        .into_result()?;
+    let err = bridges
+        .relayer
+        .attest_to_near(&bridges.near, &mut bridges.solana, &inbound)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("not pending"), "{err}");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn solana_attestation_is_rejected_on_near() -> anyhow::Result<()> {
+    let mut bridges = Bridges::start().await?;
+    let alice = bridges.near.user().await?;
+    let bob = bridges.solana.user(0).await;
+    let transfer = bridges.lock_on_near(&alice, 1_000, &bob).await?;
+    let delivery =
+        bridges.relayer.attest_to_solana(&bridges.near, &bridges.solana, &transfer).await?;
+
+    // The validators' signatures over the Solana unlock, replayed as a NEAR
+    // unlock of the same message back to the sender
+    let unlock = UnlockFt {
+        message_id: transfer.message_id,
+        source_chain: transfer.source_chain.clone(),
+        sequence: transfer.sequence,
+        sender_address: bob.pubkey().to_string(),
+        recipient: id(&alice),
+        token_contract: bridges.near.token_id(),
+        amount: transfer.amount.into(),
+        signatures: delivery
+            .signatures
+            .iter()
+            .map(|signature| {
+                ed25519_signature(signature.public_key.to_bytes(), signature.signature)
+            })
+            .collect(),
+        aggregate_signature: None,
+    };
+    let outcome = bridges.near.call(&alice, &unlock, NearToken::from_yoctonear(0)).await?;
+    assert_failure(outcome, "Insufficient valid signatures");
+    assert_eq!(bridges.near.ft_balance(&id(&alice)).await?, USER_TOKENS - 1_000);
+
+    // The attestation itself still delivers on Solana
+    bridges.relayer.deliver_to_solana(&mut bridges.solana, &delivery).await??;
+    assert_eq!(bridges.solana.balance(bob.token_account).await, 1_000);
+
+    Ok(())
+}
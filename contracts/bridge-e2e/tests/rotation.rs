@@ -0,0 +1,99 @@
+//! Validator set rotated on both chains while transfers are in flight
+
+mod common;
+
+use bridge_e2e::near::{id, USER_TOKENS};
+use common::{assert_failure, bridge_error, Bridges};
+use near_bridge_client::args::{AddValidator, RemoveValidator};
+use solana_bridge::error::BridgeError;
+use solana_bridge_client::AdminInstruction;
+
+/// Replace validator 0 with a new validator on both chains, returning the
+/// new validator's index
+async fn rotate(bridges: &mut Bridges) -> anyhow::Result<usize> {
+    let new = bridges.relayer.validators.add();
+    let validators = &bridges.relayer.validators;
+
+    let add = AddValidator { validator: validators.near_key(new) };
+    bridges.near.admin(&add).await?.into_result()?;
+    let remove = RemoveValidator { validator: validators.near_key(0) };
+    bridges.near.admin(&remove).await?.into_result()?;
+
+    let (add, remove) = (validators.solana_key(new), validators.solana_key(0));
+    bridges.solana.admin(AdminInstruction::AddValidator(add)).await?;
+    bridges.solana.admin(AdminInstruction::RemoveValidator(remove)).await?;
+
+    Ok(new)
+}
+
+#[tokio::test]
+async fn solana_delivery_attested_by_removed_validator() -> anyhow::Result<()> {
+    let mut bridges = Bridges::start().await?;
+    let alice = bridges.near.user().await?;
+    let bob = bridges.solana.user(0).await;
+    let transfer = bridges.lock_on_near(&alice, 1_000, &bob).await?;
+
+    // Attested by validators 0 and 1, then 0 leaves before delivery
+    let stale = bridges.relayer.attest_to_solana(&bridges.near, &bridges.solana, &transfer).await?;
+    let new = rotate(&mut bridges).await?;
+    let result = bridges.relayer.deliver_to_solana(&mut bridges.solana, &stale).await?;
+    assert_eq!(result.unwrap_err(), bridge_error(2, BridgeError::InsufficientSignatures));
+    assert_eq!(bridges.solana.balance(bob.token_account).await, 0);
+
+    // The current set attests it again
+    bridges.relayer.signers = vec![1, new];
+    bridges.relayer.relay_to_solana(&bridges.near, &mut bridges.solana, &transfer).await?;
+    assert_eq!(bridges.solana.balance(bob.token_account).await, 1_000);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn near_delivery_attested_by_removed_validator() -> anyhow::Result<()> {
+    let mut bridges = Bridges::start().await?;
+    let alice = bridges.near.user().await?;
+    let bob = bridges.solana.user(1_000).await;
+    let transfer = bridges.lock_on_solana(&bob, 1_000, &alice).await?;
+
+    let stale =
+        bridges.relayer.attest_to_near(&bridges.near, &mut bridges.solana, &transfer).await?;
+    let new = rotate(&mut bridges).await?;
+    let outcome = bridges.relayer.deliver_to_near(&bridges.near, &stale).await?;
+    assert_failure(outcome, "Insufficient valid signatures");
+    assert_eq!(bridges.near.ft_balance(&id(&alice)).await?, USER_TOKENS);
+
+    bridges.relayer.signers = vec![1, new];
+    let outcome =
+        bridges.relayer.relay_to_near(&bridges.near, &mut bridges.solana, &transfer).await?;
+    assert!(outcome.json::<bool>()?);
+    assert_eq!(bridges.near.ft_balance(&id(&alice)).await?, USER_TOKENS + 1_000);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn removed_validator_does_not_count_towards_quorum() -> anyhow::Result<()> {
+    let mut bridges = Bridges::start().await?;
+    let alice = bridges.near.user().await?;
+    let bob = bridges.solana.user(1_000).await;
+    let new = rotate(&mut bridges).await?;
+
+    // The removed validator cannot make up a quorum with a current one
+    bridges.relayer.signers = vec![0, new];
+    let outbound = bridges.lock_on_near(&alice, 400, &bob).await?;
+    let delivery =
+        bridges.relayer.attest_to_solana(&bridges.near, &bridges.solana, &outbound).await?;
+    let result = bridges.relayer.deliver_to_solana(&mut bridges.solana, &delivery).await?;
+    assert_eq!(result.unwrap_err(), bridge_error(2, BridgeError::InsufficientSignatures));
+
+    // Any two current validators can
+    bridges.relayer.signers = vec![new, 2];
+    bridges.relayer.relay_to_solana(&bridges.near, &mut bridges.solana, &outbound).await?;
+    let inbound = bridges.lock_on_solana(&bob, 1_400, &alice).await?;
+    let outcome =
+        bridges.relayer.relay_to_near(&bridges.near, &mut bridges.solana, &inbound).await?;
+    assert!(outcome.json::<bool>()?);
+    assert_eq!(bridges.near.ft_balance(&id(&alice)).await?, USER_TOKENS - 400 + 1_400);
+
+    Ok(())
+}
@@ -0,0 +1,78 @@
+//! Transfers relayed from NEAR to Solana and back
+
+mod common;
+
+use bridge_e2e::near::{id, USER_TOKENS};
+use common::{Bridges, LIQUIDITY};
+use near_bridge::types::LockStatus as NearLockStatus;
+use solana_bridge::state::{LockRecord, LockStatus as SolanaLockStatus};
+use solana_bridge_client::pda;
+
+#[tokio::test]
+async fn near_to_solana_and_back() -> anyhow::Result<()> {
+    let mut bridges = Bridges::start().await?;
+    let alice = bridges.near.user().await?;
+    let bob = bridges.solana.user(0).await;
+
+    // NEAR -> Solana
+    let outbound = bridges.lock_on_near(&alice, 1_000, &bob).await?;
+    assert_eq!(bridges.near.ft_balance(&id(&alice)).await?, USER_TOKENS - 1_000);
+    let signature =
+        bridges.relayer.relay_to_solana(&bridges.near, &mut bridges.solana, &outbound).await?;
+    assert_eq!(bridges.solana.balance(bob.token_account).await, 1_000);
+    assert_eq!(bridges.solana.vault_locked().await, LIQUIDITY - 1_000);
+    let record = bridges.near.lock_record(outbound.message_id).await?.unwrap();
+    assert_eq!(record.status, NearLockStatus::Delivered { destination_tx: signature.to_string() });
+
+    // Solana -> NEAR
+    let inbound = bridges.lock_on_solana(&bob, 400, &alice).await?;
+    assert_eq!(bridges.solana.balance(bob.token_account).await, 600);
+    let outcome =
+        bridges.relayer.relay_to_near(&bridges.near, &mut bridges.solana, &inbound).await?;
+    assert!(outcome.receipt_failures().is_empty(), "{outcome:?}");
+    assert!(outcome.json::<bool>()?);
+    assert_eq!(bridges.near.ft_balance(&id(&alice)).await?, USER_TOKENS - 600);
+    assert_eq!(bridges.solana.vault_locked().await, LIQUIDITY - 600);
+
+    let record: LockRecord =
+        bridges.solana.fetch(pda::lock_record(&bob.pubkey(), inbound.nonce).0).await.unwrap();
+    assert!(matches!(record.status, SolanaLockStatus::Delivered { .. }), "{:?}", record.status);
+
+    assert_eq!(bridges.near.total_locked().await?, u128::from(LIQUIDITY) + 1_000);
+    assert_eq!(bridges.near.total_unlocked().await?, 400);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn transfers_in_both_directions_interleave() -> anyhow::Result<()> {
+    let mut bridges = Bridges::start().await?;
+    let alice = bridges.near.user().await?;
+    let carol = bridges.near.user().await?;
+    let bob = bridges.solana.user(5_000).await;
+    let dave = bridges.solana.user(0).await;
+
+    // Locks on both chains before anything is relayed
+    let to_dave = bridges.lock_on_near(&alice, 700, &dave).await?;
+    let to_carol = bridges.lock_on_solana(&bob, 2_000, &carol).await?;
+    let to_bob = bridges.lock_on_near(&carol, 300, &bob).await?;
+    assert_ne!(to_dave.message_id, to_bob.message_id);
+
+    // Relayed out of order
+    bridges.relayer.relay_to_solana(&bridges.near, &mut bridges.solana, &to_bob).await?;
+    bridges
+        .relayer
+        .relay_to_near(&bridges.near, &mut bridges.solana, &to_carol)
+        .await?
+        .into_result()?;
+    bridges.relayer.relay_to_solana(&bridges.near, &mut bridges.solana, &to_dave).await?;
+
+    assert_eq!(bridges.solana.balance(bob.token_account).await, 5_000 - 2_000 + 300);
+    assert_eq!(bridges.solana.balance(dave.token_account).await, 700);
+    assert_eq!(bridges.near.ft_balance(&id(&alice)).await?, USER_TOKENS - 700);
+    assert_eq!(bridges.near.ft_balance(&id(&carol)).await?, USER_TOKENS - 300 + 2_000);
+    assert_eq!(bridges.solana.vault_locked().await, LIQUIDITY + 2_000 - 1_000);
+    assert_eq!(bridges.near.total_unlocked().await?, 2_000);
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+//! Signed attestations in the JSON form relayers collect
+
+use anyhow::{anyhow, Context, Result};
+use near_bridge_client::hash::ed25519_signature;
+use near_bridge_client::Signature;
+use serde::{Deserialize, Serialize};
+
+use crate::keystore::{Keystore, ED25519_PREFIX};
+use crate::unlock::Unlock;
+
+/// One validator's signature over an unlock
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SignedUnlock {
+    pub unlock: Unlock,
+    /// Hex hash the signature covers
+    pub message_hash: String,
+    pub signature: ValidatorSignature,
+}
+
+/// A signature in the form the destination chain takes it
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum ValidatorSignature {
+    /// Base58 validator pubkey and the hex 64-byte signature passed to
+    /// `unlock_token`
+    Solana { public_key: String, signature: String },
+    /// `Signature` as `unlock_ft` and `unlock_near` take it
+    Near(Signature),
+}
+
+impl ValidatorSignature {
+    /// Raw public key and signature
+    pub fn to_bytes(&self) -> Result<([u8; 32], [u8; 64])> {
+        match self {
+            ValidatorSignature::Solana {
+                public_key,
+                signature,
+            } => {
+                let signature = hex::decode(signature).context("signature is not hex")?;
+                Ok((decode_public_key(public_key)?, to_array(&signature, "signature")?))
+            }
+            ValidatorSignature::Near(signature) => {
+                // First byte of the serialized key is the curve type
+                let public_key = to_array(&signature.public_key.as_bytes()[1..], "public key")?;
+                Ok((public_key, to_array(&signature.signature, "signature")?))
+            }
+        }
+    }
+}
+
+impl SignedUnlock {
+    /// Sign `unlock` with the validator key in `keystore`
+    pub fn sign(unlock: Unlock, keystore: &Keystore) -> Result<Self> {
+        let message_hash = unlock.message_hash()?;
+        let public_key = keystore.public_key();
+        let signature = keystore.sign(&message_hash);
+
+        let signature = match unlock.destination_chain.as_str() {
+            bridge_types::chain::NEAR => {
+                ValidatorSignature::Near(ed25519_signature(public_key, signature))
+            }
+            _ => ValidatorSignature::Solana {
+                public_key: bs58::encode(public_key).into_string(),
+                signature: hex::encode(signature),
+            },
+        };
+
+        Ok(SignedUnlock {
+            unlock,
+            message_hash: hex::encode(message_hash),
+            signature,
+        })
+    }
+}
+
+/// Decode a validator key written as base58, with or without the NEAR
+/// `ed25519:` prefix
+pub fn decode_public_key(key: &str) -> Result<[u8; 32]> {
+    let encoded = key.strip_prefix(ED25519_PREFIX).unwrap_or(key);
+    let bytes = bs58::decode(encoded)
+        .into_vec()
+        .with_context(|| format!("public key {key} is not base58"))?;
+    to_array(&bytes, "public key")
+}
+
+fn to_array<const N: usize>(bytes: &[u8], what: &str) -> Result<[u8; N]> {
+    bytes
+        .try_into()
+        .map_err(|_| anyhow!("{what} is {} bytes, expected {N}", bytes.len()))
+}
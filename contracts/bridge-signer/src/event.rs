@@ -0,0 +1,129 @@
+//! Lock events read from an event, log lines or a raw transaction
+
+use anyhow::{anyhow, bail, Context, Result};
+use bridge_types::{chain, MessageId};
+use near_bridge_client::events::EVENT_JSON_PREFIX;
+use near_bridge_client::BridgeEvent as NearEvent;
+use serde_json::Value;
+use solana_bridge_client::BridgeEvent as SolanaEvent;
+
+/// An outbound transfer locked on its source chain
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Lock {
+    pub message_id: MessageId,
+    pub source_chain: String,
+    /// Sequence of the lock on the source bridge
+    pub sequence: u64,
+    /// Sender as the relayer reports it: NEAR account ID or base58 pubkey
+    pub sender: String,
+    pub destination_chain: String,
+    pub recipient: String,
+    pub amount: u128,
+}
+
+/// Read the locks in `input`, which is one of:
+///
+/// - a NEP-297 event object, or `EVENT_JSON:` log lines
+/// - Solana `Program ...` log lines
+/// - a NEAR `tx` RPC result (`receipts_outcome`)
+/// - a Solana `getTransaction` RPC result (`meta.logMessages`)
+///
+/// RPC results may be wrapped in the JSON-RPC `result` envelope. Locks that
+/// carry a call are rejected: they are released by `*_and_call` unlocks,
+/// which this tool does not sign.
+pub fn read_locks(input: &str) -> Result<Vec<Lock>> {
+    let input = input.trim();
+    let value: Value = match serde_json::from_str(input) {
+        Ok(value) => value,
+        Err(_) => return locks_from_logs(&input.lines().collect::<Vec<_>>()),
+    };
+    let value = value.get("result").unwrap_or(&value);
+
+    if value.get("standard").is_some() {
+        return locks_from_logs(&[format!("{EVENT_JSON_PREFIX}{value}")]);
+    }
+    if let Some(receipts) = value.get("receipts_outcome").and_then(Value::as_array) {
+        let logs: Vec<&str> = receipts
+            .iter()
+            .filter_map(|receipt| receipt.pointer("/outcome/logs").and_then(Value::as_array))
+            .flatten()
+            .filter_map(Value::as_str)
+            .collect();
+        return locks_from_logs(&logs);
+    }
+    if let Some(logs) = value.pointer("/meta/logMessages").and_then(Value::as_array) {
+        return locks_from_logs(&logs.iter().filter_map(Value::as_str).collect::<Vec<_>>());
+    }
+    if let Some(logs) = value.as_array() {
+        return locks_from_logs(&logs.iter().filter_map(Value::as_str).collect::<Vec<_>>());
+    }
+
+    bail!("input is not a lock event, log list or transaction")
+}
+
+fn locks_from_logs<S: AsRef<str>>(logs: &[S]) -> Result<Vec<Lock>> {
+    let is_near = logs
+        .iter()
+        .any(|line| line.as_ref().trim_start().starts_with(EVENT_JSON_PREFIX));
+    if is_near {
+        let logs: Vec<&str> = logs.iter().map(|line| line.as_ref().trim()).collect();
+        near_locks(near_bridge_client::parse_logs(&logs)?)
+    } else {
+        solana_locks(solana_bridge_client::events::parse_logs(logs)?)
+    }
+}
+
+fn near_locks(events: Vec<NearEvent>) -> Result<Vec<Lock>> {
+    events
+        .into_iter()
+        .filter_map(|event| match event {
+            NearEvent::TokenLocked(locked) => Some(locked),
+            _ => None,
+        })
+        .map(|locked| {
+            if locked.call.is_some() {
+                bail!("lock {} carries a call", locked.message_id);
+            }
+            Ok(Lock {
+                message_id: parse_message_id(&locked.message_id)?,
+                source_chain: chain::NEAR.to_string(),
+                sequence: locked.nonce,
+                sender: locked.sender.to_string(),
+                destination_chain: locked.destination_chain,
+                recipient: locked.recipient,
+                amount: locked.amount.0,
+            })
+        })
+        .collect()
+}
+
+fn solana_locks(events: Vec<SolanaEvent>) -> Result<Vec<Lock>> {
+    let mut locks = Vec::new();
+    for event in events {
+        match event {
+            SolanaEvent::TokenLocked(locked) => locks.push(Lock {
+                message_id: locked.message_id,
+                source_chain: chain::SOLANA.to_string(),
+                sequence: locked.sequence,
+                sender: locked.sender.to_string(),
+                destination_chain: locked.destination_chain,
+                recipient: locked.destination_address,
+                amount: u128::from(locked.amount),
+            }),
+            SolanaEvent::LockCallAttached(attached) => {
+                bail!("lock {} carries a call", hex::encode(attached.message_id))
+            }
+            _ => {}
+        }
+    }
+    Ok(locks)
+}
+
+/// Parse a message ID written as 64 hex characters
+pub fn parse_message_id(id: &str) -> Result<MessageId> {
+    let bytes = hex::decode(id.trim_start_matches("0x"))
+        .with_context(|| format!("message ID {id} is not hex"))?;
+    bytes
+        .try_into()
+        .map_err(|_| anyhow!("message ID {id} is not 32 bytes"))
+}
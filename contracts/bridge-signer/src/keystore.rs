@@ -0,0 +1,69 @@
+//! Validator signing keys stored on disk
+
+use anyhow::{anyhow, bail, Context, Result};
+use ed25519_dalek::{Keypair, PublicKey, SecretKey, Signer};
+use serde_json::Value;
+
+/// Prefix of Ed25519 keys in NEAR key files
+pub const ED25519_PREFIX: &str = "ed25519:";
+
+/// An Ed25519 validator key
+pub struct Keystore {
+    keypair: Keypair,
+}
+
+impl Keystore {
+    /// Load a key file in either format:
+    ///
+    /// - NEAR credentials: `{"public_key": "ed25519:..", "private_key": "ed25519:.."}`
+    ///   (`secret_key` is accepted for `private_key`)
+    /// - Solana keypair: a JSON array of 64 bytes
+    pub fn load(path: &str) -> Result<Self> {
+        let contents = std::fs::read_to_string(path).with_context(|| format!("reading {path}"))?;
+        let value: Value =
+            serde_json::from_str(&contents).with_context(|| format!("parsing {path}"))?;
+
+        let bytes: Vec<u8> = if let Some(bytes) = value.as_array() {
+            bytes
+                .iter()
+                .map(|byte| byte.as_u64().and_then(|byte| u8::try_from(byte).ok()))
+                .collect::<Option<_>>()
+                .ok_or_else(|| anyhow!("{path}: keypair must be an array of bytes"))?
+        } else {
+            let key = value
+                .get("private_key")
+                .or_else(|| value.get("secret_key"))
+                .and_then(Value::as_str)
+                .ok_or_else(|| anyhow!("{path}: no private_key"))?;
+            let key = key
+                .strip_prefix(ED25519_PREFIX)
+                .ok_or_else(|| anyhow!("{path}: only ed25519 keys can sign attestations"))?;
+            bs58::decode(key).into_vec().with_context(|| format!("{path}: private_key"))?
+        };
+
+        Self::from_keypair_bytes(&bytes).with_context(|| format!("{path}: invalid key"))
+    }
+
+    /// Key from 64 bytes: the secret key followed by its public key
+    pub fn from_keypair_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != 64 {
+            bail!("expected 64 key bytes, got {}", bytes.len());
+        }
+        let secret = SecretKey::from_bytes(&bytes[..32])?;
+        let public = PublicKey::from(&secret);
+        if public.as_bytes() != &bytes[32..] {
+            bail!("public key does not match secret key");
+        }
+        Ok(Self {
+            keypair: Keypair { secret, public },
+        })
+    }
+
+    pub fn public_key(&self) -> [u8; 32] {
+        self.keypair.public.to_bytes()
+    }
+
+    pub fn sign(&self, message_hash: &[u8; 32]) -> [u8; 64] {
+        self.keypair.sign(message_hash).to_bytes()
+    }
+}
@@ -0,0 +1,158 @@
+//! Validator attestation signer for Articium bridge unlocks
+//!
+//! `sign` reads a lock event, rebuilds the unlock message hash the
+//! destination bridge verifies and signs it with a local validator key.
+//! `verify` checks collected attestations against a validator set snapshot
+//! and, once the threshold is met, prints the unlock ready to submit.
+
+mod attestation;
+mod event;
+mod keystore;
+mod unlock;
+mod verify;
+
+use std::io::Read;
+use std::process::ExitCode;
+
+use anyhow::{bail, Context, Result};
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use serde_json::Value;
+
+use attestation::SignedUnlock;
+use keystore::Keystore;
+use unlock::Unlock;
+use verify::ValidatorSet;
+
+#[derive(Parser)]
+#[command(version, about = "Sign and verify Articium bridge unlock attestations")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Sign the unlock of a lock event
+    Sign {
+        /// Lock event, log lines or raw transaction JSON; `-` reads stdin
+        #[arg(long, default_value = "-")]
+        event: String,
+        /// NEAR credentials file or Solana keypair file
+        #[arg(long)]
+        keystore: String,
+        /// Token released on the destination chain: NEP-141 contract,
+        /// `near` for native NEAR, or SPL mint
+        #[arg(long)]
+        token: String,
+        /// Destination bridge account, required for unlocks on NEAR
+        #[arg(long)]
+        bridge: Option<String>,
+        /// Lock to sign when the input holds several
+        #[arg(long)]
+        message_id: Option<String>,
+    },
+    /// Check attestations against a validator set snapshot
+    Verify {
+        /// JSON file with `validators` and `required_signatures`
+        #[arg(long)]
+        validators: String,
+        /// Files holding one attestation or an array of them
+        #[arg(required = true)]
+        attestations: Vec<String>,
+    },
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("error: {err:#}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<ExitCode> {
+    match cli.command {
+        Command::Sign {
+            event,
+            keystore,
+            token,
+            bridge,
+            message_id,
+        } => {
+            let locks = event::read_locks(&read_input(&event)?)?;
+            let lock = match message_id {
+                Some(id) => {
+                    let id = event::parse_message_id(&id)?;
+                    locks
+                        .iter()
+                        .find(|lock| lock.message_id == id)
+                        .context("no lock with that message ID in the input")?
+                }
+                None => match locks.as_slice() {
+                    [lock] => lock,
+                    [] => bail!("no lock event in the input"),
+                    _ => bail!(
+                        "the input holds {} locks, choose one with --message-id: {}",
+                        locks.len(),
+                        locks
+                            .iter()
+                            .map(|lock| hex::encode(lock.message_id))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                },
+            };
+
+            let keystore = Keystore::load(&keystore)?;
+            let unlock = Unlock::new(lock, bridge.as_deref(), &token)?;
+            print_json(&SignedUnlock::sign(unlock, &keystore)?)?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::Verify {
+            validators,
+            attestations,
+        } => {
+            let validator_set: ValidatorSet = serde_json::from_str(&read_input(&validators)?)
+                .with_context(|| format!("parsing {validators}"))?;
+
+            let mut signed = Vec::new();
+            for path in &attestations {
+                let value: Value = serde_json::from_str(&read_input(path)?)
+                    .with_context(|| format!("parsing {path}"))?;
+                match value {
+                    Value::Array(_) => {
+                        signed.extend(serde_json::from_value::<Vec<SignedUnlock>>(value)?)
+                    }
+                    _ => signed.push(serde_json::from_value(value)?),
+                }
+            }
+
+            let report = verify::verify(&signed, &validator_set)?;
+            print_json(&report)?;
+            // Distinguish "not enough signatures yet" from a usage error
+            Ok(if report.is_ready() {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            })
+        }
+    }
+}
+
+fn read_input(path: &str) -> Result<String> {
+    if path == "-" {
+        let mut input = String::new();
+        std::io::stdin().read_to_string(&mut input)?;
+        Ok(input)
+    } else {
+        std::fs::read_to_string(path).with_context(|| format!("reading {path}"))
+    }
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
@@ -0,0 +1,145 @@
+//! The unlock a validator attests to, and its hash on each destination chain
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, bail, Context, Result};
+use bridge_types::chain;
+use near_bridge_client::args::{UnlockFt, UnlockNear};
+use near_bridge_client::hash::unlock_message_hash;
+use near_bridge_client::{Signature, NATIVE_TOKEN};
+use near_sdk::json_types::U128;
+use near_sdk::AccountId;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use solana_bridge_client::{UnlockToken, PROGRAM_ID};
+
+use crate::event::{parse_message_id, Lock};
+
+/// Release of a locked transfer on its destination chain. Addresses are in
+/// the destination chain's native string form.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Unlock {
+    pub destination_chain: String,
+    /// NEAR bridge account or Solana program ID
+    pub bridge: String,
+    /// Hex message ID of the lock
+    pub message_id: String,
+    pub source_chain: String,
+    /// Sequence of the lock on the source bridge
+    pub sequence: u64,
+    pub sender: String,
+    pub recipient: String,
+    /// Token released: NEP-141 contract, `near`, or SPL mint
+    pub token: String,
+    pub amount: U128,
+}
+
+impl Unlock {
+    /// Unlock releasing `lock` as `token` through `bridge`. Solana unlocks
+    /// always go through the program this tool was built against.
+    pub fn new(lock: &Lock, bridge: Option<&str>, token: &str) -> Result<Self> {
+        let bridge = match (lock.destination_chain.as_str(), bridge) {
+            (chain::NEAR, Some(bridge)) => bridge.to_string(),
+            (chain::NEAR, None) => bail!("--bridge is required for unlocks on NEAR"),
+            (chain::SOLANA, Some(bridge)) if bridge != PROGRAM_ID.to_string() => {
+                bail!("bridge {bridge} is not the Solana program {PROGRAM_ID}")
+            }
+            (chain::SOLANA, _) => PROGRAM_ID.to_string(),
+            (other, _) => bail!("unlocks on {other} are not supported"),
+        };
+
+        let unlock = Unlock {
+            destination_chain: lock.destination_chain.clone(),
+            bridge,
+            message_id: hex::encode(lock.message_id),
+            source_chain: lock.source_chain.clone(),
+            sequence: lock.sequence,
+            sender: lock.sender.clone(),
+            recipient: lock.recipient.clone(),
+            token: token.to_string(),
+            amount: U128(lock.amount),
+        };
+        // Reject addresses the destination chain would not accept
+        unlock.message_hash()?;
+        Ok(unlock)
+    }
+
+    /// Hash the destination bridge verifies validator signatures against
+    pub fn message_hash(&self) -> Result<[u8; 32]> {
+        let message_id = parse_message_id(&self.message_id)?;
+        match self.destination_chain.as_str() {
+            chain::NEAR => Ok(unlock_message_hash(
+                &account_id(&self.bridge)?,
+                &message_id,
+                &self.source_chain,
+                self.sequence,
+                &self.sender,
+                &account_id(&self.recipient)?,
+                &account_id(&self.token)?,
+                self.amount.0,
+            )),
+            chain::SOLANA => {
+                if self.bridge != PROGRAM_ID.to_string() {
+                    bail!("bridge {} is not the Solana program {PROGRAM_ID}", self.bridge);
+                }
+                Ok(self.solana_unlock(message_id)?.message_hash())
+            }
+            other => bail!("unlocks on {other} are not supported"),
+        }
+    }
+
+    /// NEAR method and arguments submitting this unlock with `signatures`
+    pub fn near_call(&self, signatures: Vec<Signature>) -> Result<(&'static str, Value)> {
+        let message_id = parse_message_id(&self.message_id)?;
+        if self.token == NATIVE_TOKEN {
+            let args = UnlockNear {
+                message_id,
+                source_chain: self.source_chain.clone(),
+                sequence: self.sequence,
+                sender_address: self.sender.clone(),
+                recipient: account_id(&self.recipient)?,
+                amount: self.amount,
+                signatures,
+                aggregate_signature: None,
+            };
+            Ok(("unlock_near", serde_json::to_value(args)?))
+        } else {
+            let args = UnlockFt {
+                message_id,
+                source_chain: self.source_chain.clone(),
+                sequence: self.sequence,
+                sender_address: self.sender.clone(),
+                recipient: account_id(&self.recipient)?,
+                token_contract: account_id(&self.token)?,
+                amount: self.amount,
+                signatures,
+                aggregate_signature: None,
+            };
+            Ok(("unlock_ft", serde_json::to_value(args)?))
+        }
+    }
+
+    fn solana_unlock(&self, message_id: [u8; 32]) -> Result<UnlockToken> {
+        let amount = u64::try_from(self.amount.0)
+            .map_err(|_| anyhow!("amount {} does not fit a Solana token amount", self.amount.0))?;
+        Ok(UnlockToken {
+            // Accounts the attestation does not cover
+            payer: Pubkey::default(),
+            recipient_token_account: Pubkey::default(),
+            recipient: pubkey(&self.recipient)?,
+            token_mint: pubkey(&self.token)?,
+            message_id,
+            source_chain: self.source_chain.clone(),
+            sequence: self.sequence,
+            sender: self.sender.clone(),
+            amount,
+        })
+    }
+}
+
+fn account_id(id: &str) -> Result<AccountId> {
+    id.parse().with_context(|| format!("invalid NEAR account ID {id}"))
+}
+
+fn pubkey(key: &str) -> Result<Pubkey> {
+    key.parse().with_context(|| format!("invalid Solana address {key}"))
+}
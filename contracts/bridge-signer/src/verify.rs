@@ -0,0 +1,151 @@
+//! Checking collected attestations against a validator set snapshot
+
+use std::collections::HashSet;
+
+use anyhow::{bail, Context, Result};
+use ed25519_dalek::{PublicKey, Signature, Verifier};
+use near_bridge_client::hash::ed25519_signature;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::attestation::{decode_public_key, SignedUnlock};
+use crate::unlock::Unlock;
+
+/// Validators and threshold of a bridge deployment at some point in time.
+/// Keys are base58, with or without the NEAR `ed25519:` prefix.
+#[derive(Deserialize, Debug)]
+pub struct ValidatorSet {
+    pub validators: Vec<String>,
+    pub required_signatures: u8,
+}
+
+/// Outcome of checking one attestation
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SignatureStatus {
+    Valid,
+    NotAValidator,
+    InvalidSignature,
+    Duplicate,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SignerReport {
+    pub public_key: String,
+    pub status: SignatureStatus,
+}
+
+/// Result of `verify`. `submission` is set once enough valid signatures are
+/// collected, in the form the destination bridge takes them.
+#[derive(Serialize, Debug)]
+pub struct Report {
+    pub unlock: Unlock,
+    pub message_hash: String,
+    pub required_signatures: u8,
+    pub valid_signatures: usize,
+    pub signers: Vec<SignerReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub submission: Option<Value>,
+}
+
+impl Report {
+    pub fn is_ready(&self) -> bool {
+        self.submission.is_some()
+    }
+}
+
+/// Check `attestations`, which must all cover the same unlock, against
+/// `validator_set`
+pub fn verify(attestations: &[SignedUnlock], validator_set: &ValidatorSet) -> Result<Report> {
+    let Some(first) = attestations.first() else {
+        bail!("no attestations to verify");
+    };
+    if let Some(other) = attestations.iter().find(|signed| signed.unlock != first.unlock) {
+        bail!(
+            "attestations cover different unlocks: {} and {}",
+            first.unlock.message_id,
+            other.unlock.message_id
+        );
+    }
+
+    // Recompute rather than trust the hash carried in the attestations
+    let message_hash = first.unlock.message_hash()?;
+    let validators = validator_set
+        .validators
+        .iter()
+        .map(|key| decode_public_key(key))
+        .collect::<Result<HashSet<_>>>()
+        .context("validator set")?;
+
+    let mut seen = HashSet::new();
+    let mut signers = Vec::new();
+    let mut valid = Vec::new();
+    for signed in attestations {
+        let (public_key, signature) = signed.signature.to_bytes()?;
+        let status = if !validators.contains(&public_key) {
+            SignatureStatus::NotAValidator
+        } else if !verify_signature(&public_key, &signature, &message_hash) {
+            SignatureStatus::InvalidSignature
+        } else if !seen.insert(public_key) {
+            SignatureStatus::Duplicate
+        } else {
+            valid.push((public_key, signature));
+            SignatureStatus::Valid
+        };
+        signers.push(SignerReport {
+            public_key: bs58::encode(public_key).into_string(),
+            status,
+        });
+    }
+
+    let submission = if valid.len() >= usize::from(validator_set.required_signatures) {
+        Some(submission(&first.unlock, &valid)?)
+    } else {
+        None
+    };
+
+    Ok(Report {
+        unlock: first.unlock.clone(),
+        message_hash: hex::encode(message_hash),
+        required_signatures: validator_set.required_signatures,
+        valid_signatures: valid.len(),
+        signers,
+        submission,
+    })
+}
+
+fn verify_signature(public_key: &[u8; 32], signature: &[u8; 64], message: &[u8]) -> bool {
+    let Ok(public_key) = PublicKey::from_bytes(public_key) else {
+        return false;
+    };
+    let Ok(signature) = Signature::from_bytes(signature) else {
+        return false;
+    };
+    public_key.verify(message, &signature).is_ok()
+}
+
+/// NEAR method and arguments, or the Solana `unlock_token` signatures
+fn submission(unlock: &Unlock, signatures: &[([u8; 32], [u8; 64])]) -> Result<Value> {
+    match unlock.destination_chain.as_str() {
+        bridge_types::chain::NEAR => {
+            let signatures = signatures
+                .iter()
+                .map(|(public_key, signature)| ed25519_signature(*public_key, *signature))
+                .collect();
+            let (method, args) = unlock.near_call(signatures)?;
+            Ok(json!({ "contract": unlock.bridge, "method": method, "args": args }))
+        }
+        _ => {
+            let signatures: Vec<Value> = signatures
+                .iter()
+                .map(|(public_key, signature)| {
+                    json!({
+                        "public_key": bs58::encode(public_key).into_string(),
+                        "signature": hex::encode(signature),
+                    })
+                })
+                .collect();
+            Ok(json!({ "program": unlock.bridge, "signatures": signatures }))
+        }
+    }
+}
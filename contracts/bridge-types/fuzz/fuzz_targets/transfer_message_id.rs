@@ -0,0 +1,45 @@
+//! Distinct transfers never share a message ID, and an ID only depends on
+//! the transfer
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use bridge_types::hash::Sha3Keccak;
+use bridge_types::TransferMessage;
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Arbitrary, Debug)]
+struct Transfer {
+    source_chain: String,
+    source_bridge: Vec<u8>,
+    sequence: u64,
+    sender: Vec<u8>,
+    token: Vec<u8>,
+    amount: u128,
+    destination_chain: String,
+    recipient: String,
+}
+
+impl From<Transfer> for TransferMessage {
+    fn from(transfer: Transfer) -> Self {
+        TransferMessage {
+            source_chain: transfer.source_chain,
+            source_bridge: transfer.source_bridge,
+            sequence: transfer.sequence,
+            sender: transfer.sender,
+            token: transfer.token,
+            amount: transfer.amount,
+            destination_chain: transfer.destination_chain,
+            recipient: transfer.recipient,
+        }
+    }
+}
+
+fuzz_target!(|transfers: (Transfer, Transfer)| {
+    let first = TransferMessage::from(transfers.0);
+    let second = TransferMessage::from(transfers.1);
+
+    let id = first.id::<Sha3Keccak>();
+    assert_eq!(id, first.clone().id::<Sha3Keccak>());
+    assert_eq!(first == second, id == second.id::<Sha3Keccak>());
+});
@@ -0,0 +1,45 @@
+//! Distinct attestations never share a signing hash
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use bridge_types::hash::Sha3Keccak;
+use bridge_types::UnlockAttestation;
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Arbitrary, Debug)]
+struct Attestation {
+    message_id: [u8; 32],
+    source_chain: String,
+    sequence: u64,
+    sender: String,
+    destination_chain: String,
+    destination_bridge: Vec<u8>,
+    recipient: Vec<u8>,
+    token: Vec<u8>,
+    amount: u128,
+}
+
+impl From<Attestation> for UnlockAttestation {
+    fn from(attestation: Attestation) -> Self {
+        UnlockAttestation {
+            message_id: attestation.message_id,
+            source_chain: attestation.source_chain,
+            sequence: attestation.sequence,
+            sender: attestation.sender,
+            destination_chain: attestation.destination_chain,
+            destination_bridge: attestation.destination_bridge,
+            recipient: attestation.recipient,
+            token: attestation.token,
+            amount: attestation.amount,
+        }
+    }
+}
+
+fuzz_target!(|attestations: (Attestation, Attestation)| {
+    let first = UnlockAttestation::from(attestations.0);
+    let second = UnlockAttestation::from(attestations.1);
+
+    let hash = first.signing_hash::<Sha3Keccak>();
+    assert_eq!(first == second, hash == second.signing_hash::<Sha3Keccak>());
+});
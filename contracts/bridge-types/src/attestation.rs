@@ -0,0 +1,102 @@
+//! What validators sign to release a transfer on the destination chain.
+//!
+//! `hash = keccak256(UNLOCK_DOMAIN || message_id || encode(attestation))`,
+//! where `encode` concatenates, in order:
+//!
+//! - `lp(source_chain)`
+//! - `u64_le(sequence)`: sequence of the transfer on the source bridge, as
+//!   committed to by its message ID
+//! - `lp(sender)`: source sender as the relayer reports it (account ID,
+//!   base58 or hex string)
+//! - `lp(destination_chain)`
+//! - `lp(destination_bridge)`: destination bridge address, native encoding
+//! - `lp(recipient)`: recipient, native encoding
+//! - `lp(token)`: token released, native encoding
+//! - `u128_le(amount)`
+//!
+//! Binding the destination bridge keeps an attestation from being replayed
+//! against another deployment. Destination bridges record released
+//! transfers by source chain and sequence, so the sequence is attested too.
+//!
+//! A threshold group signs any attestation hash together with the signer
+//! bitmap the contracts check against the group threshold:
+//! `keccak256(AGGREGATE_DOMAIN || hash || u32_le(signer_bitmap))`.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+#[cfg(feature = "anchor")]
+use anchor_lang::prelude::borsh;
+
+use crate::encoding::{push_prefixed, push_u128, push_u64};
+use crate::hash::Keccak256;
+use crate::message::MessageId;
+
+/// Domain separator and scheme version of unlock attestations
+pub const UNLOCK_DOMAIN: &[u8] = b"articium:unlock:v2";
+
+/// Domain separator and scheme version of threshold group signatures
+pub const AGGREGATE_DOMAIN: &[u8] = b"articium:aggregate:v1";
+
+/// Hash a threshold group signs for the attestation hashing to
+/// `message_hash`, so the participants named in `signer_bitmap` cannot be
+/// changed after signing
+pub fn aggregate_signing_hash<H: Keccak256>(
+    message_hash: &[u8; 32],
+    signer_bitmap: u32,
+) -> [u8; 32] {
+    let mut data = AGGREGATE_DOMAIN.to_vec();
+    data.extend_from_slice(message_hash);
+    data.extend_from_slice(&signer_bitmap.to_le_bytes());
+    H::keccak256(&data)
+}
+
+/// A transfer release attested by the validators
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "near",
+    derive(
+        near_sdk::borsh::BorshSerialize,
+        near_sdk::borsh::BorshDeserialize,
+        near_sdk::serde::Serialize,
+        near_sdk::serde::Deserialize,
+    ),
+    borsh(crate = "near_sdk::borsh"),
+    serde(crate = "near_sdk::serde")
+)]
+#[cfg_attr(feature = "anchor", derive(anchor_lang::AnchorSerialize, anchor_lang::AnchorDeserialize))]
+pub struct UnlockAttestation {
+    pub message_id: MessageId,
+    pub source_chain: String,
+    pub sequence: u64,
+    pub sender: String,
+    pub destination_chain: String,
+    pub destination_bridge: Vec<u8>,
+    pub recipient: Vec<u8>,
+    pub token: Vec<u8>,
+    pub amount: u128,
+}
+
+impl UnlockAttestation {
+    /// Canonical length-prefixed encoding of the fields after `message_id`
+    pub fn encode(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        push_prefixed(&mut data, self.source_chain.as_bytes());
+        push_u64(&mut data, self.sequence);
+        push_prefixed(&mut data, self.sender.as_bytes());
+        push_prefixed(&mut data, self.destination_chain.as_bytes());
+        push_prefixed(&mut data, &self.destination_bridge);
+        push_prefixed(&mut data, &self.recipient);
+        push_prefixed(&mut data, &self.token);
+        push_u128(&mut data, self.amount);
+        data
+    }
+
+    /// Hash the validators sign
+    pub fn signing_hash<H: Keccak256>(&self) -> [u8; 32] {
+        let mut data = UNLOCK_DOMAIN.to_vec();
+        data.extend_from_slice(&self.message_id);
+        data.extend_from_slice(&self.encode());
+        H::keccak256(&data)
+    }
+}
@@ -0,0 +1,15 @@
+//! Chain identifiers used in message IDs and attestations. These are the
+//! names the relayer uses for `source_chain` and `destination_chain`.
+
+pub const NEAR: &str = "near";
+pub const SOLANA: &str = "solana";
+pub const ETHEREUM: &str = "ethereum";
+pub const POLYGON: &str = "polygon";
+pub const BSC: &str = "bsc";
+pub const AVALANCHE: &str = "avalanche";
+pub const ARBITRUM: &str = "arbitrum";
+pub const OPTIMISM: &str = "optimism";
+pub const BASE: &str = "base";
+
+/// Longest chain identifier the contracts accept
+pub const MAX_CHAIN_ID_LEN: usize = 32;
@@ -0,0 +1,20 @@
+//! Canonical byte encoding. Variable-length fields are prefixed with their
+//! length as a little-endian `u32`; integers are little-endian and fixed width.
+
+use alloc::vec::Vec;
+
+/// Append `u32_le(len(field)) || field`
+pub fn push_prefixed(data: &mut Vec<u8>, field: &[u8]) {
+    data.extend_from_slice(&(field.len() as u32).to_le_bytes());
+    data.extend_from_slice(field);
+}
+
+/// Append `u64_le(value)`
+pub fn push_u64(data: &mut Vec<u8>, value: u64) {
+    data.extend_from_slice(&value.to_le_bytes());
+}
+
+/// Append `u128_le(value)`
+pub fn push_u128(data: &mut Vec<u8>, value: u128) {
+    data.extend_from_slice(&value.to_le_bytes());
+}
@@ -0,0 +1,126 @@
+//! Generic messages: an arbitrary payload for a contract or program on the
+//! destination chain.
+//!
+//! `message_id = keccak256(GENERIC_MESSAGE_DOMAIN || encode(message))`, where
+//! `encode` concatenates, in order:
+//!
+//! - `lp(source_chain)`
+//! - `lp(source_bridge)`: source bridge address in its native encoding
+//! - `u64_le(sequence)`: per-bridge sequence number, shared with token locks
+//! - `lp(sender)`: sender address in its native encoding
+//! - `lp(destination_chain)`
+//! - `lp(target)`: destination contract or program exactly as submitted
+//! - `lp(payload)`
+//!
+//! To deliver the message, validators sign
+//! `keccak256(MESSAGE_DELIVERY_DOMAIN || message_id || encode(attestation))`
+//! with `lp(source_chain) || lp(sender) || lp(destination_chain) ||
+//! lp(destination_bridge) || lp(target) || lp(payload)`.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+#[cfg(feature = "anchor")]
+use anchor_lang::prelude::borsh;
+
+use crate::encoding::{push_prefixed, push_u64};
+use crate::hash::Keccak256;
+use crate::message::MessageId;
+
+/// Domain separator and scheme version of generic messages
+pub const GENERIC_MESSAGE_DOMAIN: &[u8] = b"articium:message:v1";
+
+/// Domain separator and scheme version of message delivery attestations
+pub const MESSAGE_DELIVERY_DOMAIN: &[u8] = b"articium:message-delivery:v1";
+
+/// An outbound generic message as committed to by its message ID
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "near",
+    derive(
+        near_sdk::borsh::BorshSerialize,
+        near_sdk::borsh::BorshDeserialize,
+        near_sdk::serde::Serialize,
+        near_sdk::serde::Deserialize,
+    ),
+    borsh(crate = "near_sdk::borsh"),
+    serde(crate = "near_sdk::serde")
+)]
+#[cfg_attr(feature = "anchor", derive(anchor_lang::AnchorSerialize, anchor_lang::AnchorDeserialize))]
+pub struct GenericMessage {
+    pub source_chain: String,
+    pub source_bridge: Vec<u8>,
+    pub sequence: u64,
+    pub sender: Vec<u8>,
+    pub destination_chain: String,
+    pub target: String,
+    pub payload: Vec<u8>,
+}
+
+impl GenericMessage {
+    /// Canonical length-prefixed encoding, without the domain separator
+    pub fn encode(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        push_prefixed(&mut data, self.source_chain.as_bytes());
+        push_prefixed(&mut data, &self.source_bridge);
+        push_u64(&mut data, self.sequence);
+        push_prefixed(&mut data, &self.sender);
+        push_prefixed(&mut data, self.destination_chain.as_bytes());
+        push_prefixed(&mut data, self.target.as_bytes());
+        push_prefixed(&mut data, &self.payload);
+        data
+    }
+
+    /// Message ID of the message
+    pub fn id<H: Keccak256>(&self) -> MessageId {
+        let mut data = GENERIC_MESSAGE_DOMAIN.to_vec();
+        data.extend_from_slice(&self.encode());
+        H::keccak256(&data)
+    }
+}
+
+/// A generic message delivery attested by the validators
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "near",
+    derive(
+        near_sdk::borsh::BorshSerialize,
+        near_sdk::borsh::BorshDeserialize,
+        near_sdk::serde::Serialize,
+        near_sdk::serde::Deserialize,
+    ),
+    borsh(crate = "near_sdk::borsh"),
+    serde(crate = "near_sdk::serde")
+)]
+#[cfg_attr(feature = "anchor", derive(anchor_lang::AnchorSerialize, anchor_lang::AnchorDeserialize))]
+pub struct MessageDeliveryAttestation {
+    pub message_id: MessageId,
+    pub source_chain: String,
+    pub sender: String,
+    pub destination_chain: String,
+    pub destination_bridge: Vec<u8>,
+    pub target: Vec<u8>,
+    pub payload: Vec<u8>,
+}
+
+impl MessageDeliveryAttestation {
+    /// Canonical length-prefixed encoding of the fields after `message_id`
+    pub fn encode(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        push_prefixed(&mut data, self.source_chain.as_bytes());
+        push_prefixed(&mut data, self.sender.as_bytes());
+        push_prefixed(&mut data, self.destination_chain.as_bytes());
+        push_prefixed(&mut data, &self.destination_bridge);
+        push_prefixed(&mut data, &self.target);
+        push_prefixed(&mut data, &self.payload);
+        data
+    }
+
+    /// Hash the validators sign
+    pub fn signing_hash<H: Keccak256>(&self) -> [u8; 32] {
+        let mut data = MESSAGE_DELIVERY_DOMAIN.to_vec();
+        data.extend_from_slice(&self.message_id);
+        data.extend_from_slice(&self.encode());
+        H::keccak256(&data)
+    }
+}
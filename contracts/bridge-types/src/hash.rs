@@ -0,0 +1,42 @@
+//! Keccak-256 backends. Contracts use their chain's host implementation, which
+//! is far cheaper than hashing in the VM; everything else uses [`Sha3Keccak`].
+
+/// A Keccak-256 implementation (the original Keccak, not SHA3-256)
+pub trait Keccak256 {
+    fn keccak256(data: &[u8]) -> [u8; 32];
+}
+
+/// Pure-Rust Keccak-256
+#[cfg(feature = "keccak")]
+pub struct Sha3Keccak;
+
+#[cfg(feature = "keccak")]
+impl Keccak256 for Sha3Keccak {
+    fn keccak256(data: &[u8]) -> [u8; 32] {
+        use sha3::Digest;
+
+        sha3::Keccak256::digest(data).into()
+    }
+}
+
+/// NEAR host function `keccak256`
+#[cfg(feature = "near")]
+pub struct NearKeccak;
+
+#[cfg(feature = "near")]
+impl Keccak256 for NearKeccak {
+    fn keccak256(data: &[u8]) -> [u8; 32] {
+        near_sdk::env::keccak256_array(data)
+    }
+}
+
+/// Solana `sol_keccak256` syscall
+#[cfg(feature = "anchor")]
+pub struct SolanaKeccak;
+
+#[cfg(feature = "anchor")]
+impl Keccak256 for SolanaKeccak {
+    fn keccak256(data: &[u8]) -> [u8; 32] {
+        anchor_lang::solana_program::keccak::hash(data).to_bytes()
+    }
+}
@@ -0,0 +1,32 @@
+//! Cross-chain message types shared by the Articium bridge contracts.
+//!
+//! Both the NEAR contract and the Solana program derive message IDs and verify
+//! validator attestations through this crate, so the two cannot drift apart.
+//! The encodings are specified in `docs/specs/MESSAGE_ID.md`.
+//!
+//! The crate is `no_std` (with `alloc`). Features:
+//!
+//! - `keccak` (default): pure-Rust Keccak-256 as [`hash::Sha3Keccak`]
+//! - `near`: Borsh and JSON serialization through `near-sdk`, and
+//!   [`hash::NearKeccak`] backed by the host function
+//! - `anchor`: Anchor (Borsh) serialization, and [`hash::SolanaKeccak`]
+//!   backed by the syscall
+//! - `std`: links `std`
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod attestation;
+pub mod chain;
+pub mod encoding;
+pub mod generic;
+pub mod hash;
+pub mod message;
+pub mod nft;
+
+pub use attestation::{aggregate_signing_hash, UnlockAttestation};
+pub use generic::{GenericMessage, MessageDeliveryAttestation};
+pub use hash::Keccak256;
+pub use message::{MessageId, TransferMessage};
+pub use nft::{NftTransferMessage, NftUnlockAttestation};
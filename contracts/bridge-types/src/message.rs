@@ -0,0 +1,79 @@
+//! Canonical transfer message and its ID.
+//!
+//! `message_id = keccak256(TRANSFER_MESSAGE_DOMAIN || encode(message))`, where
+//! `encode` concatenates, in order:
+//!
+//! - `lp(source_chain)`: chain identifier, see [`crate::chain`]
+//! - `lp(source_bridge)`: source bridge address in its native encoding
+//! - `u64_le(sequence)`: per-bridge sequence number of the lock
+//! - `lp(sender)`: sender address in its native encoding
+//! - `lp(token)`: locked token in its native encoding
+//! - `u128_le(amount)`
+//! - `lp(destination_chain)`
+//! - `lp(recipient)`: destination address exactly as submitted
+//!
+//! with `lp(x) = u32_le(len(x)) || x`. Native encodings are UTF-8 account IDs
+//! on NEAR and raw 32-byte public keys on Solana.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+#[cfg(feature = "anchor")]
+use anchor_lang::prelude::borsh;
+
+use crate::encoding::{push_prefixed, push_u128, push_u64};
+use crate::hash::Keccak256;
+
+/// Domain separator and scheme version prefixed to every encoded message
+pub const TRANSFER_MESSAGE_DOMAIN: &[u8] = b"articium:transfer:v1";
+
+/// 32-byte cross-chain message identifier
+pub type MessageId = [u8; 32];
+
+/// An outbound token transfer as committed to by its message ID
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "near",
+    derive(
+        near_sdk::borsh::BorshSerialize,
+        near_sdk::borsh::BorshDeserialize,
+        near_sdk::serde::Serialize,
+        near_sdk::serde::Deserialize,
+    ),
+    borsh(crate = "near_sdk::borsh"),
+    serde(crate = "near_sdk::serde")
+)]
+#[cfg_attr(feature = "anchor", derive(anchor_lang::AnchorSerialize, anchor_lang::AnchorDeserialize))]
+pub struct TransferMessage {
+    pub source_chain: String,
+    pub source_bridge: Vec<u8>,
+    pub sequence: u64,
+    pub sender: Vec<u8>,
+    pub token: Vec<u8>,
+    pub amount: u128,
+    pub destination_chain: String,
+    pub recipient: String,
+}
+
+impl TransferMessage {
+    /// Canonical length-prefixed encoding, without the domain separator
+    pub fn encode(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        push_prefixed(&mut data, self.source_chain.as_bytes());
+        push_prefixed(&mut data, &self.source_bridge);
+        push_u64(&mut data, self.sequence);
+        push_prefixed(&mut data, &self.sender);
+        push_prefixed(&mut data, &self.token);
+        push_u128(&mut data, self.amount);
+        push_prefixed(&mut data, self.destination_chain.as_bytes());
+        push_prefixed(&mut data, self.recipient.as_bytes());
+        data
+    }
+
+    /// Message ID of the transfer
+    pub fn id<H: Keccak256>(&self) -> MessageId {
+        let mut data = TRANSFER_MESSAGE_DOMAIN.to_vec();
+        data.extend_from_slice(&self.encode());
+        H::keccak256(&data)
+    }
+}
@@ -0,0 +1,136 @@
+//! NFT transfers: message ID and unlock attestation.
+//!
+//! `message_id = keccak256(NFT_TRANSFER_MESSAGE_DOMAIN || encode(message))`,
+//! where `encode` concatenates, in order:
+//!
+//! - `lp(source_chain)`
+//! - `lp(source_bridge)`: source bridge address in its native encoding
+//! - `u64_le(sequence)`: per-bridge sequence number, shared with token locks
+//! - `lp(sender)`: sender address in its native encoding
+//! - `lp(nft_contract)`: collection in its native encoding
+//! - `lp(token_id)`
+//! - `lp(token_uri)`: metadata URI, empty when the token has none
+//! - `lp(destination_chain)`
+//! - `lp(recipient)`: destination address exactly as submitted
+//!
+//! To release or mint the NFT on the destination chain, validators sign
+//! `keccak256(NFT_UNLOCK_DOMAIN || message_id || encode(attestation))` with
+//! `lp(source_chain) || lp(sender) || lp(destination_chain) ||
+//! lp(destination_bridge) || lp(recipient) || lp(nft_contract) ||
+//! lp(token_id) || lp(token_uri)`.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+#[cfg(feature = "anchor")]
+use anchor_lang::prelude::borsh;
+
+use crate::encoding::{push_prefixed, push_u64};
+use crate::hash::Keccak256;
+use crate::message::MessageId;
+
+/// Domain separator and scheme version of NFT transfer messages
+pub const NFT_TRANSFER_MESSAGE_DOMAIN: &[u8] = b"articium:nft-transfer:v1";
+
+/// Domain separator and scheme version of NFT unlock attestations
+pub const NFT_UNLOCK_DOMAIN: &[u8] = b"articium:nft-unlock:v1";
+
+/// An outbound NFT transfer as committed to by its message ID
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "near",
+    derive(
+        near_sdk::borsh::BorshSerialize,
+        near_sdk::borsh::BorshDeserialize,
+        near_sdk::serde::Serialize,
+        near_sdk::serde::Deserialize,
+    ),
+    borsh(crate = "near_sdk::borsh"),
+    serde(crate = "near_sdk::serde")
+)]
+#[cfg_attr(feature = "anchor", derive(anchor_lang::AnchorSerialize, anchor_lang::AnchorDeserialize))]
+pub struct NftTransferMessage {
+    pub source_chain: String,
+    pub source_bridge: Vec<u8>,
+    pub sequence: u64,
+    pub sender: Vec<u8>,
+    pub nft_contract: Vec<u8>,
+    pub token_id: String,
+    pub token_uri: String,
+    pub destination_chain: String,
+    pub recipient: String,
+}
+
+impl NftTransferMessage {
+    /// Canonical length-prefixed encoding, without the domain separator
+    pub fn encode(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        push_prefixed(&mut data, self.source_chain.as_bytes());
+        push_prefixed(&mut data, &self.source_bridge);
+        push_u64(&mut data, self.sequence);
+        push_prefixed(&mut data, &self.sender);
+        push_prefixed(&mut data, &self.nft_contract);
+        push_prefixed(&mut data, self.token_id.as_bytes());
+        push_prefixed(&mut data, self.token_uri.as_bytes());
+        push_prefixed(&mut data, self.destination_chain.as_bytes());
+        push_prefixed(&mut data, self.recipient.as_bytes());
+        data
+    }
+
+    /// Message ID of the transfer
+    pub fn id<H: Keccak256>(&self) -> MessageId {
+        let mut data = NFT_TRANSFER_MESSAGE_DOMAIN.to_vec();
+        data.extend_from_slice(&self.encode());
+        H::keccak256(&data)
+    }
+}
+
+/// An NFT release (or mint) attested by the validators
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "near",
+    derive(
+        near_sdk::borsh::BorshSerialize,
+        near_sdk::borsh::BorshDeserialize,
+        near_sdk::serde::Serialize,
+        near_sdk::serde::Deserialize,
+    ),
+    borsh(crate = "near_sdk::borsh"),
+    serde(crate = "near_sdk::serde")
+)]
+#[cfg_attr(feature = "anchor", derive(anchor_lang::AnchorSerialize, anchor_lang::AnchorDeserialize))]
+pub struct NftUnlockAttestation {
+    pub message_id: MessageId,
+    pub source_chain: String,
+    pub sender: String,
+    pub destination_chain: String,
+    pub destination_bridge: Vec<u8>,
+    pub recipient: Vec<u8>,
+    pub nft_contract: Vec<u8>,
+    pub token_id: String,
+    pub token_uri: String,
+}
+
+impl NftUnlockAttestation {
+    /// Canonical length-prefixed encoding of the fields after `message_id`
+    pub fn encode(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        push_prefixed(&mut data, self.source_chain.as_bytes());
+        push_prefixed(&mut data, self.sender.as_bytes());
+        push_prefixed(&mut data, self.destination_chain.as_bytes());
+        push_prefixed(&mut data, &self.destination_bridge);
+        push_prefixed(&mut data, &self.recipient);
+        push_prefixed(&mut data, &self.nft_contract);
+        push_prefixed(&mut data, self.token_id.as_bytes());
+        push_prefixed(&mut data, self.token_uri.as_bytes());
+        data
+    }
+
+    /// Hash the validators sign
+    pub fn signing_hash<H: Keccak256>(&self) -> [u8; 32] {
+        let mut data = NFT_UNLOCK_DOMAIN.to_vec();
+        data.extend_from_slice(&self.message_id);
+        data.extend_from_slice(&self.encode());
+        H::keccak256(&data)
+    }
+}
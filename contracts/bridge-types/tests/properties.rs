@@ -0,0 +1,234 @@
+//! Property tests of the canonical encodings: every encoding decodes back to
+//! the one message it came from, so distinct messages cannot share an ID or
+//! an attestation hash short of a Keccak collision.
+
+use bridge_types::hash::Sha3Keccak;
+use bridge_types::{GenericMessage, TransferMessage, UnlockAttestation};
+use proptest::prelude::*;
+
+/// Reads the fields of a canonical encoding back, in order
+struct Decoder<'a>(&'a [u8]);
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.0.len() < len {
+            return None;
+        }
+        let (field, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Some(field)
+    }
+
+    fn prefixed(&mut self) -> Option<Vec<u8>> {
+        let len = u32::from_le_bytes(self.take(4)?.try_into().ok()?);
+        Some(self.take(len as usize)?.to_vec())
+    }
+
+    fn string(&mut self) -> Option<String> {
+        String::from_utf8(self.prefixed()?).ok()
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.take(8)?.try_into().ok()?))
+    }
+
+    fn u128(&mut self) -> Option<u128> {
+        Some(u128::from_le_bytes(self.take(16)?.try_into().ok()?))
+    }
+
+    fn finish<T>(self, value: T) -> Option<T> {
+        self.0.is_empty().then_some(value)
+    }
+}
+
+fn decode_transfer(data: &[u8]) -> Option<TransferMessage> {
+    let mut decoder = Decoder(data);
+    let message = TransferMessage {
+        source_chain: decoder.string()?,
+        source_bridge: decoder.prefixed()?,
+        sequence: decoder.u64()?,
+        sender: decoder.prefixed()?,
+        token: decoder.prefixed()?,
+        amount: decoder.u128()?,
+        destination_chain: decoder.string()?,
+        recipient: decoder.string()?,
+    };
+    decoder.finish(message)
+}
+
+/// Decode the fields after the message ID
+fn decode_attestation(message_id: [u8; 32], data: &[u8]) -> Option<UnlockAttestation> {
+    let mut decoder = Decoder(data);
+    let attestation = UnlockAttestation {
+        message_id,
+        source_chain: decoder.string()?,
+        sequence: decoder.u64()?,
+        sender: decoder.string()?,
+        destination_chain: decoder.string()?,
+        destination_bridge: decoder.prefixed()?,
+        recipient: decoder.prefixed()?,
+        token: decoder.prefixed()?,
+        amount: decoder.u128()?,
+    };
+    decoder.finish(attestation)
+}
+
+fn decode_generic(data: &[u8]) -> Option<GenericMessage> {
+    let mut decoder = Decoder(data);
+    let message = GenericMessage {
+        source_chain: decoder.string()?,
+        source_bridge: decoder.prefixed()?,
+        sequence: decoder.u64()?,
+        sender: decoder.prefixed()?,
+        destination_chain: decoder.string()?,
+        target: decoder.string()?,
+        payload: decoder.prefixed()?,
+    };
+    decoder.finish(message)
+}
+
+fn bytes() -> impl Strategy<Value = Vec<u8>> {
+    proptest::collection::vec(any::<u8>(), 0..48)
+}
+
+fn text() -> impl Strategy<Value = String> {
+    "\\PC{0,24}"
+}
+
+prop_compose! {
+    fn transfer()(
+        source_chain in text(),
+        source_bridge in bytes(),
+        sequence in any::<u64>(),
+        sender in bytes(),
+        token in bytes(),
+        amount in any::<u128>(),
+        destination_chain in text(),
+        recipient in text(),
+    ) -> TransferMessage {
+        TransferMessage {
+            source_chain,
+            source_bridge,
+            sequence,
+            sender,
+            token,
+            amount,
+            destination_chain,
+            recipient,
+        }
+    }
+}
+
+prop_compose! {
+    fn attestation()(
+        message_id in any::<[u8; 32]>(),
+        source_chain in text(),
+        sequence in any::<u64>(),
+        sender in text(),
+        destination_chain in text(),
+        destination_bridge in bytes(),
+        recipient in bytes(),
+        token in bytes(),
+        amount in any::<u128>(),
+    ) -> UnlockAttestation {
+        UnlockAttestation {
+            message_id,
+            source_chain,
+            sequence,
+            sender,
+            destination_chain,
+            destination_bridge,
+            recipient,
+            token,
+            amount,
+        }
+    }
+}
+
+prop_compose! {
+    fn generic()(
+        source_chain in text(),
+        source_bridge in bytes(),
+        sequence in any::<u64>(),
+        sender in bytes(),
+        destination_chain in text(),
+        target in text(),
+        payload in bytes(),
+    ) -> GenericMessage {
+        GenericMessage {
+            source_chain,
+            source_bridge,
+            sequence,
+            sender,
+            destination_chain,
+            target,
+            payload,
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn transfer_encoding_round_trips(message in transfer()) {
+        prop_assert_eq!(decode_transfer(&message.encode()), Some(message));
+    }
+
+    #[test]
+    fn attestation_encoding_round_trips(attestation in attestation()) {
+        let encoded = attestation.encode();
+        prop_assert_eq!(decode_attestation(attestation.message_id, &encoded), Some(attestation));
+    }
+
+    #[test]
+    fn generic_encoding_round_trips(message in generic()) {
+        prop_assert_eq!(decode_generic(&message.encode()), Some(message));
+    }
+
+    #[test]
+    fn distinct_transfers_have_distinct_ids(first in transfer(), second in transfer()) {
+        prop_assume!(first != second);
+        prop_assert_ne!(first.id::<Sha3Keccak>(), second.id::<Sha3Keccak>());
+    }
+
+    #[test]
+    fn distinct_attestations_have_distinct_hashes(first in attestation(), second in attestation()) {
+        prop_assume!(first != second);
+        prop_assert_ne!(first.signing_hash::<Sha3Keccak>(), second.signing_hash::<Sha3Keccak>());
+    }
+
+    /// Moving bytes between neighbouring fields keeps their concatenation,
+    /// which is how the former decimal-concatenation IDs collided
+    #[test]
+    fn shifting_field_boundaries_changes_the_id(
+        mut message in transfer(),
+        split in any::<prop::sample::Index>(),
+    ) {
+        let joined = [message.sender.clone(), message.token.clone()].concat();
+        let split = split.index(joined.len() + 1);
+        prop_assume!(split != message.sender.len());
+
+        let original = message.id::<Sha3Keccak>();
+        message.sender = joined[..split].to_vec();
+        message.token = joined[split..].to_vec();
+        prop_assert_ne!(message.id::<Sha3Keccak>(), original);
+    }
+
+    #[test]
+    fn sequence_alone_separates_identical_transfers(message in transfer(), other in any::<u64>()) {
+        prop_assume!(other != message.sequence);
+        let next = TransferMessage { sequence: other, ..message.clone() };
+        prop_assert_ne!(next.id::<Sha3Keccak>(), message.id::<Sha3Keccak>());
+    }
+
+    /// Solana amounts are `u64` and NEAR amounts `u128`; both hash the
+    /// 16-byte little-endian value, so a Solana amount attests the same as
+    /// the equal NEAR amount
+    #[test]
+    fn amounts_encode_at_a_fixed_width(attestation in attestation(), amount in any::<u64>()) {
+        let attestation = UnlockAttestation { amount: u128::from(amount), ..attestation };
+        let encoded = attestation.encode();
+        let (_, tail) = encoded.split_at(encoded.len() - 16);
+        prop_assert_eq!(&tail[..8], &amount.to_le_bytes()[..]);
+        prop_assert_eq!(&tail[8..], &[0u8; 8][..]);
+    }
+}
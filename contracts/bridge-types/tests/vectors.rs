@@ -0,0 +1,257 @@
+use bridge_types::hash::{Keccak256, Sha3Keccak};
+use bridge_types::{
+    aggregate_signing_hash, chain, GenericMessage, MessageDeliveryAttestation, NftTransferMessage, NftUnlockAttestation,
+    TransferMessage, UnlockAttestation,
+};
+
+fn near_to_ethereum() -> TransferMessage {
+    TransferMessage {
+        source_chain: chain::NEAR.to_string(),
+        source_bridge: b"bridge.near".to_vec(),
+        sequence: 7,
+        sender: b"alice.near".to_vec(),
+        token: b"usdc.near".to_vec(),
+        amount: 1_000_000,
+        destination_chain: chain::ETHEREUM.to_string(),
+        recipient: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
+    }
+}
+
+fn solana_to_near() -> TransferMessage {
+    TransferMessage {
+        source_chain: chain::SOLANA.to_string(),
+        source_bridge: vec![0x11; 32],
+        sequence: 0,
+        sender: vec![0x22; 32],
+        token: vec![0x33; 32],
+        amount: u128::from(u64::MAX),
+        destination_chain: chain::NEAR.to_string(),
+        recipient: "alice.near".to_string(),
+    }
+}
+
+fn ethereum_to_near_unlock() -> UnlockAttestation {
+    UnlockAttestation {
+        message_id: [0xab; 32],
+        source_chain: chain::ETHEREUM.to_string(),
+        sequence: 7,
+        sender: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
+        destination_chain: chain::NEAR.to_string(),
+        destination_bridge: b"bridge.near".to_vec(),
+        recipient: b"alice.near".to_vec(),
+        token: b"usdc.near".to_vec(),
+        amount: 1_000_000,
+    }
+}
+
+fn near_nft_to_ethereum() -> NftTransferMessage {
+    NftTransferMessage {
+        source_chain: chain::NEAR.to_string(),
+        source_bridge: b"bridge.near".to_vec(),
+        sequence: 7,
+        sender: b"alice.near".to_vec(),
+        nft_contract: b"art.near".to_vec(),
+        token_id: "42".to_string(),
+        token_uri: "ipfs://bafybeigdyrzt/42.json".to_string(),
+        destination_chain: chain::ETHEREUM.to_string(),
+        recipient: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
+    }
+}
+
+fn ethereum_nft_to_near_unlock() -> NftUnlockAttestation {
+    NftUnlockAttestation {
+        message_id: [0xab; 32],
+        source_chain: chain::ETHEREUM.to_string(),
+        sender: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
+        destination_chain: chain::NEAR.to_string(),
+        destination_bridge: b"bridge.near".to_vec(),
+        recipient: b"alice.near".to_vec(),
+        nft_contract: b"punks.nft.bridge.near".to_vec(),
+        token_id: "42".to_string(),
+        token_uri: "ipfs://bafybeigdyrzt/42.json".to_string(),
+    }
+}
+
+fn near_message_to_solana() -> GenericMessage {
+    GenericMessage {
+        source_chain: chain::NEAR.to_string(),
+        source_bridge: b"bridge.near".to_vec(),
+        sequence: 7,
+        sender: b"alice.near".to_vec(),
+        destination_chain: chain::SOLANA.to_string(),
+        target: "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin".to_string(),
+        payload: b"{\"action\":\"ping\"}".to_vec(),
+    }
+}
+
+fn ethereum_message_delivery_on_near() -> MessageDeliveryAttestation {
+    MessageDeliveryAttestation {
+        message_id: [0xab; 32],
+        source_chain: chain::ETHEREUM.to_string(),
+        sender: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
+        destination_chain: chain::NEAR.to_string(),
+        destination_bridge: b"bridge.near".to_vec(),
+        target: b"app.near".to_vec(),
+        payload: b"{\"action\":\"ping\"}".to_vec(),
+    }
+}
+
+#[test]
+fn keccak_backend_is_original_keccak() {
+    // keccak256("") differs from sha3_256("")
+    assert_eq!(
+        hex::encode(Sha3Keccak::keccak256(b"")),
+        "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+    );
+}
+
+#[test]
+fn transfer_encoding() {
+    assert_eq!(
+        hex::encode(near_to_ethereum().encode()),
+        concat!(
+            "04000000", "6e656172", // "near"
+            "0b000000", "6272696467652e6e656172", // "bridge.near"
+            "0700000000000000", // sequence 7
+            "0a000000", "616c6963652e6e656172", // "alice.near"
+            "09000000", "757364632e6e656172", // "usdc.near"
+            "40420f00000000000000000000000000", // amount 1_000_000
+            "08000000", "657468657265756d", // "ethereum"
+            "29000000", // 41-byte recipient
+            "3078373432643335436336363334433035333239323561336238343442633965373539356630624562",
+        )
+    );
+}
+
+#[test]
+fn transfer_message_ids() {
+    assert_eq!(
+        hex::encode(near_to_ethereum().id::<Sha3Keccak>()),
+        "f624ae7e88cc0fb59c358a7d3003c5f942b1806a30a6b476dbc29a9fd631eb1b"
+    );
+    assert_eq!(
+        hex::encode(solana_to_near().id::<Sha3Keccak>()),
+        "7f1409111676f37649d5c136e72a210ec4557883daee8641c9ee8fc69a5e07ea"
+    );
+}
+
+#[test]
+fn unlock_attestation_hash() {
+    assert_eq!(
+        hex::encode(ethereum_to_near_unlock().signing_hash::<Sha3Keccak>()),
+        "14981c874ce3a90f2a6991630329ef67d3368bddec37ed6a2fa13b77d2592afc"
+    );
+}
+
+#[test]
+fn aggregate_hash_binds_signer_bitmap() {
+    let hash = ethereum_to_near_unlock().signing_hash::<Sha3Keccak>();
+    assert_eq!(
+        hex::encode(aggregate_signing_hash::<Sha3Keccak>(&hash, 0b111)),
+        "79685c0d611dee4a73dd1a35fe645203d072799106dec1c7720241b867af366c"
+    );
+    assert_ne!(
+        aggregate_signing_hash::<Sha3Keccak>(&hash, 0b111),
+        aggregate_signing_hash::<Sha3Keccak>(&hash, 0b011)
+    );
+}
+
+#[test]
+fn length_prefixes_separate_adjacent_fields() {
+    // Under the old decimal concatenation these produced the same ID
+    let mut first = near_to_ethereum();
+    first.amount = 12;
+    first.sequence = 3;
+    let mut second = near_to_ethereum();
+    second.amount = 1;
+    second.sequence = 23;
+    assert_ne!(first.id::<Sha3Keccak>(), second.id::<Sha3Keccak>());
+
+    // Moving bytes between neighbouring strings changes the encoding
+    let mut split = near_to_ethereum();
+    split.sender = b"alice.nearusdc".to_vec();
+    split.token = b".near".to_vec();
+    assert_ne!(split.encode(), near_to_ethereum().encode());
+}
+
+#[test]
+fn attestation_binds_destination_bridge() {
+    let mut other = ethereum_to_near_unlock();
+    other.destination_bridge = b"bridge2.near".to_vec();
+    assert_ne!(
+        other.signing_hash::<Sha3Keccak>(),
+        ethereum_to_near_unlock().signing_hash::<Sha3Keccak>()
+    );
+}
+
+#[test]
+fn attestation_binds_sequence() {
+    let mut other = ethereum_to_near_unlock();
+    other.sequence = 8;
+    assert_ne!(
+        other.signing_hash::<Sha3Keccak>(),
+        ethereum_to_near_unlock().signing_hash::<Sha3Keccak>()
+    );
+}
+
+#[test]
+fn nft_message_id_and_unlock_hash() {
+    assert_eq!(
+        hex::encode(near_nft_to_ethereum().id::<Sha3Keccak>()),
+        "74bfb71211c0678bbff6cf69d2bb24fb51a4296fd4c8a7ec43365766afacab40"
+    );
+    assert_eq!(
+        hex::encode(ethereum_nft_to_near_unlock().signing_hash::<Sha3Keccak>()),
+        "cb453551cdbe8343dc578baf9cb15651daf98080eddedcb320ff3556e934bb02"
+    );
+}
+
+#[test]
+fn nft_hashes_bind_token_and_metadata() {
+    let mut other = near_nft_to_ethereum();
+    other.token_id = "43".to_string();
+    assert_ne!(other.id::<Sha3Keccak>(), near_nft_to_ethereum().id::<Sha3Keccak>());
+
+    let mut other = ethereum_nft_to_near_unlock();
+    other.token_uri = "ipfs://bafybeigdyrzt/43.json".to_string();
+    assert_ne!(
+        other.signing_hash::<Sha3Keccak>(),
+        ethereum_nft_to_near_unlock().signing_hash::<Sha3Keccak>()
+    );
+
+    // Moving bytes between the token ID and URI changes the encoding
+    let mut split = ethereum_nft_to_near_unlock();
+    split.token_id = "42ipfs:".to_string();
+    split.token_uri = "//bafybeigdyrzt/42.json".to_string();
+    assert_ne!(split.encode(), ethereum_nft_to_near_unlock().encode());
+}
+
+#[test]
+fn generic_message_id_and_delivery_hash() {
+    assert_eq!(
+        hex::encode(near_message_to_solana().id::<Sha3Keccak>()),
+        "c1bc5706f4c064a93b56e1a05cb34f4b3a60ea6d9b1678438dd91da18b356fd5"
+    );
+    assert_eq!(
+        hex::encode(ethereum_message_delivery_on_near().signing_hash::<Sha3Keccak>()),
+        "bb55b67b419ffeb9d41bc3c7207b517c583e1368591d6ae93678396ff1ded5be"
+    );
+}
+
+#[test]
+fn generic_hashes_bind_target_and_payload() {
+    let mut other = ethereum_message_delivery_on_near();
+    other.destination_bridge = b"other-bridge.near".to_vec();
+    assert_ne!(
+        other.signing_hash::<Sha3Keccak>(),
+        ethereum_message_delivery_on_near().signing_hash::<Sha3Keccak>()
+    );
+
+    // Moving bytes between the target and the payload changes the encoding,
+    // which the former concatenated message hashes did not
+    let mut split = near_message_to_solana();
+    split.target.push('{');
+    split.payload.remove(0);
+    assert_ne!(split.encode(), near_message_to_solana().encode());
+    assert_ne!(split.id::<Sha3Keccak>(), near_message_to_solana().id::<Sha3Keccak>());
+}
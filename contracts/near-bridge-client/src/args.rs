@@ -0,0 +1,419 @@
+//! JSON arguments of the `BridgeContract` methods
+
+use near_bridge::types::{
+    BridgeConfig, BridgedNftCollection, LockRecord, NftEscrow, StorageBalance,
+};
+use near_bridge::{DELIVERED_DOMAIN, NATIVE_TOKEN, REFUND_DOMAIN};
+use near_sdk::json_types::U128;
+use near_sdk::serde::de::DeserializeOwned;
+use near_sdk::{AccountId, PublicKey};
+use serde::Serialize;
+use crate::hash::{lock_outcome_hash, nft_unlock_message_hash, unlock_message_hash};
+use crate::key;
+use crate::{
+    AggregateSignature, FtLockMessage, MessageId, NftLockMessage, Signature, SignatureMode,
+};
+
+/// A change method and its arguments
+pub trait ChangeMethod: Serialize {
+    const NAME: &'static str;
+
+    /// Arguments as the JSON the contract expects
+    fn args(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("arguments serialize to JSON")
+    }
+}
+
+/// A view method, its arguments and its return type
+pub trait ViewMethod: Serialize {
+    const NAME: &'static str;
+    type Output: DeserializeOwned;
+
+    /// Arguments as the JSON the contract expects
+    fn args(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("arguments serialize to JSON")
+    }
+}
+
+macro_rules! change_method {
+    ($name:literal, $args:ident) => {
+        impl ChangeMethod for $args {
+            const NAME: &'static str = $name;
+        }
+    };
+}
+
+macro_rules! view_method {
+    ($name:literal, $args:ident, $output:ty) => {
+        impl ViewMethod for $args {
+            const NAME: &'static str = $name;
+            type Output = $output;
+        }
+    };
+}
+
+/// `new`: initialize the contract
+#[derive(Serialize, Clone, Debug)]
+pub struct New {
+    pub owner: AccountId,
+    #[serde(serialize_with = "key::serialize_vec")]
+    pub validators: Vec<PublicKey>,
+    pub required_signatures: u8,
+}
+change_method!("new", New);
+
+/// `storage_deposit`: pay for the lock records of `account_id` (the caller
+/// by default)
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct StorageDeposit {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_id: Option<AccountId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub registration_only: Option<bool>,
+}
+change_method!("storage_deposit", StorageDeposit);
+
+/// `ft_transfer_call`, called on a NEP-141 contract. Sent to the bridge, it
+/// locks `amount` for delivery as described by `msg`.
+#[derive(Serialize, Clone, Debug)]
+pub struct FtTransferCall {
+    pub receiver_id: AccountId,
+    pub amount: U128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+    pub msg: String,
+}
+change_method!("ft_transfer_call", FtTransferCall);
+
+impl FtTransferCall {
+    /// Lock `amount` of the called token with the bridge at `bridge`
+    pub fn lock(bridge: AccountId, amount: U128, lock: &FtLockMessage) -> Self {
+        Self {
+            receiver_id: bridge,
+            amount,
+            memo: None,
+            msg: serde_json::to_string(lock).expect("lock message serializes to JSON"),
+        }
+    }
+}
+
+/// `lock_near`: lock `amount`, or the whole attached deposit when `None`, for
+/// delivery to `destination_address`. The rest of the deposit pays for the
+/// lock record and the surplus is refunded.
+#[derive(Serialize, Clone, Debug)]
+pub struct LockNear {
+    pub destination_chain: String,
+    pub destination_address: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<U128>,
+}
+change_method!("lock_near", LockNear);
+
+/// `unlock_ft`: release an inbound NEP-141 transfer
+#[derive(Serialize, Clone, Debug)]
+pub struct UnlockFt {
+    pub message_id: MessageId,
+    pub source_chain: String,
+    pub sequence: u64,
+    pub sender_address: String,
+    pub recipient: AccountId,
+    pub token_contract: AccountId,
+    pub amount: U128,
+    pub signatures: Vec<Signature>,
+    pub aggregate_signature: Option<AggregateSignature>,
+}
+change_method!("unlock_ft", UnlockFt);
+
+impl UnlockFt {
+    /// Hash validators sign for this unlock on the contract at `bridge`
+    pub fn message_hash(&self, bridge: &AccountId) -> [u8; 32] {
+        unlock_message_hash(
+            bridge,
+            &self.message_id,
+            &self.source_chain,
+            self.sequence,
+            &self.sender_address,
+            &self.recipient,
+            &self.token_contract,
+            self.amount.0,
+        )
+    }
+}
+
+/// `unlock_near`: release an inbound native NEAR transfer
+#[derive(Serialize, Clone, Debug)]
+pub struct UnlockNear {
+    pub message_id: MessageId,
+    pub source_chain: String,
+    pub sequence: u64,
+    pub sender_address: String,
+    pub recipient: AccountId,
+    pub amount: U128,
+    pub signatures: Vec<Signature>,
+    pub aggregate_signature: Option<AggregateSignature>,
+}
+change_method!("unlock_near", UnlockNear);
+
+impl UnlockNear {
+    /// Hash validators sign for this unlock on the contract at `bridge`
+    pub fn message_hash(&self, bridge: &AccountId) -> [u8; 32] {
+        unlock_message_hash(
+            bridge,
+            &self.message_id,
+            &self.source_chain,
+            self.sequence,
+            &self.sender_address,
+            &self.recipient,
+            &NATIVE_TOKEN.parse().expect("valid account ID"),
+            self.amount.0,
+        )
+    }
+}
+
+/// `nft_transfer_call`, called on a NEP-171 contract. Sent to the bridge, it
+/// escrows `token_id` for delivery as described by `msg`.
+#[derive(Serialize, Clone, Debug)]
+pub struct NftTransferCall {
+    pub receiver_id: AccountId,
+    pub token_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub approval_id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+    pub msg: String,
+}
+change_method!("nft_transfer_call", NftTransferCall);
+
+impl NftTransferCall {
+    /// Lock `token_id` of the called collection with the bridge at `bridge`
+    pub fn lock(bridge: AccountId, token_id: String, lock: &NftLockMessage) -> Self {
+        Self {
+            receiver_id: bridge,
+            token_id,
+            approval_id: None,
+            memo: None,
+            msg: serde_json::to_string(lock).expect("lock message serializes to JSON"),
+        }
+    }
+}
+
+/// `unlock_nft`: release an inbound NFT from escrow, or mint it into its
+/// bridged collection
+#[derive(Serialize, Clone, Debug)]
+pub struct UnlockNft {
+    pub message_id: MessageId,
+    pub source_chain: String,
+    pub sender_address: String,
+    pub recipient: AccountId,
+    pub nft_contract: AccountId,
+    pub token_id: String,
+    pub token_uri: String,
+    pub signatures: Vec<Signature>,
+    pub aggregate_signature: Option<AggregateSignature>,
+}
+change_method!("unlock_nft", UnlockNft);
+
+impl UnlockNft {
+    /// Hash validators sign for this unlock on the contract at `bridge`
+    pub fn message_hash(&self, bridge: &AccountId) -> [u8; 32] {
+        nft_unlock_message_hash(
+            bridge,
+            &self.message_id,
+            &self.source_chain,
+            &self.sender_address,
+            &self.recipient,
+            &self.nft_contract,
+            &self.token_id,
+            &self.token_uri,
+        )
+    }
+}
+
+/// `claim_unlock_fallback`: send the caller the unlock-and-call fallbacks of
+/// `token_contract` that could not be sent to it
+#[derive(Serialize, Clone, Debug)]
+pub struct ClaimUnlockFallback {
+    pub token_contract: AccountId,
+}
+change_method!("claim_unlock_fallback", ClaimUnlockFallback);
+
+/// `refund`: return a pending lock to its sender after the validators attest
+/// that delivery failed
+#[derive(Serialize, Clone, Debug)]
+pub struct Refund {
+    pub message_id: MessageId,
+    pub signatures: Vec<Signature>,
+    pub aggregate_signature: Option<AggregateSignature>,
+}
+change_method!("refund", Refund);
+
+impl Refund {
+    /// Hash validators sign to refund `lock_record` on the contract at `bridge`
+    pub fn message_hash(bridge: &AccountId, lock_record: &LockRecord) -> [u8; 32] {
+        lock_outcome_hash(bridge, REFUND_DOMAIN, lock_record, &[])
+    }
+}
+
+/// `acknowledge_delivery`: mark a pending lock as delivered by
+/// `destination_tx` on the destination chain
+#[derive(Serialize, Clone, Debug)]
+pub struct AcknowledgeDelivery {
+    pub message_id: MessageId,
+    pub destination_tx: String,
+    pub signatures: Vec<Signature>,
+    pub aggregate_signature: Option<AggregateSignature>,
+}
+change_method!("acknowledge_delivery", AcknowledgeDelivery);
+
+impl AcknowledgeDelivery {
+    /// Hash validators sign to mark `lock_record` as delivered by
+    /// `destination_tx` on the contract at `bridge`
+    pub fn message_hash(
+        bridge: &AccountId,
+        lock_record: &LockRecord,
+        destination_tx: &str,
+    ) -> [u8; 32] {
+        lock_outcome_hash(bridge, DELIVERED_DOMAIN, lock_record, destination_tx.as_bytes())
+    }
+}
+
+// ===== Admin methods =====
+
+/// `add_validator`
+#[derive(Serialize, Clone, Debug)]
+pub struct AddValidator {
+    #[serde(serialize_with = "key::serialize")]
+    pub validator: PublicKey,
+}
+change_method!("add_validator", AddValidator);
+
+/// `remove_validator`
+#[derive(Serialize, Clone, Debug)]
+pub struct RemoveValidator {
+    #[serde(serialize_with = "key::serialize")]
+    pub validator: PublicKey,
+}
+change_method!("remove_validator", RemoveValidator);
+
+/// `update_required_signatures`
+#[derive(Serialize, Clone, Debug)]
+pub struct UpdateRequiredSignatures {
+    pub required_signatures: u8,
+}
+change_method!("update_required_signatures", UpdateRequiredSignatures);
+
+/// `set_group_key`
+#[derive(Serialize, Clone, Debug)]
+pub struct SetGroupKey {
+    #[serde(serialize_with = "key::serialize")]
+    pub public_key: PublicKey,
+    pub threshold: u8,
+    pub group_size: u8,
+}
+change_method!("set_group_key", SetGroupKey);
+
+/// `set_signature_mode`
+#[derive(Serialize, Clone, Debug)]
+pub struct SetSignatureMode {
+    pub mode: SignatureMode,
+}
+change_method!("set_signature_mode", SetSignatureMode);
+
+/// `register_bridged_nft_collection`
+#[derive(Serialize, Clone, Debug)]
+pub struct RegisterBridgedNftCollection {
+    pub collection: AccountId,
+    pub source_chain: String,
+    pub source_contract: String,
+}
+change_method!("register_bridged_nft_collection", RegisterBridgedNftCollection);
+
+/// `pause`
+#[derive(Serialize, Clone, Debug)]
+pub struct Pause {}
+change_method!("pause", Pause);
+
+/// `unpause`
+#[derive(Serialize, Clone, Debug)]
+pub struct Unpause {}
+change_method!("unpause", Unpause);
+
+/// `transfer_ownership`
+#[derive(Serialize, Clone, Debug)]
+pub struct TransferOwnership {
+    pub new_owner: AccountId,
+}
+change_method!("transfer_ownership", TransferOwnership);
+
+// ===== View methods =====
+
+/// `get_config`
+#[derive(Serialize, Clone, Debug)]
+pub struct GetConfig {}
+view_method!("get_config", GetConfig, BridgeConfig);
+
+/// `is_message_processed`
+#[derive(Serialize, Clone, Debug)]
+pub struct IsMessageProcessed {
+    pub message_id: MessageId,
+}
+view_method!("is_message_processed", IsMessageProcessed, bool);
+
+/// `is_transfer_processed`
+#[derive(Serialize, Clone, Debug)]
+pub struct IsTransferProcessed {
+    pub source_chain: String,
+    pub sequence: u64,
+}
+view_method!("is_transfer_processed", IsTransferProcessed, bool);
+
+/// `get_lock_record`
+#[derive(Serialize, Clone, Debug)]
+pub struct GetLockRecord {
+    pub message_id: MessageId,
+}
+view_method!("get_lock_record", GetLockRecord, Option<LockRecord>);
+
+/// `get_total_locked`
+#[derive(Serialize, Clone, Debug)]
+pub struct GetTotalLocked {
+    pub token_contract: AccountId,
+}
+view_method!("get_total_locked", GetTotalLocked, U128);
+
+/// `get_total_unlocked`
+#[derive(Serialize, Clone, Debug)]
+pub struct GetTotalUnlocked {
+    pub token_contract: AccountId,
+}
+view_method!("get_total_unlocked", GetTotalUnlocked, U128);
+
+/// `get_unclaimed_fallback`
+#[derive(Serialize, Clone, Debug)]
+pub struct GetUnclaimedFallback {
+    pub account_id: AccountId,
+    pub token_contract: AccountId,
+}
+view_method!("get_unclaimed_fallback", GetUnclaimedFallback, U128);
+
+/// `get_escrowed_nft`
+#[derive(Serialize, Clone, Debug)]
+pub struct GetEscrowedNft {
+    pub nft_contract: AccountId,
+    pub token_id: String,
+}
+view_method!("get_escrowed_nft", GetEscrowedNft, Option<NftEscrow>);
+
+/// `get_bridged_nft_collection`
+#[derive(Serialize, Clone, Debug)]
+pub struct GetBridgedNftCollection {
+    pub collection: AccountId,
+}
+view_method!("get_bridged_nft_collection", GetBridgedNftCollection, Option<BridgedNftCollection>);
+
+/// `storage_balance_of`
+#[derive(Serialize, Clone, Debug)]
+pub struct StorageBalanceOf {
+    pub account_id: AccountId,
+}
+view_method!("storage_balance_of", StorageBalanceOf, Option<StorageBalance>);
@@ -0,0 +1,10 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ClientError {
+    #[error("invalid event JSON: {0}")]
+    InvalidEvent(#[from] serde_json::Error),
+
+    #[error("unsupported event version {0}")]
+    UnsupportedEventVersion(String),
+}
@@ -0,0 +1,55 @@
+//! Parser for the NEP-297 logs emitted by `events.rs` in the contract
+
+use near_bridge::events::{BridgeEvent, EVENT_STANDARD, EVENT_VERSION};
+use serde::Deserialize;
+use crate::error::ClientError;
+
+pub const EVENT_JSON_PREFIX: &str = "EVENT_JSON:";
+
+/// NEP-297 envelope around a `BridgeEvent`
+#[derive(Deserialize, Debug)]
+pub struct EventLog {
+    pub standard: String,
+    pub version: String,
+    #[serde(flatten)]
+    pub event: BridgeEvent,
+}
+
+#[derive(Deserialize)]
+struct Envelope {
+    standard: String,
+    version: String,
+}
+
+/// Parse a single log line. Returns `None` for plain logs and for events of
+/// other standards, e.g. NEP-141 transfers logged in the same receipt.
+pub fn parse_log(log: &str) -> Result<Option<EventLog>, ClientError> {
+    let Some(json) = log.strip_prefix(EVENT_JSON_PREFIX) else {
+        return Ok(None);
+    };
+
+    let envelope: Envelope = serde_json::from_str(json)?;
+    if envelope.standard != EVENT_STANDARD {
+        return Ok(None);
+    }
+    if major(&envelope.version) != major(EVENT_VERSION) {
+        return Err(ClientError::UnsupportedEventVersion(envelope.version));
+    }
+
+    Ok(Some(serde_json::from_str(json)?))
+}
+
+/// Parse every bridge event in a transaction's logs, in order
+pub fn parse_logs<S: AsRef<str>>(logs: &[S]) -> Result<Vec<BridgeEvent>, ClientError> {
+    let mut events = Vec::new();
+    for log in logs {
+        if let Some(log) = parse_log(log.as_ref())? {
+            events.push(log.event);
+        }
+    }
+    Ok(events)
+}
+
+fn major(version: &str) -> &str {
+    version.split('.').next().unwrap_or_default()
+}
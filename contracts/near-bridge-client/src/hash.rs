@@ -0,0 +1,157 @@
+//! Off-chain computation of the hashes the contract derives on-chain
+
+use bridge_types::hash::{Keccak256, Sha3Keccak};
+use bridge_types::{
+    chain, NftTransferMessage, NftUnlockAttestation, TransferMessage, UnlockAttestation,
+};
+use near_bridge::types::LockRecord;
+use near_sdk::{AccountId, CurveType, PublicKey};
+use crate::{MessageId, Signature};
+
+/// Attestation hash the contract deployed at `bridge` verifies before
+/// releasing `amount` of `token` to `recipient`. Mirrors
+/// `BridgeContract::create_unlock_message_hash`.
+#[allow(clippy::too_many_arguments)]
+pub fn unlock_message_hash(
+    bridge: &AccountId,
+    message_id: &MessageId,
+    source_chain: &str,
+    sequence: u64,
+    sender_address: &str,
+    recipient: &AccountId,
+    token: &AccountId,
+    amount: u128,
+) -> [u8; 32] {
+    UnlockAttestation {
+        message_id: *message_id,
+        source_chain: source_chain.to_string(),
+        sequence,
+        sender: sender_address.to_string(),
+        destination_chain: chain::NEAR.to_string(),
+        destination_bridge: bridge.as_str().as_bytes().to_vec(),
+        recipient: recipient.as_str().as_bytes().to_vec(),
+        token: token.as_str().as_bytes().to_vec(),
+        amount,
+    }
+    .signing_hash::<Sha3Keccak>()
+}
+
+/// Message ID the contract at `bridge` assigns to a lock without an attached
+/// call, made when its `message_count` equals `sequence`
+pub fn lock_message_id(
+    bridge: &AccountId,
+    sequence: u64,
+    sender: &AccountId,
+    token: &AccountId,
+    amount: u128,
+    destination_chain: &str,
+    destination_address: &str,
+) -> MessageId {
+    TransferMessage {
+        source_chain: chain::NEAR.to_string(),
+        source_bridge: bridge.as_str().as_bytes().to_vec(),
+        sequence,
+        sender: sender.as_str().as_bytes().to_vec(),
+        token: token.as_str().as_bytes().to_vec(),
+        amount,
+        destination_chain: destination_chain.to_string(),
+        recipient: destination_address.to_string(),
+    }
+    .id::<Sha3Keccak>()
+}
+
+/// Attestation hash the contract deployed at `bridge` verifies before
+/// releasing or minting `token_id` of `nft_contract` to `recipient`. Mirrors
+/// `BridgeContract::create_nft_unlock_message_hash`.
+#[allow(clippy::too_many_arguments)]
+pub fn nft_unlock_message_hash(
+    bridge: &AccountId,
+    message_id: &MessageId,
+    source_chain: &str,
+    sender_address: &str,
+    recipient: &AccountId,
+    nft_contract: &AccountId,
+    token_id: &str,
+    token_uri: &str,
+) -> [u8; 32] {
+    NftUnlockAttestation {
+        message_id: *message_id,
+        source_chain: source_chain.to_string(),
+        sender: sender_address.to_string(),
+        destination_chain: chain::NEAR.to_string(),
+        destination_bridge: bridge.as_str().as_bytes().to_vec(),
+        recipient: recipient.as_str().as_bytes().to_vec(),
+        nft_contract: nft_contract.as_str().as_bytes().to_vec(),
+        token_id: token_id.to_string(),
+        token_uri: token_uri.to_string(),
+    }
+    .signing_hash::<Sha3Keccak>()
+}
+
+/// Message ID the contract at `bridge` assigns to an NFT lock made when its
+/// `message_count` equals `sequence`. NFT and token locks share the sequence.
+#[allow(clippy::too_many_arguments)]
+pub fn nft_lock_message_id(
+    bridge: &AccountId,
+    sequence: u64,
+    sender: &AccountId,
+    nft_contract: &AccountId,
+    token_id: &str,
+    token_uri: &str,
+    destination_chain: &str,
+    destination_address: &str,
+) -> MessageId {
+    NftTransferMessage {
+        source_chain: chain::NEAR.to_string(),
+        source_bridge: bridge.as_str().as_bytes().to_vec(),
+        sequence,
+        sender: sender.as_str().as_bytes().to_vec(),
+        nft_contract: nft_contract.as_str().as_bytes().to_vec(),
+        token_id: token_id.to_string(),
+        token_uri: token_uri.to_string(),
+        destination_chain: destination_chain.to_string(),
+        recipient: destination_address.to_string(),
+    }
+    .id::<Sha3Keccak>()
+}
+
+/// Hash validators sign to attest the outcome of `lock_record` on the
+/// destination chain: `near_bridge::REFUND_DOMAIN`, `EXPIRED_DOMAIN` or
+/// `DELIVERED_DOMAIN`, with the delivering transaction as `extra` for the
+/// latter. Mirrors `BridgeContract::create_lock_outcome_hash`.
+pub fn lock_outcome_hash(
+    bridge: &AccountId,
+    domain: &[u8],
+    lock_record: &LockRecord,
+    extra: &[u8],
+) -> [u8; 32] {
+    let mut data = Vec::new();
+    data.extend_from_slice(domain);
+    data.extend_from_slice(bridge.as_str().as_bytes());
+    data.extend_from_slice(&lock_record.message_id);
+    data.extend_from_slice(lock_record.sender.as_str().as_bytes());
+    data.extend_from_slice(lock_record.token_contract.as_str().as_bytes());
+    data.extend_from_slice(&lock_record.amount.to_le_bytes());
+    data.extend_from_slice(extra);
+
+    Sha3Keccak::keccak256(&data)
+}
+
+/// Validator signature in the form `unlock_*` expects
+pub fn ed25519_signature(public_key: [u8; 32], signature: [u8; 64]) -> Signature {
+    Signature {
+        public_key: ed25519_public_key(public_key),
+        signature: signature.to_vec(),
+    }
+}
+
+/// NEAR public key of a raw Ed25519 key
+pub fn ed25519_public_key(public_key: [u8; 32]) -> PublicKey {
+    PublicKey::from_parts(CurveType::ED25519, public_key.to_vec())
+        .expect("32 bytes is a valid Ed25519 key")
+}
+
+/// Lowercase hex, as message IDs appear in events
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
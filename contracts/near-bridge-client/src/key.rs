@@ -0,0 +1,55 @@
+//! Public keys and signatures in contract arguments
+//!
+//! `near_sdk::PublicKey` serializes through `PublicKey::curve_type`, which
+//! aborts through the NEAR host on an unknown curve byte and so links only
+//! inside a contract or against near-sdk's mocked blockchain. Arguments
+//! built here spell keys from their bytes instead, in the same
+//! `<curve>:<base58>` form.
+
+use near_sdk::PublicKey;
+use serde::{Deserialize, Serialize, Serializer};
+
+/// Validator signature in the form `unlock_*` expects
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Signature {
+    #[serde(serialize_with = "serialize")]
+    pub public_key: PublicKey,
+    pub signature: Vec<u8>,
+}
+
+/// `<curve>:<base58>` form of `key`, as the contract parses it
+pub fn to_string(key: &PublicKey) -> String {
+    let (curve, data) = key.as_bytes().split_first().expect("public key has a curve byte");
+    let curve = match curve {
+        0 => "ed25519",
+        1 => "secp256k1",
+        _ => unreachable!("near_sdk::PublicKey only holds known curves"),
+    };
+    format!("{}:{}", curve, bs58::encode(data).into_string())
+}
+
+/// `serialize_with` for a `PublicKey` field
+pub fn serialize<S: Serializer>(key: &PublicKey, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&to_string(key))
+}
+
+/// `serialize_with` for a `Vec<PublicKey>` field
+pub fn serialize_vec<S: Serializer>(
+    keys: &[PublicKey],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(keys.iter().map(to_string))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_near_sdk_encoding() {
+        let key: PublicKey =
+            "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp".parse().unwrap();
+
+        assert_eq!(to_string(&key), String::from(&key));
+    }
+}
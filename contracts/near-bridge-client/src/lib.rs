@@ -0,0 +1,23 @@
+//! Off-chain client for the NEAR bridge contract
+//!
+//! Typed JSON arguments for the `BridgeContract` methods, the token and NFT
+//! unlock attestation hashes validators sign, and parsers for the contract's
+//! `EVENT_JSON:` logs and `BridgeError` failures. Transport is left to the
+//! caller: every method exposes its name and serialized arguments for use
+//! with any RPC client.
+
+pub mod args;
+pub mod error;
+pub mod events;
+pub mod hash;
+pub mod key;
+
+pub use args::{ChangeMethod, ViewMethod};
+pub use error::ClientError;
+pub use events::{parse_log, parse_logs, EventLog};
+pub use key::Signature;
+pub use near_bridge::events::BridgeEvent;
+pub use near_bridge::{BridgeError, NATIVE_TOKEN};
+pub use near_bridge::types::{
+    AggregateSignature, CallData, FtLockMessage, MessageId, NftLockMessage, SignatureMode,
+};
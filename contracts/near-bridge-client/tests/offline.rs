@@ -0,0 +1,150 @@
+use near_bridge::events::{TokenLockedEvent, ValidatorChangeEvent};
+use near_bridge_client::args::{ChangeMethod, LockNear, NftTransferCall, UnlockFt, UnlockNft};
+use near_bridge_client::hash::{ed25519_public_key, hex};
+use near_bridge_client::{
+    parse_log, parse_logs, BridgeError, BridgeEvent, ClientError, NftLockMessage,
+};
+use near_sdk::json_types::U128;
+
+#[test]
+fn unlock_hash_matches_golden_vector() {
+    // Same inputs as `ethereum_to_near_unlock` in bridge-types/tests/vectors.rs
+    let unlock = UnlockFt {
+        message_id: [0xab; 32],
+        source_chain: "ethereum".to_string(),
+        sequence: 7,
+        sender_address: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
+        recipient: "alice.near".parse().unwrap(),
+        token_contract: "usdc.near".parse().unwrap(),
+        amount: U128(1_000_000),
+        signatures: Vec::new(),
+        aggregate_signature: None,
+    };
+
+    assert_eq!(
+        hex(&unlock.message_hash(&"bridge.near".parse().unwrap())),
+        "14981c874ce3a90f2a6991630329ef67d3368bddec37ed6a2fa13b77d2592afc"
+    );
+}
+
+#[test]
+fn nft_unlock_hash_matches_golden_vector() {
+    // Same inputs as `ethereum_nft_to_near_unlock` in bridge-types/tests/vectors.rs
+    let unlock = UnlockNft {
+        message_id: [0xab; 32],
+        source_chain: "ethereum".to_string(),
+        sender_address: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
+        recipient: "alice.near".parse().unwrap(),
+        nft_contract: "punks.nft.bridge.near".parse().unwrap(),
+        token_id: "42".to_string(),
+        token_uri: "ipfs://bafybeigdyrzt/42.json".to_string(),
+        signatures: Vec::new(),
+        aggregate_signature: None,
+    };
+
+    assert_eq!(
+        hex(&unlock.message_hash(&"bridge.near".parse().unwrap())),
+        "cb453551cdbe8343dc578baf9cb15651daf98080eddedcb320ff3556e934bb02"
+    );
+}
+
+#[test]
+fn nft_lock_serializes_msg() {
+    let lock = NftLockMessage {
+        destination_chain: "ethereum".to_string(),
+        destination_address: "0xabc".to_string(),
+    };
+    let args = NftTransferCall::lock("bridge.near".parse().unwrap(), "42".to_string(), &lock);
+
+    assert_eq!(NftTransferCall::NAME, "nft_transfer_call");
+    assert_eq!(
+        String::from_utf8(args.args()).unwrap(),
+        concat!(
+            r#"{"receiver_id":"bridge.near","token_id":"42","#,
+            r#""msg":"{\"destination_chain\":\"ethereum\",\"destination_address\":\"0xabc\"}"}"#,
+        )
+    );
+}
+
+#[test]
+fn args_serialize_as_contract_json() {
+    let args = LockNear {
+        destination_chain: "ethereum".to_string(),
+        destination_address: "0xabc".to_string(),
+        amount: None,
+    };
+    assert_eq!(LockNear::NAME, "lock_near");
+    assert_eq!(
+        String::from_utf8(args.args()).unwrap(),
+        r#"{"destination_chain":"ethereum","destination_address":"0xabc"}"#
+    );
+
+    let args = LockNear { amount: Some(U128(5)), ..args };
+    assert_eq!(
+        String::from_utf8(args.args()).unwrap(),
+        r#"{"destination_chain":"ethereum","destination_address":"0xabc","amount":"5"}"#
+    );
+}
+
+#[test]
+fn parse_token_locked_round_trip() {
+    let event = BridgeEvent::TokenLocked(TokenLockedEvent {
+        message_id: "ab".repeat(32),
+        sender: "alice.near".parse().unwrap(),
+        recipient: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
+        token: "usdc.near".parse().unwrap(),
+        amount: U128(42),
+        destination_chain: "ethereum".to_string(),
+        nonce: 3,
+        timestamp: 1_700_000_000_000_000_000,
+        call: None,
+    });
+    let log = format!("EVENT_JSON:{}", event.to_json());
+
+    let parsed = parse_log(&log).unwrap().unwrap();
+    assert_eq!(parsed.standard, "bridge");
+    let BridgeEvent::TokenLocked(locked) = parsed.event else {
+        panic!("expected token_locked");
+    };
+    assert_eq!(locked.message_id, "ab".repeat(32));
+    assert_eq!(locked.sender.as_str(), "alice.near");
+    assert_eq!(locked.amount, U128(42));
+    assert_eq!(locked.nonce, 3);
+}
+
+#[test]
+fn parse_logs_skips_other_logs() {
+    let validator = BridgeEvent::ValidatorAdded(ValidatorChangeEvent {
+        validator: ed25519_public_key([7; 32]),
+        validator_count: 4,
+        updated_by: "owner.near".parse().unwrap(),
+    });
+    let logs = vec![
+        "Tokens locked: amount=1, destination=ethereum".to_string(),
+        r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_transfer","data":[]}"#.to_string(),
+        format!("EVENT_JSON:{}", validator.to_json()),
+    ];
+
+    let events = parse_logs(&logs).unwrap();
+    assert_eq!(events.len(), 1);
+    assert!(matches!(&events[0], BridgeEvent::ValidatorAdded(event) if event.validator_count == 4));
+}
+
+#[test]
+fn parse_log_rejects_unknown_major_version() {
+    let log = r#"EVENT_JSON:{"standard":"bridge","version":"1.0.0","event":"token_locked","data":{}}"#;
+    assert!(matches!(parse_log(log), Err(ClientError::UnsupportedEventVersion(version)) if version == "1.0.0"));
+
+    let log = r#"EVENT_JSON:{"standard":"bridge","version":"2.0.0","event":"token_locked","data":{}}"#;
+    assert!(matches!(parse_log(log), Err(ClientError::InvalidEvent(_))));
+}
+
+#[test]
+fn parse_contract_failure() {
+    let failure = "Action #0: ExecutionError(\"Smart contract panicked: \
+                   BridgeError::LockNotPending: Lock is not pending\")";
+    assert_eq!(BridgeError::parse(failure), Some(BridgeError::LockNotPending));
+    assert_eq!(BridgeError::LockNotPending.message(), "Lock is not pending");
+
+    assert_eq!(BridgeError::parse("Smart contract panicked: Lock is not pending"), None);
+}
@@ -0,0 +1,216 @@
+//! Sandbox tests against the compiled contract. Build it first with
+//! `contracts/near/build.sh`.
+
+use ed25519_dalek::{Keypair, PublicKey, SecretKey, Signer};
+use near_bridge::types::BridgeConfig;
+use near_bridge_client::args::{
+    ChangeMethod, GetConfig, IsTransferProcessed, LockNear, New, Pause, StorageDeposit, UnlockNear,
+    Unpause, ViewMethod,
+};
+use near_bridge_client::hash::{ed25519_public_key, ed25519_signature, hex, lock_message_id};
+use near_bridge_client::{parse_logs, BridgeError, BridgeEvent, Signature};
+use near_sdk::json_types::U128;
+use near_sdk::AccountId;
+use near_workspaces::network::Sandbox;
+use near_workspaces::result::ExecutionFinalResult;
+use near_workspaces::types::NearToken;
+use near_workspaces::{Account, Contract, Worker};
+
+const WASM_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../near/res/near_bridge.wasm");
+
+struct Bridge {
+    worker: Worker<Sandbox>,
+    contract: Contract,
+    owner: Account,
+    validators: Vec<Keypair>,
+}
+
+impl Bridge {
+    /// Deploy the bridge with two of three validators required
+    async fn deploy() -> anyhow::Result<Self> {
+        let worker = near_workspaces::sandbox().await?;
+        let wasm = std::fs::read(WASM_PATH)
+            .map_err(|err| anyhow::anyhow!("{WASM_PATH}: {err}; run contracts/near/build.sh"))?;
+        let contract = worker.dev_deploy(&wasm).await?;
+        let owner = worker.dev_create_account().await?;
+
+        let validators: Vec<Keypair> = (1..=3u8)
+            .map(|seed| {
+                let secret = SecretKey::from_bytes(&[seed; 32]).unwrap();
+                Keypair { public: PublicKey::from(&secret), secret }
+            })
+            .collect();
+        let new = New {
+            owner: id(&owner),
+            validators: validators
+                .iter()
+                .map(|key| ed25519_public_key(key.public.to_bytes()))
+                .collect(),
+            required_signatures: 2,
+        };
+        contract.call(New::NAME).args(new.args()).transact().await?.into_result()?;
+
+        Ok(Self { worker, contract, owner, validators })
+    }
+
+    async fn call<M: ChangeMethod>(
+        &self,
+        account: &Account,
+        method: &M,
+        deposit: NearToken,
+    ) -> anyhow::Result<ExecutionFinalResult> {
+        Ok(account
+            .call(self.contract.id(), M::NAME)
+            .args(method.args())
+            .deposit(deposit)
+            .max_gas()
+            .transact()
+            .await?)
+    }
+
+    async fn view<M: ViewMethod>(&self, method: &M) -> anyhow::Result<M::Output> {
+        Ok(self.contract.view(M::NAME).args(method.args()).await?.json()?)
+    }
+
+    fn sign(&self, hash: &[u8; 32], validators: &[usize]) -> Vec<Signature> {
+        validators
+            .iter()
+            .map(|&index| {
+                let key = &self.validators[index];
+                ed25519_signature(key.public.to_bytes(), key.sign(hash).to_bytes())
+            })
+            .collect()
+    }
+}
+
+/// SDK account ID of a sandbox account
+fn id(account: &Account) -> AccountId {
+    account.id().as_str().parse().unwrap()
+}
+
+fn bridge_id(contract: &Contract) -> AccountId {
+    contract.id().as_str().parse().unwrap()
+}
+
+fn events(outcome: &ExecutionFinalResult) -> Vec<BridgeEvent> {
+    parse_logs(&outcome.logs()).unwrap()
+}
+
+#[tokio::test]
+async fn lock_near_emits_predicted_message_id() -> anyhow::Result<()> {
+    let bridge = Bridge::deploy().await?;
+    let alice = bridge.worker.dev_create_account().await?;
+
+    bridge
+        .call(&alice, &StorageDeposit::default(), NearToken::from_millinear(100))
+        .await?
+        .into_result()?;
+
+    let lock = LockNear {
+        destination_chain: "ethereum".to_string(),
+        destination_address: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
+        amount: None,
+    };
+    let outcome = bridge.call(&alice, &lock, NearToken::from_near(2)).await?;
+    assert!(outcome.is_success(), "{outcome:?}");
+
+    let expected = lock_message_id(
+        &bridge_id(&bridge.contract),
+        0,
+        &id(&alice),
+        &"near".parse()?,
+        NearToken::from_near(2).as_yoctonear(),
+        &lock.destination_chain,
+        &lock.destination_address,
+    );
+    let events = events(&outcome);
+    let [BridgeEvent::TokenLocked(locked)] = events.as_slice() else {
+        panic!("expected a single token_locked event, got {events:?}");
+    };
+    assert_eq!(locked.message_id, hex(&expected));
+    assert_eq!(locked.sender, id(&alice));
+    assert_eq!(locked.amount, U128(NearToken::from_near(2).as_yoctonear()));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn unlock_near_with_client_hash() -> anyhow::Result<()> {
+    let bridge = Bridge::deploy().await?;
+    let alice = bridge.worker.dev_create_account().await?;
+    let bob = bridge.worker.dev_create_account().await?;
+
+    // Fund the bridge with a lock
+    bridge
+        .call(&alice, &StorageDeposit::default(), NearToken::from_millinear(100))
+        .await?
+        .into_result()?;
+    let lock = LockNear {
+        destination_chain: "solana".to_string(),
+        destination_address: "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin".to_string(),
+        amount: None,
+    };
+    bridge.call(&alice, &lock, NearToken::from_near(3)).await?.into_result()?;
+
+    let mut unlock = UnlockNear {
+        message_id: [5; 32],
+        source_chain: "solana".to_string(),
+        sequence: 5,
+        sender_address: "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin".to_string(),
+        recipient: id(&bob),
+        amount: U128(NearToken::from_near(1).as_yoctonear()),
+        signatures: Vec::new(),
+        aggregate_signature: None,
+    };
+    unlock.signatures = bridge.sign(&unlock.message_hash(&bridge_id(&bridge.contract)), &[0, 2]);
+
+    let before = bob.view_account().await?.balance;
+    let outcome = bridge.call(&bridge.owner, &unlock, NearToken::from_yoctonear(0)).await?;
+    assert!(outcome.is_success(), "{outcome:?}");
+    let after = bob.view_account().await?.balance;
+    assert_eq!(after.as_yoctonear() - before.as_yoctonear(), unlock.amount.0);
+
+    let events = events(&outcome);
+    let [BridgeEvent::TokenUnlocked(unlocked)] = events.as_slice() else {
+        panic!("expected a single token_unlocked event, got {events:?}");
+    };
+    assert_eq!(unlocked.message_id, hex(&unlock.message_id));
+    assert_eq!(unlocked.recipient, id(&bob));
+    let processed = IsTransferProcessed { source_chain: unlock.source_chain.clone(), sequence: 5 };
+    assert!(bridge.view(&processed).await?);
+
+    // Signatures over other terms are rejected
+    let mut inflated = unlock.clone();
+    inflated.message_id = [6; 32];
+    inflated.sequence = 6;
+    inflated.amount = U128(NearToken::from_near(2).as_yoctonear());
+    assert!(bridge.call(&bridge.owner, &inflated, NearToken::from_yoctonear(0)).await?.is_failure());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn pause_and_unpause() -> anyhow::Result<()> {
+    let bridge = Bridge::deploy().await?;
+
+    let outcome = bridge.call(&bridge.owner, &Pause {}, NearToken::from_yoctonear(0)).await?;
+    assert!(outcome.is_success(), "{outcome:?}");
+    assert!(matches!(
+        events(&outcome).as_slice(),
+        [BridgeEvent::BridgePaused(event)] if event.updated_by == id(&bridge.owner)
+    ));
+    let config: BridgeConfig = bridge.view(&GetConfig {}).await?;
+    assert!(config.is_paused);
+
+    // Only the owner may unpause
+    let mallory = bridge.worker.dev_create_account().await?;
+    let outcome = bridge.call(&mallory, &Unpause {}, NearToken::from_yoctonear(0)).await?;
+    let failure = format!("{:?}", outcome.into_result().unwrap_err());
+    assert_eq!(BridgeError::parse(&failure), Some(BridgeError::Unauthorized), "{failure}");
+
+    let outcome = bridge.call(&bridge.owner, &Unpause {}, NearToken::from_yoctonear(0)).await?;
+    assert!(matches!(events(&outcome).as_slice(), [BridgeEvent::BridgeUnpaused(_)]));
+    assert!(!bridge.view(&GetConfig {}).await?.is_paused);
+
+    Ok(())
+}
@@ -0,0 +1,147 @@
+//! NEP-171 collection for NFTs bridged from another chain
+//!
+//! Holds the NEAR copies of one source-chain collection. The bridge, as the
+//! minter, mints a token when it first arrives. A token sent back to its
+//! source chain is escrowed by the bridge rather than burned, so a later
+//! return releases the same token instead of minting it again. Minted tokens
+//! are paid for from the collection account's own balance.
+
+use std::collections::HashMap;
+
+use near_contract_standards::non_fungible_token::core::{
+    NonFungibleTokenCore, NonFungibleTokenResolver,
+};
+use near_contract_standards::non_fungible_token::metadata::{
+    NFTContractMetadata, NonFungibleTokenMetadataProvider, TokenMetadata,
+};
+use near_contract_standards::non_fungible_token::{NonFungibleToken, Token, TokenId};
+use near_sdk::collections::LazyOption;
+use near_sdk::{env, near, require, AccountId, BorshStorageKey, PanicOnDefault, PromiseOrValue};
+
+#[derive(BorshStorageKey)]
+#[near]
+enum StorageKey {
+    TokenOwners,
+    TokenMetadata,
+    Metadata,
+}
+
+/// Chain and collection the tokens were created on
+#[near(serializers = [json])]
+pub struct NftSource {
+    pub chain: String,
+    /// Collection address on the source chain, as the relayer reports it
+    pub contract: String,
+}
+
+#[near(contract_state)]
+#[derive(PanicOnDefault)]
+pub struct Contract {
+    tokens: NonFungibleToken,
+    metadata: LazyOption<NFTContractMetadata>,
+    /// Account allowed to mint, normally the bridge
+    minter: AccountId,
+    source_chain: String,
+    source_contract: String,
+}
+
+#[near]
+impl Contract {
+    #[init]
+    pub fn new(
+        minter: AccountId,
+        metadata: NFTContractMetadata,
+        source_chain: String,
+        source_contract: String,
+    ) -> Self {
+        metadata.assert_valid();
+        Self {
+            tokens: NonFungibleToken::new(
+                StorageKey::TokenOwners,
+                minter.clone(),
+                Some(StorageKey::TokenMetadata),
+                None::<StorageKey>,
+                None::<StorageKey>,
+            ),
+            metadata: LazyOption::new(StorageKey::Metadata, Some(&metadata)),
+            minter,
+            source_chain,
+            source_contract,
+        }
+    }
+
+    /// Mint `token_id` to `receiver_id`. Only the minter may call this.
+    pub fn nft_mint(
+        &mut self,
+        token_id: TokenId,
+        receiver_id: AccountId,
+        token_metadata: TokenMetadata,
+    ) -> Token {
+        require!(env::predecessor_account_id() == self.minter, "Only the minter can mint");
+        self.tokens.internal_mint_with_refund(token_id, receiver_id, Some(token_metadata), None)
+    }
+
+    pub fn minter(&self) -> AccountId {
+        self.minter.clone()
+    }
+
+    pub fn nft_source(&self) -> NftSource {
+        NftSource { chain: self.source_chain.clone(), contract: self.source_contract.clone() }
+    }
+}
+
+#[near]
+impl NonFungibleTokenCore for Contract {
+    #[payable]
+    fn nft_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+    ) {
+        self.tokens.nft_transfer(receiver_id, token_id, approval_id, memo)
+    }
+
+    #[payable]
+    fn nft_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<bool> {
+        self.tokens.nft_transfer_call(receiver_id, token_id, approval_id, memo, msg)
+    }
+
+    fn nft_token(&self, token_id: TokenId) -> Option<Token> {
+        self.tokens.nft_token(token_id)
+    }
+}
+
+#[near]
+impl NonFungibleTokenResolver for Contract {
+    #[private]
+    fn nft_resolve_transfer(
+        &mut self,
+        previous_owner_id: AccountId,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approved_account_ids: Option<HashMap<AccountId, u64>>,
+    ) -> bool {
+        self.tokens.nft_resolve_transfer(
+            previous_owner_id,
+            receiver_id,
+            token_id,
+            approved_account_ids,
+        )
+    }
+}
+
+#[near]
+impl NonFungibleTokenMetadataProvider for Contract {
+    fn nft_metadata(&self) -> NFTContractMetadata {
+        self.metadata.get().unwrap()
+    }
+}
@@ -5,8 +5,8 @@ set -e
 
 echo "Building NEAR bridge contract..."
 
-# Set target directory
-TARGET_DIR="../../target"
+# Set target directory (shared by the contracts workspace)
+TARGET_DIR="../target"
 WASM_DIR="$TARGET_DIR/wasm32-unknown-unknown/release"
 
 # Clean previous builds
@@ -21,6 +21,14 @@ mkdir -p ./res
 # Copy wasm file
 cp $WASM_DIR/near_bridge.wasm ./res/
 
+# Reference NEP-141 token deployed by the sandbox tests
+RUSTFLAGS='-C link-arg=-s' cargo build -p near-test-token --target wasm32-unknown-unknown --release
+cp $WASM_DIR/near_test_token.wasm ./res/
+
+# NEP-171 collection for NFTs bridged from other chains
+RUSTFLAGS='-C link-arg=-s' cargo build -p near-bridged-nft --target wasm32-unknown-unknown --release
+cp $WASM_DIR/near_bridged_nft.wasm ./res/
+
 # Get file size
 wasm_size=$(stat -f%z "./res/near_bridge.wasm" 2>/dev/null || stat -c%s "./res/near_bridge.wasm")
 
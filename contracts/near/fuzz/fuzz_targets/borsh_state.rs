@@ -0,0 +1,26 @@
+//! Stored state and Borsh arguments fail to decode on malformed bytes
+//! instead of panicking
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use near_bridge::migration::{BridgeContractV0, LockRecordV0};
+use near_bridge::types::{
+    AggregateSignature, BatchRoot, CallData, GroupKey, LockRecord, LockStatus, Signature,
+};
+use near_bridge::BridgeContract;
+use near_sdk::borsh::BorshDeserialize;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = BridgeContract::try_from_slice(data);
+    let _ = BridgeContractV0::try_from_slice(data);
+    let _ = LockRecord::try_from_slice(data);
+    let _ = LockRecordV0::try_from_slice(data);
+    let _ = LockStatus::try_from_slice(data);
+    let _ = CallData::try_from_slice(data);
+    let _ = Signature::try_from_slice(data);
+    let _ = Vec::<Signature>::try_from_slice(data);
+    let _ = AggregateSignature::try_from_slice(data);
+    let _ = GroupKey::try_from_slice(data);
+    let _ = BatchRoot::try_from_slice(data);
+});
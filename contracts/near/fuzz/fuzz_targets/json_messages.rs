@@ -0,0 +1,12 @@
+//! JSON messages chosen by callers (`ft_transfer_call` messages and batch
+//! claim payloads) fail to parse instead of panicking
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use near_bridge::types::{BatchTokenPayload, FtLockMessage};
+
+fuzz_target!(|msg: &str| {
+    let _ = near_sdk::serde_json::from_str::<FtLockMessage>(msg);
+    let _ = near_sdk::serde_json::from_str::<BatchTokenPayload>(msg);
+});
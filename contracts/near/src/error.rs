@@ -0,0 +1,196 @@
+//! Errors the contract panics with
+//!
+//! Every failure panics with `BridgeError::<Name>: <message>`, e.g.
+//! `BridgeError::LockNotPending: Lock is not pending`. Variants share their
+//! names with the Solana program's `BridgeError` where the condition is the
+//! same, and [`BridgeError::parse`] recovers the variant from an execution
+//! failure off-chain.
+
+use std::fmt;
+use near_sdk::env;
+
+/// Prefix of every error message
+pub const ERROR_PREFIX: &str = "BridgeError::";
+
+macro_rules! bridge_errors {
+    ($($name:ident => $message:literal,)*) => {
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        pub enum BridgeError {
+            $($name,)*
+        }
+
+        impl BridgeError {
+            /// Every variant, in declaration order
+            pub const ALL: &'static [BridgeError] = &[$(BridgeError::$name,)*];
+
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(BridgeError::$name => stringify!($name),)*
+                }
+            }
+
+            pub fn message(self) -> &'static str {
+                match self {
+                    $(BridgeError::$name => $message,)*
+                }
+            }
+        }
+    };
+}
+
+bridge_errors! {
+    // Shared with the Solana program
+    BridgePaused => "Bridge is paused",
+    InsufficientSignatures => "Insufficient signatures",
+    MaxValidatorsReached => "Maximum validators reached",
+    ValidatorAlreadyExists => "Validator already exists",
+    ValidatorNotFound => "Validator not found",
+    MessageAlreadyProcessed => "Message already processed",
+    InvalidAmount => "Amount must be greater than zero",
+    DestinationChainTooLong => "Destination chain name too long",
+    DestinationAddressTooLong => "Destination address too long",
+    InvalidRequiredSignatures => "Invalid required signatures",
+    ArithmeticOverflow => "Arithmetic overflow",
+    ThresholdModeDisabled => "Threshold signatures are disabled",
+    GroupKeyNotSet => "Group key not set",
+    InvalidBatch => "Batch must contain messages",
+    InvalidMerkleProof => "Invalid Merkle proof",
+    InvalidBatchPayload => "Invalid batch payload",
+    PayloadTooLarge => "Payload too large",
+    InvalidMessageTarget => "Bridge cannot be a message target",
+    LockNotPending => "Lock is not pending",
+    LockNotExpired => "Lock has not expired",
+    DestinationTxTooLong => "Destination tx reference too long",
+
+    // Signatures and validators
+    InsufficientValidSignatures => "Insufficient valid signatures",
+    InvalidAggregateSignature => "Invalid aggregate signature",
+    InvalidValidatorCount => "Invalid validator count",
+    RequiredSignaturesExceedValidators => "Invalid required signatures after removal",
+    InvalidGroupKey => "Group key must be an Ed25519 key",
+    InvalidGroupSize => "Invalid group size",
+    InvalidGroupThreshold => "Invalid group threshold",
+
+    // Transfers
+    InvalidLockMessage => "Invalid lock message",
+    InvalidCallPayload => "Call payload must be UTF-8 for NEP-141 receivers",
+    CallPayloadTooLarge => "Call payload too large",
+    CallGasLimitTooHigh => "Call gas limit too high",
+    InsufficientLockedBalance => "Insufficient locked balance",
+    LockNotFound => "Lock record not found",
+    InvalidLockOutcome => "Invalid lock outcome",
+    TooManyRecords => "Too many records",
+    NoUnclaimedFallback => "No unclaimed fallback for this token",
+
+    // Batches
+    BatchAlreadySubmitted => "Batch root already submitted",
+    BatchNotFound => "Batch root not found",
+    LeafIndexOutOfRange => "Leaf index out of range",
+    WrongDestinationChain => "Message is not destined for this chain",
+    InvalidBatchAmount => "Invalid batch payload amount",
+    InvalidBatchToken => "Invalid batch payload token",
+
+    // NFTs
+    NftNotEscrowed => "NFT is not held in escrow",
+    NftSourceMismatch => "NFT is bridged to or from another chain",
+    NftCollectionAlreadyRegistered => "NFT collection already registered",
+    TokenIdTooLong => "Token ID too long",
+    TokenUriTooLong => "Token URI too long",
+
+    // Generic messages
+    TargetAddressTooLong => "Target address too long",
+    MessageNotFound => "Message not found",
+    MessageNotAwaitingRetry => "Message is not awaiting retry",
+    MaxDeliveryAttemptsReached => "Maximum delivery attempts reached",
+    InsufficientDeliveryGas => "Not enough gas attached for delivery",
+
+    // Storage management
+    AccountNotRegistered => "Account is not registered",
+    DepositBelowMinimum => "Deposit is less than the minimum storage balance",
+    WithdrawalExceedsBalance => "Amount exceeds available storage balance",
+    InsufficientStorageDeposit => "Insufficient storage deposit",
+    AmountExceedsDeposit => "Amount exceeds the attached deposit",
+    OneYoctoRequired => "Requires attached deposit of exactly 1 yoctoNEAR",
+
+    // Administration
+    AlreadyInitialized => "Already initialized",
+    Unauthorized => "Only owner can call this method",
+    EmptyCode => "Code must not be empty",
+    UnknownStateVersion => "Unknown state version",
+    InvalidState => "Stored state does not match its version",
+}
+
+impl BridgeError {
+    /// Abort the call with this error
+    pub fn panic(self) -> ! {
+        env::panic_str(&self.to_string())
+    }
+
+    /// Error a failed call panicked with, found anywhere in `failure` (such
+    /// as an RPC error message or an execution failure's display)
+    pub fn parse(failure: &str) -> Option<Self> {
+        let start = failure.find(ERROR_PREFIX)? + ERROR_PREFIX.len();
+        let name = failure[start..]
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .next()
+            .unwrap_or_default();
+        Self::ALL.iter().copied().find(|error| error.name() == name)
+    }
+}
+
+impl fmt::Display for BridgeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{ERROR_PREFIX}{}: {}", self.name(), self.message())
+    }
+}
+
+/// `require!` that panics with a [`BridgeError`]
+macro_rules! ensure {
+    ($condition:expr, $error:expr $(,)?) => {
+        if !$condition {
+            $error.panic()
+        }
+    };
+}
+pub(crate) use ensure;
+
+/// Unwrap a value or panic with a [`BridgeError`], for lookups and checked
+/// arithmetic: `a.checked_add(b).or_panic(BridgeError::ArithmeticOverflow)`
+pub trait OrPanic<T> {
+    fn or_panic(self, error: BridgeError) -> T;
+}
+
+impl<T> OrPanic<T> for Option<T> {
+    fn or_panic(self, error: BridgeError) -> T {
+        self.unwrap_or_else(|| error.panic())
+    }
+}
+
+impl<T, E> OrPanic<T> for Result<T, E> {
+    fn or_panic(self, error: BridgeError) -> T {
+        self.unwrap_or_else(|_| error.panic())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_round_trip() {
+        for &error in BridgeError::ALL {
+            let failure = format!("Smart contract panicked: {error}");
+            assert_eq!(BridgeError::parse(&failure), Some(error));
+        }
+    }
+
+    #[test]
+    fn parse_requires_whole_name() {
+        assert_eq!(BridgeError::parse("BridgeError::Insufficient"), None);
+        assert_eq!(BridgeError::parse("Insufficient signatures"), None);
+        assert_eq!(
+            BridgeError::parse("Error(BridgeError::LockNotPending: Lock is not pending)"),
+            Some(BridgeError::LockNotPending)
+        );
+    }
+}
@@ -1,51 +1,627 @@
 use near_sdk::serde::{Deserialize, Serialize};
-use near_sdk::{log, AccountId, Balance};
+use near_sdk::json_types::{Base64VecU8, U128};
+use near_sdk::{log, AccountId, PublicKey};
 
-/// Event emitted when tokens are locked
+use crate::types::{CallData, LockStatus, SignatureMode};
+
+/// NEP-297 standard name the relayer's NEAR listener filters on
+pub const EVENT_STANDARD: &str = "bridge";
+
+/// Version of the event payloads below
+pub const EVENT_VERSION: &str = "2.0.0";
+
+/// Every event emitted by the bridge. Serialized per NEP-297 as
+/// `EVENT_JSON:{"standard":..,"version":..,"event":..,"data":{..}}` with the
+/// variant name in snake_case as `event`.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum BridgeEvent {
+    // Transfers
+    TokenLocked(TokenLockedEvent),
+    TokenUnlocked(TokenUnlockedEvent),
+    UnlockCallFallback(UnlockCallFallbackEvent),
+    UnlockCallFallbackFailed(UnlockCallFallbackEvent),
+    UnlockFallbackClaimed(UnlockFallbackClaimedEvent),
+    UnlockFailed(UnlockFailedEvent),
+    TokenRefunded(TokenRefundedEvent),
+    RefundFailed(RefundFailedEvent),
+    LockStatusChanged(LockStatusEvent),
+    LockRecordsPruned(LockRecordsPrunedEvent),
+    BatchRootSubmitted(BatchRootSubmittedEvent),
+
+    // NFTs
+    NftLocked(NftLockedEvent),
+    NftUnlocked(NftUnlockedEvent),
+    NftUnlockFailed(NftUnlockFailedEvent),
+    BridgedNftCollectionRegistered(BridgedNftCollectionEvent),
+
+    // Generic messages
+    MessageSent(MessageSentEvent),
+    MessageReceived(MessageReceivedEvent),
+    MessageDelivered(MessageDeliveryEvent),
+    MessageDeliveryFailed(MessageDeliveryEvent),
+
+    // Storage management
+    StorageDeposited(StorageChangeEvent),
+    StorageWithdrawn(StorageChangeEvent),
+    StorageUnregistered(StorageChangeEvent),
+
+    // Administration
+    BridgeInitialized(BridgeInitializedEvent),
+    ValidatorAdded(ValidatorChangeEvent),
+    ValidatorRemoved(ValidatorChangeEvent),
+    RequiredSignaturesUpdated(RequiredSignaturesUpdatedEvent),
+    GroupKeyRotated(GroupKeyRotatedEvent),
+    SignatureModeUpdated(SignatureModeUpdatedEvent),
+    BridgePaused(PauseEvent),
+    BridgeUnpaused(PauseEvent),
+    OwnershipTransferred(OwnershipTransferredEvent),
+    ContractUpgraded(ContractUpgradedEvent),
+    StateMigrated(StateMigratedEvent),
+}
+
+/// NEP-297 envelope around a `BridgeEvent`
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct EventLog<'a> {
+    standard: &'static str,
+    version: &'static str,
+    #[serde(flatten)]
+    event: &'a BridgeEvent,
+}
+
+impl BridgeEvent {
+    /// JSON body of the event log, without the `EVENT_JSON:` prefix
+    pub fn to_json(&self) -> String {
+        near_sdk::serde_json::to_string(&EventLog {
+            standard: EVENT_STANDARD,
+            version: EVENT_VERSION,
+            event: self,
+        })
+        .unwrap_or_else(|_| near_sdk::env::panic_str("Failed to serialize event"))
+    }
+
+    /// Log the event for indexers
+    pub fn emit(&self) {
+        log!("EVENT_JSON:{}", self.to_json());
+    }
+}
+
+/// Event emitted when tokens are locked. `recipient` is the address on the
+/// destination chain.
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(crate = "near_sdk::serde")]
 pub struct TokenLockedEvent {
     pub message_id: String,
     pub sender: AccountId,
-    pub token_contract: AccountId,
-    pub amount: Balance,
+    pub recipient: String,
+    pub token: AccountId,
+    pub amount: U128,
     pub destination_chain: String,
-    pub destination_address: String,
     pub nonce: u64,
     pub timestamp: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub call: Option<CallData>,
 }
 
 /// Event emitted when tokens are unlocked
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(crate = "near_sdk::serde")]
 pub struct TokenUnlockedEvent {
+    pub message_id: String,
+    pub source_chain: String,
+    pub sequence: u64,
+    pub sender_address: String,
+    pub recipient: AccountId,
+    pub token: AccountId,
+    pub amount: U128,
+    pub timestamp: u64,
+}
+
+/// Event emitted when an unlock-and-call falls back to the recipient
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct UnlockCallFallbackEvent {
+    pub message_id: String,
+    pub recipient: AccountId,
+    pub token: AccountId,
+    pub amount: U128,
+}
+
+/// Event emitted when an unlock-and-call fallback is claimed by its recipient
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct UnlockFallbackClaimedEvent {
+    pub recipient: AccountId,
+    pub token: AccountId,
+    pub amount: U128,
+}
+
+/// Event emitted when the token transfer of an unlock fails. The message can
+/// be unlocked again.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct UnlockFailedEvent {
+    pub message_id: String,
+    pub recipient: AccountId,
+    pub token: AccountId,
+    pub amount: U128,
+}
+
+/// Event emitted when the token transfer of a refund or expiry fails. The
+/// lock is pending again and can be refunded again.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RefundFailedEvent {
+    pub message_id: String,
+    pub sender: AccountId,
+    pub token: AccountId,
+    pub amount: U128,
+}
+
+/// Event emitted when a failed outbound transfer is refunded to the sender
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TokenRefundedEvent {
+    pub message_id: String,
+    pub sender: AccountId,
+    pub token: AccountId,
+    pub amount: U128,
+    pub timestamp: u64,
+}
+
+/// Event emitted when a lock changes lifecycle state
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct LockStatusEvent {
+    pub message_id: String,
+    pub status: LockStatus,
+    pub timestamp: u64,
+}
+
+/// Event emitted when finalized lock records are removed
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct LockRecordsPrunedEvent {
+    pub message_ids: Vec<String>,
+}
+
+/// Event emitted when validators attest a batch Merkle root
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BatchRootSubmittedEvent {
+    pub batch_root: String,
+    pub message_count: u32,
+    pub timestamp: u64,
+}
+
+/// Event emitted when an NFT is escrowed for transfer. `recipient` is the
+/// address on the destination chain and `token_uri` the token's metadata URI.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftLockedEvent {
+    pub message_id: String,
+    pub sender: AccountId,
+    pub recipient: String,
+    pub nft_contract: AccountId,
+    pub token_id: String,
+    pub token_uri: String,
+    pub destination_chain: String,
+    pub nonce: u64,
+    pub timestamp: u64,
+}
+
+/// Event emitted when an inbound NFT is released from escrow or, if
+/// `minted`, minted into a bridged collection
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftUnlockedEvent {
     pub message_id: String,
     pub source_chain: String,
     pub sender_address: String,
     pub recipient: AccountId,
-    pub token_contract: AccountId,
-    pub amount: Balance,
+    pub nft_contract: AccountId,
+    pub token_id: String,
+    pub minted: bool,
     pub timestamp: u64,
 }
 
-/// Emit a token locked event
-pub fn emit_token_locked_event(event: &TokenLockedEvent) {
-    let event_json = near_sdk::serde_json::to_string(event)
-        .unwrap_or_else(|_| "{}".to_string());
+/// Event emitted when the transfer or mint of an NFT unlock fails. The
+/// message can be unlocked again.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftUnlockFailedEvent {
+    pub message_id: String,
+    pub recipient: AccountId,
+    pub nft_contract: AccountId,
+    pub token_id: String,
+}
 
-    log!(
-        "EVENT_JSON:{{\"standard\":\"articium\",\"version\":\"1.0.0\",\"event\":\"token_locked\",\"data\":{}}}",
-        event_json
-    );
+/// Event emitted when a collection is registered for NFTs from another chain
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BridgedNftCollectionEvent {
+    pub collection: AccountId,
+    pub source_chain: String,
+    pub source_contract: String,
+    pub updated_by: AccountId,
 }
 
-/// Emit a token unlocked event
-pub fn emit_token_unlocked_event(event: &TokenUnlockedEvent) {
-    let event_json = near_sdk::serde_json::to_string(event)
-        .unwrap_or_else(|_| "{}".to_string());
+/// Event emitted when a generic message is sent to another chain
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MessageSentEvent {
+    pub message_id: String,
+    pub sender: AccountId,
+    pub destination_chain: String,
+    pub target: String,
+    pub payload: Base64VecU8,
+    pub nonce: u64,
+    pub timestamp: u64,
+}
+
+/// Event emitted when an attested inbound message is accepted
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MessageReceivedEvent {
+    pub message_id: String,
+    pub source_chain: String,
+    pub sender_address: String,
+    pub target: AccountId,
+    pub timestamp: u64,
+}
+
+/// Event emitted when delivery of an inbound message completes or fails
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MessageDeliveryEvent {
+    pub message_id: String,
+    pub source_chain: String,
+    pub target: AccountId,
+    pub attempts: u8,
+    pub timestamp: u64,
+}
+
+/// Event emitted when an account's NEP-145 storage balance changes.
+/// `amount` is what was deposited or withdrawn; `available` is the balance left.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageChangeEvent {
+    pub account_id: AccountId,
+    pub amount: U128,
+    pub available: U128,
+}
+
+/// Event emitted once when the contract is initialized
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BridgeInitializedEvent {
+    pub owner: AccountId,
+    pub validators: Vec<PublicKey>,
+    pub required_signatures: u8,
+}
+
+/// Event emitted when a validator is added or removed
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ValidatorChangeEvent {
+    pub validator: PublicKey,
+    pub validator_count: u64,
+    pub updated_by: AccountId,
+}
+
+/// Event emitted when the signature threshold changes
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RequiredSignaturesUpdatedEvent {
+    pub old_required_signatures: u8,
+    pub new_required_signatures: u8,
+    pub updated_by: AccountId,
+}
+
+/// Event emitted when the threshold group key is rotated
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct GroupKeyRotatedEvent {
+    pub old_public_key: Option<PublicKey>,
+    pub new_public_key: PublicKey,
+    pub threshold: u8,
+    pub group_size: u8,
+    pub epoch: u64,
+}
+
+/// Event emitted when the signature verification mode changes
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SignatureModeUpdatedEvent {
+    pub old_mode: SignatureMode,
+    pub new_mode: SignatureMode,
+    pub updated_by: AccountId,
+}
+
+/// Event emitted when the bridge is paused or unpaused
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PauseEvent {
+    pub updated_by: AccountId,
+    pub timestamp: u64,
+}
+
+/// Event emitted when ownership is transferred
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OwnershipTransferredEvent {
+    pub old_owner: AccountId,
+    pub new_owner: AccountId,
+}
+
+/// Event emitted when new contract code is deployed
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ContractUpgradedEvent {
+    pub code_hash: String,
+    pub updated_by: AccountId,
+}
+
+/// Event emitted when the state layout is migrated
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StateMigratedEvent {
+    pub from_version: u8,
+    pub to_version: u8,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::accounts;
+
+    fn validator_key() -> PublicKey {
+        "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp".parse().unwrap()
+    }
+
+    #[test]
+    fn token_locked_matches_listener_format() {
+        let event = BridgeEvent::TokenLocked(TokenLockedEvent {
+            message_id: "ab".repeat(32),
+            sender: accounts(1),
+            recipient: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
+            token: accounts(2),
+            amount: U128(340_282_366_920_938_463_463_374_607_431_768_211_455),
+            destination_chain: "ethereum".to_string(),
+            nonce: 7,
+            timestamp: 1_700_000_000_000_000_000,
+            call: None,
+        });
+
+        assert_eq!(
+            event.to_json(),
+            format!(
+                concat!(
+                    r#"{{"standard":"bridge","version":"2.0.0","event":"token_locked","data":{{"#,
+                    r#""message_id":"{}","sender":"bob","#,
+                    r#""recipient":"0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb","token":"charlie","#,
+                    r#""amount":"340282366920938463463374607431768211455","destination_chain":"ethereum","#,
+                    r#""nonce":7,"timestamp":1700000000000000000}}}}"#,
+                ),
+                "ab".repeat(32),
+            )
+        );
+    }
+
+    #[test]
+    fn token_locked_includes_call() {
+        let event = BridgeEvent::TokenLocked(TokenLockedEvent {
+            message_id: "01".to_string(),
+            sender: accounts(1),
+            recipient: "receiver".to_string(),
+            token: accounts(2),
+            amount: U128(5),
+            destination_chain: "solana".to_string(),
+            nonce: 0,
+            timestamp: 0,
+            call: Some(CallData {
+                payload: Base64VecU8(b"hi".to_vec()),
+                gas_limit: 10,
+            }),
+        });
+
+        assert!(event.to_json().ends_with(r#""call":{"payload":"aGk=","gas_limit":10}}}"#));
+    }
+
+    #[test]
+    fn token_unlocked_format() {
+        let event = BridgeEvent::TokenUnlocked(TokenUnlockedEvent {
+            message_id: "01".to_string(),
+            source_chain: "ethereum".to_string(),
+            sequence: 7,
+            sender_address: "0xabc".to_string(),
+            recipient: accounts(1),
+            token: accounts(2),
+            amount: U128(1_000),
+            timestamp: 42,
+        });
+
+        assert_eq!(
+            event.to_json(),
+            concat!(
+                r#"{"standard":"bridge","version":"2.0.0","event":"token_unlocked","data":{"#,
+                r#""message_id":"01","source_chain":"ethereum","sequence":7,"#,
+                r#""sender_address":"0xabc","#,
+                r#""recipient":"bob","token":"charlie","amount":"1000","timestamp":42}}"#,
+            )
+        );
+    }
+
+    #[test]
+    fn nft_locked_matches_listener_format() {
+        let event = BridgeEvent::NftLocked(NftLockedEvent {
+            message_id: "ab".repeat(32),
+            sender: accounts(1),
+            recipient: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
+            nft_contract: accounts(2),
+            token_id: "42".to_string(),
+            token_uri: "ipfs://bafybeigdyrzt/42.json".to_string(),
+            destination_chain: "ethereum".to_string(),
+            nonce: 7,
+            timestamp: 1_700_000_000_000_000_000,
+        });
+
+        assert_eq!(
+            event.to_json(),
+            format!(
+                concat!(
+                    r#"{{"standard":"bridge","version":"2.0.0","event":"nft_locked","data":{{"#,
+                    r#""message_id":"{}","sender":"bob","#,
+                    r#""recipient":"0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb","#,
+                    r#""nft_contract":"charlie","token_id":"42","#,
+                    r#""token_uri":"ipfs://bafybeigdyrzt/42.json","destination_chain":"ethereum","#,
+                    r#""nonce":7,"timestamp":1700000000000000000}}}}"#,
+                ),
+                "ab".repeat(32),
+            )
+        );
+    }
+
+    #[test]
+    fn lock_status_changed_format() {
+        let event = BridgeEvent::LockStatusChanged(LockStatusEvent {
+            message_id: "01".to_string(),
+            status: LockStatus::Delivered { destination_tx: "0xdef".to_string() },
+            timestamp: 42,
+        });
+
+        assert_eq!(
+            event.to_json(),
+            concat!(
+                r#"{"standard":"bridge","version":"2.0.0","event":"lock_status_changed","data":{"#,
+                r#""message_id":"01","status":{"Delivered":{"destination_tx":"0xdef"}},"timestamp":42}}"#,
+            )
+        );
+    }
+
+    #[test]
+    fn validator_added_format() {
+        let event = BridgeEvent::ValidatorAdded(ValidatorChangeEvent {
+            validator: validator_key(),
+            validator_count: 3,
+            updated_by: accounts(0),
+        });
+
+        assert_eq!(
+            event.to_json(),
+            concat!(
+                r#"{"standard":"bridge","version":"2.0.0","event":"validator_added","data":{"#,
+                r#""validator":"ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp","#,
+                r#""validator_count":3,"updated_by":"alice"}}"#,
+            )
+        );
+    }
+
+    #[test]
+    fn required_signatures_updated_format() {
+        let event = BridgeEvent::RequiredSignaturesUpdated(RequiredSignaturesUpdatedEvent {
+            old_required_signatures: 2,
+            new_required_signatures: 3,
+            updated_by: accounts(0),
+        });
+
+        assert_eq!(
+            event.to_json(),
+            concat!(
+                r#"{"standard":"bridge","version":"2.0.0","event":"required_signatures_updated","data":{"#,
+                r#""old_required_signatures":2,"new_required_signatures":3,"updated_by":"alice"}}"#,
+            )
+        );
+    }
+
+    #[test]
+    fn signature_mode_updated_format() {
+        let event = BridgeEvent::SignatureModeUpdated(SignatureModeUpdatedEvent {
+            old_mode: SignatureMode::Individual,
+            new_mode: SignatureMode::Threshold,
+            updated_by: accounts(0),
+        });
+
+        assert_eq!(
+            event.to_json(),
+            concat!(
+                r#"{"standard":"bridge","version":"2.0.0","event":"signature_mode_updated","data":{"#,
+                r#""old_mode":"Individual","new_mode":"Threshold","updated_by":"alice"}}"#,
+            )
+        );
+    }
+
+    #[test]
+    fn pause_events_format() {
+        let pause = PauseEvent { updated_by: accounts(0), timestamp: 42 };
+        assert_eq!(
+            BridgeEvent::BridgePaused(pause).to_json(),
+            r#"{"standard":"bridge","version":"2.0.0","event":"bridge_paused","data":{"updated_by":"alice","timestamp":42}}"#
+        );
+
+        let unpause = PauseEvent { updated_by: accounts(0), timestamp: 43 };
+        assert_eq!(
+            BridgeEvent::BridgeUnpaused(unpause).to_json(),
+            r#"{"standard":"bridge","version":"2.0.0","event":"bridge_unpaused","data":{"updated_by":"alice","timestamp":43}}"#
+        );
+    }
+
+    #[test]
+    fn ownership_transferred_format() {
+        let event = BridgeEvent::OwnershipTransferred(OwnershipTransferredEvent {
+            old_owner: accounts(0),
+            new_owner: accounts(1),
+        });
+
+        assert_eq!(
+            event.to_json(),
+            r#"{"standard":"bridge","version":"2.0.0","event":"ownership_transferred","data":{"old_owner":"alice","new_owner":"bob"}}"#
+        );
+    }
+
+    #[test]
+    fn admin_methods_emit_events() {
+        near_sdk::testing_env!(near_sdk::test_utils::VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .predecessor_account_id(accounts(0))
+            .block_timestamp(42)
+            .build());
+        let mut contract = crate::BridgeContract::new(accounts(0), vec![validator_key()], 1);
+
+        contract.pause();
+        contract.transfer_ownership(accounts(1));
+
+        let logs = near_sdk::test_utils::get_logs();
+        let events: Vec<&str> = logs
+            .iter()
+            .filter(|log| log.starts_with("EVENT_JSON:"))
+            .map(String::as_str)
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                concat!(
+                    r#"EVENT_JSON:{"standard":"bridge","version":"2.0.0","event":"bridge_initialized","data":{"#,
+                    r#""owner":"alice","validators":["ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp"],"#,
+                    r#""required_signatures":1}}"#,
+                ),
+                r#"EVENT_JSON:{"standard":"bridge","version":"2.0.0","event":"bridge_paused","data":{"updated_by":"alice","timestamp":42}}"#,
+                r#"EVENT_JSON:{"standard":"bridge","version":"2.0.0","event":"ownership_transferred","data":{"old_owner":"alice","new_owner":"bob"}}"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn emit_logs_with_prefix() {
+        near_sdk::testing_env!(near_sdk::test_utils::VMContextBuilder::new().build());
+
+        BridgeEvent::StateMigrated(StateMigratedEvent { from_version: 0, to_version: 1 }).emit();
 
-    log!(
-        "EVENT_JSON:{{\"standard\":\"articium\",\"version\":\"1.0.0\",\"event\":\"token_unlocked\",\"data\":{}}}",
-        event_json
-    );
+        assert_eq!(
+            near_sdk::test_utils::get_logs(),
+            vec![r#"EVENT_JSON:{"standard":"bridge","version":"2.0.0","event":"state_migrated","data":{"from_version":0,"to_version":1}}"#]
+        );
+    }
 }
@@ -1,21 +1,39 @@
-use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::collections::{UnorderedMap, UnorderedSet};
-use near_sdk::serde::{Deserialize, Serialize};
+// Contract methods take the attested fields as separate JSON arguments
+#![allow(clippy::too_many_arguments)]
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{LookupMap, LookupSet, UnorderedMap, UnorderedSet};
 use near_sdk::{
-    env, near_bindgen, AccountId, Balance, PanicOnDefault, Promise, PublicKey,
-    require, log,
+    env, ext_contract, log, near_bindgen, AccountId, CurveType, Gas, NearToken, PanicOnDefault,
+    Promise, PromiseError, PromiseOrValue, PublicKey,
 };
 
 pub mod storage;
+pub mod error;
 pub mod events;
 pub mod types;
+pub mod merkle;
+pub mod storage_management;
+pub mod migration;
+pub mod nft;
+#[cfg(test)]
+mod properties;
 
 use storage::*;
+use error::{ensure, OrPanic};
+pub use error::BridgeError;
 use events::*;
 use types::*;
+use migration::{LockRecordV0, CURRENT_STATE_VERSION};
+use bridge_types::hash::NearKeccak;
+use bridge_types::{
+    aggregate_signing_hash, chain, GenericMessage, MessageDeliveryAttestation, TransferMessage,
+    UnlockAttestation,
+};
 
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+#[borsh(crate = "near_sdk::borsh")]
 pub struct BridgeContract {
     /// Contract owner/admin
     pub owner: AccountId,
@@ -35,14 +53,59 @@ pub struct BridgeContract {
     /// Total tokens unlocked (by token contract ID)
     pub total_unlocked: UnorderedMap<AccountId, Balance>,
 
-    /// Processed message IDs to prevent replay
-    pub processed_messages: UnorderedSet<MessageId>,
+    /// Processed NFT transfer and generic message IDs to prevent replay. A
+    /// `LookupSet` stores one entry per ID, without the index an
+    /// `UnorderedSet` keeps for iteration.
+    pub processed_messages: LookupSet<MessageId>,
+
+    /// Released token transfers, one bit per source sequence: bit
+    /// `sequence % 64` of the word at `(source_chain, sequence / 64)`.
+    /// Source bridges number their locks densely, so a word covers 64
+    /// transfers for the storage of one.
+    pub processed_sequences: LookupMap<(String, u64), u64>,
+
+    /// Processed message IDs carried over from the v0 layout, kept in place
+    /// because copying them could exceed the gas limit of `migrate`
+    pub legacy_processed_messages: Option<UnorderedSet<MessageId>>,
 
-    /// Lock records for outgoing transfers
-    pub lock_records: UnorderedMap<String, LockRecord>,
+    /// Number of processed messages, including legacy ones
+    pub processed_message_count: u64,
+
+    /// Lock records for outgoing transfers, prunable once finalized
+    pub lock_records: UnorderedMap<MessageId, LockRecord>,
+
+    /// Lock records carried over from the v0 layout, keyed by hex message
+    /// ID. Each moves to `lock_records` when it is next updated, because
+    /// copying them all could exceed the gas limit of `migrate`.
+    pub legacy_lock_records: Option<UnorderedMap<String, LockRecordV0>>,
 
     /// Message counter
     pub message_count: u64,
+
+    /// How unlock attestations are verified
+    pub signature_mode: SignatureMode,
+
+    /// Threshold group key used in `SignatureMode::Threshold`
+    pub group_key: Option<GroupKey>,
+
+    /// Validator-attested batch Merkle roots
+    pub batch_roots: UnorderedMap<[u8; 32], BatchRoot>,
+
+    /// Inbound generic messages and their delivery state
+    pub inbound_messages: UnorderedMap<MessageId, InboundMessage>,
+
+    /// NEP-145 storage balances available to pay for lock records
+    pub storage_deposits: LookupMap<AccountId, Balance>,
+
+    /// NFTs held for outbound transfers, by collection and token ID
+    pub nft_escrow: UnorderedMap<(AccountId, String), NftEscrow>,
+
+    /// Collections the bridge mints NFTs arriving from other chains into
+    pub bridged_nft_collections: UnorderedMap<AccountId, BridgedNftCollection>,
+
+    /// Unlock-and-call fallbacks the recipient could not be sent, by
+    /// recipient and token, until claimed with `claim_unlock_fallback`
+    pub unclaimed_fallbacks: LookupMap<(AccountId, AccountId), Balance>,
 }
 
 #[near_bindgen]
@@ -54,14 +117,14 @@ impl BridgeContract {
         validators: Vec<PublicKey>,
         required_signatures: u8,
     ) -> Self {
-        require!(!env::state_exists(), "Already initialized");
-        require!(
-            validators.len() > 0 && validators.len() <= MAX_VALIDATORS,
-            "Invalid validator count"
+        ensure!(!env::state_exists(), BridgeError::AlreadyInitialized);
+        ensure!(
+            !validators.is_empty() && validators.len() <= MAX_VALIDATORS,
+            BridgeError::InvalidValidatorCount
         );
-        require!(
+        ensure!(
             required_signatures > 0 && required_signatures as usize <= validators.len(),
-            "Invalid required signatures"
+            BridgeError::InvalidRequiredSignatures
         );
 
         let mut validator_set = UnorderedSet::new(StorageKey::Validators);
@@ -69,6 +132,12 @@ impl BridgeContract {
             validator_set.insert(validator);
         }
 
+        let event = BridgeInitializedEvent {
+            owner: owner.clone(),
+            validators: validators.clone(),
+            required_signatures,
+        };
+
         let contract = Self {
             owner,
             validators: validator_set,
@@ -76,11 +145,27 @@ impl BridgeContract {
             is_paused: false,
             total_locked: UnorderedMap::new(StorageKey::TotalLocked),
             total_unlocked: UnorderedMap::new(StorageKey::TotalUnlocked),
-            processed_messages: UnorderedSet::new(StorageKey::ProcessedMessages),
-            lock_records: UnorderedMap::new(StorageKey::LockRecords),
+            processed_messages: LookupSet::new(StorageKey::ProcessedMessageIds),
+            processed_sequences: LookupMap::new(StorageKey::ProcessedSequences),
+            legacy_processed_messages: None,
+            processed_message_count: 0,
+            lock_records: UnorderedMap::new(StorageKey::LockRecordsById),
+            legacy_lock_records: None,
             message_count: 0,
+            signature_mode: SignatureMode::Individual,
+            group_key: None,
+            batch_roots: UnorderedMap::new(StorageKey::BatchRoots),
+            inbound_messages: UnorderedMap::new(StorageKey::InboundMessages),
+            storage_deposits: LookupMap::new(StorageKey::StorageDeposits),
+            nft_escrow: UnorderedMap::new(StorageKey::NftEscrow),
+            bridged_nft_collections: UnorderedMap::new(StorageKey::BridgedNftCollections),
+            unclaimed_fallbacks: LookupMap::new(StorageKey::UnclaimedFallbacks),
         };
 
+        migration::write_state_version(CURRENT_STATE_VERSION);
+
+        BridgeEvent::BridgeInitialized(event).emit();
+
         log!("Bridge initialized with {} validators, requiring {} signatures",
             validators.len(),
             required_signatures
@@ -89,215 +174,350 @@ impl BridgeContract {
         contract
     }
 
-    /// Lock tokens for cross-chain transfer
-    #[payable]
-    pub fn lock_ft(
+    /// NEP-141 receiver: lock the tokens transferred with `ft_transfer_call`.
+    /// `msg` is a JSON `FtLockMessage`. The lock record is paid for from the
+    /// sender's storage balance. If the lock is rejected the token contract
+    /// returns the whole amount to the sender.
+    pub fn ft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        amount: U128,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        ensure!(!self.is_paused, BridgeError::BridgePaused);
+        ensure!(amount.0 > 0, BridgeError::InvalidAmount);
+
+        let lock: FtLockMessage = near_sdk::serde_json::from_str(&msg)
+            .or_panic(BridgeError::InvalidLockMessage);
+        let token_contract = env::predecessor_account_id();
+
+        let initial_storage = env::storage_usage();
+        self.record_lock(
+            sender_id.clone(),
+            token_contract,
+            amount.0,
+            lock.destination_chain.clone(),
+            lock.destination_address,
+            lock.call,
+        );
+        self.charge_storage(&sender_id, initial_storage, 0);
+
+        log!("Tokens locked: amount={}, destination={}", amount.0, lock.destination_chain);
+
+        // Keep the whole transfer
+        PromiseOrValue::Value(U128(0))
+    }
+
+    /// Unlock tokens after cross-chain transfer (requires validator signatures).
+    /// `sequence` is the transfer's sequence on the source bridge.
+    pub fn unlock_ft(
         &mut self,
+        message_id: MessageId,
+        source_chain: String,
+        sequence: u64,
+        sender_address: String,
+        recipient: AccountId,
         token_contract: AccountId,
         amount: U128,
-        destination_chain: String,
-        destination_address: String,
+        signatures: Vec<Signature>,
+        aggregate_signature: Option<AggregateSignature>,
     ) -> Promise {
-        require!(!self.is_paused, "Bridge is paused");
-        require!(amount.0 > 0, "Amount must be greater than zero");
-        require!(
-            destination_chain.len() <= MAX_CHAIN_NAME_LEN,
-            "Destination chain name too long"
-        );
-        require!(
-            destination_address.len() <= MAX_ADDRESS_LEN,
-            "Destination address too long"
+        ensure!(!self.is_paused, BridgeError::BridgePaused);
+        ensure!(amount.0 > 0, BridgeError::InvalidAmount);
+        ensure!(
+            !self.is_transfer_processed_by(&message_id, &source_chain, sequence),
+            BridgeError::MessageAlreadyProcessed
         );
 
-        let sender = env::predecessor_account_id();
-        let nonce = self.message_count;
-        self.message_count += 1;
-
-        // Generate message ID
-        let message_id = Self::generate_message_id(
-            &sender,
+        let message_hash = Self::create_unlock_message_hash(
+            &message_id,
+            &source_chain,
+            sequence,
+            &sender_address,
+            &recipient,
             &token_contract,
             amount.0,
-            &destination_chain,
-            nonce,
         );
 
-        // Create lock record
-        let lock_record = LockRecord {
-            message_id: message_id.clone(),
-            sender: sender.clone(),
-            token_contract: token_contract.clone(),
-            amount: amount.0,
-            destination_chain: destination_chain.clone(),
-            destination_address: destination_address.clone(),
-            nonce,
-            timestamp: env::block_timestamp(),
-        };
-
-        self.lock_records.insert(&message_id, &lock_record);
-
-        // Update stats
-        let current_locked = self.total_locked.get(&token_contract).unwrap_or(0);
-        self.total_locked.insert(&token_contract, &(current_locked + amount.0));
+        // Verify signatures
+        self.assert_attested(&message_hash, &signatures, aggregate_signature.as_ref());
 
-        // Emit event
-        emit_token_locked_event(&TokenLockedEvent {
-            message_id: message_id.clone(),
-            sender,
-            token_contract: token_contract.clone(),
-            amount: amount.0,
-            destination_chain,
-            destination_address,
-            nonce,
-            timestamp: lock_record.timestamp,
-        });
+        self.record_unlock(
+            &message_id,
+            source_chain.clone(),
+            sequence,
+            sender_address,
+            &recipient,
+            &token_contract,
+            amount.0,
+        );
 
-        log!("Tokens locked: amount={}, destination={}", amount.0, destination_chain);
+        log!("Tokens unlocked: amount={}, recipient={}", amount.0, recipient);
 
-        // Transfer tokens to bridge contract
-        // Using ft_transfer_call to lock tokens in this contract
-        ext_fungible_token::ext(token_contract)
-            .with_attached_deposit(1)
+        // Transfer tokens from bridge to recipient
+        ext_fungible_token::ext(token_contract.clone())
+            .with_attached_deposit(NearToken::from_yoctonear(1))
             .with_static_gas(FT_TRANSFER_GAS)
             .ft_transfer(
-                env::current_account_id(),
+                recipient.clone(),
                 amount,
-                Some(format!("Lock for cross-chain transfer: {}", message_id)),
+                Some(format!("Unlock from cross-chain transfer: {}", Self::hex(&message_id))),
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(UNLOCK_CALLBACK_GAS)
+                    .on_ft_unlock_resolved(
+                        message_id,
+                        source_chain,
+                        sequence,
+                        recipient,
+                        token_contract,
+                        amount,
+                    ),
             )
     }
 
-    /// Unlock tokens after cross-chain transfer (requires validator signatures)
-    pub fn unlock_ft(
+    /// Reopen an unlock whose `ft_transfer` failed, typically because the
+    /// recipient is not registered with the token. The tokens never left the
+    /// bridge, so the same attestation can be submitted again.
+    #[private]
+    pub fn on_ft_unlock_resolved(
+        &mut self,
+        message_id: MessageId,
+        source_chain: String,
+        sequence: u64,
+        recipient: AccountId,
+        token_contract: AccountId,
+        amount: U128,
+    ) -> bool {
+        if near_sdk::is_promise_success() {
+            return true;
+        }
+
+        self.reopen_unlock(&message_id, &source_chain, sequence, recipient, token_contract, amount);
+
+        false
+    }
+
+    /// Lock NEAR tokens for cross-chain transfer. With `amount`, the rest of
+    /// the deposit pays for the lock record and any surplus is refunded;
+    /// without it, the whole deposit is locked.
+    #[payable]
+    pub fn lock_near(
+        &mut self,
+        destination_chain: String,
+        destination_address: String,
+        amount: Option<U128>,
+    ) {
+        self.internal_lock_near(destination_chain, destination_address, None, amount);
+    }
+
+    /// Lock NEAR tokens and attach a call to execute on the destination chain
+    #[payable]
+    pub fn lock_near_and_call(
+        &mut self,
+        destination_chain: String,
+        destination_address: String,
+        call: CallData,
+        amount: Option<U128>,
+    ) {
+        self.internal_lock_near(destination_chain, destination_address, Some(call), amount);
+    }
+
+    /// Unlock tokens to `receiver` and invoke it with the attached call via
+    /// `ft_transfer_call`. Whatever the receiver does not keep, including
+    /// everything when the call fails, goes to `recipient`.
+    pub fn unlock_ft_and_call(
         &mut self,
         message_id: MessageId,
         source_chain: String,
+        sequence: u64,
         sender_address: String,
         recipient: AccountId,
+        receiver: AccountId,
         token_contract: AccountId,
         amount: U128,
+        call: CallData,
         signatures: Vec<Signature>,
+        aggregate_signature: Option<AggregateSignature>,
     ) -> Promise {
-        require!(!self.is_paused, "Bridge is paused");
-        require!(amount.0 > 0, "Amount must be greater than zero");
-        require!(
-            !self.processed_messages.contains(&message_id),
-            "Message already processed"
+        ensure!(!self.is_paused, BridgeError::BridgePaused);
+        ensure!(amount.0 > 0, BridgeError::InvalidAmount);
+        ensure!(
+            !self.is_transfer_processed_by(&message_id, &source_chain, sequence),
+            BridgeError::MessageAlreadyProcessed
         );
 
-        // Verify signatures
-        require!(
-            signatures.len() >= self.required_signatures as usize,
-            "Insufficient signatures"
-        );
+        // NEP-141 receivers take the call payload as the string `msg`
+        let msg = String::from_utf8(call.payload.0.clone())
+            .or_panic(BridgeError::InvalidCallPayload);
 
-        let message_hash = Self::create_unlock_message_hash(
+        let unlock_hash = Self::create_unlock_message_hash(
             &message_id,
             &source_chain,
+            sequence,
             &sender_address,
             &recipient,
             &token_contract,
             amount.0,
         );
+        let message_hash = Self::create_unlock_call_message_hash(&unlock_hash, &receiver, &call);
 
-        let mut valid_signatures = 0;
-        for sig in signatures.iter() {
-            if self.verify_signature(&message_hash, sig) {
-                valid_signatures += 1;
-            }
-        }
+        // Verify signatures
+        self.assert_attested(&message_hash, &signatures, aggregate_signature.as_ref());
 
-        require!(
-            valid_signatures >= self.required_signatures as usize,
-            "Insufficient valid signatures"
+        self.record_unlock(
+            &message_id,
+            source_chain,
+            sequence,
+            sender_address,
+            &receiver,
+            &token_contract,
+            amount.0,
         );
 
-        // Mark message as processed
-        self.processed_messages.insert(&message_id);
+        log!("Tokens unlocked with call: amount={}, receiver={}", amount.0, receiver);
 
-        // Update stats
-        let current_unlocked = self.total_unlocked.get(&token_contract).unwrap_or(0);
-        self.total_unlocked.insert(&token_contract, &(current_unlocked + amount.0));
+        ext_fungible_token::ext(token_contract.clone())
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .with_static_gas(Self::call_gas(&call).saturating_add(FT_TRANSFER_CALL_OVERHEAD_GAS))
+            .ft_transfer_call(
+                receiver,
+                amount,
+                Some(format!("Unlock from cross-chain transfer: {}", Self::hex(&message_id))),
+                msg,
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(UNLOCK_CALL_CALLBACK_GAS)
+                    .on_ft_unlock_call_resolved(message_id, token_contract, recipient, amount),
+            )
+    }
 
-        // Emit event
-        emit_token_unlocked_event(&TokenUnlockedEvent {
-            message_id: message_id.clone(),
-            source_chain,
-            sender_address,
+    /// Send whatever the receiver refunded to the fallback recipient
+    #[private]
+    pub fn on_ft_unlock_call_resolved(
+        &mut self,
+        message_id: MessageId,
+        token_contract: AccountId,
+        recipient: AccountId,
+        amount: U128,
+        #[callback_result] used: Result<U128, PromiseError>,
+    ) -> PromiseOrValue<U128> {
+        // A failed ft_transfer_call leaves every token with the bridge
+        let used = used.map(|used| used.0.min(amount.0)).unwrap_or(0);
+        let refund = amount.0.checked_sub(used).or_panic(BridgeError::ArithmeticOverflow);
+        if refund == 0 {
+            return PromiseOrValue::Value(U128(0));
+        }
+
+        BridgeEvent::UnlockCallFallback(UnlockCallFallbackEvent {
+            message_id: Self::hex(&message_id),
             recipient: recipient.clone(),
-            token_contract: token_contract.clone(),
-            amount: amount.0,
-            timestamp: env::block_timestamp(),
-        });
+            token: token_contract.clone(),
+            amount: U128(refund),
+        })
+        .emit();
 
-        log!("Tokens unlocked: amount={}, recipient={}", amount.0, recipient);
+        log!("Call fallback: refunded={}, recipient={}", refund, recipient);
 
-        // Transfer tokens from bridge to recipient
-        ext_fungible_token::ext(token_contract)
-            .with_attached_deposit(1)
+        ext_fungible_token::ext(token_contract.clone())
+            .with_attached_deposit(NearToken::from_yoctonear(1))
             .with_static_gas(FT_TRANSFER_GAS)
             .ft_transfer(
-                recipient,
-                amount,
-                Some(format!("Unlock from cross-chain transfer: {}", message_id)),
+                recipient.clone(),
+                U128(refund),
+                Some(format!("Call fallback for cross-chain transfer: {}", Self::hex(&message_id))),
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(UNLOCK_CALLBACK_GAS)
+                    .on_ft_unlock_fallback_resolved(
+                        message_id,
+                        token_contract,
+                        recipient,
+                        U128(refund),
+                    ),
             )
+            .into()
     }
 
-    /// Lock NEAR tokens for cross-chain transfer
-    #[payable]
-    pub fn lock_near(
+    /// Keep a fallback the recipient could not be sent, typically because it
+    /// is not registered with the token, for `claim_unlock_fallback`.
+    /// Returns the amount sent.
+    #[private]
+    pub fn on_ft_unlock_fallback_resolved(
         &mut self,
-        destination_chain: String,
-        destination_address: String,
-    ) {
-        require!(!self.is_paused, "Bridge is paused");
-        let amount = env::attached_deposit();
-        require!(amount > 0, "Amount must be greater than zero");
+        message_id: MessageId,
+        token_contract: AccountId,
+        recipient: AccountId,
+        amount: U128,
+    ) -> U128 {
+        if near_sdk::is_promise_success() {
+            return amount;
+        }
 
-        let sender = env::predecessor_account_id();
-        let nonce = self.message_count;
-        self.message_count += 1;
+        self.credit_unclaimed_fallback(&recipient, &token_contract, amount.0);
 
-        // Generate message ID
-        let message_id = Self::generate_message_id(
-            &sender,
-            &AccountId::new_unchecked("near".to_string()),
+        BridgeEvent::UnlockCallFallbackFailed(UnlockCallFallbackEvent {
+            message_id: Self::hex(&message_id),
+            recipient: recipient.clone(),
+            token: token_contract,
             amount,
-            &destination_chain,
-            nonce,
-        );
+        })
+        .emit();
 
-        // Create lock record
-        let lock_record = LockRecord {
-            message_id: message_id.clone(),
-            sender: sender.clone(),
-            token_contract: AccountId::new_unchecked("near".to_string()),
-            amount,
-            destination_chain: destination_chain.clone(),
-            destination_address: destination_address.clone(),
-            nonce,
-            timestamp: env::block_timestamp(),
-        };
+        log!("Call fallback failed: amount={}, recipient={}", amount.0, recipient);
 
-        self.lock_records.insert(&message_id, &lock_record);
+        U128(0)
+    }
 
-        // Update stats for NEAR
-        let near_token = AccountId::new_unchecked("near".to_string());
-        let current_locked = self.total_locked.get(&near_token).unwrap_or(0);
-        self.total_locked.insert(&near_token, &(current_locked + amount));
+    /// Send the caller the unlock-and-call fallbacks of `token_contract` that
+    /// could not be sent to it
+    pub fn claim_unlock_fallback(&mut self, token_contract: AccountId) -> Promise {
+        ensure!(!self.is_paused, BridgeError::BridgePaused);
+        let recipient = env::predecessor_account_id();
+        let amount = self
+            .unclaimed_fallbacks
+            .remove(&(recipient.clone(), token_contract.clone()))
+            .or_panic(BridgeError::NoUnclaimedFallback);
+
+        ext_fungible_token::ext(token_contract.clone())
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .with_static_gas(FT_TRANSFER_GAS)
+            .ft_transfer(
+                recipient.clone(),
+                U128(amount),
+                Some("Claim of cross-chain unlock fallback".to_string()),
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(UNLOCK_CALLBACK_GAS)
+                    .on_fallback_claim_resolved(token_contract, recipient, U128(amount)),
+            )
+    }
 
-        // Emit event
-        emit_token_locked_event(&TokenLockedEvent {
-            message_id: message_id.clone(),
-            sender,
-            token_contract: near_token,
+    /// Restore a claimed fallback whose `ft_transfer` failed
+    #[private]
+    pub fn on_fallback_claim_resolved(
+        &mut self,
+        token_contract: AccountId,
+        recipient: AccountId,
+        amount: U128,
+    ) -> bool {
+        if !near_sdk::is_promise_success() {
+            self.credit_unclaimed_fallback(&recipient, &token_contract, amount.0);
+            return false;
+        }
+
+        BridgeEvent::UnlockFallbackClaimed(UnlockFallbackClaimedEvent {
+            recipient,
+            token: token_contract,
             amount,
-            destination_chain,
-            destination_address,
-            nonce,
-            timestamp: lock_record.timestamp,
-        });
+        })
+        .emit();
 
-        log!("NEAR locked: amount={}, destination={}", amount, destination_chain);
+        true
     }
 
     /// Unlock NEAR tokens after cross-chain transfer
@@ -305,224 +525,1429 @@ impl BridgeContract {
         &mut self,
         message_id: MessageId,
         source_chain: String,
+        sequence: u64,
         sender_address: String,
         recipient: AccountId,
         amount: U128,
         signatures: Vec<Signature>,
+        aggregate_signature: Option<AggregateSignature>,
     ) -> Promise {
-        require!(!self.is_paused, "Bridge is paused");
-        require!(amount.0 > 0, "Amount must be greater than zero");
-        require!(
-            !self.processed_messages.contains(&message_id),
-            "Message already processed"
-        );
-
-        // Verify signatures
-        require!(
-            signatures.len() >= self.required_signatures as usize,
-            "Insufficient signatures"
+        ensure!(!self.is_paused, BridgeError::BridgePaused);
+        ensure!(amount.0 > 0, BridgeError::InvalidAmount);
+        ensure!(
+            !self.is_transfer_processed_by(&message_id, &source_chain, sequence),
+            BridgeError::MessageAlreadyProcessed
         );
 
-        let near_token = AccountId::new_unchecked("near".to_string());
+        let near_token = "near".parse::<AccountId>().expect("valid account ID");
         let message_hash = Self::create_unlock_message_hash(
             &message_id,
             &source_chain,
+            sequence,
             &sender_address,
             &recipient,
             &near_token,
             amount.0,
         );
 
-        let mut valid_signatures = 0;
-        for sig in signatures.iter() {
-            if self.verify_signature(&message_hash, sig) {
-                valid_signatures += 1;
-            }
-        }
-
-        require!(
-            valid_signatures >= self.required_signatures as usize,
-            "Insufficient valid signatures"
-        );
-
-        // Mark message as processed
-        self.processed_messages.insert(&message_id);
-
-        // Update stats
-        let current_unlocked = self.total_unlocked.get(&near_token).unwrap_or(0);
-        self.total_unlocked.insert(&near_token, &(current_unlocked + amount.0));
+        // Verify signatures
+        self.assert_attested(&message_hash, &signatures, aggregate_signature.as_ref());
 
-        // Emit event
-        emit_token_unlocked_event(&TokenUnlockedEvent {
-            message_id: message_id.clone(),
+        self.record_unlock(
+            &message_id,
             source_chain,
+            sequence,
             sender_address,
-            recipient: recipient.clone(),
-            token_contract: near_token,
-            amount: amount.0,
-            timestamp: env::block_timestamp(),
-        });
+            &recipient,
+            &near_token,
+            amount.0,
+        );
 
         log!("NEAR unlocked: amount={}, recipient={}", amount.0, recipient);
 
         // Transfer NEAR to recipient
-        Promise::new(recipient).transfer(amount.0)
+        Promise::new(recipient).transfer(NearToken::from_yoctonear(amount.0))
     }
 
-    // ===== View methods =====
+    /// Mark a lock as completed on the destination chain. `destination_tx`
+    /// references the transaction that delivered it.
+    pub fn acknowledge_delivery(
+        &mut self,
+        message_id: MessageId,
+        destination_tx: String,
+        signatures: Vec<Signature>,
+        aggregate_signature: Option<AggregateSignature>,
+    ) {
+        ensure!(!self.is_paused, BridgeError::BridgePaused);
+        ensure!(destination_tx.len() <= MAX_TX_REF_LEN, BridgeError::DestinationTxTooLong);
 
-    /// Get bridge configuration
-    pub fn get_config(&self) -> BridgeConfig {
-        BridgeConfig {
-            owner: self.owner.clone(),
-            validators: self.validators.len() as u8,
-            required_signatures: self.required_signatures,
-            is_paused: self.is_paused,
-            message_count: self.message_count,
-        }
-    }
+        let mut lock_record =
+            self.lock_record(&message_id).or_panic(BridgeError::LockNotFound);
+        ensure!(lock_record.status == LockStatus::Pending, BridgeError::LockNotPending);
 
-    /// Check if message has been processed
-    pub fn is_message_processed(&self, message_id: MessageId) -> bool {
-        self.processed_messages.contains(&message_id)
-    }
+        let message_hash = Self::create_lock_outcome_hash(
+            DELIVERED_DOMAIN,
+            &lock_record,
+            destination_tx.as_bytes(),
+        );
 
-    /// Get lock record
-    pub fn get_lock_record(&self, message_id: MessageId) -> Option<LockRecord> {
-        self.lock_records.get(&message_id)
-    }
+        // Verify signatures
+        self.assert_attested(&message_hash, &signatures, aggregate_signature.as_ref());
 
-    /// Get total locked for a token
-    pub fn get_total_locked(&self, token_contract: AccountId) -> U128 {
-        U128(self.total_locked.get(&token_contract).unwrap_or(0))
+        lock_record.status = LockStatus::Delivered { destination_tx };
+        self.save_lock_record(&message_id, &lock_record);
+
+        BridgeEvent::LockStatusChanged(LockStatusEvent {
+            message_id: Self::hex(&message_id),
+            status: lock_record.status,
+            timestamp: env::block_timestamp(),
+        })
+        .emit();
+
+        log!("Lock delivered: amount={}, sender={}", lock_record.amount, lock_record.sender);
     }
 
-    /// Get total unlocked for a token
-    pub fn get_total_unlocked(&self, token_contract: AccountId) -> U128 {
-        U128(self.total_unlocked.get(&token_contract).unwrap_or(0))
+    /// Return locked funds to the sender after the validators attest that the
+    /// transfer failed on the destination chain. Callable by anyone.
+    pub fn refund(
+        &mut self,
+        message_id: MessageId,
+        signatures: Vec<Signature>,
+        aggregate_signature: Option<AggregateSignature>,
+    ) -> Promise {
+        self.return_to_sender(
+            message_id,
+            LockStatus::Refunded,
+            &signatures,
+            aggregate_signature.as_ref(),
+        )
     }
 
-    // ===== Admin methods =====
+    /// Return locked funds to the sender once the expiry period has passed and
+    /// the validators attest the transfer was never delivered. Callable by anyone.
+    pub fn expire_lock(
+        &mut self,
+        message_id: MessageId,
+        signatures: Vec<Signature>,
+        aggregate_signature: Option<AggregateSignature>,
+    ) -> Promise {
+        let lock_record = self.lock_record(&message_id).or_panic(BridgeError::LockNotFound);
+        let expires_at = lock_record
+            .timestamp
+            .checked_add(LOCK_EXPIRY_NS)
+            .or_panic(BridgeError::ArithmeticOverflow);
+        ensure!(
+            env::block_timestamp() >= expires_at,
+            BridgeError::LockNotExpired
+        );
+
+        self.return_to_sender(
+            message_id,
+            LockStatus::Expired,
+            &signatures,
+            aggregate_signature.as_ref(),
+        )
+    }
+
+    /// Remove finalized (delivered, refunded or expired) lock records. Their
+    /// history remains in the emitted events. Callable by anyone; pending
+    /// and unknown IDs are skipped. Returns the number of records removed.
+    pub fn prune_lock_records(&mut self, message_ids: Vec<MessageId>) -> u32 {
+        ensure!(message_ids.len() <= MAX_PRUNE_BATCH, BridgeError::TooManyRecords);
+
+        let mut pruned = Vec::new();
+        for message_id in message_ids.iter() {
+            let record = match self.lock_record(message_id) {
+                Some(record) if record.status != LockStatus::Pending => record,
+                _ => continue,
+            };
+
+            // Return the freed storage to the sender who paid for it
+            let initial_storage = env::storage_usage();
+            self.remove_lock_record(message_id);
+            self.release_storage(&record.sender, initial_storage);
+            pruned.push(Self::hex(message_id));
+        }
+
+        log!("Lock records pruned: count={}", pruned.len());
+
+        let count = pruned.len() as u32;
+        if count > 0 {
+            BridgeEvent::LockRecordsPruned(LockRecordsPrunedEvent { message_ids: pruned }).emit();
+        }
+
+        count
+    }
+
+    /// Record a batch Merkle root signed once by the validators. Messages in
+    /// the batch are then released individually through `claim_from_batch`.
+    pub fn submit_batch_root(
+        &mut self,
+        batch_root: [u8; 32],
+        message_count: u32,
+        signatures: Vec<Signature>,
+        aggregate_signature: Option<AggregateSignature>,
+    ) {
+        ensure!(!self.is_paused, BridgeError::BridgePaused);
+        ensure!(message_count > 0, BridgeError::InvalidBatch);
+        ensure!(
+            self.batch_roots.get(&batch_root).is_none(),
+            BridgeError::BatchAlreadySubmitted
+        );
+
+        let root_hash = Self::create_batch_root_hash(&batch_root, message_count);
+        self.assert_attested(&root_hash, &signatures, aggregate_signature.as_ref());
+
+        let timestamp = env::block_timestamp();
+        self.batch_roots.insert(&batch_root, &BatchRoot {
+            message_count,
+            claimed_count: 0,
+            timestamp,
+        });
+
+        BridgeEvent::BatchRootSubmitted(BatchRootSubmittedEvent {
+            batch_root: Self::hex(&batch_root),
+            message_count,
+            timestamp,
+        })
+        .emit();
+
+        log!("Batch root submitted: messages={}", message_count);
+    }
+
+    /// Unlock a single message from an attested batch using its Merkle proof.
+    /// Leaf fields must be exactly those hashed by `internal/batching`;
+    /// `payload` is the relayer's JSON `TokenTransferPayload`.
+    pub fn claim_from_batch(
+        &mut self,
+        batch_root: [u8; 32],
+        message_id: MessageId,
+        source_chain: String,
+        sequence: u64,
+        destination_chain: String,
+        sender_address: String,
+        recipient: AccountId,
+        payload: String,
+        leaf_index: u32,
+        proof: Vec<[u8; 32]>,
+    ) -> Promise {
+        ensure!(!self.is_paused, BridgeError::BridgePaused);
+        ensure!(
+            !self.is_transfer_processed_by(&message_id, &source_chain, sequence),
+            BridgeError::MessageAlreadyProcessed
+        );
+
+        ensure!(destination_chain == chain::NEAR, BridgeError::WrongDestinationChain);
+
+        let mut batch = self.batch_roots.get(&batch_root).or_panic(BridgeError::BatchNotFound);
+        ensure!(leaf_index < batch.message_count, BridgeError::LeafIndexOutOfRange);
+
+        // Verify inclusion in the attested batch
+        let leaf = merkle::hash_leaf(
+            &message_id,
+            &source_chain,
+            sequence,
+            &destination_chain,
+            &sender_address,
+            recipient.as_str(),
+            payload.as_bytes(),
+        );
+        ensure!(
+            merkle::verify_proof(&leaf, leaf_index, &proof, &batch_root),
+            BridgeError::InvalidMerkleProof
+        );
+
+        // The token and amount come from the attested payload only
+        let transfer: BatchTokenPayload = near_sdk::serde_json::from_str(&payload)
+            .or_panic(BridgeError::InvalidBatchPayload);
+        let amount: Balance = transfer.amount.parse()
+            .or_panic(BridgeError::InvalidBatchAmount);
+        ensure!(amount > 0, BridgeError::InvalidAmount);
+        let token_contract: AccountId = transfer.token_address.raw.parse()
+            .or_panic(BridgeError::InvalidBatchToken);
+
+        batch.claimed_count =
+            batch.claimed_count.checked_add(1).or_panic(BridgeError::ArithmeticOverflow);
+        self.batch_roots.insert(&batch_root, &batch);
+
+        self.record_unlock(
+            &message_id,
+            source_chain.clone(),
+            sequence,
+            sender_address,
+            &recipient,
+            &token_contract,
+            amount,
+        );
+
+        log!("Batch message claimed: amount={}, recipient={}", amount, recipient);
+
+        if token_contract.as_str() == NATIVE_TOKEN {
+            return Promise::new(recipient).transfer(NearToken::from_yoctonear(amount));
+        }
+
+        ext_fungible_token::ext(token_contract.clone())
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .with_static_gas(FT_TRANSFER_GAS)
+            .ft_transfer(
+                recipient.clone(),
+                U128(amount),
+                Some(format!("Unlock from cross-chain batch: {}", Self::hex(&message_id))),
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(UNLOCK_CALLBACK_GAS)
+                    .on_batch_claim_resolved(
+                        batch_root,
+                        message_id,
+                        source_chain,
+                        sequence,
+                        recipient,
+                        token_contract,
+                        U128(amount),
+                    ),
+            )
+    }
+
+    /// Reopen a batch claim whose `ft_transfer` failed, so the same proof can
+    /// be submitted again
+    #[private]
+    pub fn on_batch_claim_resolved(
+        &mut self,
+        batch_root: [u8; 32],
+        message_id: MessageId,
+        source_chain: String,
+        sequence: u64,
+        recipient: AccountId,
+        token_contract: AccountId,
+        amount: U128,
+    ) -> bool {
+        if near_sdk::is_promise_success() {
+            return true;
+        }
+
+        if let Some(mut batch) = self.batch_roots.get(&batch_root) {
+            batch.claimed_count =
+                batch.claimed_count.checked_sub(1).or_panic(BridgeError::ArithmeticOverflow);
+            self.batch_roots.insert(&batch_root, &batch);
+        }
+        self.reopen_unlock(&message_id, &source_chain, sequence, recipient, token_contract, amount);
+
+        false
+    }
+
+    /// Reopen a refund or expiry whose `ft_transfer` failed
+    #[private]
+    pub fn on_refund_resolved(&mut self, message_id: MessageId) -> bool {
+        if near_sdk::is_promise_success() {
+            return true;
+        }
+
+        self.reopen_refund(&message_id);
+
+        false
+    }
+
+    /// Send an arbitrary payload to `target` on another chain
+    pub fn send_message(
+        &mut self,
+        destination_chain: String,
+        target: String,
+        payload: Base64VecU8,
+    ) -> MessageId {
+        ensure!(!self.is_paused, BridgeError::BridgePaused);
+        ensure!(
+            destination_chain.len() <= MAX_CHAIN_NAME_LEN,
+            BridgeError::DestinationChainTooLong
+        );
+        ensure!(target.len() <= MAX_ADDRESS_LEN, BridgeError::TargetAddressTooLong);
+        ensure!(payload.0.len() <= MAX_PAYLOAD_LEN, BridgeError::PayloadTooLarge);
+
+        let sender = env::predecessor_account_id();
+        let nonce = self.message_count;
+        self.message_count = nonce.checked_add(1).or_panic(BridgeError::ArithmeticOverflow);
+
+        let message_id = Self::generate_call_message_id(
+            &sender,
+            &destination_chain,
+            &target,
+            &payload.0,
+            nonce,
+        );
+
+        BridgeEvent::MessageSent(MessageSentEvent {
+            message_id: Self::hex(&message_id),
+            sender,
+            destination_chain: destination_chain.clone(),
+            target,
+            payload,
+            nonce,
+            timestamp: env::block_timestamp(),
+        })
+        .emit();
+
+        log!("Message sent: destination={}", destination_chain);
+
+        message_id
+    }
+
+    /// Accept a validator-attested message and deliver it to `target` through
+    /// its `on_bridge_message` method
+    pub fn receive_message(
+        &mut self,
+        message_id: MessageId,
+        source_chain: String,
+        sender_address: String,
+        target: AccountId,
+        payload: Base64VecU8,
+        signatures: Vec<Signature>,
+        aggregate_signature: Option<AggregateSignature>,
+    ) -> Promise {
+        ensure!(!self.is_paused, BridgeError::BridgePaused);
+        ensure!(payload.0.len() <= MAX_PAYLOAD_LEN, BridgeError::PayloadTooLarge);
+        ensure!(
+            !self.is_processed(&message_id),
+            BridgeError::MessageAlreadyProcessed
+        );
+        ensure!(
+            target != env::current_account_id(),
+            BridgeError::InvalidMessageTarget
+        );
+
+        let message_hash = Self::create_message_hash(
+            &message_id,
+            &source_chain,
+            &sender_address,
+            &target,
+            &payload.0,
+        );
+        self.assert_attested(&message_hash, &signatures, aggregate_signature.as_ref());
+
+        // Mark message as processed
+        self.mark_processed(&message_id);
+
+        BridgeEvent::MessageReceived(MessageReceivedEvent {
+            message_id: Self::hex(&message_id),
+            source_chain: source_chain.clone(),
+            sender_address: sender_address.clone(),
+            target: target.clone(),
+            timestamp: env::block_timestamp(),
+        })
+        .emit();
+
+        let message = InboundMessage {
+            message_id,
+            source_chain,
+            sender_address,
+            target,
+            payload,
+            status: DeliveryStatus::Pending,
+            attempts: 0,
+            last_attempt_timestamp: 0,
+        };
+
+        self.deliver_message(message)
+    }
+
+    /// Retry delivery of a message whose receiver call failed
+    pub fn retry_message(&mut self, message_id: MessageId) -> Promise {
+        ensure!(!self.is_paused, BridgeError::BridgePaused);
+        let message = self.inbound_messages.get(&message_id).or_panic(BridgeError::MessageNotFound);
+        ensure!(
+            message.status == DeliveryStatus::Failed,
+            BridgeError::MessageNotAwaitingRetry
+        );
+        ensure!(
+            message.attempts < MAX_DELIVERY_ATTEMPTS,
+            BridgeError::MaxDeliveryAttemptsReached
+        );
+
+        self.deliver_message(message)
+    }
+
+    /// Record the outcome of an `on_bridge_message` call
+    #[private]
+    pub fn on_message_delivered(&mut self, message_id: MessageId) -> bool {
+        let mut message =
+            self.inbound_messages.get(&message_id).or_panic(BridgeError::MessageNotFound);
+        let delivered = near_sdk::is_promise_success();
+
+        message.status = if delivered {
+            DeliveryStatus::Delivered
+        } else {
+            DeliveryStatus::Failed
+        };
+        self.inbound_messages.insert(&message_id, &message);
+
+        let event = MessageDeliveryEvent {
+            message_id: Self::hex(&message_id),
+            source_chain: message.source_chain.clone(),
+            target: message.target.clone(),
+            attempts: message.attempts,
+            timestamp: env::block_timestamp(),
+        };
+        if delivered {
+            BridgeEvent::MessageDelivered(event).emit();
+            log!("Message delivered to {}", message.target);
+        } else {
+            BridgeEvent::MessageDeliveryFailed(event).emit();
+            log!("Message delivery to {} failed (attempt {})", message.target, message.attempts);
+        }
+
+        delivered
+    }
+
+    /// Unlock NEAR to `receiver` by calling its `on_bridge_transfer` with the
+    /// amount attached. If the call fails, the NEAR goes to `recipient`.
+    pub fn unlock_near_and_call(
+        &mut self,
+        message_id: MessageId,
+        source_chain: String,
+        sequence: u64,
+        sender_address: String,
+        recipient: AccountId,
+        receiver: AccountId,
+        amount: U128,
+        call: CallData,
+        signatures: Vec<Signature>,
+        aggregate_signature: Option<AggregateSignature>,
+    ) -> Promise {
+        ensure!(!self.is_paused, BridgeError::BridgePaused);
+        ensure!(amount.0 > 0, BridgeError::InvalidAmount);
+        ensure!(
+            !self.is_transfer_processed_by(&message_id, &source_chain, sequence),
+            BridgeError::MessageAlreadyProcessed
+        );
+
+        let near_token = NATIVE_TOKEN.parse::<AccountId>().expect("valid account ID");
+        let unlock_hash = Self::create_unlock_message_hash(
+            &message_id,
+            &source_chain,
+            sequence,
+            &sender_address,
+            &recipient,
+            &near_token,
+            amount.0,
+        );
+        let message_hash = Self::create_unlock_call_message_hash(&unlock_hash, &receiver, &call);
+
+        // Verify signatures
+        self.assert_attested(&message_hash, &signatures, aggregate_signature.as_ref());
+
+        self.record_unlock(
+            &message_id,
+            source_chain.clone(),
+            sequence,
+            sender_address.clone(),
+            &receiver,
+            &near_token,
+            amount.0,
+        );
+
+        log!("NEAR unlocked with call: amount={}, receiver={}", amount.0, receiver);
+
+        ext_bridge_transfer_receiver::ext(receiver)
+            .with_attached_deposit(NearToken::from_yoctonear(amount.0))
+            .with_static_gas(Self::call_gas(&call))
+            .on_bridge_transfer(message_id, source_chain, sender_address, call.payload)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(UNLOCK_CALL_CALLBACK_GAS)
+                    .on_near_unlock_call_resolved(message_id, recipient, amount),
+            )
+    }
+
+    /// Send the NEAR to the fallback recipient if the receiver call failed.
+    /// The attached deposit of a failed call is refunded to the bridge.
+    #[private]
+    pub fn on_near_unlock_call_resolved(
+        &mut self,
+        message_id: MessageId,
+        recipient: AccountId,
+        amount: U128,
+    ) -> PromiseOrValue<bool> {
+        if near_sdk::is_promise_success() {
+            return PromiseOrValue::Value(true);
+        }
+
+        BridgeEvent::UnlockCallFallback(UnlockCallFallbackEvent {
+            message_id: Self::hex(&message_id),
+            recipient: recipient.clone(),
+            token: NATIVE_TOKEN.parse::<AccountId>().expect("valid account ID"),
+            amount,
+        })
+        .emit();
+
+        log!("Call fallback: refunded={}, recipient={}", amount.0, recipient);
+
+        Promise::new(recipient).transfer(NearToken::from_yoctonear(amount.0)).into()
+    }
+
+    // ===== View methods =====
+
+    /// Get bridge configuration
+    pub fn get_config(&self) -> BridgeConfig {
+        BridgeConfig {
+            owner: self.owner.clone(),
+            validators: self.validators.len() as u8,
+            required_signatures: self.required_signatures,
+            is_paused: self.is_paused,
+            message_count: self.message_count,
+            signature_mode: self.signature_mode,
+        }
+    }
+
+    /// Get the threshold group key, if one has been set
+    pub fn get_group_key(&self) -> Option<GroupKey> {
+        self.group_key.clone()
+    }
+
+    /// Check if an NFT transfer or generic message has been processed
+    pub fn is_message_processed(&self, message_id: MessageId) -> bool {
+        self.is_processed(&message_id)
+    }
+
+    /// Check if the token transfer with `sequence` from `source_chain` has
+    /// been released
+    pub fn is_transfer_processed(&self, source_chain: String, sequence: u64) -> bool {
+        self.is_sequence_processed(&source_chain, sequence)
+    }
+
+    /// Get lock record
+    pub fn get_lock_record(&self, message_id: MessageId) -> Option<LockRecord> {
+        self.lock_record(&message_id)
+    }
+
+    /// Get lifecycle state of a lock
+    pub fn get_lock_status(&self, message_id: MessageId) -> Option<LockStatus> {
+        self.lock_record(&message_id).map(|record| record.status)
+    }
+
+    /// Get how much state the bridge holds
+    pub fn get_storage_usage(&self) -> StorageUsage {
+        StorageUsage {
+            storage_bytes: env::storage_usage(),
+            lock_records: self.lock_records.len().saturating_add(
+                self.legacy_lock_records.as_ref().map_or(0, |legacy| legacy.len()),
+            ),
+            processed_messages: self.processed_message_count,
+            batch_roots: self.batch_roots.len(),
+            inbound_messages: self.inbound_messages.len(),
+            escrowed_nfts: self.nft_escrow.len(),
+        }
+    }
+
+    /// Get an attested batch root
+    pub fn get_batch_root(&self, batch_root: [u8; 32]) -> Option<BatchRoot> {
+        self.batch_roots.get(&batch_root)
+    }
+
+    /// Get an inbound message and its delivery state
+    pub fn get_inbound_message(&self, message_id: MessageId) -> Option<InboundMessage> {
+        self.inbound_messages.get(&message_id)
+    }
+
+    /// Get total locked for a token
+    pub fn get_total_locked(&self, token_contract: AccountId) -> U128 {
+        U128(self.total_locked.get(&token_contract).unwrap_or(0))
+    }
+
+    /// Get total unlocked for a token
+    pub fn get_total_unlocked(&self, token_contract: AccountId) -> U128 {
+        U128(self.total_unlocked.get(&token_contract).unwrap_or(0))
+    }
+
+    /// Get the unlock-and-call fallbacks of a token waiting to be claimed by
+    /// `account_id`
+    pub fn get_unclaimed_fallback(&self, account_id: AccountId, token_contract: AccountId) -> U128 {
+        U128(self.unclaimed_fallbacks.get(&(account_id, token_contract)).unwrap_or(0))
+    }
+
+    // ===== Admin methods =====
 
     /// Add a new validator
     pub fn add_validator(&mut self, validator: PublicKey) {
         self.assert_owner();
-        require!(
-            self.validators.len() < MAX_VALIDATORS,
-            "Maximum validators reached"
+        ensure!(
+            self.validators.len() < MAX_VALIDATORS as u64,
+            BridgeError::MaxValidatorsReached
         );
-        require!(
+        ensure!(
             !self.validators.contains(&validator),
-            "Validator already exists"
+            BridgeError::ValidatorAlreadyExists
         );
 
         self.validators.insert(&validator);
-        log!("Validator added");
+
+        BridgeEvent::ValidatorAdded(ValidatorChangeEvent {
+            validator,
+            validator_count: self.validators.len(),
+            updated_by: env::predecessor_account_id(),
+        })
+        .emit();
     }
 
     /// Remove a validator
     pub fn remove_validator(&mut self, validator: PublicKey) {
         self.assert_owner();
-        require!(
+        ensure!(
             self.validators.contains(&validator),
-            "Validator not found"
+            BridgeError::ValidatorNotFound
         );
 
         self.validators.remove(&validator);
 
         // Ensure required signatures is still valid
-        require!(
-            self.required_signatures as usize <= self.validators.len(),
-            "Invalid required signatures after removal"
+        ensure!(
+            u64::from(self.required_signatures) <= self.validators.len(),
+            BridgeError::RequiredSignaturesExceedValidators
         );
 
-        log!("Validator removed");
+        BridgeEvent::ValidatorRemoved(ValidatorChangeEvent {
+            validator,
+            validator_count: self.validators.len(),
+            updated_by: env::predecessor_account_id(),
+        })
+        .emit();
     }
 
     /// Update required signatures
     pub fn update_required_signatures(&mut self, required_signatures: u8) {
         self.assert_owner();
-        require!(
-            required_signatures > 0 && required_signatures as usize <= self.validators.len(),
-            "Invalid required signatures"
+        ensure!(
+            required_signatures > 0 && u64::from(required_signatures) <= self.validators.len(),
+            BridgeError::InvalidRequiredSignatures
         );
 
+        let old_required_signatures = self.required_signatures;
         self.required_signatures = required_signatures;
-        log!("Required signatures updated to: {}", required_signatures);
+
+        BridgeEvent::RequiredSignaturesUpdated(RequiredSignaturesUpdatedEvent {
+            old_required_signatures,
+            new_required_signatures: required_signatures,
+            updated_by: env::predecessor_account_id(),
+        })
+        .emit();
+    }
+
+    /// Rotate the threshold group key produced by the validators' DKG
+    pub fn set_group_key(&mut self, public_key: PublicKey, threshold: u8, group_size: u8) {
+        self.assert_owner();
+        ensure!(
+            public_key.curve_type() == CurveType::ED25519,
+            BridgeError::InvalidGroupKey
+        );
+        ensure!(
+            group_size > 0 && group_size as usize <= MAX_GROUP_SIZE,
+            BridgeError::InvalidGroupSize
+        );
+        ensure!(
+            threshold > 0 && threshold <= group_size,
+            BridgeError::InvalidGroupThreshold
+        );
+
+        let old_public_key = self.group_key.as_ref().map(|key| key.public_key.clone());
+        let epoch = match &self.group_key {
+            Some(key) => key.epoch.checked_add(1).or_panic(BridgeError::ArithmeticOverflow),
+            None => 0,
+        };
+
+        self.group_key = Some(GroupKey {
+            public_key: public_key.clone(),
+            threshold,
+            group_size,
+            epoch,
+        });
+
+        BridgeEvent::GroupKeyRotated(GroupKeyRotatedEvent {
+            old_public_key,
+            new_public_key: public_key,
+            threshold,
+            group_size,
+            epoch,
+        })
+        .emit();
+
+        log!("Group key rotated to epoch {}", epoch);
+    }
+
+    /// Switch between individual and threshold signature verification
+    pub fn set_signature_mode(&mut self, mode: SignatureMode) {
+        self.assert_owner();
+        if mode == SignatureMode::Threshold {
+            ensure!(self.group_key.is_some(), BridgeError::GroupKeyNotSet);
+        }
+
+        let old_mode = self.signature_mode;
+        self.signature_mode = mode;
+
+        BridgeEvent::SignatureModeUpdated(SignatureModeUpdatedEvent {
+            old_mode,
+            new_mode: mode,
+            updated_by: env::predecessor_account_id(),
+        })
+        .emit();
     }
 
     /// Pause the bridge
     pub fn pause(&mut self) {
         self.assert_owner();
         self.is_paused = true;
-        log!("Bridge paused");
+
+        BridgeEvent::BridgePaused(PauseEvent {
+            updated_by: env::predecessor_account_id(),
+            timestamp: env::block_timestamp(),
+        })
+        .emit();
     }
 
     /// Unpause the bridge
     pub fn unpause(&mut self) {
         self.assert_owner();
         self.is_paused = false;
-        log!("Bridge unpaused");
+
+        BridgeEvent::BridgeUnpaused(PauseEvent {
+            updated_by: env::predecessor_account_id(),
+            timestamp: env::block_timestamp(),
+        })
+        .emit();
     }
 
     /// Transfer ownership
     pub fn transfer_ownership(&mut self, new_owner: AccountId) {
         self.assert_owner();
-        self.owner = new_owner.clone();
-        log!("Ownership transferred to: {}", new_owner);
+        let old_owner = std::mem::replace(&mut self.owner, new_owner.clone());
+
+        BridgeEvent::OwnershipTransferred(OwnershipTransferredEvent {
+            old_owner,
+            new_owner,
+        })
+        .emit();
     }
 
     // ===== Internal methods =====
 
     fn assert_owner(&self) {
-        require!(
+        ensure!(
             env::predecessor_account_id() == self.owner,
-            "Only owner can call this method"
+            BridgeError::Unauthorized
         );
     }
 
-    fn generate_message_id(
-        sender: &AccountId,
+    /// Hash validators sign to release an inbound transfer
+    fn create_unlock_message_hash(
+        message_id: &MessageId,
+        source_chain: &str,
+        sequence: u64,
+        sender_address: &str,
+        recipient: &AccountId,
         token_contract: &AccountId,
         amount: Balance,
-        destination_chain: &str,
-        nonce: u64,
+    ) -> [u8; 32] {
+        UnlockAttestation {
+            message_id: *message_id,
+            source_chain: source_chain.to_string(),
+            sequence,
+            sender: sender_address.to_string(),
+            destination_chain: chain::NEAR.to_string(),
+            destination_bridge: env::current_account_id().as_str().as_bytes().to_vec(),
+            recipient: recipient.as_str().as_bytes().to_vec(),
+            token: token_contract.as_str().as_bytes().to_vec(),
+            amount,
+        }
+        .signing_hash::<NearKeccak>()
+    }
+
+    fn internal_lock_near(
+        &mut self,
+        destination_chain: String,
+        destination_address: String,
+        call: Option<CallData>,
+        amount: Option<U128>,
+    ) {
+        ensure!(!self.is_paused, BridgeError::BridgePaused);
+        let attached = env::attached_deposit().as_yoctonear();
+        let amount = amount.map_or(attached, |amount| amount.0);
+        ensure!(amount > 0, BridgeError::InvalidAmount);
+        let surplus = attached.checked_sub(amount).or_panic(BridgeError::AmountExceedsDeposit);
+
+        let sender = env::predecessor_account_id();
+        let initial_storage = env::storage_usage();
+        self.record_lock(
+            sender.clone(),
+            NATIVE_TOKEN.parse::<AccountId>().expect("valid account ID"),
+            amount,
+            destination_chain.clone(),
+            destination_address,
+            call,
+        );
+        let unused = self.charge_storage(&sender, initial_storage, surplus);
+        if unused > 0 {
+            Promise::new(sender).transfer(NearToken::from_yoctonear(unused)).detach();
+        }
+
+        log!("NEAR locked: amount={}, destination={}", amount, destination_chain);
+    }
+
+    /// Validate and store an outgoing lock from `sender`, update stats and
+    /// emit the event
+    fn record_lock(
+        &mut self,
+        sender: AccountId,
+        token_contract: AccountId,
+        amount: Balance,
+        destination_chain: String,
+        destination_address: String,
+        call: Option<CallData>,
     ) -> MessageId {
-        let data = format!(
-            "{}{}{}{}{}",
-            sender.as_str(),
-            token_contract.as_str(),
+        ensure!(
+            destination_chain.len() <= MAX_CHAIN_NAME_LEN,
+            BridgeError::DestinationChainTooLong
+        );
+        ensure!(
+            destination_address.len() <= MAX_ADDRESS_LEN,
+            BridgeError::DestinationAddressTooLong
+        );
+        if let Some(call) = &call {
+            ensure!(call.payload.0.len() <= MAX_CALL_PAYLOAD_LEN, BridgeError::CallPayloadTooLarge);
+            ensure!(call.gas_limit <= MAX_CALL_GAS.as_gas(), BridgeError::CallGasLimitTooHigh);
+        }
+
+        let nonce = self.message_count;
+        self.message_count = nonce.checked_add(1).or_panic(BridgeError::ArithmeticOverflow);
+
+        // Generate message ID, committing to the attached call if any
+        let mut message_id = TransferMessage {
+            source_chain: chain::NEAR.to_string(),
+            source_bridge: env::current_account_id().as_str().as_bytes().to_vec(),
+            sequence: nonce,
+            sender: sender.as_str().as_bytes().to_vec(),
+            token: token_contract.as_str().as_bytes().to_vec(),
+            amount,
+            destination_chain: destination_chain.clone(),
+            recipient: destination_address.clone(),
+        }
+        .id::<NearKeccak>();
+        if let Some(call) = &call {
+            message_id = Self::commit_call(&message_id, call);
+        }
+
+        // Create lock record
+        let lock_record = LockRecord {
+            message_id,
+            sender: sender.clone(),
+            token_contract: token_contract.clone(),
             amount,
+            destination_chain: destination_chain.clone(),
+            destination_address: destination_address.clone(),
+            nonce,
+            timestamp: env::block_timestamp(),
+            call: call.clone(),
+            status: LockStatus::Pending,
+        };
+
+        self.lock_records.insert(&message_id, &lock_record);
+
+        // Update stats
+        let current_locked = self.total_locked.get(&token_contract).unwrap_or(0);
+        let current_locked =
+            current_locked.checked_add(amount).or_panic(BridgeError::ArithmeticOverflow);
+        self.total_locked.insert(&token_contract, &current_locked);
+
+        // Emit event
+        BridgeEvent::TokenLocked(TokenLockedEvent {
+            message_id: Self::hex(&message_id),
+            sender,
+            recipient: destination_address,
+            token: token_contract,
+            amount: U128(amount),
             destination_chain,
-            nonce
+            nonce,
+            timestamp: lock_record.timestamp,
+            call,
+        })
+        .emit();
+
+        message_id
+    }
+
+    /// Mark an inbound transfer as processed, update stats and emit the event
+    fn record_unlock(
+        &mut self,
+        message_id: &MessageId,
+        source_chain: String,
+        sequence: u64,
+        sender_address: String,
+        recipient: &AccountId,
+        token_contract: &AccountId,
+        amount: Balance,
+    ) {
+        // Only tokens held in escrow can be released
+        let current_unlocked = self.total_unlocked.get(token_contract).unwrap_or(0);
+        let current_locked = self.total_locked.get(token_contract).unwrap_or(0);
+        ensure!(
+            amount <= current_locked.saturating_sub(current_unlocked),
+            BridgeError::InsufficientLockedBalance
         );
 
-        env::keccak256(data.as_bytes())
+        // Mark the transfer as processed
+        self.mark_sequence_processed(&source_chain, sequence);
+
+        // Update stats
+        let current_unlocked =
+            current_unlocked.checked_add(amount).or_panic(BridgeError::ArithmeticOverflow);
+        self.total_unlocked.insert(token_contract, &current_unlocked);
+
+        // Emit event
+        BridgeEvent::TokenUnlocked(TokenUnlockedEvent {
+            message_id: Self::hex(message_id),
+            source_chain,
+            sequence,
+            sender_address,
+            recipient: recipient.clone(),
+            token: token_contract.clone(),
+            amount: U128(amount),
+            timestamp: env::block_timestamp(),
+        })
+        .emit();
+    }
+
+    /// Fold an attached call into a message ID so the call cannot be altered
+    fn commit_call(message_id: &MessageId, call: &CallData) -> MessageId {
+        let mut data = Vec::new();
+        data.extend_from_slice(message_id);
+        data.extend_from_slice(&env::keccak256(&call.payload.0));
+        data.extend_from_slice(&call.gas_limit.to_le_bytes());
+
+        env::keccak256(&data)
             .try_into()
             .expect("Hash should be 32 bytes")
     }
 
-    fn create_unlock_message_hash(
+    /// Hash validators sign for an unlock-and-call: the plain unlock hash
+    /// (with `recipient` as fallback) extended with the receiver and call
+    fn create_unlock_call_message_hash(
+        unlock_hash: &[u8; 32],
+        receiver: &AccountId,
+        call: &CallData,
+    ) -> [u8; 32] {
+        let mut data = Vec::new();
+        data.extend_from_slice(unlock_hash);
+        data.extend_from_slice(receiver.as_str().as_bytes());
+        data.extend_from_slice(&env::keccak256(&call.payload.0));
+        data.extend_from_slice(&call.gas_limit.to_le_bytes());
+
+        env::keccak256(&data)
+            .try_into()
+            .expect("Hash should be 32 bytes")
+    }
+
+    /// Gas for the destination call: the sender's hint, bounded
+    fn call_gas(call: &CallData) -> Gas {
+        Gas::from_gas(call.gas_limit.clamp(MIN_CALL_GAS.as_gas(), MAX_CALL_GAS.as_gas()))
+    }
+
+    fn deliver_message(&mut self, mut message: InboundMessage) -> Promise {
+        ensure!(
+            env::prepaid_gas().saturating_sub(env::used_gas()) >= MIN_DELIVERY_GAS,
+            BridgeError::InsufficientDeliveryGas
+        );
+
+        message.status = DeliveryStatus::Pending;
+        message.attempts =
+            message.attempts.checked_add(1).or_panic(BridgeError::ArithmeticOverflow);
+        message.last_attempt_timestamp = env::block_timestamp();
+        self.inbound_messages.insert(&message.message_id, &message);
+
+        ext_bridge_receiver::ext(message.target.clone())
+            .with_static_gas(MESSAGE_DELIVERY_GAS)
+            .on_bridge_message(
+                message.message_id,
+                message.source_chain.clone(),
+                message.sender_address.clone(),
+                message.payload.clone(),
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(DELIVERY_CALLBACK_GAS)
+                    .on_message_delivered(message.message_id),
+            )
+    }
+
+    fn generate_call_message_id(
+        sender: &AccountId,
+        destination_chain: &str,
+        target: &str,
+        payload: &[u8],
+        nonce: u64,
+    ) -> MessageId {
+        GenericMessage {
+            source_chain: chain::NEAR.to_string(),
+            source_bridge: env::current_account_id().as_str().as_bytes().to_vec(),
+            sequence: nonce,
+            sender: sender.as_str().as_bytes().to_vec(),
+            destination_chain: destination_chain.to_string(),
+            target: target.to_string(),
+            payload: payload.to_vec(),
+        }
+        .id::<NearKeccak>()
+    }
+
+    fn create_message_hash(
         message_id: &MessageId,
         source_chain: &str,
         sender_address: &str,
+        target: &AccountId,
+        payload: &[u8],
+    ) -> [u8; 32] {
+        MessageDeliveryAttestation {
+            message_id: *message_id,
+            source_chain: source_chain.to_string(),
+            sender: sender_address.to_string(),
+            destination_chain: chain::NEAR.to_string(),
+            destination_bridge: env::current_account_id().as_str().as_bytes().to_vec(),
+            target: target.as_str().as_bytes().to_vec(),
+            payload: payload.to_vec(),
+        }
+        .signing_hash::<NearKeccak>()
+    }
+
+    /// Move a pending lock to `Refunded` or `Expired` on a validator
+    /// attestation and send the funds back to the sender
+    fn return_to_sender(
+        &mut self,
+        message_id: MessageId,
+        status: LockStatus,
+        signatures: &[Signature],
+        aggregate_signature: Option<&AggregateSignature>,
+    ) -> Promise {
+        ensure!(!self.is_paused, BridgeError::BridgePaused);
+
+        let mut lock_record =
+            self.lock_record(&message_id).or_panic(BridgeError::LockNotFound);
+        ensure!(lock_record.status == LockStatus::Pending, BridgeError::LockNotPending);
+
+        let domain = match status {
+            LockStatus::Refunded => REFUND_DOMAIN,
+            LockStatus::Expired => EXPIRED_DOMAIN,
+            _ => BridgeError::InvalidLockOutcome.panic(),
+        };
+        let message_hash = Self::create_lock_outcome_hash(domain, &lock_record, &[]);
+
+        // Verify signatures
+        self.assert_attested(&message_hash, signatures, aggregate_signature);
+
+        lock_record.status = status;
+        self.save_lock_record(&message_id, &lock_record);
+
+        // Update stats
+        let token_contract = lock_record.token_contract;
+        let amount = lock_record.amount;
+        let current_locked = self.total_locked.get(&token_contract).unwrap_or(0);
+        let current_unlocked = self.total_unlocked.get(&token_contract).unwrap_or(0);
+        ensure!(
+            amount <= current_locked.saturating_sub(current_unlocked),
+            BridgeError::InsufficientLockedBalance
+        );
+        let current_locked =
+            current_locked.checked_sub(amount).or_panic(BridgeError::ArithmeticOverflow);
+        self.total_locked.insert(&token_contract, &current_locked);
+
+        // Emit events
+        let timestamp = env::block_timestamp();
+        BridgeEvent::LockStatusChanged(LockStatusEvent {
+            message_id: Self::hex(&message_id),
+            status: lock_record.status,
+            timestamp,
+        })
+        .emit();
+        BridgeEvent::TokenRefunded(TokenRefundedEvent {
+            message_id: Self::hex(&message_id),
+            sender: lock_record.sender.clone(),
+            token: token_contract.clone(),
+            amount: U128(amount),
+            timestamp,
+        })
+        .emit();
+
+        log!("Lock refunded: amount={}, sender={}", amount, lock_record.sender);
+
+        if token_contract.as_str() == NATIVE_TOKEN {
+            return Promise::new(lock_record.sender).transfer(NearToken::from_yoctonear(amount));
+        }
+
+        ext_fungible_token::ext(token_contract)
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .with_static_gas(FT_TRANSFER_GAS)
+            .ft_transfer(
+                lock_record.sender,
+                U128(amount),
+                Some(format!("Refund of cross-chain transfer: {}", Self::hex(&message_id))),
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(UNLOCK_CALLBACK_GAS)
+                    .on_refund_resolved(message_id),
+            )
+    }
+
+    /// Put a lock back to pending after the `ft_transfer` of its refund
+    /// failed, typically because the sender left the token. The tokens never
+    /// left the bridge, so the same attestation can be submitted again.
+    fn reopen_refund(&mut self, message_id: &MessageId) {
+        let mut lock_record =
+            self.lock_record(message_id).or_panic(BridgeError::LockNotFound);
+        lock_record.status = LockStatus::Pending;
+        self.save_lock_record(message_id, &lock_record);
+
+        let token_contract = lock_record.token_contract;
+        let amount = lock_record.amount;
+        let current_locked = self.total_locked.get(&token_contract).unwrap_or(0);
+        let current_locked =
+            current_locked.checked_add(amount).or_panic(BridgeError::ArithmeticOverflow);
+        self.total_locked.insert(&token_contract, &current_locked);
+
+        BridgeEvent::LockStatusChanged(LockStatusEvent {
+            message_id: Self::hex(message_id),
+            status: LockStatus::Pending,
+            timestamp: env::block_timestamp(),
+        })
+        .emit();
+        BridgeEvent::RefundFailed(RefundFailedEvent {
+            message_id: Self::hex(message_id),
+            sender: lock_record.sender.clone(),
+            token: token_contract,
+            amount: U128(amount),
+        })
+        .emit();
+
+        log!("Refund failed: amount={}, sender={}", amount, lock_record.sender);
+    }
+
+    /// Reopen an inbound message whose token transfer failed: unmark it and
+    /// take the amount back out of the unlocked total
+    fn reopen_unlock(
+        &mut self,
+        message_id: &MessageId,
+        source_chain: &str,
+        sequence: u64,
+        recipient: AccountId,
+        token_contract: AccountId,
+        amount: U128,
+    ) {
+        self.unmark_sequence_processed(source_chain, sequence);
+        let current_unlocked = self.total_unlocked.get(&token_contract).unwrap_or(0);
+        let current_unlocked =
+            current_unlocked.checked_sub(amount.0).or_panic(BridgeError::ArithmeticOverflow);
+        self.total_unlocked.insert(&token_contract, &current_unlocked);
+
+        BridgeEvent::UnlockFailed(UnlockFailedEvent {
+            message_id: Self::hex(message_id),
+            recipient: recipient.clone(),
+            token: token_contract,
+            amount,
+        })
+        .emit();
+
+        log!("Unlock failed: amount={}, recipient={}", amount.0, recipient);
+    }
+
+    fn credit_unclaimed_fallback(
+        &mut self,
         recipient: &AccountId,
         token_contract: &AccountId,
         amount: Balance,
-    ) -> [u8; 32] {
+    ) {
+        let key = (recipient.clone(), token_contract.clone());
+        let unclaimed = self.unclaimed_fallbacks.get(&key).unwrap_or(0);
+        let unclaimed = unclaimed.checked_add(amount).or_panic(BridgeError::ArithmeticOverflow);
+        self.unclaimed_fallbacks.insert(&key, &unclaimed);
+    }
+
+    /// Hash validators sign to attest the outcome of an outbound transfer on
+    /// the destination chain. `domain` selects the outcome; `extra` carries
+    /// outcome-specific data such as the delivering transaction.
+    fn create_lock_outcome_hash(domain: &[u8], lock_record: &LockRecord, extra: &[u8]) -> [u8; 32] {
         let mut data = Vec::new();
-        data.extend_from_slice(message_id);
-        data.extend_from_slice(source_chain.as_bytes());
-        data.extend_from_slice(sender_address.as_bytes());
-        data.extend_from_slice(recipient.as_str().as_bytes());
-        data.extend_from_slice(token_contract.as_str().as_bytes());
-        data.extend_from_slice(&amount.to_le_bytes());
+        data.extend_from_slice(domain);
+        data.extend_from_slice(env::current_account_id().as_str().as_bytes());
+        data.extend_from_slice(&lock_record.message_id);
+        data.extend_from_slice(lock_record.sender.as_str().as_bytes());
+        data.extend_from_slice(lock_record.token_contract.as_str().as_bytes());
+        data.extend_from_slice(&lock_record.amount.to_le_bytes());
+        data.extend_from_slice(extra);
+
+        env::keccak256(&data)
+            .try_into()
+            .expect("Hash should be 32 bytes")
+    }
+
+    /// Hash validators sign to attest a batch root. Binding the bridge account
+    /// keeps a root attested for one deployment from being replayed on another.
+    fn create_batch_root_hash(batch_root: &[u8; 32], message_count: u32) -> [u8; 32] {
+        let mut data = Vec::new();
+        data.extend_from_slice(BATCH_ROOT_DOMAIN);
+        data.extend_from_slice(env::current_account_id().as_str().as_bytes());
+        data.extend_from_slice(batch_root);
+        data.extend_from_slice(&message_count.to_le_bytes());
 
         env::keccak256(&data)
             .try_into()
             .expect("Hash should be 32 bytes")
     }
 
+    fn hex(bytes: &[u8; 32]) -> String {
+        String::from_utf8_lossy(&merkle::to_hex(bytes)).into_owned()
+    }
+
+    fn is_processed(&self, message_id: &MessageId) -> bool {
+        self.processed_messages.contains(message_id) || self.is_legacy_processed(message_id)
+    }
+
+    /// Whether `message_id` was processed before the upgrade to versioned state
+    fn is_legacy_processed(&self, message_id: &MessageId) -> bool {
+        self.legacy_processed_messages
+            .as_ref()
+            .is_some_and(|legacy| legacy.contains(message_id))
+    }
+
+    /// Whether a token transfer was released: by its sequence, or by its
+    /// message ID if released before sequences were tracked
+    fn is_transfer_processed_by(
+        &self,
+        message_id: &MessageId,
+        source_chain: &str,
+        sequence: u64,
+    ) -> bool {
+        self.is_sequence_processed(source_chain, sequence) || self.is_legacy_processed(message_id)
+    }
+
+    fn mark_processed(&mut self, message_id: &MessageId) {
+        if self.processed_messages.insert(message_id) {
+            self.processed_message_count = self
+                .processed_message_count
+                .checked_add(1)
+                .or_panic(BridgeError::ArithmeticOverflow);
+        }
+    }
+
+    fn unmark_processed(&mut self, message_id: &MessageId) {
+        if self.processed_messages.remove(message_id) {
+            self.processed_message_count = self
+                .processed_message_count
+                .checked_sub(1)
+                .or_panic(BridgeError::ArithmeticOverflow);
+        }
+    }
+
+    /// Bitmap word key and bit of the transfer with `sequence` from
+    /// `source_chain` in `processed_sequences`
+    fn sequence_bit(source_chain: &str, sequence: u64) -> ((String, u64), u64) {
+        let word = sequence / SEQUENCE_WORD_BITS;
+        let bit = 1 << (sequence % SEQUENCE_WORD_BITS);
+        ((source_chain.to_string(), word), bit)
+    }
+
+    fn is_sequence_processed(&self, source_chain: &str, sequence: u64) -> bool {
+        let (key, bit) = Self::sequence_bit(source_chain, sequence);
+        self.processed_sequences.get(&key).is_some_and(|word| word & bit != 0)
+    }
+
+    fn mark_sequence_processed(&mut self, source_chain: &str, sequence: u64) {
+        let (key, bit) = Self::sequence_bit(source_chain, sequence);
+        let word = self.processed_sequences.get(&key).unwrap_or(0);
+        if word & bit == 0 {
+            self.processed_sequences.insert(&key, &(word | bit));
+            self.processed_message_count = self
+                .processed_message_count
+                .checked_add(1)
+                .or_panic(BridgeError::ArithmeticOverflow);
+        }
+    }
+
+    fn unmark_sequence_processed(&mut self, source_chain: &str, sequence: u64) {
+        let (key, bit) = Self::sequence_bit(source_chain, sequence);
+        let word = self.processed_sequences.get(&key).unwrap_or(0);
+        if word & bit != 0 {
+            match word & !bit {
+                0 => self.processed_sequences.remove(&key),
+                word => self.processed_sequences.insert(&key, &word),
+            };
+            self.processed_message_count = self
+                .processed_message_count
+                .checked_sub(1)
+                .or_panic(BridgeError::ArithmeticOverflow);
+        }
+    }
+
+    /// Lock record of `message_id`, converting a v0 record on read
+    fn lock_record(&self, message_id: &MessageId) -> Option<LockRecord> {
+        self.lock_records.get(message_id).or_else(|| {
+            let legacy = self.legacy_lock_records.as_ref()?;
+            legacy.get(&Self::hex(message_id)).map(LockRecord::from)
+        })
+    }
+
+    /// Store an updated lock record, moving it out of the v0 map
+    fn save_lock_record(&mut self, message_id: &MessageId, lock_record: &LockRecord) {
+        if let Some(legacy) = self.legacy_lock_records.as_mut() {
+            legacy.remove(&Self::hex(message_id));
+        }
+        self.lock_records.insert(message_id, lock_record);
+    }
+
+    fn remove_lock_record(&mut self, message_id: &MessageId) {
+        if let Some(legacy) = self.legacy_lock_records.as_mut() {
+            legacy.remove(&Self::hex(message_id));
+        }
+        self.lock_records.remove(message_id);
+    }
+
+    /// Require either a valid threshold signature or enough individual
+    /// validator signatures over `message_hash`
+    fn assert_attested(
+        &self,
+        message_hash: &[u8; 32],
+        signatures: &[Signature],
+        aggregate_signature: Option<&AggregateSignature>,
+    ) {
+        if let Some(aggregate) = aggregate_signature {
+            ensure!(
+                self.signature_mode == SignatureMode::Threshold,
+                BridgeError::ThresholdModeDisabled
+            );
+            ensure!(
+                self.verify_aggregate_signature(message_hash, aggregate),
+                BridgeError::InvalidAggregateSignature
+            );
+            return;
+        }
+
+        ensure!(
+            signatures.len() >= self.required_signatures as usize,
+            BridgeError::InsufficientSignatures
+        );
+
+        ensure!(
+            self.count_validator_signers(message_hash, signatures)
+                >= self.required_signatures as usize,
+            BridgeError::InsufficientValidSignatures
+        );
+    }
+
+    /// Number of distinct validators with a valid signature over
+    /// `message_hash` in `signatures`
+    fn count_validator_signers(&self, message_hash: &[u8; 32], signatures: &[Signature]) -> usize {
+        // Each validator counts once, however many of its signatures are passed
+        let mut signers: Vec<&PublicKey> = Vec::new();
+        for sig in signatures.iter() {
+            if !signers.contains(&&sig.public_key) && self.verify_signature(message_hash, sig) {
+                signers.push(&sig.public_key);
+            }
+        }
+        signers.len()
+    }
+
     fn verify_signature(&self, message_hash: &[u8; 32], signature: &Signature) -> bool {
         // Verify that the signature's public key is a validator
         if !self.validators.contains(&signature.public_key) {
@@ -530,27 +1955,119 @@ impl BridgeContract {
         }
 
         // Verify Ed25519 signature
-        env::ed25519_verify(
-            &signature.signature,
-            message_hash,
-            &signature.public_key.as_bytes(),
-        )
+        Self::ed25519_verify(&signature.public_key, &signature.signature, message_hash)
+    }
+
+    fn verify_aggregate_signature(
+        &self,
+        message_hash: &[u8; 32],
+        aggregate: &AggregateSignature,
+    ) -> bool {
+        let group_key = match &self.group_key {
+            Some(group_key) => group_key,
+            None => return false,
+        };
+
+        // Bitmap may only reference existing participants and must reach the threshold
+        if group_key.group_size < 32 && aggregate.signer_bitmap >> group_key.group_size != 0 {
+            return false;
+        }
+        if aggregate.signer_bitmap.count_ones() < group_key.threshold as u32 {
+            return false;
+        }
+
+        // The group signs the bitmap too, so it cannot be swapped after signing
+        let signed_hash =
+            aggregate_signing_hash::<NearKeccak>(message_hash, aggregate.signer_bitmap);
+        Self::ed25519_verify(&group_key.public_key, &aggregate.signature, &signed_hash)
+    }
+
+    fn ed25519_verify(public_key: &PublicKey, signature: &[u8], message: &[u8]) -> bool {
+        if public_key.curve_type() != CurveType::ED25519 {
+            return false;
+        }
+        // First byte of the serialized key is the curve type
+        let public_key: [u8; 32] = match public_key.as_bytes()[1..].try_into() {
+            Ok(bytes) => bytes,
+            Err(_) => return false,
+        };
+        let signature: [u8; 64] = match signature.try_into() {
+            Ok(bytes) => bytes,
+            Err(_) => return false,
+        };
+
+        env::ed25519_verify(&signature, message, &public_key)
     }
 }
 
 // External contract interfaces
 #[ext_contract(ext_fungible_token)]
-trait FungibleToken {
+pub trait FungibleToken {
     fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>);
+    fn ft_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<U128>;
+}
+
+/// Interface implemented by contracts that receive NEAR through unlock-and-call.
+/// The unlocked amount is attached as deposit.
+#[ext_contract(ext_bridge_transfer_receiver)]
+pub trait BridgeTransferReceiver {
+    fn on_bridge_transfer(
+        &mut self,
+        message_id: MessageId,
+        source_chain: String,
+        sender_address: String,
+        payload: Base64VecU8,
+    );
+}
+
+/// Interface implemented by contracts that receive generic bridge messages
+#[ext_contract(ext_bridge_receiver)]
+pub trait BridgeReceiver {
+    fn on_bridge_message(
+        &mut self,
+        message_id: MessageId,
+        source_chain: String,
+        sender_address: String,
+        payload: Base64VecU8,
+    );
 }
 
 // Gas constants
-const FT_TRANSFER_GAS: near_sdk::Gas = near_sdk::Gas(10_000_000_000_000);
+const FT_TRANSFER_GAS: near_sdk::Gas = near_sdk::Gas::from_tgas(10);
+const MESSAGE_DELIVERY_GAS: near_sdk::Gas = near_sdk::Gas::from_tgas(50);
+const DELIVERY_CALLBACK_GAS: near_sdk::Gas = near_sdk::Gas::from_tgas(10);
+const MIN_DELIVERY_GAS: near_sdk::Gas = near_sdk::Gas::from_tgas(70);
+const FT_TRANSFER_CALL_OVERHEAD_GAS: near_sdk::Gas = near_sdk::Gas::from_tgas(30);
+const UNLOCK_CALL_CALLBACK_GAS: near_sdk::Gas = near_sdk::Gas::from_tgas(30);
+const UNLOCK_CALLBACK_GAS: near_sdk::Gas = near_sdk::Gas::from_tgas(10);
+const MIN_CALL_GAS: near_sdk::Gas = near_sdk::Gas::from_tgas(5);
+const MAX_CALL_GAS: near_sdk::Gas = near_sdk::Gas::from_tgas(150);
 
 // Other constants
 const MAX_VALIDATORS: usize = 10;
+const MAX_GROUP_SIZE: usize = 32;
+const MAX_PAYLOAD_LEN: usize = 4096;
+const MAX_DELIVERY_ATTEMPTS: u8 = 5;
+const MAX_CALL_PAYLOAD_LEN: usize = 1024;
+/// Token ID recorded for native NEAR transfers
+pub const NATIVE_TOKEN: &str = "near";
+const BATCH_ROOT_DOMAIN: &[u8] = b"articium:batch_root";
+/// Domains of the lock outcome hashes validators sign
+pub const REFUND_DOMAIN: &[u8] = b"articium:refund";
+pub const EXPIRED_DOMAIN: &[u8] = b"articium:expired";
+pub const DELIVERED_DOMAIN: &[u8] = b"articium:delivered";
+const LOCK_EXPIRY_NS: u64 = 7 * 24 * 60 * 60 * 1_000_000_000;
+const MAX_TX_REF_LEN: usize = 128;
+const MAX_PRUNE_BATCH: usize = 100;
+const SEQUENCE_WORD_BITS: u64 = 64;
 const MAX_CHAIN_NAME_LEN: usize = 32;
 const MAX_ADDRESS_LEN: usize = 128;
 
 // Re-exports
-pub use near_sdk::json_types::U128;
+pub use near_sdk::json_types::{Base64VecU8, U128};
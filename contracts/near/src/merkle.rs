@@ -0,0 +1,123 @@
+//! Batch Merkle proofs compatible with `internal/batching/merkle.go`.
+//!
+//! The Go pipeline works on lowercase hex strings rather than raw digests,
+//! so the on-chain verifier reproduces that exactly:
+//!
+//! - Leaf: `sha256(hex(message_id) || lp(source_chain) || u64_le(sequence) || lp(destination_chain) || lp(sender) || lp(recipient) || lp(payload))`
+//!   where `lp(x) = u32_le(len(x)) || x`. The prefixes keep bytes from moving
+//!   between neighbouring fields, such as from the sender into the recipient.
+//! - Parent: `sha256(hex(left) || hex(right))`
+//! - Sibling order: while walking up, an even index means the running hash is
+//!   the left input and the sibling the right one; an odd index swaps them.
+//!   The index is halved after every level.
+//! - A layer with an odd number of nodes duplicates its last node, so a proof
+//!   may contain the node's own hash as its sibling.
+//!
+//! `message_id` is the 32-byte on-chain ID (`CrossChainMessage.OnChainID`)
+//! and the chains are the `bridge_types::chain` names
+//! (`ChainInfo.BridgeName`), whatever the relayer config calls them.
+//! `sequence` is the transfer's sequence on the source bridge
+//! (`CrossChainMessage.Nonce`), by which the claim is recorded.
+//!
+//! Roots and siblings are passed as the raw 32-byte digests of those hex strings.
+
+use bridge_types::encoding::{push_prefixed, push_u64};
+use near_sdk::env;
+
+use crate::types::MessageId;
+
+/// Lowercase hex encoding of a 32-byte digest
+pub fn to_hex(bytes: &[u8; 32]) -> [u8; 64] {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+    let mut out = [0u8; 64];
+    for (i, byte) in bytes.iter().enumerate() {
+        out[2 * i] = HEX[(byte >> 4) as usize];
+        out[2 * i + 1] = HEX[(byte & 0x0f) as usize];
+    }
+    out
+}
+
+/// Hash a batch leaf the same way as `hashMessage` in merkle.go
+pub fn hash_leaf(
+    message_id: &MessageId,
+    source_chain: &str,
+    sequence: u64,
+    destination_chain: &str,
+    sender: &str,
+    recipient: &str,
+    payload: &[u8],
+) -> [u8; 32] {
+    let mut data = Vec::new();
+    data.extend_from_slice(&to_hex(message_id));
+    push_prefixed(&mut data, source_chain.as_bytes());
+    push_u64(&mut data, sequence);
+    push_prefixed(&mut data, destination_chain.as_bytes());
+    push_prefixed(&mut data, sender.as_bytes());
+    push_prefixed(&mut data, recipient.as_bytes());
+    push_prefixed(&mut data, payload);
+
+    sha256(&data)
+}
+
+/// Hash two child nodes the same way as `hashPair` in merkle.go
+pub fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut data = Vec::with_capacity(128);
+    data.extend_from_slice(&to_hex(left));
+    data.extend_from_slice(&to_hex(right));
+
+    sha256(&data)
+}
+
+/// Verify a proof the same way as `VerifyProof` in merkle.go
+pub fn verify_proof(leaf: &[u8; 32], index: u32, proof: &[[u8; 32]], root: &[u8; 32]) -> bool {
+    let mut current = *leaf;
+    let mut index = index;
+
+    for sibling in proof.iter() {
+        current = if index.is_multiple_of(2) {
+            hash_pair(&current, sibling)
+        } else {
+            hash_pair(sibling, &current)
+        };
+        index /= 2;
+    }
+
+    &current == root
+}
+
+fn sha256(data: &[u8]) -> [u8; 32] {
+    env::sha256(data)
+        .try_into()
+        .expect("Hash should be 32 bytes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SENDER: &str = "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb";
+    const PAYLOAD: &[u8] = br#"{"token_address":{"raw":"usdc.near"},"amount":"1000000"}"#;
+
+    fn leaf(sender: &str, recipient: &str) -> [u8; 32] {
+        hash_leaf(&[0xab; 32], "ethereum", 7, "near", sender, recipient, PAYLOAD)
+    }
+
+    #[test]
+    fn leaf_matches_shared_vector() {
+        // Same vector as merkle_test.go and the Solana program's batch tests
+        assert_eq!(
+            String::from_utf8_lossy(&to_hex(&leaf(SENDER, "alice.near"))),
+            "d8d73f0976fa70a6ab263270bf019c4ef4218e3885763800885bbcaf17bb4347"
+        );
+    }
+
+    #[test]
+    fn proof_rejects_bytes_moved_between_sender_and_recipient() {
+        let sibling = [0xcd; 32];
+        let root = hash_pair(&leaf(SENDER, "alice.near"), &sibling);
+        assert!(verify_proof(&leaf(SENDER, "alice.near"), 0, &[sibling], &root));
+
+        let shifted = leaf(&format!("{}a", SENDER), "lice.near");
+        assert!(!verify_proof(&shifted, 0, &[sibling], &root));
+    }
+}
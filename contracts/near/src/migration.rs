@@ -0,0 +1,399 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{UnorderedMap, UnorderedSet};
+use near_sdk::json_types::Base64VecU8;
+use near_sdk::{env, near_bindgen, AccountId, NearToken, Promise, PublicKey};
+
+use crate::*;
+
+/// Storage key holding the layout version of the contract state. Absent for
+/// state written before versioning was introduced (v0).
+const STATE_VERSION_KEY: &[u8] = b"STATE_VERSION";
+
+/// Storage key near-sdk keeps the contract struct under
+const STATE_KEY: &[u8] = b"STATE";
+
+/// Layout version written by this code
+pub const CURRENT_STATE_VERSION: u8 = 1;
+
+/// Gas for the `migrate` call scheduled by `upgrade`
+const MIGRATE_GAS: near_sdk::Gas = near_sdk::Gas::from_tgas(100);
+
+/// Contract state as laid out by each released version
+#[allow(clippy::large_enum_variant)]
+pub enum VersionedBridgeState {
+    V0(BridgeContractV0),
+    V1(BridgeContract),
+}
+
+/// Original flat state layout, without lifecycle, threshold, batching,
+/// messaging, storage management or NFT fields
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct BridgeContractV0 {
+    pub owner: AccountId,
+    pub validators: UnorderedSet<PublicKey>,
+    pub required_signatures: u8,
+    pub is_paused: bool,
+    pub total_locked: UnorderedMap<AccountId, Balance>,
+    pub total_unlocked: UnorderedMap<AccountId, Balance>,
+    pub processed_messages: UnorderedSet<MessageId>,
+    pub lock_records: UnorderedMap<String, LockRecordV0>,
+    pub message_count: u64,
+}
+
+/// Lock record as stored by v0
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct LockRecordV0 {
+    pub message_id: MessageId,
+    pub sender: AccountId,
+    pub token_contract: AccountId,
+    pub amount: Balance,
+    pub destination_chain: String,
+    pub destination_address: String,
+    pub nonce: u64,
+    pub timestamp: u64,
+}
+
+impl VersionedBridgeState {
+    /// Read the stored state using the layout recorded under `STATE_VERSION_KEY`
+    pub fn read() -> Self {
+        let version = env::storage_read(STATE_VERSION_KEY)
+            .map(|bytes| bytes[0])
+            .unwrap_or(0);
+
+        match version {
+            0 => Self::V0(read_state()),
+            1 => Self::V1(read_state()),
+            _ => BridgeError::UnknownStateVersion.panic(),
+        }
+    }
+
+    /// Layout version of the stored state
+    pub fn version(&self) -> u8 {
+        match self {
+            Self::V0(_) => 0,
+            Self::V1(_) => 1,
+        }
+    }
+
+    /// Convert to the current layout
+    pub fn into_current(self) -> BridgeContract {
+        match self {
+            Self::V0(old) => Self::migrate_v0(old),
+            Self::V1(current) => current,
+        }
+    }
+
+    fn migrate_v0(old: BridgeContractV0) -> BridgeContract {
+        let BridgeContractV0 {
+            owner,
+            validators,
+            required_signatures,
+            is_paused,
+            total_locked,
+            total_unlocked,
+            processed_messages,
+            lock_records,
+            message_count,
+        } = old;
+
+        // Both v0 collections stay under their old prefixes and are read
+        // alongside the new ones, so `migrate` costs the same for any size
+        BridgeContract {
+            owner,
+            validators,
+            required_signatures,
+            is_paused,
+            total_locked,
+            total_unlocked,
+            processed_messages: LookupSet::new(StorageKey::ProcessedMessageIds),
+            processed_sequences: LookupMap::new(StorageKey::ProcessedSequences),
+            processed_message_count: processed_messages.len(),
+            legacy_processed_messages: Some(processed_messages),
+            lock_records: UnorderedMap::new(StorageKey::LockRecordsById),
+            legacy_lock_records: Some(lock_records),
+            message_count,
+            signature_mode: SignatureMode::Individual,
+            group_key: None,
+            batch_roots: UnorderedMap::new(StorageKey::BatchRoots),
+            inbound_messages: UnorderedMap::new(StorageKey::InboundMessages),
+            storage_deposits: LookupMap::new(StorageKey::StorageDeposits),
+            nft_escrow: UnorderedMap::new(StorageKey::NftEscrow),
+            bridged_nft_collections: UnorderedMap::new(StorageKey::BridgedNftCollections),
+            unclaimed_fallbacks: LookupMap::new(StorageKey::UnclaimedFallbacks),
+        }
+    }
+}
+
+impl From<LockRecordV0> for LockRecord {
+    /// v0 had no calls or lifecycle, so every record is still pending
+    fn from(record: LockRecordV0) -> Self {
+        Self {
+            message_id: record.message_id,
+            sender: record.sender,
+            token_contract: record.token_contract,
+            amount: record.amount,
+            destination_chain: record.destination_chain,
+            destination_address: record.destination_address,
+            nonce: record.nonce,
+            timestamp: record.timestamp,
+            call: None,
+            status: LockStatus::Pending,
+        }
+    }
+}
+
+/// Deserialize the stored contract struct as `T`. `env::state_read` aborts
+/// with its own message when the bytes do not fit the layout.
+fn read_state<T: BorshDeserialize>() -> T {
+    env::storage_read(STATE_KEY)
+        .and_then(|bytes| T::try_from_slice(&bytes).ok())
+        .or_panic(BridgeError::InvalidState)
+}
+
+pub(crate) fn write_state_version(version: u8) {
+    env::storage_write(STATE_VERSION_KEY, &[version]);
+}
+
+#[near_bindgen]
+impl BridgeContract {
+    /// Convert the stored state to the current layout. Called by `upgrade`
+    /// after deploying new code; a no-op when the layout is already current.
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let state = VersionedBridgeState::read();
+        let from_version = state.version();
+        let contract = state.into_current();
+        write_state_version(CURRENT_STATE_VERSION);
+
+        BridgeEvent::StateMigrated(StateMigratedEvent {
+            from_version,
+            to_version: CURRENT_STATE_VERSION,
+        })
+        .emit();
+
+        contract
+    }
+
+    /// Deploy new contract code and migrate the state (owner only). Transfer
+    /// ownership to a governance contract to gate upgrades behind it.
+    pub fn upgrade(&mut self, code: Base64VecU8) -> Promise {
+        self.assert_owner();
+        ensure!(!code.0.is_empty(), BridgeError::EmptyCode);
+
+        BridgeEvent::ContractUpgraded(ContractUpgradedEvent {
+            code_hash: Self::hex(
+                &env::sha256(&code.0).try_into().expect("Hash should be 32 bytes"),
+            ),
+            updated_by: env::predecessor_account_id(),
+        })
+        .emit();
+
+        Promise::new(env::current_account_id())
+            .deploy_contract(code.0)
+            .function_call(
+                "migrate".to_string(),
+                Vec::new(),
+                NearToken::from_yoctonear(0),
+                MIGRATE_GAS,
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Keypair, PublicKey as DalekPublicKey, SecretKey, Signer as _};
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    fn validator_key() -> PublicKey {
+        "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp".parse().unwrap()
+    }
+
+    fn setup() {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .predecessor_account_id(accounts(0))
+            .build());
+    }
+
+    /// Write a v0 state with a validator, a locked balance, a processed
+    /// message and a lock record
+    fn write_v0_state() -> BridgeContractV0 {
+        let mut validators = UnorderedSet::new(StorageKey::Validators);
+        validators.insert(&validator_key());
+
+        let mut total_locked = UnorderedMap::new(StorageKey::TotalLocked);
+        total_locked.insert(&accounts(2), &1_000);
+
+        let mut processed_messages = UnorderedSet::new(StorageKey::ProcessedMessages);
+        processed_messages.insert(&[7u8; 32]);
+
+        let mut lock_records = UnorderedMap::new(StorageKey::LockRecords);
+        lock_records.insert(&"01".repeat(32), &LockRecordV0 {
+            message_id: [1u8; 32],
+            sender: accounts(1),
+            token_contract: accounts(2),
+            amount: 1_000,
+            destination_chain: "ethereum".to_string(),
+            destination_address: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
+            nonce: 0,
+            timestamp: 42,
+        });
+
+        let state = BridgeContractV0 {
+            owner: accounts(0),
+            validators,
+            required_signatures: 1,
+            is_paused: true,
+            total_locked,
+            total_unlocked: UnorderedMap::new(StorageKey::TotalUnlocked),
+            processed_messages,
+            lock_records,
+            message_count: 1,
+        };
+        env::state_write(&state);
+        state
+    }
+
+    #[test]
+    fn migrate_v0_state() {
+        setup();
+        write_v0_state();
+
+        let contract = BridgeContract::migrate();
+
+        assert_eq!(contract.owner, accounts(0));
+        assert!(contract.validators.contains(&validator_key()));
+        assert_eq!(contract.required_signatures, 1);
+        assert!(contract.is_paused);
+        assert_eq!(contract.message_count, 1);
+        assert_eq!(contract.total_locked.get(&accounts(2)), Some(1_000));
+        assert_eq!(contract.signature_mode, SignatureMode::Individual);
+        assert!(contract.group_key.is_none());
+
+        // Replay protection covers messages processed before the upgrade
+        assert!(contract.is_message_processed([7u8; 32]));
+        assert!(!contract.is_message_processed([8u8; 32]));
+        assert_eq!(contract.processed_message_count, 1);
+
+        // Lock records are read from the v0 map and start pending
+        let record = contract.get_lock_record([1u8; 32]).expect("Lock record migrated");
+        assert_eq!(record.sender, accounts(1));
+        assert_eq!(record.amount, 1_000);
+        assert_eq!(record.timestamp, 42);
+        assert!(record.call.is_none());
+        assert_eq!(record.status, LockStatus::Pending);
+        assert!(contract.lock_records.is_empty());
+        assert_eq!(contract.get_storage_usage().lock_records, 1);
+
+        assert_eq!(env::storage_read(STATE_VERSION_KEY), Some(vec![CURRENT_STATE_VERSION]));
+    }
+
+    #[test]
+    #[should_panic(expected = "Message already processed")]
+    fn transfers_released_before_the_upgrade_cannot_be_unlocked_again() {
+        setup();
+        write_v0_state();
+
+        let secret = SecretKey::from_bytes(&[1u8; 32]).unwrap();
+        let key = Keypair { public: DalekPublicKey::from(&secret), secret };
+        let public_key =
+            PublicKey::from_parts(CurveType::ED25519, key.public.to_bytes().to_vec()).unwrap();
+        let mut contract = BridgeContract::migrate();
+        contract.validators.insert(&public_key);
+        contract.is_paused = false;
+
+        // The v0 release of [7; 32] attested again under the current scheme
+        let message_hash = BridgeContract::create_unlock_message_hash(
+            &[7u8; 32],
+            chain::ETHEREUM,
+            0,
+            "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb",
+            &accounts(1),
+            &accounts(2),
+            100,
+        );
+        let signature = Signature {
+            public_key,
+            signature: key.sign(&message_hash).to_bytes().to_vec(),
+        };
+        let _ = contract.unlock_ft(
+            [7u8; 32],
+            chain::ETHEREUM.to_string(),
+            0,
+            "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
+            accounts(1),
+            accounts(2),
+            U128(100),
+            vec![signature],
+            None,
+        );
+    }
+
+    #[test]
+    fn migrated_state_round_trips() {
+        setup();
+        write_v0_state();
+
+        let mut contract = BridgeContract::migrate();
+        contract.mark_processed(&[9u8; 32]);
+        env::state_write(&contract);
+
+        // Migrating the current layout again leaves it unchanged
+        let contract = BridgeContract::migrate();
+        assert!(contract.is_message_processed([7u8; 32]));
+        assert!(contract.is_message_processed([9u8; 32]));
+        assert_eq!(contract.processed_message_count, 2);
+        assert!(contract.get_lock_record([1u8; 32]).is_some());
+    }
+
+    #[test]
+    fn updated_v0_lock_record_moves_to_new_map() {
+        setup();
+        write_v0_state();
+
+        let mut contract = BridgeContract::migrate();
+        let mut record = contract.lock_record(&[1u8; 32]).unwrap();
+        record.status = LockStatus::Refunded;
+        contract.save_lock_record(&[1u8; 32], &record);
+
+        assert!(contract.legacy_lock_records.as_ref().unwrap().is_empty());
+        assert_eq!(contract.get_lock_status([1u8; 32]), Some(LockStatus::Refunded));
+        assert_eq!(contract.get_storage_usage().lock_records, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Stored state does not match its version")]
+    fn unreadable_state_is_rejected() {
+        setup();
+        env::state_write(&0u8);
+
+        VersionedBridgeState::read();
+    }
+
+    #[test]
+    fn new_contract_records_current_version() {
+        setup();
+
+        BridgeContract::new(accounts(0), vec![validator_key()], 1);
+
+        assert_eq!(env::storage_read(STATE_VERSION_KEY), Some(vec![CURRENT_STATE_VERSION]));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner can call this method")]
+    fn upgrade_requires_owner() {
+        setup();
+        let mut contract = BridgeContract::new(accounts(0), vec![validator_key()], 1);
+
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .predecessor_account_id(accounts(1))
+            .build());
+        let _ = contract.upgrade(Base64VecU8(vec![0u8; 8]));
+    }
+}
@@ -0,0 +1,591 @@
+//! NEP-171 NFT bridging
+//!
+//! NFTs sent to the bridge with `nft_transfer_call` are escrowed until they
+//! come back. An attested inbound NFT is released from escrow if the bridge
+//! holds it, and otherwise minted into the bridged collection registered for
+//! its source chain.
+
+use bridge_types::{NftTransferMessage, NftUnlockAttestation};
+use near_sdk::{
+    env, ext_contract, log, near_bindgen, AccountId, NearToken, Promise, PromiseError,
+    PromiseOrValue,
+};
+
+use crate::*;
+
+/// Gas for reading the token from its collection while locking
+const NFT_TOKEN_GAS: near_sdk::Gas = near_sdk::Gas::from_tgas(5);
+const NFT_LOCK_CALLBACK_GAS: near_sdk::Gas = near_sdk::Gas::from_tgas(15);
+const NFT_TRANSFER_GAS: near_sdk::Gas = near_sdk::Gas::from_tgas(15);
+const NFT_MINT_GAS: near_sdk::Gas = near_sdk::Gas::from_tgas(20);
+const NFT_UNLOCK_CALLBACK_GAS: near_sdk::Gas = near_sdk::Gas::from_tgas(10);
+
+const MAX_TOKEN_ID_LEN: usize = 256;
+const MAX_TOKEN_URI_LEN: usize = 512;
+
+#[near_bindgen]
+impl BridgeContract {
+    /// NEP-171 receiver: escrow the NFT transferred with `nft_transfer_call`.
+    /// `msg` is a JSON `NftLockMessage`. The lock is recorded once the
+    /// collection has reported the token's metadata, and paid for from the
+    /// previous owner's storage balance. Resolves to `true`, returning the
+    /// NFT, if the lock is rejected.
+    // NEP-171 fixes the argument names, so `sender_id` cannot take a leading
+    // underscore without changing the JSON key the collection sends
+    #[allow(unused_variables)]
+    pub fn nft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        previous_owner_id: AccountId,
+        token_id: String,
+        msg: String,
+    ) -> PromiseOrValue<bool> {
+        ensure!(!self.is_paused, BridgeError::BridgePaused);
+        ensure!(token_id.len() <= MAX_TOKEN_ID_LEN, BridgeError::TokenIdTooLong);
+
+        let lock: NftLockMessage = near_sdk::serde_json::from_str(&msg)
+            .or_panic(BridgeError::InvalidLockMessage);
+        ensure!(
+            lock.destination_chain.len() <= MAX_CHAIN_NAME_LEN,
+            BridgeError::DestinationChainTooLong
+        );
+        ensure!(
+            lock.destination_address.len() <= MAX_ADDRESS_LEN,
+            BridgeError::DestinationAddressTooLong
+        );
+
+        // The escrow belongs to the owner, not an approved account moving it
+        let nft_contract = env::predecessor_account_id();
+
+        ext_nft::ext(nft_contract.clone())
+            .with_static_gas(NFT_TOKEN_GAS)
+            .nft_token(token_id.clone())
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(NFT_LOCK_CALLBACK_GAS)
+                    .on_nft_token(previous_owner_id, nft_contract, token_id, lock),
+            )
+            .into()
+    }
+
+    /// Record the lock of an NFT the collection reports as held by the
+    /// bridge. Returns whether the NFT should be returned to its owner.
+    #[private]
+    pub fn on_nft_token(
+        &mut self,
+        previous_owner_id: AccountId,
+        nft_contract: AccountId,
+        token_id: String,
+        lock: NftLockMessage,
+        #[callback_result] token: Result<Option<NftToken>, PromiseError>,
+    ) -> bool {
+        let token_uri = match token {
+            Ok(Some(token)) if token.owner_id == env::current_account_id() => {
+                token.metadata.unwrap_or_default().token_uri()
+            }
+            _ => {
+                log!("NFT lock rejected: token={}, collection={}", token_id, nft_contract);
+                return true;
+            }
+        };
+        if token_uri.len() > MAX_TOKEN_URI_LEN {
+            log!("NFT lock rejected: token URI too long, token={}", token_id);
+            return true;
+        }
+
+        // A panic here fails the transfer call, which also returns the NFT
+        let initial_storage = env::storage_usage();
+        self.record_nft_lock(
+            previous_owner_id.clone(),
+            nft_contract,
+            token_id,
+            token_uri,
+            lock.destination_chain.clone(),
+            lock.destination_address,
+        );
+        self.charge_storage(&previous_owner_id, initial_storage, 0);
+
+        log!("NFT locked: sender={}, destination={}", previous_owner_id, lock.destination_chain);
+
+        false
+    }
+
+    /// Release an inbound NFT to `recipient` (requires validator signatures).
+    /// An NFT held in escrow is transferred out of it; any other NFT is
+    /// minted into `nft_contract`, which must be the bridged collection
+    /// registered for `source_chain`. `token_uri` is only used when minting.
+    /// Only the token's reference URI is bridged; wallets find the media
+    /// through the JSON it points to.
+    pub fn unlock_nft(
+        &mut self,
+        message_id: MessageId,
+        source_chain: String,
+        sender_address: String,
+        recipient: AccountId,
+        nft_contract: AccountId,
+        token_id: String,
+        token_uri: String,
+        signatures: Vec<Signature>,
+        aggregate_signature: Option<AggregateSignature>,
+    ) -> Promise {
+        ensure!(!self.is_paused, BridgeError::BridgePaused);
+        ensure!(
+            !self.is_processed(&message_id),
+            BridgeError::MessageAlreadyProcessed
+        );
+        ensure!(token_id.len() <= MAX_TOKEN_ID_LEN, BridgeError::TokenIdTooLong);
+        ensure!(token_uri.len() <= MAX_TOKEN_URI_LEN, BridgeError::TokenUriTooLong);
+
+        let message_hash = Self::create_nft_unlock_message_hash(
+            &message_id,
+            &source_chain,
+            &sender_address,
+            &recipient,
+            &nft_contract,
+            &token_id,
+            &token_uri,
+        );
+
+        // Verify signatures
+        self.assert_attested(&message_hash, &signatures, aggregate_signature.as_ref());
+
+        self.mark_processed(&message_id);
+
+        // The escrow's storage goes back to the account that paid for it
+        let initial_storage = env::storage_usage();
+        let escrow = self.nft_escrow.remove(&(nft_contract.clone(), token_id.clone()));
+        match &escrow {
+            Some(escrow) => {
+                // An escrowed NFT only comes back from the chain it was sent to
+                ensure!(escrow.destination_chain == source_chain, BridgeError::NftSourceMismatch);
+                self.release_storage(&escrow.sender, initial_storage);
+            }
+            None => {
+                let collection = self
+                    .bridged_nft_collections
+                    .get(&nft_contract)
+                    .or_panic(BridgeError::NftNotEscrowed);
+                ensure!(collection.source_chain == source_chain, BridgeError::NftSourceMismatch);
+            }
+        }
+
+        BridgeEvent::NftUnlocked(NftUnlockedEvent {
+            message_id: Self::hex(&message_id),
+            source_chain,
+            sender_address,
+            recipient: recipient.clone(),
+            nft_contract: nft_contract.clone(),
+            token_id: token_id.clone(),
+            minted: escrow.is_none(),
+            timestamp: env::block_timestamp(),
+        })
+        .emit();
+
+        log!("NFT unlocked: token={}, recipient={}", token_id, recipient);
+
+        let release = match escrow {
+            Some(_) => ext_nft::ext(nft_contract.clone())
+                .with_attached_deposit(NearToken::from_yoctonear(1))
+                .with_static_gas(NFT_TRANSFER_GAS)
+                .nft_transfer(
+                    recipient.clone(),
+                    token_id.clone(),
+                    None,
+                    Some(format!("Unlock from cross-chain transfer: {}", Self::hex(&message_id))),
+                ),
+            None => ext_bridged_nft::ext(nft_contract.clone())
+                .with_static_gas(NFT_MINT_GAS)
+                .nft_mint(
+                    token_id.clone(),
+                    recipient.clone(),
+                    // The attestation covers the reference URI, not the media
+                    NftTokenMetadata {
+                        media: None,
+                        reference: Some(token_uri).filter(|uri| !uri.is_empty()),
+                    },
+                ),
+        };
+
+        release.then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(NFT_UNLOCK_CALLBACK_GAS)
+                .on_nft_unlock_resolved(message_id, recipient, nft_contract, token_id, escrow),
+        )
+    }
+
+    /// Reopen an NFT unlock whose transfer or mint failed, putting the NFT
+    /// back in escrow if it was released from there. The bridge keeps paying
+    /// for the restored escrow record.
+    #[private]
+    pub fn on_nft_unlock_resolved(
+        &mut self,
+        message_id: MessageId,
+        recipient: AccountId,
+        nft_contract: AccountId,
+        token_id: String,
+        escrow: Option<NftEscrow>,
+    ) -> bool {
+        if near_sdk::is_promise_success() {
+            return true;
+        }
+
+        self.unmark_processed(&message_id);
+        if let Some(escrow) = escrow {
+            self.nft_escrow.insert(&(nft_contract.clone(), token_id.clone()), &escrow);
+        }
+
+        BridgeEvent::NftUnlockFailed(NftUnlockFailedEvent {
+            message_id: Self::hex(&message_id),
+            recipient: recipient.clone(),
+            nft_contract,
+            token_id: token_id.clone(),
+        })
+        .emit();
+
+        log!("NFT unlock failed: token={}, recipient={}", token_id, recipient);
+
+        false
+    }
+
+    /// Get the escrow record of an NFT held by the bridge
+    pub fn get_escrowed_nft(
+        &self,
+        nft_contract: AccountId,
+        token_id: String,
+    ) -> Option<NftEscrow> {
+        self.nft_escrow.get(&(nft_contract, token_id))
+    }
+
+    /// Get the source of a bridged collection
+    pub fn get_bridged_nft_collection(
+        &self,
+        collection: AccountId,
+    ) -> Option<BridgedNftCollection> {
+        self.bridged_nft_collections.get(&collection)
+    }
+
+    /// Mint NFTs created on `source_chain` at `source_contract` into
+    /// `collection` when they arrive. The collection must let the bridge
+    /// call `nft_mint`, as `near-bridged-nft` does.
+    pub fn register_bridged_nft_collection(
+        &mut self,
+        collection: AccountId,
+        source_chain: String,
+        source_contract: String,
+    ) {
+        self.assert_owner();
+        ensure!(
+            self.bridged_nft_collections.get(&collection).is_none(),
+            BridgeError::NftCollectionAlreadyRegistered
+        );
+
+        self.bridged_nft_collections.insert(&collection, &BridgedNftCollection {
+            source_chain: source_chain.clone(),
+            source_contract: source_contract.clone(),
+        });
+
+        BridgeEvent::BridgedNftCollectionRegistered(BridgedNftCollectionEvent {
+            collection,
+            source_chain,
+            source_contract,
+            updated_by: env::predecessor_account_id(),
+        })
+        .emit();
+    }
+}
+
+impl BridgeContract {
+    /// Escrow an NFT for an outbound transfer and emit the event
+    fn record_nft_lock(
+        &mut self,
+        sender: AccountId,
+        nft_contract: AccountId,
+        token_id: String,
+        token_uri: String,
+        destination_chain: String,
+        destination_address: String,
+    ) -> MessageId {
+        let nonce = self.message_count;
+        self.message_count = nonce.checked_add(1).or_panic(BridgeError::ArithmeticOverflow);
+
+        let message_id = NftTransferMessage {
+            source_chain: chain::NEAR.to_string(),
+            source_bridge: env::current_account_id().as_str().as_bytes().to_vec(),
+            sequence: nonce,
+            sender: sender.as_str().as_bytes().to_vec(),
+            nft_contract: nft_contract.as_str().as_bytes().to_vec(),
+            token_id: token_id.clone(),
+            token_uri: token_uri.clone(),
+            destination_chain: destination_chain.clone(),
+            recipient: destination_address.clone(),
+        }
+        .id::<NearKeccak>();
+
+        let escrow = NftEscrow {
+            message_id,
+            sender: sender.clone(),
+            nft_contract: nft_contract.clone(),
+            token_id: token_id.clone(),
+            token_uri: token_uri.clone(),
+            destination_chain: destination_chain.clone(),
+            destination_address: destination_address.clone(),
+            nonce,
+            timestamp: env::block_timestamp(),
+        };
+        self.nft_escrow.insert(&(nft_contract.clone(), token_id.clone()), &escrow);
+
+        BridgeEvent::NftLocked(NftLockedEvent {
+            message_id: Self::hex(&message_id),
+            sender,
+            recipient: destination_address,
+            nft_contract,
+            token_id,
+            token_uri,
+            destination_chain,
+            nonce,
+            timestamp: escrow.timestamp,
+        })
+        .emit();
+
+        message_id
+    }
+
+    /// Hash validators sign to release or mint an inbound NFT
+    fn create_nft_unlock_message_hash(
+        message_id: &MessageId,
+        source_chain: &str,
+        sender_address: &str,
+        recipient: &AccountId,
+        nft_contract: &AccountId,
+        token_id: &str,
+        token_uri: &str,
+    ) -> [u8; 32] {
+        NftUnlockAttestation {
+            message_id: *message_id,
+            source_chain: source_chain.to_string(),
+            sender: sender_address.to_string(),
+            destination_chain: chain::NEAR.to_string(),
+            destination_bridge: env::current_account_id().as_str().as_bytes().to_vec(),
+            recipient: recipient.as_str().as_bytes().to_vec(),
+            nft_contract: nft_contract.as_str().as_bytes().to_vec(),
+            token_id: token_id.to_string(),
+            token_uri: token_uri.to_string(),
+        }
+        .signing_hash::<NearKeccak>()
+    }
+}
+
+/// NEP-171 methods the bridge calls on collections
+#[ext_contract(ext_nft)]
+pub trait NonFungibleToken {
+    fn nft_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: String,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+    );
+    fn nft_token(&self, token_id: String) -> Option<NftToken>;
+}
+
+/// Minting interface of bridged collections, callable by the bridge
+#[ext_contract(ext_bridged_nft)]
+pub trait BridgedNftMinter {
+    fn nft_mint(
+        &mut self,
+        token_id: String,
+        receiver_id: AccountId,
+        token_metadata: NftTokenMetadata,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Keypair, PublicKey as DalekPublicKey, SecretKey, Signer as _};
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    const TOKEN_URI: &str = "ipfs://bafybeigdyrzt/42.json";
+
+    fn bridge_id() -> AccountId {
+        "bridge.near".parse().unwrap()
+    }
+
+    fn validator() -> Keypair {
+        let secret = SecretKey::from_bytes(&[1u8; 32]).unwrap();
+        Keypair { public: DalekPublicKey::from(&secret), secret }
+    }
+
+    fn public_key(key: &Keypair) -> PublicKey {
+        PublicKey::from_parts(CurveType::ED25519, key.public.to_bytes().to_vec()).unwrap()
+    }
+
+    fn context(predecessor: AccountId) -> VMContextBuilder {
+        let mut builder = VMContextBuilder::new();
+        builder.current_account_id(bridge_id()).predecessor_account_id(predecessor);
+        builder
+    }
+
+    /// Bridge with one validator, where `accounts(1)` has a storage balance
+    fn setup() -> BridgeContract {
+        testing_env!(context(accounts(0)).build());
+        let mut contract = BridgeContract::new(accounts(0), vec![public_key(&validator())], 1);
+        contract.storage_deposits.insert(&accounts(1), &(10u128.pow(24)));
+        contract
+    }
+
+    /// Run the lock callback with `nft_token` having returned `token`
+    fn complete_lock(contract: &mut BridgeContract, token: Option<NftToken>) -> bool {
+        testing_env!(context(bridge_id()).build());
+        contract.on_nft_token(
+            accounts(1),
+            accounts(2),
+            "42".to_string(),
+            NftLockMessage {
+                destination_chain: chain::ETHEREUM.to_string(),
+                destination_address: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
+            },
+            Ok(token),
+        )
+    }
+
+    fn held_token(owner_id: AccountId) -> Option<NftToken> {
+        Some(NftToken {
+            token_id: "42".to_string(),
+            owner_id,
+            metadata: Some(NftTokenMetadata {
+                media: Some("https://example.com/42.png".to_string()),
+                reference: Some(TOKEN_URI.to_string()),
+            }),
+        })
+    }
+
+    /// Unlock token 42 of `nft_contract` from `source_chain`, signed by the
+    /// validator
+    fn unlock(contract: &mut BridgeContract, nft_contract: AccountId, source_chain: &str) {
+        testing_env!(context(accounts(3)).build());
+        let message_id = [7u8; 32];
+        let hash = BridgeContract::create_nft_unlock_message_hash(
+            &message_id,
+            source_chain,
+            "0xabc",
+            &accounts(4),
+            &nft_contract,
+            "42",
+            TOKEN_URI,
+        );
+        let key = validator();
+        let signature = Signature {
+            public_key: public_key(&key),
+            signature: key.sign(&hash).to_bytes().to_vec(),
+        };
+        let _ = contract.unlock_nft(
+            message_id,
+            source_chain.to_string(),
+            "0xabc".to_string(),
+            accounts(4),
+            nft_contract,
+            "42".to_string(),
+            TOKEN_URI.to_string(),
+            vec![signature],
+            None,
+        );
+    }
+
+    #[test]
+    fn lock_escrows_held_token_with_metadata() {
+        let mut contract = setup();
+
+        assert!(!complete_lock(&mut contract, held_token(bridge_id())));
+
+        let escrow = contract.get_escrowed_nft(accounts(2), "42".to_string()).unwrap();
+        assert_eq!(escrow.sender, accounts(1));
+        assert_eq!(escrow.token_uri, TOKEN_URI);
+        assert_eq!(escrow.nonce, 0);
+        assert_eq!(contract.message_count, 1);
+        assert_eq!(contract.get_storage_usage().escrowed_nfts, 1);
+        assert!(contract.storage_deposits.get(&accounts(1)).unwrap() < 10u128.pow(24));
+    }
+
+    #[test]
+    fn lock_returns_token_not_held_by_bridge() {
+        let mut contract = setup();
+
+        assert!(complete_lock(&mut contract, held_token(accounts(1))));
+        assert!(complete_lock(&mut contract, None));
+
+        assert!(contract.get_escrowed_nft(accounts(2), "42".to_string()).is_none());
+        assert_eq!(contract.message_count, 0);
+    }
+
+    #[test]
+    fn unlock_releases_escrowed_token() {
+        let mut contract = setup();
+        complete_lock(&mut contract, held_token(bridge_id()));
+        let deposit = contract.storage_deposits.get(&accounts(1)).unwrap();
+
+        unlock(&mut contract, accounts(2), chain::ETHEREUM);
+
+        assert!(contract.get_escrowed_nft(accounts(2), "42".to_string()).is_none());
+        assert!(contract.is_message_processed([7u8; 32]));
+        assert!(contract.storage_deposits.get(&accounts(1)).unwrap() > deposit);
+    }
+
+    #[test]
+    fn unlock_mints_into_bridged_collection() {
+        let mut contract = setup();
+        contract.register_bridged_nft_collection(
+            accounts(2),
+            chain::ETHEREUM.to_string(),
+            "0xb47e3cd837dDF8e4c57F05d70Ab865de6e193BBB".to_string(),
+        );
+
+        unlock(&mut contract, accounts(2), chain::ETHEREUM);
+
+        assert!(contract.is_message_processed([7u8; 32]));
+    }
+
+    #[test]
+    #[should_panic(expected = "BridgeError::NftNotEscrowed")]
+    fn unlock_requires_escrow_or_bridged_collection() {
+        let mut contract = setup();
+
+        unlock(&mut contract, accounts(2), chain::ETHEREUM);
+    }
+
+    #[test]
+    #[should_panic(expected = "BridgeError::NftSourceMismatch")]
+    fn unlock_rejects_other_source_chain() {
+        let mut contract = setup();
+        contract.register_bridged_nft_collection(
+            accounts(2),
+            chain::ETHEREUM.to_string(),
+            "0xb47e3cd837dDF8e4c57F05d70Ab865de6e193BBB".to_string(),
+        );
+
+        unlock(&mut contract, accounts(2), chain::SOLANA);
+    }
+
+    #[test]
+    #[should_panic(expected = "BridgeError::NftSourceMismatch")]
+    fn unlock_rejects_escrow_sent_to_other_chain() {
+        let mut contract = setup();
+        complete_lock(&mut contract, held_token(bridge_id()));
+
+        unlock(&mut contract, accounts(2), chain::SOLANA);
+    }
+
+    #[test]
+    #[should_panic(expected = "BridgeError::NftCollectionAlreadyRegistered")]
+    fn bridged_collection_registers_once() {
+        let mut contract = setup();
+        for _ in 0..2 {
+            contract.register_bridged_nft_collection(
+                accounts(2),
+                chain::ETHEREUM.to_string(),
+                "0xb47e3cd837dDF8e4c57F05d70Ab865de6e193BBB".to_string(),
+            );
+        }
+    }
+}
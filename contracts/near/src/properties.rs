@@ -0,0 +1,418 @@
+//! Property tests of message IDs, unlock hashes, signature-set and
+//! threshold-signature validation and the per-token counters, run against
+//! the mocked blockchain
+
+use super::*;
+use ed25519_dalek::{Keypair, PublicKey as DalekPublicKey, SecretKey, Signer as _};
+use near_sdk::json_types::Base64VecU8;
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::testing_env;
+use proptest::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+fn bridge_id() -> AccountId {
+    "bridge.near".parse().unwrap()
+}
+
+fn validator_key(seed: u8) -> Keypair {
+    let secret = SecretKey::from_bytes(&[seed; 32]).unwrap();
+    Keypair { public: DalekPublicKey::from(&secret), secret }
+}
+
+fn public_key(key: &Keypair) -> PublicKey {
+    PublicKey::from_parts(CurveType::ED25519, key.public.to_bytes().to_vec()).unwrap()
+}
+
+fn sign(key: &Keypair, hash: &[u8; 32]) -> Signature {
+    Signature {
+        public_key: public_key(key),
+        signature: key.sign(hash).to_bytes().to_vec(),
+    }
+}
+
+/// Fresh bridge with `validators` validators (seeds `1..=validators`), of
+/// which `required_signatures` are required
+fn bridge(validators: u8, required_signatures: u8) -> BridgeContract {
+    // `testing_env!` keeps the storage of the previous case
+    near_sdk::mock::with_mocked_blockchain(|blockchain| blockchain.take_storage());
+    testing_env!(VMContextBuilder::new()
+        .current_account_id(bridge_id())
+        .predecessor_account_id(accounts(0))
+        .build());
+    let validators = (1..=validators).map(|seed| public_key(&validator_key(seed))).collect();
+    BridgeContract::new(accounts(0), validators, required_signatures)
+}
+
+fn token(index: usize) -> AccountId {
+    if index == 0 {
+        NATIVE_TOKEN.parse().unwrap()
+    } else {
+        accounts(index)
+    }
+}
+
+fn chain_name() -> impl Strategy<Value = String> {
+    prop_oneof![
+        Just(chain::SOLANA.to_string()),
+        Just(chain::ETHEREUM.to_string()),
+        "[a-z]{0,32}",
+    ]
+}
+
+fn call_data() -> impl Strategy<Value = CallData> {
+    (proptest::collection::vec(any::<u8>(), 0..64), 0..=MAX_CALL_GAS.as_gas())
+        .prop_map(|(payload, gas_limit)| CallData { payload: Base64VecU8(payload), gas_limit })
+}
+
+/// Unlock terms validators attest to
+#[derive(Clone, Debug)]
+struct UnlockTerms {
+    message_id: MessageId,
+    source_chain: String,
+    sequence: u64,
+    sender_address: String,
+    recipient: AccountId,
+    token_contract: AccountId,
+    amount: Balance,
+}
+
+impl UnlockTerms {
+    fn hash(&self) -> [u8; 32] {
+        BridgeContract::create_unlock_message_hash(
+            &self.message_id,
+            &self.source_chain,
+            self.sequence,
+            &self.sender_address,
+            &self.recipient,
+            &self.token_contract,
+            self.amount,
+        )
+    }
+}
+
+fn unlock_terms() -> impl Strategy<Value = UnlockTerms> {
+    (
+        any::<[u8; 32]>(),
+        chain_name(),
+        any::<u64>(),
+        "[a-zA-Z0-9.]{0,64}",
+        1..6usize,
+        0..6usize,
+        any::<u128>(),
+    )
+        .prop_map(
+            |(message_id, source_chain, sequence, sender_address, recipient, token_index, amount)| {
+                UnlockTerms {
+                    message_id,
+                    source_chain,
+                    sequence,
+                    sender_address,
+                    recipient: accounts(recipient),
+                    token_contract: token(token_index),
+                    amount,
+                }
+            },
+        )
+}
+
+/// A change to exactly one attested field
+#[derive(Clone, Debug)]
+enum Tamper {
+    MessageId(usize),
+    SourceChain(String),
+    Sequence(u64),
+    SenderAddress(String),
+    Recipient(usize),
+    Token(usize),
+    Amount(u128),
+}
+
+fn tamper() -> impl Strategy<Value = Tamper> {
+    prop_oneof![
+        (0..32usize).prop_map(Tamper::MessageId),
+        "[a-z]{0,33}".prop_map(Tamper::SourceChain),
+        any::<u64>().prop_map(Tamper::Sequence),
+        "[a-zA-Z0-9.]{0,65}".prop_map(Tamper::SenderAddress),
+        (0..6usize).prop_map(Tamper::Recipient),
+        (0..6usize).prop_map(Tamper::Token),
+        any::<u128>().prop_map(Tamper::Amount),
+    ]
+}
+
+/// An entry of a submitted signature set
+#[derive(Clone, Debug)]
+enum Submitted {
+    /// Validator `i` signing the attested hash
+    Valid(u8),
+    /// Validator `i` signing other terms
+    OtherTerms(u8),
+    /// Key outside the validator set signing the attested hash
+    Outsider(u8),
+    /// Validator `i` with truncated signature bytes
+    Malformed(u8),
+}
+
+fn submitted() -> impl Strategy<Value = Submitted> {
+    prop_oneof![
+        3 => (0..12u8).prop_map(Submitted::Valid),
+        1 => (0..12u8).prop_map(Submitted::OtherTerms),
+        1 => (100..120u8).prop_map(Submitted::Outsider),
+        1 => (0..12u8).prop_map(Submitted::Malformed),
+    ]
+}
+
+/// A state change of the per-token counters
+#[derive(Clone, Debug)]
+enum CounterOp {
+    Lock { token: usize, amount: Balance },
+    Unlock { token: usize, amount: Balance },
+}
+
+fn counter_op() -> impl Strategy<Value = CounterOp> {
+    // Large enough to go past `u64`, small enough that a run cannot overflow
+    let amount = 1..=u128::MAX >> 8;
+    prop_oneof![
+        (0..3usize, amount.clone()).prop_map(|(token, amount)| CounterOp::Lock { token, amount }),
+        (0..3usize, amount).prop_map(|(token, amount)| CounterOp::Unlock { token, amount }),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn lock_message_ids_never_collide(
+        locks in proptest::collection::vec(
+            (
+                1..6usize,
+                0..6usize,
+                1..=u128::MAX >> 8,
+                chain_name(),
+                "[a-zA-Z0-9]{0,64}",
+                proptest::option::of(call_data()),
+            ),
+            1..32,
+        ),
+    ) {
+        let mut contract = bridge(3, 2);
+        let mut message_ids = HashSet::new();
+
+        for (nonce, (sender, token_index, amount, chain, address, call)) in
+            locks.into_iter().enumerate()
+        {
+            let message_id = contract.record_lock(
+                accounts(sender),
+                token(token_index),
+                amount,
+                chain,
+                address,
+                call,
+            );
+            prop_assert!(message_ids.insert(message_id), "message ID reused");
+
+            let record = contract.get_lock_record(message_id).unwrap();
+            prop_assert_eq!(record.nonce, nonce as u64);
+            prop_assert_eq!(record.amount, amount);
+        }
+        prop_assert_eq!(contract.message_count, message_ids.len() as u64);
+    }
+
+    #[test]
+    fn attached_calls_change_the_message_id(
+        message_id in any::<[u8; 32]>(),
+        first in call_data(),
+        second in call_data(),
+    ) {
+        let _ = bridge(1, 1);
+        prop_assume!(first.payload.0 != second.payload.0 || first.gas_limit != second.gas_limit);
+
+        let first_id = BridgeContract::commit_call(&message_id, &first);
+        prop_assert_ne!(first_id, BridgeContract::commit_call(&message_id, &second));
+        prop_assert_ne!(first_id, message_id);
+    }
+
+    #[test]
+    fn unlock_hash_binds_every_field(terms in unlock_terms(), tamper in tamper()) {
+        let _ = bridge(1, 1);
+        let mut tampered = terms.clone();
+        match tamper {
+            Tamper::MessageId(byte) => tampered.message_id[byte] ^= 1,
+            Tamper::SourceChain(chain) => tampered.source_chain = chain,
+            Tamper::Sequence(sequence) => tampered.sequence = sequence,
+            Tamper::SenderAddress(sender) => tampered.sender_address = sender,
+            Tamper::Recipient(index) => tampered.recipient = accounts(index),
+            Tamper::Token(index) => tampered.token_contract = token(index),
+            Tamper::Amount(amount) => tampered.amount = amount,
+        }
+
+        let changed = tampered.message_id != terms.message_id
+            || tampered.source_chain != terms.source_chain
+            || tampered.sequence != terms.sequence
+            || tampered.sender_address != terms.sender_address
+            || tampered.recipient != terms.recipient
+            || tampered.token_contract != terms.token_contract
+            || tampered.amount != terms.amount;
+        prop_assert_eq!(tampered.hash() != terms.hash(), changed);
+    }
+
+    #[test]
+    fn signature_sets_count_each_validator_once(
+        validators in 1..=10u8,
+        signatures in proptest::collection::vec(submitted(), 0..12),
+        terms in unlock_terms(),
+    ) {
+        let contract = bridge(validators, 1);
+        let hash = terms.hash();
+        let other_hash = UnlockTerms { amount: terms.amount.wrapping_add(1), ..terms }.hash();
+        // Seeds `1..=validators` are validators, other seeds are not
+        let seed = |index: u8| index + 1;
+
+        let mut expected = HashSet::new();
+        let signatures: Vec<Signature> = signatures
+            .iter()
+            .map(|entry| match *entry {
+                Submitted::Valid(index) => {
+                    if index < validators {
+                        expected.insert(index);
+                    }
+                    sign(&validator_key(seed(index)), &hash)
+                }
+                Submitted::OtherTerms(index) => sign(&validator_key(seed(index)), &other_hash),
+                Submitted::Outsider(outsider) => sign(&validator_key(outsider), &hash),
+                Submitted::Malformed(index) => {
+                    let mut signature = sign(&validator_key(seed(index)), &hash);
+                    signature.signature.truncate(63);
+                    signature
+                }
+            })
+            .collect();
+
+        prop_assert_eq!(contract.count_validator_signers(&hash, &signatures), expected.len());
+    }
+
+    #[test]
+    fn aggregate_signatures_bind_the_signer_bitmap(
+        signed_bitmap in 0..16u32,
+        submitted_bitmap in 0..16u32,
+        terms in unlock_terms(),
+    ) {
+        let mut contract = bridge(3, 2);
+        let group = validator_key(100);
+        contract.group_key = Some(GroupKey {
+            public_key: public_key(&group),
+            threshold: 2,
+            group_size: 3,
+            epoch: 0,
+        });
+        let hash = terms.hash();
+        let signed_hash = aggregate_signing_hash::<NearKeccak>(&hash, signed_bitmap);
+        let aggregate = AggregateSignature {
+            signature: group.sign(&signed_hash).to_bytes().to_vec(),
+            signer_bitmap: submitted_bitmap,
+        };
+
+        // Valid only for the bitmap the group signed, and only if it names
+        // enough participants of the group
+        let valid = submitted_bitmap == signed_bitmap
+            && submitted_bitmap >> 3 == 0
+            && submitted_bitmap.count_ones() >= 2;
+        prop_assert_eq!(contract.verify_aggregate_signature(&hash, &aggregate), valid);
+    }
+
+    #[test]
+    fn unlocks_never_exceed_locks(ops in proptest::collection::vec(counter_op(), 1..48)) {
+        let mut contract = bridge(3, 2);
+        let mut locked: HashMap<AccountId, Balance> = HashMap::new();
+        let mut unlocked: HashMap<AccountId, Balance> = HashMap::new();
+        let mut locks = 0u64;
+        let mut unlocks = 0u64;
+
+        for (index, op) in ops.into_iter().enumerate() {
+            // Each operation is its own transaction, with its own log limit
+            testing_env!(VMContextBuilder::new()
+                .current_account_id(bridge_id())
+                .predecessor_account_id(accounts(0))
+                .build());
+            match op {
+                CounterOp::Lock { token: token_index, amount } => {
+                    contract.record_lock(
+                        accounts(1),
+                        token(token_index),
+                        amount,
+                        chain::SOLANA.to_string(),
+                        "4Nd1mBQtrMJVYVfKf2PJy9NZUZdTAsp7D4xWLs4gDB4T".to_string(),
+                        None,
+                    );
+                    *locked.entry(token(token_index)).or_default() += amount;
+                    locks += 1;
+                }
+                CounterOp::Unlock { token: token_index, amount } => {
+                    let token_contract = token(token_index);
+                    let escrowed = locked.get(&token_contract).copied().unwrap_or(0)
+                        - unlocked.get(&token_contract).copied().unwrap_or(0);
+                    let message_id = [index as u8; 32];
+                    let result = catch_unwind(AssertUnwindSafe(|| {
+                        contract.record_unlock(
+                            &message_id,
+                            chain::SOLANA.to_string(),
+                            index as u64,
+                            "4Nd1mBQtrMJVYVfKf2PJy9NZUZdTAsp7D4xWLs4gDB4T".to_string(),
+                            &accounts(2),
+                            &token_contract,
+                            amount,
+                        )
+                    }));
+                    prop_assert_eq!(result.is_ok(), amount <= escrowed);
+                    if result.is_ok() {
+                        *unlocked.entry(token_contract).or_default() += amount;
+                        unlocks += 1;
+                    }
+                    prop_assert_eq!(
+                        contract.is_sequence_processed(chain::SOLANA, index as u64),
+                        result.is_ok()
+                    );
+                }
+            }
+
+            for token_index in 0..3 {
+                let token_contract = token(token_index);
+                let total_locked = contract.get_total_locked(token_contract.clone()).0;
+                let total_unlocked = contract.get_total_unlocked(token_contract.clone()).0;
+                prop_assert!(total_unlocked <= total_locked);
+                prop_assert_eq!(total_locked, locked.get(&token_contract).copied().unwrap_or(0));
+                prop_assert_eq!(
+                    total_unlocked,
+                    unlocked.get(&token_contract).copied().unwrap_or(0)
+                );
+            }
+            prop_assert_eq!(contract.message_count, locks);
+            prop_assert_eq!(contract.processed_message_count, unlocks);
+        }
+    }
+
+    /// Sequence bitmaps record exactly the transfers marked, per source chain
+    #[test]
+    fn sequence_bitmaps_track_each_transfer(
+        marked in proptest::collection::btree_set((0..2usize, 0..256u64), 0..64),
+        probe in (0..2usize, 0..256u64),
+    ) {
+        let mut contract = bridge(1, 1);
+        let chains = [chain::SOLANA, chain::ETHEREUM];
+        for &(source, sequence) in marked.iter() {
+            contract.mark_sequence_processed(chains[source], sequence);
+        }
+        prop_assert_eq!(contract.processed_message_count, marked.len() as u64);
+
+        let (source, sequence) = probe;
+        let was_marked = marked.contains(&probe);
+        prop_assert_eq!(contract.is_sequence_processed(chains[source], sequence), was_marked);
+
+        // Reopening one transfer leaves its neighbours in the word alone
+        contract.unmark_sequence_processed(chains[source], sequence);
+        prop_assert!(!contract.is_sequence_processed(chains[source], sequence));
+        for &(other_source, other) in marked.iter().filter(|&&entry| entry != probe) {
+            prop_assert!(contract.is_sequence_processed(chains[other_source], other));
+        }
+        let remaining = marked.len() as u64 - u64::from(was_marked);
+        prop_assert_eq!(contract.processed_message_count, remaining);
+    }
+}
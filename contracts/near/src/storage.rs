@@ -1,11 +1,22 @@
-use near_sdk::borsh::{self, BorshSerialize};
+use near_sdk::borsh::BorshSerialize;
+use near_sdk::BorshStorageKey;
 
 /// Storage keys for collections
-#[derive(BorshSerialize)]
+#[derive(BorshSerialize, BorshStorageKey)]
+#[borsh(crate = "near_sdk::borsh")]
 pub enum StorageKey {
     Validators,
     TotalLocked,
     TotalUnlocked,
     ProcessedMessages,
     LockRecords,
+    BatchRoots,
+    InboundMessages,
+    StorageDeposits,
+    NftEscrow,
+    BridgedNftCollections,
+    LockRecordsById,
+    ProcessedMessageIds,
+    ProcessedSequences,
+    UnclaimedFallbacks,
 }
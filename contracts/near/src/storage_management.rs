@@ -0,0 +1,178 @@
+use near_sdk::{env, log, near_bindgen, AccountId, NearToken, Promise};
+
+use crate::*;
+
+/// Bytes of the `storage_deposits` entry created when an account registers
+const STORAGE_REGISTRATION_BYTES: u64 = 125;
+
+/// NEP-145 storage management. Lock records are paid for by their sender
+/// from the sender's storage balance.
+#[near_bindgen]
+impl BridgeContract {
+    #[payable]
+    pub fn storage_deposit(
+        &mut self,
+        account_id: Option<AccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance {
+        let amount = env::attached_deposit().as_yoctonear();
+        let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+        let registration_only = registration_only.unwrap_or(false);
+        let min_balance = Self::storage_registration_cost();
+
+        match self.storage_deposits.get(&account_id) {
+            Some(available) => {
+                if registration_only {
+                    log!("Account already registered: {}", account_id);
+                    if amount > 0 {
+                        Promise::new(env::predecessor_account_id())
+                            .transfer(NearToken::from_yoctonear(amount))
+                            .detach();
+                    }
+                } else {
+                    let available =
+                        available.checked_add(amount).or_panic(BridgeError::ArithmeticOverflow);
+                    self.storage_deposits.insert(&account_id, &available);
+                }
+            }
+            None => {
+                ensure!(amount >= min_balance, BridgeError::DepositBelowMinimum);
+                let mut available =
+                    amount.checked_sub(min_balance).or_panic(BridgeError::ArithmeticOverflow);
+                if registration_only && available > 0 {
+                    Promise::new(env::predecessor_account_id())
+                        .transfer(NearToken::from_yoctonear(available))
+                        .detach();
+                    available = 0;
+                }
+                self.storage_deposits.insert(&account_id, &available);
+            }
+        }
+
+        let balance = self.storage_balance_of(account_id.clone()).unwrap();
+        BridgeEvent::StorageDeposited(StorageChangeEvent {
+            account_id,
+            amount: U128(amount),
+            available: balance.available,
+        })
+        .emit();
+
+        balance
+    }
+
+    #[payable]
+    pub fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        ensure!(env::attached_deposit().as_yoctonear() == 1, BridgeError::OneYoctoRequired);
+
+        let account_id = env::predecessor_account_id();
+        let available =
+            self.storage_deposits.get(&account_id).or_panic(BridgeError::AccountNotRegistered);
+        let amount = amount.map(|amount| amount.0).unwrap_or(available);
+        ensure!(amount <= available, BridgeError::WithdrawalExceedsBalance);
+
+        let remaining = available.checked_sub(amount).or_panic(BridgeError::ArithmeticOverflow);
+        self.storage_deposits.insert(&account_id, &remaining);
+        if amount > 0 {
+            Promise::new(account_id.clone()).transfer(NearToken::from_yoctonear(amount)).detach();
+        }
+
+        BridgeEvent::StorageWithdrawn(StorageChangeEvent {
+            account_id: account_id.clone(),
+            amount: U128(amount),
+            available: U128(remaining),
+        })
+        .emit();
+
+        self.storage_balance_of(account_id).unwrap()
+    }
+
+    /// Unregister and withdraw the whole storage balance. Lock records the
+    /// account paid for stay until pruned; their storage is then kept by the
+    /// bridge. There are no other balances, so `force` has no effect.
+    #[payable]
+    pub fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        ensure!(env::attached_deposit().as_yoctonear() == 1, BridgeError::OneYoctoRequired);
+        let _ = force;
+
+        let account_id = env::predecessor_account_id();
+        match self.storage_deposits.remove(&account_id) {
+            Some(available) => {
+                let amount = available
+                    .checked_add(Self::storage_registration_cost())
+                    .or_panic(BridgeError::ArithmeticOverflow);
+                Promise::new(account_id.clone()).transfer(NearToken::from_yoctonear(amount)).detach();
+
+                BridgeEvent::StorageUnregistered(StorageChangeEvent {
+                    account_id,
+                    amount: U128(amount),
+                    available: U128(0),
+                })
+                .emit();
+
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        StorageBalanceBounds {
+            min: U128(Self::storage_registration_cost()),
+            max: None,
+        }
+    }
+
+    pub fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+        self.storage_deposits.get(&account_id).map(|available| StorageBalance {
+            total: U128(available.saturating_add(Self::storage_registration_cost())),
+            available: U128(available),
+        })
+    }
+}
+
+impl BridgeContract {
+    /// Charge the storage added since `initial_storage` to `account_id`, first
+    /// from `attached`, then from the account's storage balance. Returns the
+    /// unused part of `attached`, which the caller refunds.
+    pub(crate) fn charge_storage(
+        &mut self,
+        account_id: &AccountId,
+        initial_storage: u64,
+        attached: Balance,
+    ) -> Balance {
+        let used = env::storage_usage().saturating_sub(initial_storage);
+        let cost = Balance::from(used)
+            .checked_mul(env::storage_byte_cost().as_yoctonear())
+            .or_panic(BridgeError::ArithmeticOverflow);
+        if let Some(unused) = attached.checked_sub(cost) {
+            return unused;
+        }
+
+        let shortfall = cost.checked_sub(attached).or_panic(BridgeError::ArithmeticOverflow);
+        let available = self.storage_deposits.get(account_id).unwrap_or(0);
+        let remaining =
+            available.checked_sub(shortfall).or_panic(BridgeError::InsufficientStorageDeposit);
+        self.storage_deposits.insert(account_id, &remaining);
+
+        0
+    }
+
+    /// Credit the storage released since `initial_storage` back to
+    /// `account_id`, if still registered
+    pub(crate) fn release_storage(&mut self, account_id: &AccountId, initial_storage: u64) {
+        let released = initial_storage.saturating_sub(env::storage_usage());
+        if let Some(available) = self.storage_deposits.get(account_id) {
+            let credit = Balance::from(released)
+                .checked_mul(env::storage_byte_cost().as_yoctonear())
+                .or_panic(BridgeError::ArithmeticOverflow);
+            let available = available.checked_add(credit).or_panic(BridgeError::ArithmeticOverflow);
+            self.storage_deposits.insert(account_id, &available);
+        }
+    }
+
+    fn storage_registration_cost() -> Balance {
+        Balance::from(STORAGE_REGISTRATION_BYTES)
+            .checked_mul(env::storage_byte_cost().as_yoctonear())
+            .or_panic(BridgeError::ArithmeticOverflow)
+    }
+}
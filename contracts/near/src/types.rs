@@ -1,12 +1,17 @@
-use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
 use near_sdk::serde::{Deserialize, Serialize};
-use near_sdk::{AccountId, Balance, PublicKey};
+use near_sdk::json_types::{Base64VecU8, U128};
+use near_sdk::{AccountId, PublicKey};
 
-/// Message ID type (32 bytes)
-pub type MessageId = [u8; 32];
+/// Token amount in the token's smallest unit (yoctoNEAR for native NEAR)
+pub type Balance = u128;
+
+/// Message ID type (32 bytes), shared with the other bridge contracts
+pub use bridge_types::MessageId;
 
 /// Lock record for outgoing cross-chain transfers
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
 #[serde(crate = "near_sdk::serde")]
 pub struct LockRecord {
     pub message_id: MessageId,
@@ -17,16 +22,219 @@ pub struct LockRecord {
     pub destination_address: String,
     pub nonce: u64,
     pub timestamp: u64,
+    /// Call to execute on the destination chain, if any
+    pub call: Option<CallData>,
+    /// Lifecycle state, advanced by validator acknowledgements
+    pub status: LockStatus,
+}
+
+/// Lifecycle state of an outbound lock
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub enum LockStatus {
+    /// Locked, outcome on the destination chain not yet acknowledged
+    Pending,
+    /// Completed on the destination chain
+    Delivered { destination_tx: String },
+    /// Rejected on the destination chain and returned to the sender
+    Refunded,
+    /// Not delivered within the expiry period and returned to the sender
+    Expired,
+}
+
+/// Call attached to a lock, executed by the destination bridge after the
+/// tokens are delivered to the receiver
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct CallData {
+    /// Calldata for the receiver (`msg` for NEP-141 receivers)
+    pub payload: Base64VecU8,
+    /// Gas (or compute units) the receiver call should be given
+    pub gas_limit: u64,
+}
+
+/// `msg` of an `ft_transfer_call` to the bridge: where the transferred
+/// tokens are delivered, and the call to attach, if any
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtLockMessage {
+    pub destination_chain: String,
+    pub destination_address: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub call: Option<CallData>,
+}
+
+/// `msg` of an `nft_transfer_call` to the bridge: where the NFT is delivered
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftLockMessage {
+    pub destination_chain: String,
+    pub destination_address: String,
+}
+
+/// NFT held by the bridge for an outbound transfer. Removed when the NFT is
+/// unlocked on its return.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftEscrow {
+    pub message_id: MessageId,
+    pub sender: AccountId,
+    pub nft_contract: AccountId,
+    pub token_id: String,
+    /// NEP-177 `reference` of the token, or its `media` if it has no
+    /// reference; empty when the token has no metadata
+    pub token_uri: String,
+    pub destination_chain: String,
+    pub destination_address: String,
+    pub nonce: u64,
+    pub timestamp: u64,
+}
+
+/// Collection the bridge mints NFTs into when they arrive from the chain
+/// they were created on
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct BridgedNftCollection {
+    pub source_chain: String,
+    /// Collection address on the source chain, as the relayer reports it
+    pub source_contract: String,
+}
+
+/// NEP-171 token as returned by `nft_token`; unknown fields are ignored
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftToken {
+    pub token_id: String,
+    pub owner_id: AccountId,
+    #[serde(default)]
+    pub metadata: Option<NftTokenMetadata>,
+}
+
+/// The NEP-177 token metadata fields the bridge reads and mints with
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftTokenMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub media: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+}
+
+impl NftTokenMetadata {
+    /// URI of the token's metadata, carried across chains
+    pub fn token_uri(&self) -> String {
+        self.reference.clone().or_else(|| self.media.clone()).unwrap_or_default()
+    }
 }
 
 /// Signature from a validator
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
 #[serde(crate = "near_sdk::serde")]
 pub struct Signature {
     pub public_key: PublicKey,
     pub signature: Vec<u8>,
 }
 
+/// Aggregated threshold signature (e.g. FROST Ed25519) over an attestation
+/// hash and the signer bitmap, see `bridge_types::aggregate_signing_hash`
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct AggregateSignature {
+    /// 64-byte Ed25519 signature valid under the current group key
+    pub signature: Vec<u8>,
+    /// Bit `i` is set when group participant `i` contributed a share
+    pub signer_bitmap: u32,
+}
+
+/// How unlock attestations are verified
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub enum SignatureMode {
+    /// One Ed25519 signature per validator
+    Individual,
+    /// A single threshold signature under `GroupKey`; individual signatures
+    /// remain accepted as a fallback
+    Threshold,
+}
+
+/// Group key produced by the validators' distributed key generation
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct GroupKey {
+    pub public_key: PublicKey,
+    /// Minimum number of participants that must sign
+    pub threshold: u8,
+    /// Number of participants holding key shares
+    pub group_size: u8,
+    /// Incremented on every rotation
+    pub epoch: u64,
+}
+
+/// Batch Merkle root attested by the validators
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct BatchRoot {
+    /// Number of leaves in the batch
+    pub message_count: u32,
+    /// Number of leaves claimed so far
+    pub claimed_count: u32,
+    pub timestamp: u64,
+}
+
+/// Token transfer payload as serialized by the Go relayer
+/// (`types.TokenTransferPayload`); unknown fields are ignored
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BatchTokenPayload {
+    pub token_address: BatchPayloadAddress,
+    pub amount: String,
+}
+
+/// Address as serialized by the Go relayer (`types.Address`)
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BatchPayloadAddress {
+    pub raw: String,
+}
+
+/// Delivery state of an inbound cross-chain message
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub enum DeliveryStatus {
+    /// Delivery call in flight
+    Pending,
+    /// Receiver accepted the message
+    Delivered,
+    /// Receiver call failed; can be retried
+    Failed,
+}
+
+/// Validated inbound message awaiting or after delivery
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct InboundMessage {
+    pub message_id: MessageId,
+    pub source_chain: String,
+    pub sender_address: String,
+    pub target: AccountId,
+    pub payload: Base64VecU8,
+    pub status: DeliveryStatus,
+    /// Number of delivery attempts made
+    pub attempts: u8,
+    pub last_attempt_timestamp: u64,
+}
+
 /// Bridge configuration (view)
 #[derive(Serialize, Deserialize)]
 #[serde(crate = "near_sdk::serde")]
@@ -36,4 +244,34 @@ pub struct BridgeConfig {
     pub required_signatures: u8,
     pub is_paused: bool,
     pub message_count: u64,
+    pub signature_mode: SignatureMode,
+}
+
+/// Amount of state held by the bridge
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageUsage {
+    /// Total bytes used by the contract account
+    pub storage_bytes: u64,
+    pub lock_records: u64,
+    pub processed_messages: u64,
+    pub batch_roots: u64,
+    pub inbound_messages: u64,
+    pub escrowed_nfts: u64,
+}
+
+/// NEP-145 storage balance of an account
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageBalance {
+    pub total: U128,
+    pub available: U128,
+}
+
+/// NEP-145 storage balance bounds
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageBalanceBounds {
+    pub min: U128,
+    pub max: Option<U128>,
 }
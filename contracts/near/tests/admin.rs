@@ -0,0 +1,291 @@
+//! Owner methods: pausing, validator set changes and ownership
+
+mod common;
+
+use common::{assert_failure, bridge_wasm, events, id, public_key, validator_key, Bridge, Unlock};
+use near_bridge::events::BridgeEvent;
+use near_bridge::types::BridgeConfig;
+use near_workspaces::types::NearToken;
+use serde_json::json;
+
+const SOLANA_RECIPIENT: &str = "4Nd1mBQtrMJVYVfKf2PJy9NZUZdTAsp7D4xWLs4gDB4T";
+
+#[tokio::test]
+async fn pause_blocks_transfers_until_unpaused() -> anyhow::Result<()> {
+    let bridge = Bridge::deploy().await?;
+    let user = bridge.user().await?;
+    bridge.lock_ft(&user, 1_000, "solana", SOLANA_RECIPIENT).await?.into_result()?;
+
+    let outcome = bridge.admin("pause", json!({})).await?;
+    match &events(&outcome)[..] {
+        [BridgeEvent::BridgePaused(event)] => assert_eq!(event.updated_by, id(&bridge.owner)),
+        events => panic!("unexpected events {events:?}"),
+    }
+    let config: BridgeConfig = bridge.view("get_config", json!({})).await?;
+    assert!(config.is_paused);
+
+    // Paused locks are refunded by the token
+    let outcome = bridge.lock_ft(&user, 100, "solana", SOLANA_RECIPIENT).await?;
+    common::assert_receipt_failure(&outcome, "Bridge is paused");
+    let outcome = bridge
+        .call_with_deposit(
+            &user,
+            "lock_near",
+            json!({ "destination_chain": "solana", "destination_address": SOLANA_RECIPIENT }),
+            NearToken::from_near(1),
+        )
+        .await?;
+    assert_failure(outcome, "Bridge is paused");
+
+    let unlock = Unlock::new(1, &user, 100);
+    let args = bridge.unlock_ft_args(&unlock, &[0, 1]);
+    let outcome = bridge.call(&user, "unlock_ft", args.clone()).await?;
+    assert_failure(outcome, "Bridge is paused");
+    assert!(!bridge.is_unlocked(&unlock).await?);
+
+    let outcome = bridge.admin("unpause", json!({})).await?;
+    assert!(matches!(&events(&outcome)[..], [BridgeEvent::BridgeUnpaused(_)]));
+    bridge.call(&user, "unlock_ft", args).await?.into_result()?;
+    assert!(bridge.is_unlocked(&unlock).await?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn admin_methods_are_owner_only() -> anyhow::Result<()> {
+    let bridge = Bridge::deploy().await?;
+    let user = bridge.user().await?;
+    let validator = public_key(&validator_key(4));
+
+    for (method, args) in [
+        ("pause", json!({})),
+        ("unpause", json!({})),
+        ("add_validator", json!({ "validator": validator })),
+        ("remove_validator", json!({ "validator": public_key(&bridge.validators[0]) })),
+        ("update_required_signatures", json!({ "required_signatures": 1 })),
+        ("transfer_ownership", json!({ "new_owner": user.id() })),
+    ] {
+        let outcome = bridge.call(&user, method, args).await?;
+        assert_failure(outcome, "Only owner can call this method");
+    }
+
+    let config: BridgeConfig = bridge.view("get_config", json!({})).await?;
+    assert_eq!(config.owner, id(&bridge.owner));
+    assert_eq!(config.validators, 3);
+    assert_eq!(config.required_signatures, 2);
+    assert!(!config.is_paused);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_validator_grows_the_set_up_to_the_maximum() -> anyhow::Result<()> {
+    let bridge = Bridge::deploy().await?;
+
+    let outcome = bridge
+        .admin("add_validator", json!({ "validator": public_key(&bridge.validators[0]) }))
+        .await?;
+    assert_failure(outcome, "Validator already exists");
+
+    for seed in 4..=10 {
+        let validator = public_key(&validator_key(seed));
+        let outcome = bridge.admin("add_validator", json!({ "validator": validator })).await?;
+        match &events(&outcome)[..] {
+            [BridgeEvent::ValidatorAdded(event)] => {
+                assert_eq!(event.validator, validator);
+                assert_eq!(event.validator_count, u64::from(seed));
+            }
+            events => panic!("unexpected events {events:?}"),
+        }
+    }
+
+    let outcome = bridge
+        .admin("add_validator", json!({ "validator": public_key(&validator_key(11)) }))
+        .await?;
+    assert_failure(outcome, "Maximum validators reached");
+
+    let config: BridgeConfig = bridge.view("get_config", json!({})).await?;
+    assert_eq!(config.validators, 10);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn added_validator_signatures_count() -> anyhow::Result<()> {
+    let bridge = Bridge::deploy().await?;
+    let user = bridge.user().await?;
+    bridge.lock_ft(&user, 1_000, "solana", SOLANA_RECIPIENT).await?.into_result()?;
+
+    let new_validator = validator_key(4);
+    bridge
+        .admin("add_validator", json!({ "validator": public_key(&new_validator) }))
+        .await?
+        .into_result()?;
+
+    let unlock = Unlock::new(1, &user, 100);
+    let hash = bridge.unlock_hash(&unlock, bridge.token.id().as_str());
+    let mut signatures = bridge.sign(&hash, &[0]);
+    signatures.push(common::sign(&new_validator, &hash));
+    bridge
+        .call(&user, "unlock_ft", bridge.unlock_ft_args_with(&unlock, signatures))
+        .await?
+        .into_result()?;
+    assert!(bridge.is_unlocked(&unlock).await?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn removed_validator_signatures_no_longer_count() -> anyhow::Result<()> {
+    let bridge = Bridge::deploy().await?;
+    let user = bridge.user().await?;
+    bridge.lock_ft(&user, 1_000, "solana", SOLANA_RECIPIENT).await?.into_result()?;
+
+    let removed = public_key(&bridge.validators[2]);
+    let outcome = bridge.admin("remove_validator", json!({ "validator": removed })).await?;
+    match &events(&outcome)[..] {
+        [BridgeEvent::ValidatorRemoved(event)] => {
+            assert_eq!(event.validator, removed);
+            assert_eq!(event.validator_count, 2);
+        }
+        events => panic!("unexpected events {events:?}"),
+    }
+
+    let unlock = Unlock::new(1, &user, 100);
+    let outcome = bridge.call(&user, "unlock_ft", bridge.unlock_ft_args(&unlock, &[0, 2])).await?;
+    assert_failure(outcome, "Insufficient valid signatures");
+    bridge
+        .call(&user, "unlock_ft", bridge.unlock_ft_args(&unlock, &[0, 1]))
+        .await?
+        .into_result()?;
+
+    let outcome = bridge.admin("remove_validator", json!({ "validator": removed })).await?;
+    assert_failure(outcome, "Validator not found");
+
+    // Two validators are left and two signatures are required
+    let outcome = bridge
+        .admin("remove_validator", json!({ "validator": public_key(&bridge.validators[0]) }))
+        .await?;
+    assert_failure(outcome, "Invalid required signatures after removal");
+    let config: BridgeConfig = bridge.view("get_config", json!({})).await?;
+    assert_eq!(config.validators, 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn update_required_signatures_stays_within_the_validator_set() -> anyhow::Result<()> {
+    let bridge = Bridge::deploy().await?;
+
+    for required_signatures in [0, 4] {
+        let outcome = bridge
+            .admin(
+                "update_required_signatures",
+                json!({ "required_signatures": required_signatures }),
+            )
+            .await?;
+        assert_failure(outcome, "Invalid required signatures");
+    }
+
+    let outcome = bridge
+        .admin("update_required_signatures", json!({ "required_signatures": 3 }))
+        .await?;
+    match &events(&outcome)[..] {
+        [BridgeEvent::RequiredSignaturesUpdated(event)] => {
+            assert_eq!(event.old_required_signatures, 2);
+            assert_eq!(event.new_required_signatures, 3);
+        }
+        events => panic!("unexpected events {events:?}"),
+    }
+    let config: BridgeConfig = bridge.view("get_config", json!({})).await?;
+    assert_eq!(config.required_signatures, 3);
+
+    // Two signatures no longer make a quorum
+    let user = bridge.user().await?;
+    bridge.lock_ft(&user, 1_000, "solana", SOLANA_RECIPIENT).await?.into_result()?;
+    let unlock = Unlock::new(1, &user, 100);
+    let outcome = bridge.call(&user, "unlock_ft", bridge.unlock_ft_args(&unlock, &[0, 1])).await?;
+    assert_failure(outcome, "Insufficient signatures");
+    bridge
+        .call(&user, "unlock_ft", bridge.unlock_ft_args(&unlock, &[0, 1, 2]))
+        .await?
+        .into_result()?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn transfer_ownership_hands_over_admin_rights() -> anyhow::Result<()> {
+    let bridge = Bridge::deploy().await?;
+    let new_owner = bridge.worker.dev_create_account().await?;
+
+    let outcome = bridge
+        .admin("transfer_ownership", json!({ "new_owner": new_owner.id() }))
+        .await?;
+    match &events(&outcome)[..] {
+        [BridgeEvent::OwnershipTransferred(event)] => {
+            assert_eq!(event.old_owner, id(&bridge.owner));
+            assert_eq!(event.new_owner, id(&new_owner));
+        }
+        events => panic!("unexpected events {events:?}"),
+    }
+
+    assert_failure(bridge.admin("pause", json!({})).await?, "Only owner can call this method");
+    bridge.call(&new_owner, "pause", json!({})).await?.into_result()?;
+    let config: BridgeConfig = bridge.view("get_config", json!({})).await?;
+    assert_eq!(config.owner, id(&new_owner));
+    assert!(config.is_paused);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn new_rejects_invalid_validator_sets() -> anyhow::Result<()> {
+    let worker = near_workspaces::sandbox().await?;
+    let contract = worker.dev_deploy(&bridge_wasm()?).await?;
+    let validators = |count: u8| -> Vec<near_sdk::PublicKey> {
+        (1..=count).map(|seed| public_key(&validator_key(seed))).collect()
+    };
+    let cases = [
+        (vec![], 1, "Invalid validator count"),
+        (validators(11), 6, "Invalid validator count"),
+        (validators(3), 0, "Invalid required signatures"),
+        (validators(3), 4, "Invalid required signatures"),
+    ];
+
+    for (validators, required_signatures, message) in cases {
+        let outcome = contract
+            .call("new")
+            .args_json(json!({
+                "owner": contract.id(),
+                "validators": validators,
+                "required_signatures": required_signatures,
+            }))
+            .transact()
+            .await?;
+        assert_failure(outcome, message);
+    }
+
+    contract
+        .call("new")
+        .args_json(json!({
+            "owner": contract.id(),
+            "validators": validators(3),
+            "required_signatures": 2,
+        }))
+        .transact()
+        .await?
+        .into_result()?;
+    let outcome = contract
+        .call("new")
+        .args_json(json!({
+            "owner": contract.id(),
+            "validators": validators(3),
+            "required_signatures": 2,
+        }))
+        .transact()
+        .await?;
+    assert!(outcome.is_failure(), "initialized twice");
+
+    Ok(())
+}
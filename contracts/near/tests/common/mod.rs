@@ -0,0 +1,442 @@
+//! Sandbox fixture shared by the integration tests: the bridge with three
+//! validators, two of them required, and a reference NEP-141 token. Build
+//! the contracts first with `contracts/near/build.sh`.
+
+#![allow(dead_code)]
+
+use bridge_types::hash::{Keccak256, Sha3Keccak};
+use bridge_types::{chain, TransferMessage, UnlockAttestation};
+use ed25519_dalek::{Keypair, PublicKey, SecretKey, Signer};
+use near_bridge::events::{BridgeEvent, EVENT_STANDARD};
+use near_bridge::types::{CallData, MessageId, Signature};
+use near_sdk::json_types::Base64VecU8;
+use near_sdk::{AccountId, CurveType};
+use near_workspaces::network::Sandbox;
+use near_workspaces::result::ExecutionFinalResult;
+use near_workspaces::types::NearToken;
+use near_workspaces::{Account, Contract, Worker};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+const BRIDGE_WASM: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/res/near_bridge.wasm");
+const TOKEN_WASM: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/res/near_test_token.wasm");
+const NFT_WASM: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/res/near_bridged_nft.wasm");
+
+/// Tokens minted to the token owner and handed out to users
+pub const TOTAL_SUPPLY: u128 = 1_000_000_000;
+pub const USER_TOKENS: u128 = 1_000_000;
+
+pub struct Bridge {
+    pub worker: Worker<Sandbox>,
+    pub contract: Contract,
+    pub token: Contract,
+    pub owner: Account,
+    pub validators: Vec<Keypair>,
+}
+
+impl Bridge {
+    /// Deploy the bridge with two of three validators required, and a token
+    /// the bridge is registered with
+    pub async fn deploy() -> anyhow::Result<Self> {
+        let worker = near_workspaces::sandbox().await?;
+        let contract = worker.dev_deploy(&bridge_wasm()?).await?;
+        let token = worker.dev_deploy(&read_wasm(TOKEN_WASM)?).await?;
+        let owner = worker.dev_create_account().await?;
+
+        let validators: Vec<Keypair> = (1..=3).map(validator_key).collect();
+        contract
+            .call("new")
+            .args_json(json!({
+                "owner": owner.id(),
+                "validators": validators.iter().map(public_key).collect::<Vec<_>>(),
+                "required_signatures": 2,
+            }))
+            .transact()
+            .await?
+            .into_result()?;
+
+        token
+            .call("new")
+            .args_json(json!({ "owner_id": owner.id(), "total_supply": TOTAL_SUPPLY.to_string() }))
+            .transact()
+            .await?
+            .into_result()?;
+        let bridge = Self { worker, contract, token, owner, validators };
+        bridge.register_with_token(bridge.contract.as_account()).await?;
+
+        Ok(bridge)
+    }
+
+    /// New account holding `USER_TOKENS`, registered with the token and with
+    /// a bridge storage balance for its lock records
+    pub async fn user(&self) -> anyhow::Result<Account> {
+        let user = self.worker.dev_create_account().await?;
+        self.register_with_token(&user).await?;
+        self.owner
+            .call(self.token.id(), "ft_transfer")
+            .args_json(json!({ "receiver_id": user.id(), "amount": USER_TOKENS.to_string() }))
+            .deposit(NearToken::from_yoctonear(1))
+            .transact()
+            .await?
+            .into_result()?;
+        user.call(self.contract.id(), "storage_deposit")
+            .args_json(json!({}))
+            .deposit(NearToken::from_millinear(100))
+            .transact()
+            .await?
+            .into_result()?;
+        Ok(user)
+    }
+
+    pub async fn register_with_token(&self, account: &Account) -> anyhow::Result<()> {
+        account
+            .call(self.token.id(), "storage_deposit")
+            .args_json(json!({ "account_id": account.id(), "registration_only": true }))
+            .deposit(NearToken::from_millinear(10))
+            .transact()
+            .await?
+            .into_result()?;
+        Ok(())
+    }
+
+    /// Lock `amount` of the token with `ft_transfer_call` and `msg`
+    pub async fn lock_ft_with_msg(
+        &self,
+        sender: &Account,
+        amount: u128,
+        msg: &str,
+    ) -> anyhow::Result<ExecutionFinalResult> {
+        Ok(sender
+            .call(self.token.id(), "ft_transfer_call")
+            .args_json(json!({
+                "receiver_id": self.contract.id(),
+                "amount": amount.to_string(),
+                "msg": msg,
+            }))
+            .deposit(NearToken::from_yoctonear(1))
+            .max_gas()
+            .transact()
+            .await?)
+    }
+
+    /// Lock `amount` of the token for delivery to `destination_address`
+    pub async fn lock_ft(
+        &self,
+        sender: &Account,
+        amount: u128,
+        destination_chain: &str,
+        destination_address: &str,
+    ) -> anyhow::Result<ExecutionFinalResult> {
+        let msg = json!({
+            "destination_chain": destination_chain,
+            "destination_address": destination_address,
+        });
+        self.lock_ft_with_msg(sender, amount, &msg.to_string()).await
+    }
+
+    /// Call a bridge method as `account` with all the gas available
+    pub async fn call(
+        &self,
+        account: &Account,
+        method: &str,
+        args: Value,
+    ) -> anyhow::Result<ExecutionFinalResult> {
+        self.call_with_deposit(account, method, args, NearToken::from_yoctonear(0)).await
+    }
+
+    pub async fn call_with_deposit(
+        &self,
+        account: &Account,
+        method: &str,
+        args: Value,
+        deposit: NearToken,
+    ) -> anyhow::Result<ExecutionFinalResult> {
+        Ok(account
+            .call(self.contract.id(), method)
+            .args_json(args)
+            .deposit(deposit)
+            .max_gas()
+            .transact()
+            .await?)
+    }
+
+    /// Call an owner-only bridge method as the owner
+    pub async fn admin(&self, method: &str, args: Value) -> anyhow::Result<ExecutionFinalResult> {
+        self.call(&self.owner, method, args).await
+    }
+
+    pub async fn view<T: DeserializeOwned>(&self, method: &str, args: Value) -> anyhow::Result<T> {
+        Ok(self.contract.view(method).args_json(args).await?.json()?)
+    }
+
+    pub async fn ft_balance(&self, account: &near_workspaces::AccountId) -> anyhow::Result<u128> {
+        let balance: String = self
+            .token
+            .view("ft_balance_of")
+            .args_json(json!({ "account_id": account }))
+            .await?
+            .json()?;
+        Ok(balance.parse()?)
+    }
+
+    pub async fn total_locked(&self, token: &str) -> anyhow::Result<u128> {
+        let total: String =
+            self.view("get_total_locked", json!({ "token_contract": token })).await?;
+        Ok(total.parse()?)
+    }
+
+    pub async fn total_unlocked(&self, token: &str) -> anyhow::Result<u128> {
+        let total: String =
+            self.view("get_total_unlocked", json!({ "token_contract": token })).await?;
+        Ok(total.parse()?)
+    }
+
+    pub async fn is_processed(&self, message_id: &MessageId) -> anyhow::Result<bool> {
+        self.view("is_message_processed", json!({ "message_id": message_id })).await
+    }
+
+    /// Whether the transfer with `sequence` from `source_chain` was released
+    pub async fn is_transfer_processed(
+        &self,
+        source_chain: &str,
+        sequence: u64,
+    ) -> anyhow::Result<bool> {
+        self.view(
+            "is_transfer_processed",
+            json!({ "source_chain": source_chain, "sequence": sequence }),
+        )
+        .await
+    }
+
+    pub async fn is_unlocked(&self, unlock: &Unlock) -> anyhow::Result<bool> {
+        self.is_transfer_processed(&unlock.source_chain, unlock.sequence).await
+    }
+
+    /// Signatures of the validators at `indices` over `hash`
+    pub fn sign(&self, hash: &[u8; 32], indices: &[usize]) -> Vec<Signature> {
+        indices.iter().map(|&index| sign(&self.validators[index], hash)).collect()
+    }
+
+    /// Hash the validators sign to release `amount` of `token` to `recipient`
+    pub fn unlock_hash(&self, unlock: &Unlock, token: &str) -> [u8; 32] {
+        UnlockAttestation {
+            message_id: unlock.message_id,
+            source_chain: unlock.source_chain.clone(),
+            sequence: unlock.sequence,
+            sender: unlock.sender_address.clone(),
+            destination_chain: chain::NEAR.to_string(),
+            destination_bridge: self.contract.id().as_bytes().to_vec(),
+            recipient: unlock.recipient.as_bytes().to_vec(),
+            token: token.as_bytes().to_vec(),
+            amount: unlock.amount,
+        }
+        .signing_hash::<Sha3Keccak>()
+    }
+
+    /// Message ID of the lock made at `sequence`
+    pub fn lock_message_id(
+        &self,
+        sequence: u64,
+        sender: &Account,
+        token: &str,
+        amount: u128,
+        destination_chain: &str,
+        destination_address: &str,
+    ) -> MessageId {
+        TransferMessage {
+            source_chain: chain::NEAR.to_string(),
+            source_bridge: self.contract.id().as_bytes().to_vec(),
+            sequence,
+            sender: sender.id().as_bytes().to_vec(),
+            token: token.as_bytes().to_vec(),
+            amount,
+            destination_chain: destination_chain.to_string(),
+            recipient: destination_address.to_string(),
+        }
+        .id::<Sha3Keccak>()
+    }
+
+    /// `unlock_ft` arguments for `unlock` signed by the validators at `indices`
+    pub fn unlock_ft_args(&self, unlock: &Unlock, indices: &[usize]) -> Value {
+        let token = self.token.id().to_string();
+        let signatures = self.sign(&self.unlock_hash(unlock, &token), indices);
+        self.unlock_ft_args_with(unlock, signatures)
+    }
+
+    pub fn unlock_ft_args_with(&self, unlock: &Unlock, signatures: Vec<Signature>) -> Value {
+        json!({
+            "message_id": unlock.message_id,
+            "source_chain": unlock.source_chain,
+            "sequence": unlock.sequence,
+            "sender_address": unlock.sender_address,
+            "recipient": unlock.recipient,
+            "token_contract": self.token.id(),
+            "amount": unlock.amount.to_string(),
+            "signatures": signatures,
+            "aggregate_signature": null,
+        })
+    }
+
+    /// `unlock_ft_and_call` arguments for `unlock` delivered to `receiver`
+    /// with `msg`, signed by the validators at `indices`
+    pub fn unlock_ft_and_call_args(
+        &self,
+        unlock: &Unlock,
+        receiver: &str,
+        msg: &str,
+        indices: &[usize],
+    ) -> Value {
+        let gas_limit = 10_000_000_000_000u64;
+        let unlock_hash = self.unlock_hash(unlock, self.token.id().as_str());
+        let hash = Sha3Keccak::keccak256(
+            &[
+                &unlock_hash[..],
+                receiver.as_bytes(),
+                &Sha3Keccak::keccak256(msg.as_bytes()),
+                &gas_limit.to_le_bytes(),
+            ]
+            .concat(),
+        );
+        json!({
+            "message_id": unlock.message_id,
+            "source_chain": unlock.source_chain,
+            "sequence": unlock.sequence,
+            "sender_address": unlock.sender_address,
+            "recipient": unlock.recipient,
+            "receiver": receiver,
+            "token_contract": self.token.id(),
+            "amount": unlock.amount.to_string(),
+            "call": CallData { payload: Base64VecU8(msg.as_bytes().to_vec()), gas_limit },
+            "signatures": self.sign(&hash, indices),
+            "aggregate_signature": null,
+        })
+    }
+
+    /// `unlock_near` arguments for `unlock` signed by the validators at `indices`
+    pub fn unlock_near_args(&self, unlock: &Unlock, indices: &[usize]) -> Value {
+        let signatures = self.sign(&self.unlock_hash(unlock, near_bridge::NATIVE_TOKEN), indices);
+        json!({
+            "message_id": unlock.message_id,
+            "source_chain": unlock.source_chain,
+            "sequence": unlock.sequence,
+            "sender_address": unlock.sender_address,
+            "recipient": unlock.recipient,
+            "amount": unlock.amount.to_string(),
+            "signatures": signatures,
+            "aggregate_signature": null,
+        })
+    }
+}
+
+/// Terms of an inbound transfer
+#[derive(Clone, Debug)]
+pub struct Unlock {
+    pub message_id: MessageId,
+    pub source_chain: String,
+    pub sequence: u64,
+    pub sender_address: String,
+    pub recipient: String,
+    pub amount: u128,
+}
+
+impl Unlock {
+    /// Transfer from Solana to `recipient`, at sequence `message_id`
+    pub fn new(message_id: u8, recipient: &Account, amount: u128) -> Self {
+        Self {
+            message_id: [message_id; 32],
+            source_chain: chain::SOLANA.to_string(),
+            sequence: u64::from(message_id),
+            sender_address: "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin".to_string(),
+            recipient: recipient.id().to_string(),
+            amount,
+        }
+    }
+}
+
+/// Deterministic validator key
+pub fn validator_key(seed: u8) -> Keypair {
+    let secret = SecretKey::from_bytes(&[seed; 32]).expect("32 bytes is a valid secret key");
+    Keypair { public: PublicKey::from(&secret), secret }
+}
+
+pub fn public_key(key: &Keypair) -> near_sdk::PublicKey {
+    near_sdk::PublicKey::from_parts(CurveType::ED25519, key.public.to_bytes().to_vec())
+        .expect("32 bytes is a valid Ed25519 key")
+}
+
+pub fn sign(key: &Keypair, hash: &[u8; 32]) -> Signature {
+    Signature {
+        public_key: public_key(key),
+        signature: key.sign(hash).to_bytes().to_vec(),
+    }
+}
+
+/// SDK account ID of a sandbox account
+pub fn id(account: &Account) -> AccountId {
+    account.id().as_str().parse().unwrap()
+}
+
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[derive(Deserialize)]
+struct EventLog {
+    standard: String,
+    #[serde(flatten)]
+    event: BridgeEvent,
+}
+
+/// Bridge events logged anywhere in the transaction, in order
+pub fn events(outcome: &ExecutionFinalResult) -> Vec<BridgeEvent> {
+    outcome
+        .logs()
+        .into_iter()
+        .filter_map(|log| log.strip_prefix("EVENT_JSON:"))
+        .filter_map(|json| serde_json::from_str::<EventLog>(json).ok())
+        .filter(|log| log.standard == EVENT_STANDARD)
+        .map(|log| log.event)
+        .collect()
+}
+
+/// Assert that the transaction failed with `message`
+pub fn assert_failure(outcome: ExecutionFinalResult, message: &str) {
+    match outcome.into_result() {
+        Ok(_) => panic!("expected the call to fail with `{message}`"),
+        Err(failure) => {
+            let failure = failure.to_string();
+            assert!(failure.contains(message), "expected `{message}`, got `{failure}`");
+        }
+    }
+}
+
+/// Assert that the transaction completed but one of its receipts failed with
+/// `message`, as when a receiver rejects a transfer that is then refunded
+pub fn assert_receipt_failure(outcome: &ExecutionFinalResult, message: &str) {
+    assert!(outcome.is_success(), "expected the transaction to complete: {outcome:?}");
+    let failures: Vec<String> = outcome
+        .receipt_failures()
+        .into_iter()
+        .filter_map(|receipt| receipt.clone().into_result().err())
+        .map(|failure| failure.to_string())
+        .collect();
+    assert!(
+        failures.iter().any(|failure| failure.contains(message)),
+        "expected a receipt to fail with `{message}`, got {failures:?}"
+    );
+}
+
+/// Compiled bridge contract
+pub fn bridge_wasm() -> anyhow::Result<Vec<u8>> {
+    read_wasm(BRIDGE_WASM)
+}
+
+/// Compiled NEP-171 collection, `near-bridged-nft`
+pub fn nft_wasm() -> anyhow::Result<Vec<u8>> {
+    read_wasm(NFT_WASM)
+}
+
+fn read_wasm(path: &str) -> anyhow::Result<Vec<u8>> {
+    std::fs::read(path).map_err(|err| anyhow::anyhow!("{path}: {err}; run contracts/near/build.sh"))
+}
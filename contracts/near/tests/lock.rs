@@ -0,0 +1,335 @@
+//! Outbound transfers: NEP-141 locks through `ft_transfer_call`, native
+//! NEAR locks and refunds
+
+mod common;
+
+use bridge_types::hash::{Keccak256, Sha3Keccak};
+use common::{assert_failure, assert_receipt_failure, events, hex, id, Bridge, USER_TOKENS};
+use near_bridge::events::BridgeEvent;
+use near_bridge::types::{BridgeConfig, LockRecord, LockStatus, StorageBalance};
+use near_bridge::{NATIVE_TOKEN, REFUND_DOMAIN};
+use near_workspaces::types::NearToken;
+use serde_json::json;
+
+const SOLANA_RECIPIENT: &str = "4Nd1mBQtrMJVYVfKf2PJy9NZUZdTAsp7D4xWLs4gDB4T";
+
+#[tokio::test]
+async fn lock_ft_escrows_tokens_and_records_the_lock() -> anyhow::Result<()> {
+    let bridge = Bridge::deploy().await?;
+    let user = bridge.user().await?;
+    let token = bridge.token.id().to_string();
+    let storage_before: StorageBalance =
+        bridge.view("storage_balance_of", json!({ "account_id": user.id() })).await?;
+
+    let outcome = bridge.lock_ft(&user, 400, "solana", SOLANA_RECIPIENT).await?;
+    assert!(outcome.is_success(), "{outcome:?}");
+
+    let message_id = bridge.lock_message_id(0, &user, &token, 400, "solana", SOLANA_RECIPIENT);
+    let events = events(&outcome);
+    assert_eq!(events.len(), 1);
+    match &events[0] {
+        BridgeEvent::TokenLocked(event) => {
+            assert_eq!(event.message_id, hex(&message_id));
+            assert_eq!(event.sender, id(&user));
+            assert_eq!(event.recipient, SOLANA_RECIPIENT);
+            assert_eq!(event.token.as_str(), token);
+            assert_eq!(event.amount.0, 400);
+            assert_eq!(event.destination_chain, "solana");
+            assert_eq!(event.nonce, 0);
+            assert!(event.call.is_none());
+        }
+        event => panic!("unexpected event {event:?}"),
+    }
+
+    assert_eq!(bridge.ft_balance(user.id()).await?, USER_TOKENS - 400);
+    assert_eq!(bridge.ft_balance(bridge.contract.id()).await?, 400);
+    assert_eq!(bridge.total_locked(&token).await?, 400);
+
+    let record: Option<LockRecord> =
+        bridge.view("get_lock_record", json!({ "message_id": message_id })).await?;
+    let record = record.expect("lock record");
+    assert_eq!(record.sender, id(&user));
+    assert_eq!(record.amount, 400);
+    assert_eq!(record.status, LockStatus::Pending);
+
+    let config: BridgeConfig = bridge.view("get_config", json!({})).await?;
+    assert_eq!(config.message_count, 1);
+
+    // The record is paid for from the sender's storage balance
+    let storage_after: StorageBalance =
+        bridge.view("storage_balance_of", json!({ "account_id": user.id() })).await?;
+    assert!(storage_after.available.0 < storage_before.available.0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn lock_ft_message_ids_follow_the_sequence() -> anyhow::Result<()> {
+    let bridge = Bridge::deploy().await?;
+    let user = bridge.user().await?;
+    let token = bridge.token.id().to_string();
+
+    for sequence in 0..3 {
+        let outcome = bridge.lock_ft(&user, 10, "solana", SOLANA_RECIPIENT).await?;
+        let expected =
+            bridge.lock_message_id(sequence, &user, &token, 10, "solana", SOLANA_RECIPIENT);
+        match &events(&outcome)[..] {
+            [BridgeEvent::TokenLocked(event)] => {
+                assert_eq!(event.message_id, hex(&expected));
+                assert_eq!(event.nonce, sequence);
+            }
+            events => panic!("unexpected events {events:?}"),
+        }
+    }
+    assert_eq!(bridge.total_locked(&token).await?, 30);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn lock_ft_with_invalid_message_is_refunded() -> anyhow::Result<()> {
+    let bridge = Bridge::deploy().await?;
+    let user = bridge.user().await?;
+
+    let outcome = bridge.lock_ft_with_msg(&user, 400, "not a lock message").await?;
+    assert_receipt_failure(&outcome, "Invalid lock message");
+    assert!(events(&outcome).is_empty());
+
+    assert_eq!(bridge.ft_balance(user.id()).await?, USER_TOKENS);
+    assert_eq!(bridge.ft_balance(bridge.contract.id()).await?, 0);
+    assert_eq!(bridge.total_locked(bridge.token.id().as_str()).await?, 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn lock_ft_without_storage_balance_is_refunded() -> anyhow::Result<()> {
+    let bridge = Bridge::deploy().await?;
+    let user = bridge.user().await?;
+    bridge
+        .call_with_deposit(
+            &user,
+            "storage_unregister",
+            json!({ "force": true }),
+            NearToken::from_yoctonear(1),
+        )
+        .await?
+        .into_result()?;
+
+    let outcome = bridge.lock_ft(&user, 400, "solana", SOLANA_RECIPIENT).await?;
+    assert_receipt_failure(&outcome, "Insufficient storage deposit");
+
+    assert_eq!(bridge.ft_balance(user.id()).await?, USER_TOKENS);
+    assert_eq!(bridge.total_locked(bridge.token.id().as_str()).await?, 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn lock_ft_with_zero_amount_is_rejected() -> anyhow::Result<()> {
+    let bridge = Bridge::deploy().await?;
+    let user = bridge.user().await?;
+
+    // The token refuses zero transfers before calling the bridge
+    let outcome = bridge.lock_ft(&user, 0, "solana", SOLANA_RECIPIENT).await?;
+    assert!(outcome.is_failure());
+    assert!(events(&outcome).is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn lock_near_records_the_attached_deposit() -> anyhow::Result<()> {
+    let bridge = Bridge::deploy().await?;
+    let user = bridge.user().await?;
+    let amount = NearToken::from_near(1);
+
+    let outcome = bridge
+        .call_with_deposit(
+            &user,
+            "lock_near",
+            json!({ "destination_chain": "solana", "destination_address": SOLANA_RECIPIENT }),
+            amount,
+        )
+        .await?;
+    assert!(outcome.is_success(), "{outcome:?}");
+
+    let message_id = bridge.lock_message_id(
+        0,
+        &user,
+        NATIVE_TOKEN,
+        amount.as_yoctonear(),
+        "solana",
+        SOLANA_RECIPIENT,
+    );
+    match &events(&outcome)[..] {
+        [BridgeEvent::TokenLocked(event)] => {
+            assert_eq!(event.message_id, hex(&message_id));
+            assert_eq!(event.token.as_str(), NATIVE_TOKEN);
+            assert_eq!(event.amount.0, amount.as_yoctonear());
+        }
+        events => panic!("unexpected events {events:?}"),
+    }
+    assert_eq!(bridge.total_locked(NATIVE_TOKEN).await?, amount.as_yoctonear());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn lock_near_rejects_oversized_destination() -> anyhow::Result<()> {
+    let bridge = Bridge::deploy().await?;
+    let user = bridge.user().await?;
+
+    let outcome = bridge
+        .call_with_deposit(
+            &user,
+            "lock_near",
+            json!({ "destination_chain": "solana", "destination_address": "x".repeat(1024) }),
+            NearToken::from_near(1),
+        )
+        .await?;
+    assert_failure(outcome, "Destination address too long");
+    assert_eq!(bridge.total_locked(NATIVE_TOKEN).await?, 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn lock_near_with_amount_pays_storage_from_the_deposit_and_refunds_the_rest(
+) -> anyhow::Result<()> {
+    let bridge = Bridge::deploy().await?;
+    let user = bridge.user().await?;
+    bridge
+        .call_with_deposit(
+            &user,
+            "storage_unregister",
+            json!({ "force": true }),
+            NearToken::from_yoctonear(1),
+        )
+        .await?
+        .into_result()?;
+    let amount = NearToken::from_near(1);
+    let balance_before = user.view_account().await?.balance;
+
+    let outcome = bridge
+        .call_with_deposit(
+            &user,
+            "lock_near",
+            json!({
+                "destination_chain": "solana",
+                "destination_address": SOLANA_RECIPIENT,
+                "amount": amount.as_yoctonear().to_string(),
+            }),
+            NearToken::from_near(2),
+        )
+        .await?;
+    assert!(outcome.is_success(), "{outcome:?}");
+    match &events(&outcome)[..] {
+        [BridgeEvent::TokenLocked(event)] => assert_eq!(event.amount.0, amount.as_yoctonear()),
+        events => panic!("unexpected events {events:?}"),
+    }
+    assert_eq!(bridge.total_locked(NATIVE_TOKEN).await?, amount.as_yoctonear());
+
+    // Only the amount, the record's storage and gas are spent; the surplus
+    // comes back and no storage balance is created
+    let spent = balance_before.as_yoctonear() - user.view_account().await?.balance.as_yoctonear();
+    assert!(spent > amount.as_yoctonear());
+    assert!(spent < NearToken::from_millinear(1_100).as_yoctonear(), "spent {spent}");
+    let storage: Option<StorageBalance> =
+        bridge.view("storage_balance_of", json!({ "account_id": user.id() })).await?;
+    assert!(storage.is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn lock_near_with_amount_requires_a_deposit_covering_it() -> anyhow::Result<()> {
+    let bridge = Bridge::deploy().await?;
+    let user = bridge.user().await?;
+
+    let outcome = bridge
+        .call_with_deposit(
+            &user,
+            "lock_near",
+            json!({
+                "destination_chain": "solana",
+                "destination_address": SOLANA_RECIPIENT,
+                "amount": NearToken::from_near(2).as_yoctonear().to_string(),
+            }),
+            NearToken::from_near(1),
+        )
+        .await?;
+    assert_failure(outcome, "Amount exceeds the attached deposit");
+    assert_eq!(bridge.total_locked(NATIVE_TOKEN).await?, 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn failed_refund_transfer_can_be_retried() -> anyhow::Result<()> {
+    let bridge = Bridge::deploy().await?;
+    let user = bridge.user().await?;
+    let token = bridge.token.id().to_string();
+    bridge.lock_ft(&user, USER_TOKENS, "solana", SOLANA_RECIPIENT).await?.into_result()?;
+    let message_id =
+        bridge.lock_message_id(0, &user, &token, USER_TOKENS, "solana", SOLANA_RECIPIENT);
+
+    // With nothing left, the sender can leave the token, so the refund fails
+    user.call(bridge.token.id(), "storage_unregister")
+        .args_json(json!({}))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?
+        .into_result()?;
+
+    let hash = Sha3Keccak::keccak256(
+        &[
+            REFUND_DOMAIN,
+            bridge.contract.id().as_bytes(),
+            &message_id,
+            user.id().as_bytes(),
+            token.as_bytes(),
+            &USER_TOKENS.to_le_bytes(),
+        ]
+        .concat(),
+    );
+    let args = json!({
+        "message_id": message_id,
+        "signatures": bridge.sign(&hash, &[0, 1]),
+        "aggregate_signature": null,
+    });
+
+    let outcome = bridge.call(&user, "refund", args.clone()).await?;
+    assert!(outcome.is_success(), "{outcome:?}");
+    assert!(!outcome.receipt_failures().is_empty());
+    match &events(&outcome)[..] {
+        [
+            BridgeEvent::LockStatusChanged(_),
+            BridgeEvent::TokenRefunded(_),
+            BridgeEvent::LockStatusChanged(reopened),
+            BridgeEvent::RefundFailed(event),
+        ] => {
+            assert_eq!(reopened.status, LockStatus::Pending);
+            assert_eq!(event.message_id, hex(&message_id));
+            assert_eq!(event.sender, id(&user));
+            assert_eq!(event.amount.0, USER_TOKENS);
+        }
+        events => panic!("unexpected events {events:?}"),
+    }
+    assert!(!outcome.json::<bool>()?);
+
+    let status: Option<LockStatus> =
+        bridge.view("get_lock_status", json!({ "message_id": message_id })).await?;
+    assert_eq!(status, Some(LockStatus::Pending));
+    assert_eq!(bridge.total_locked(&token).await?, USER_TOKENS);
+
+    // The same attestation goes through once the sender can receive again
+    bridge.register_with_token(&user).await?;
+    let outcome = bridge.call(&user, "refund", args).await?;
+    assert!(outcome.receipt_failures().is_empty(), "{outcome:?}");
+    assert!(outcome.json::<bool>()?);
+    assert_eq!(bridge.ft_balance(user.id()).await?, USER_TOKENS);
+    assert_eq!(bridge.total_locked(&token).await?, 0);
+
+    Ok(())
+}
@@ -0,0 +1,236 @@
+//! NEP-171 bridging: escrow through `nft_transfer_call`, release of escrowed
+//! NFTs and minting into a bridged collection
+
+mod common;
+
+use bridge_types::hash::Sha3Keccak;
+use bridge_types::{chain, NftTransferMessage, NftUnlockAttestation};
+use common::{assert_receipt_failure, events, hex, id, nft_wasm, Bridge};
+use near_bridge::events::BridgeEvent;
+use near_bridge::types::{MessageId, NftEscrow};
+use near_workspaces::result::ExecutionFinalResult;
+use near_workspaces::types::NearToken;
+use near_workspaces::{Account, Contract};
+use serde_json::{json, Value};
+
+const ETHEREUM_RECIPIENT: &str = "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb";
+const ETHEREUM_COLLECTION: &str = "0xb47e3cd837dDF8e4c57F05d70Ab865de6e193BBB";
+const TOKEN_URI: &str = "ipfs://bafybeigdyrzt/42.json";
+
+/// Deploy a collection minted into by `minter`
+async fn collection(bridge: &Bridge, minter: &Account) -> anyhow::Result<Contract> {
+    let collection = bridge.worker.dev_deploy(&nft_wasm()?).await?;
+    collection
+        .call("new")
+        .args_json(json!({
+            "minter": minter.id(),
+            "metadata": { "spec": "nft-1.0.0", "name": "Art", "symbol": "ART" },
+            "source_chain": chain::ETHEREUM,
+            "source_contract": ETHEREUM_COLLECTION,
+        }))
+        .transact()
+        .await?
+        .into_result()?;
+    // Minted tokens are paid for by the collection
+    bridge
+        .owner
+        .transfer_near(collection.id(), NearToken::from_near(1))
+        .await?
+        .into_result()?;
+    Ok(collection)
+}
+
+/// Collection native to NEAR with token "42" minted to `owner`
+async fn native_collection(bridge: &Bridge, owner: &Account) -> anyhow::Result<Contract> {
+    let collection = collection(bridge, &bridge.owner).await?;
+    bridge
+        .owner
+        .call(collection.id(), "nft_mint")
+        .args_json(json!({
+            "token_id": "42",
+            "receiver_id": owner.id(),
+            "token_metadata": { "reference": TOKEN_URI },
+        }))
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+    Ok(collection)
+}
+
+async fn lock_nft(
+    bridge: &Bridge,
+    sender: &Account,
+    collection: &Contract,
+    msg: &str,
+) -> anyhow::Result<ExecutionFinalResult> {
+    Ok(sender
+        .call(collection.id(), "nft_transfer_call")
+        .args_json(json!({
+            "receiver_id": bridge.contract.id(),
+            "token_id": "42",
+            "msg": msg,
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await?)
+}
+
+fn lock_msg() -> String {
+    json!({ "destination_chain": "ethereum", "destination_address": ETHEREUM_RECIPIENT })
+        .to_string()
+}
+
+async fn nft_token(collection: &Contract) -> anyhow::Result<Value> {
+    Ok(collection.view("nft_token").args_json(json!({ "token_id": "42" })).await?.json()?)
+}
+
+/// `unlock_nft` arguments releasing token "42" of `collection` to
+/// `recipient`, signed by two validators
+fn unlock_args(
+    bridge: &Bridge,
+    message_id: MessageId,
+    recipient: &Account,
+    collection: &Contract,
+) -> Value {
+    let hash = NftUnlockAttestation {
+        message_id,
+        source_chain: chain::ETHEREUM.to_string(),
+        sender: ETHEREUM_RECIPIENT.to_string(),
+        destination_chain: chain::NEAR.to_string(),
+        destination_bridge: bridge.contract.id().as_bytes().to_vec(),
+        recipient: recipient.id().as_bytes().to_vec(),
+        nft_contract: collection.id().as_bytes().to_vec(),
+        token_id: "42".to_string(),
+        token_uri: TOKEN_URI.to_string(),
+    }
+    .signing_hash::<Sha3Keccak>();
+    json!({
+        "message_id": message_id,
+        "source_chain": chain::ETHEREUM,
+        "sender_address": ETHEREUM_RECIPIENT,
+        "recipient": recipient.id(),
+        "nft_contract": collection.id(),
+        "token_id": "42",
+        "token_uri": TOKEN_URI,
+        "signatures": bridge.sign(&hash, &[0, 1]),
+        "aggregate_signature": null,
+    })
+}
+
+#[tokio::test]
+async fn lock_nft_escrows_token_with_metadata() -> anyhow::Result<()> {
+    let bridge = Bridge::deploy().await?;
+    let user = bridge.user().await?;
+    let collection = native_collection(&bridge, &user).await?;
+
+    let outcome = lock_nft(&bridge, &user, &collection, &lock_msg()).await?;
+    assert!(outcome.is_success(), "{outcome:?}");
+    assert!(outcome.receipt_failures().is_empty(), "{outcome:?}");
+
+    let message_id = NftTransferMessage {
+        source_chain: chain::NEAR.to_string(),
+        source_bridge: bridge.contract.id().as_bytes().to_vec(),
+        sequence: 0,
+        sender: user.id().as_bytes().to_vec(),
+        nft_contract: collection.id().as_bytes().to_vec(),
+        token_id: "42".to_string(),
+        token_uri: TOKEN_URI.to_string(),
+        destination_chain: chain::ETHEREUM.to_string(),
+        recipient: ETHEREUM_RECIPIENT.to_string(),
+    }
+    .id::<Sha3Keccak>();
+    match &events(&outcome)[..] {
+        [BridgeEvent::NftLocked(event)] => {
+            assert_eq!(event.message_id, hex(&message_id));
+            assert_eq!(event.sender, id(&user));
+            assert_eq!(event.nft_contract.as_str(), collection.id().as_str());
+            assert_eq!(event.token_id, "42");
+            assert_eq!(event.token_uri, TOKEN_URI);
+            assert_eq!(event.recipient, ETHEREUM_RECIPIENT);
+        }
+        events => panic!("unexpected events {events:?}"),
+    }
+
+    let escrow: Option<NftEscrow> = bridge
+        .view("get_escrowed_nft", json!({ "nft_contract": collection.id(), "token_id": "42" }))
+        .await?;
+    assert_eq!(escrow.expect("escrow record").message_id, message_id);
+    assert_eq!(nft_token(&collection).await?["owner_id"], json!(bridge.contract.id()));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn lock_nft_returns_token_on_invalid_msg() -> anyhow::Result<()> {
+    let bridge = Bridge::deploy().await?;
+    let user = bridge.user().await?;
+    let collection = native_collection(&bridge, &user).await?;
+
+    let outcome = lock_nft(&bridge, &user, &collection, "not json").await?;
+    assert_receipt_failure(&outcome, "BridgeError::InvalidLockMessage");
+
+    assert_eq!(nft_token(&collection).await?["owner_id"], json!(user.id()));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn unlock_nft_releases_escrowed_token() -> anyhow::Result<()> {
+    let bridge = Bridge::deploy().await?;
+    let user = bridge.user().await?;
+    let recipient = bridge.user().await?;
+    let collection = native_collection(&bridge, &user).await?;
+    lock_nft(&bridge, &user, &collection, &lock_msg()).await?.into_result()?;
+
+    let args = unlock_args(&bridge, [1; 32], &recipient, &collection);
+    let outcome = bridge.call(&recipient, "unlock_nft", args).await?;
+    assert!(outcome.is_success(), "{outcome:?}");
+    assert!(outcome.receipt_failures().is_empty(), "{outcome:?}");
+
+    match &events(&outcome)[..] {
+        [BridgeEvent::NftUnlocked(event)] => {
+            assert_eq!(event.recipient, id(&recipient));
+            assert!(!event.minted);
+        }
+        events => panic!("unexpected events {events:?}"),
+    }
+    assert_eq!(nft_token(&collection).await?["owner_id"], json!(recipient.id()));
+    assert!(bridge.is_processed(&[1; 32]).await?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn unlock_nft_mints_into_bridged_collection() -> anyhow::Result<()> {
+    let bridge = Bridge::deploy().await?;
+    let recipient = bridge.user().await?;
+    let collection = collection(&bridge, bridge.contract.as_account()).await?;
+    bridge
+        .admin(
+            "register_bridged_nft_collection",
+            json!({
+                "collection": collection.id(),
+                "source_chain": chain::ETHEREUM,
+                "source_contract": ETHEREUM_COLLECTION,
+            }),
+        )
+        .await?
+        .into_result()?;
+
+    let args = unlock_args(&bridge, [2; 32], &recipient, &collection);
+    let outcome = bridge.call(&recipient, "unlock_nft", args).await?;
+    assert!(outcome.is_success(), "{outcome:?}");
+    assert!(outcome.receipt_failures().is_empty(), "{outcome:?}");
+
+    match &events(&outcome)[..] {
+        [BridgeEvent::NftUnlocked(event)] => assert!(event.minted),
+        events => panic!("unexpected events {events:?}"),
+    }
+    let token = nft_token(&collection).await?;
+    assert_eq!(token["owner_id"], json!(recipient.id()));
+    assert_eq!(token["metadata"]["reference"], json!(TOKEN_URI));
+
+    Ok(())
+}
@@ -0,0 +1,440 @@
+//! Inbound transfers: validator-attested unlocks, signature checks, replay
+//! protection and failed token transfers
+
+mod common;
+
+use bridge_types::chain;
+use bridge_types::hash::{Keccak256, Sha3Keccak};
+use common::{assert_failure, events, hex, id, sign, validator_key, Bridge, Unlock, USER_TOKENS};
+use near_bridge::events::BridgeEvent;
+use near_bridge::types::{BatchRoot, MessageId};
+use near_bridge::{merkle, NATIVE_TOKEN};
+use near_workspaces::types::NearToken;
+use near_workspaces::Account;
+use serde_json::{json, Value};
+
+/// Bridge holding `amount` of the token locked by another user
+async fn funded_bridge(amount: u128) -> anyhow::Result<Bridge> {
+    let bridge = Bridge::deploy().await?;
+    let locker = bridge.user().await?;
+    bridge
+        .lock_ft(&locker, amount, "solana", "4Nd1mBQtrMJVYVfKf2PJy9NZUZdTAsp7D4xWLs4gDB4T")
+        .await?
+        .into_result()?;
+    Ok(bridge)
+}
+
+#[tokio::test]
+async fn unlock_ft_releases_tokens() -> anyhow::Result<()> {
+    let bridge = funded_bridge(1_000).await?;
+    let recipient = bridge.user().await?;
+    let unlock = Unlock::new(1, &recipient, 600);
+
+    let outcome = bridge
+        .call(&recipient, "unlock_ft", bridge.unlock_ft_args(&unlock, &[0, 1]))
+        .await?;
+    assert!(outcome.is_success(), "{outcome:?}");
+    assert!(outcome.receipt_failures().is_empty(), "{outcome:?}");
+
+    match &events(&outcome)[..] {
+        [BridgeEvent::TokenUnlocked(event)] => {
+            assert_eq!(event.message_id, hex(&unlock.message_id));
+            assert_eq!(event.source_chain, unlock.source_chain);
+            assert_eq!(event.sender_address, unlock.sender_address);
+            assert_eq!(event.recipient, id(&recipient));
+            assert_eq!(event.token.as_str(), bridge.token.id().as_str());
+            assert_eq!(event.amount.0, 600);
+        }
+        events => panic!("unexpected events {events:?}"),
+    }
+
+    assert_eq!(bridge.ft_balance(recipient.id()).await?, USER_TOKENS + 600);
+    assert_eq!(bridge.ft_balance(bridge.contract.id()).await?, 400);
+    assert!(bridge.is_unlocked(&unlock).await?);
+    assert_eq!(bridge.total_unlocked(bridge.token.id().as_str()).await?, 600);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn unlock_ft_accepts_any_quorum() -> anyhow::Result<()> {
+    let bridge = funded_bridge(1_000).await?;
+    let recipient = bridge.user().await?;
+
+    for (message_id, signers) in [(1, &[0, 2][..]), (2, &[1, 2]), (3, &[2, 0, 1])] {
+        let unlock = Unlock::new(message_id, &recipient, 100);
+        bridge
+            .call(&recipient, "unlock_ft", bridge.unlock_ft_args(&unlock, signers))
+            .await?
+            .into_result()?;
+    }
+    assert_eq!(bridge.ft_balance(recipient.id()).await?, USER_TOKENS + 300);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn unlock_ft_rejects_too_few_signatures() -> anyhow::Result<()> {
+    let bridge = funded_bridge(1_000).await?;
+    let recipient = bridge.user().await?;
+    let unlock = Unlock::new(1, &recipient, 600);
+
+    let outcome = bridge
+        .call(&recipient, "unlock_ft", bridge.unlock_ft_args(&unlock, &[0]))
+        .await?;
+    assert_failure(outcome, "Insufficient signatures");
+    assert!(!bridge.is_unlocked(&unlock).await?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn unlock_ft_rejects_non_validator_signatures() -> anyhow::Result<()> {
+    let bridge = funded_bridge(1_000).await?;
+    let recipient = bridge.user().await?;
+    let unlock = Unlock::new(1, &recipient, 600);
+    let hash = bridge.unlock_hash(&unlock, bridge.token.id().as_str());
+
+    let mut signatures = bridge.sign(&hash, &[0]);
+    signatures.push(sign(&validator_key(42), &hash));
+    let outcome = bridge
+        .call(&recipient, "unlock_ft", bridge.unlock_ft_args_with(&unlock, signatures))
+        .await?;
+    assert_failure(outcome, "Insufficient valid signatures");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn unlock_ft_rejects_signatures_over_other_terms() -> anyhow::Result<()> {
+    let bridge = funded_bridge(1_000).await?;
+    let recipient = bridge.user().await?;
+    let unlock = Unlock::new(1, &recipient, 600);
+
+    // Signed for 6 tokens, claimed for 600
+    let signed = Unlock { amount: 6, ..unlock.clone() };
+    let token = bridge.token.id().as_str();
+    let signatures = bridge.sign(&bridge.unlock_hash(&signed, token), &[0, 1]);
+    let outcome = bridge
+        .call(&recipient, "unlock_ft", bridge.unlock_ft_args_with(&unlock, signatures))
+        .await?;
+    assert_failure(outcome, "Insufficient valid signatures");
+
+    // Signed for the native token, claimed for the NEP-141 token
+    let signatures = bridge.sign(&bridge.unlock_hash(&unlock, NATIVE_TOKEN), &[0, 1]);
+    let outcome = bridge
+        .call(&recipient, "unlock_ft", bridge.unlock_ft_args_with(&unlock, signatures))
+        .await?;
+    assert_failure(outcome, "Insufficient valid signatures");
+
+    // Malformed signature bytes
+    let mut signatures = bridge.sign(&bridge.unlock_hash(&unlock, token), &[0, 1]);
+    signatures[1].signature.truncate(10);
+    let outcome = bridge
+        .call(&recipient, "unlock_ft", bridge.unlock_ft_args_with(&unlock, signatures))
+        .await?;
+    assert_failure(outcome, "Insufficient valid signatures");
+
+    assert_eq!(bridge.ft_balance(recipient.id()).await?, USER_TOKENS);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn unlock_ft_counts_each_validator_once() -> anyhow::Result<()> {
+    let bridge = funded_bridge(1_000).await?;
+    let recipient = bridge.user().await?;
+    let unlock = Unlock::new(1, &recipient, 600);
+
+    let outcome = bridge
+        .call(&recipient, "unlock_ft", bridge.unlock_ft_args(&unlock, &[0, 0, 0]))
+        .await?;
+    assert_failure(outcome, "Insufficient valid signatures");
+    assert!(!bridge.is_unlocked(&unlock).await?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn unlock_ft_rejects_replay() -> anyhow::Result<()> {
+    let bridge = funded_bridge(1_000).await?;
+    let recipient = bridge.user().await?;
+    let unlock = Unlock::new(1, &recipient, 300);
+    let args = bridge.unlock_ft_args(&unlock, &[0, 1]);
+
+    bridge.call(&recipient, "unlock_ft", args.clone()).await?.into_result()?;
+    let outcome = bridge.call(&recipient, "unlock_ft", args).await?;
+    assert_failure(outcome, "Message already processed");
+
+    // Other signers attesting the same message do not reopen it either
+    let outcome = bridge
+        .call(&recipient, "unlock_ft", bridge.unlock_ft_args(&unlock, &[1, 2]))
+        .await?;
+    assert_failure(outcome, "Message already processed");
+
+    assert_eq!(bridge.ft_balance(recipient.id()).await?, USER_TOKENS + 300);
+    assert_eq!(bridge.total_unlocked(bridge.token.id().as_str()).await?, 300);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn failed_unlock_transfer_can_be_retried() -> anyhow::Result<()> {
+    let bridge = funded_bridge(1_000).await?;
+    // Not registered with the token, so the transfer to it fails
+    let recipient = bridge.worker.dev_create_account().await?;
+    let unlock = Unlock::new(1, &recipient, 600);
+    let args = bridge.unlock_ft_args(&unlock, &[0, 1]);
+
+    let outcome = bridge.call(&recipient, "unlock_ft", args.clone()).await?;
+    assert!(outcome.is_success(), "{outcome:?}");
+    assert!(!outcome.receipt_failures().is_empty());
+    match &events(&outcome)[..] {
+        [BridgeEvent::TokenUnlocked(_), BridgeEvent::UnlockFailed(event)] => {
+            assert_eq!(event.message_id, hex(&unlock.message_id));
+            assert_eq!(event.recipient, id(&recipient));
+            assert_eq!(event.amount.0, 600);
+        }
+        events => panic!("unexpected events {events:?}"),
+    }
+    assert!(!outcome.json::<bool>()?);
+
+    assert!(!bridge.is_unlocked(&unlock).await?);
+    assert_eq!(bridge.total_unlocked(bridge.token.id().as_str()).await?, 0);
+    assert_eq!(bridge.ft_balance(bridge.contract.id()).await?, 1_000);
+
+    // The same attestation goes through once the recipient can receive
+    bridge.register_with_token(&recipient).await?;
+    let outcome = bridge.call(&recipient, "unlock_ft", args).await?;
+    assert!(outcome.receipt_failures().is_empty(), "{outcome:?}");
+    assert!(outcome.json::<bool>()?);
+    assert!(bridge.is_unlocked(&unlock).await?);
+    assert_eq!(bridge.ft_balance(recipient.id()).await?, 600);
+    assert_eq!(bridge.total_unlocked(bridge.token.id().as_str()).await?, 600);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn failed_unlock_call_fallback_can_be_claimed() -> anyhow::Result<()> {
+    let bridge = funded_bridge(1_000).await?;
+    // Neither is registered with the token, so the call and the fallback fail
+    let recipient = bridge.worker.dev_create_account().await?;
+    let receiver = bridge.worker.dev_create_account().await?;
+    let unlock = Unlock::new(1, &recipient, 600);
+    let args = bridge.unlock_ft_and_call_args(&unlock, receiver.id().as_str(), "swap", &[0, 1]);
+
+    let outcome = bridge.call(&recipient, "unlock_ft_and_call", args.clone()).await?;
+    assert!(outcome.is_success(), "{outcome:?}");
+    match &events(&outcome)[..] {
+        [
+            BridgeEvent::TokenUnlocked(_),
+            BridgeEvent::UnlockCallFallback(_),
+            BridgeEvent::UnlockCallFallbackFailed(event),
+        ] => {
+            assert_eq!(event.message_id, hex(&unlock.message_id));
+            assert_eq!(event.recipient, id(&recipient));
+            assert_eq!(event.amount.0, 600);
+        }
+        events => panic!("unexpected events {events:?}"),
+    }
+    assert_eq!(outcome.json::<String>()?, "0");
+
+    // The transfer stays released and the tokens are held for the recipient
+    assert!(bridge.is_unlocked(&unlock).await?);
+    assert_failure(
+        bridge.call(&recipient, "unlock_ft_and_call", args).await?,
+        "Message already processed",
+    );
+    let claim_args = json!({ "token_contract": bridge.token.id() });
+    let unclaimed: String = bridge
+        .view(
+            "get_unclaimed_fallback",
+            json!({ "account_id": recipient.id(), "token_contract": bridge.token.id() }),
+        )
+        .await?;
+    assert_eq!(unclaimed, "600");
+    assert_eq!(bridge.ft_balance(bridge.contract.id()).await?, 1_000);
+
+    bridge.register_with_token(&recipient).await?;
+    let outcome = bridge.call(&recipient, "claim_unlock_fallback", claim_args.clone()).await?;
+    assert!(outcome.receipt_failures().is_empty(), "{outcome:?}");
+    match &events(&outcome)[..] {
+        [BridgeEvent::UnlockFallbackClaimed(event)] => {
+            assert_eq!(event.recipient, id(&recipient));
+            assert_eq!(event.amount.0, 600);
+        }
+        events => panic!("unexpected events {events:?}"),
+    }
+    assert!(outcome.json::<bool>()?);
+    assert_eq!(bridge.ft_balance(recipient.id()).await?, 600);
+    assert_eq!(bridge.ft_balance(bridge.contract.id()).await?, 400);
+
+    assert_failure(
+        bridge.call(&recipient, "claim_unlock_fallback", claim_args).await?,
+        "No unclaimed fallback for this token",
+    );
+
+    Ok(())
+}
+
+/// Submit a batch holding only a transfer of `amount` of the token to
+/// `recipient` on `destination_chain`, and return the `claim_from_batch`
+/// arguments for it. The transfer's sequence is the first ID byte.
+async fn single_transfer_batch(
+    bridge: &Bridge,
+    message_id: MessageId,
+    destination_chain: &str,
+    recipient: &Account,
+    amount: u128,
+) -> anyhow::Result<Value> {
+    let sender = "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb";
+    let sequence = u64::from(message_id[0]);
+    let payload =
+        json!({ "token_address": { "raw": bridge.token.id() }, "amount": amount.to_string() })
+            .to_string();
+
+    // With one leaf the root is the leaf itself
+    let batch_root = merkle::hash_leaf(
+        &message_id,
+        chain::ETHEREUM,
+        sequence,
+        destination_chain,
+        sender,
+        recipient.id().as_str(),
+        payload.as_bytes(),
+    );
+    let root_hash = Sha3Keccak::keccak256(
+        &[
+            b"articium:batch_root".as_ref(),
+            bridge.contract.id().as_bytes(),
+            &batch_root,
+            &1u32.to_le_bytes(),
+        ]
+        .concat(),
+    );
+    bridge
+        .call(
+            &bridge.owner,
+            "submit_batch_root",
+            json!({
+                "batch_root": batch_root,
+                "message_count": 1,
+                "signatures": bridge.sign(&root_hash, &[0, 1]),
+                "aggregate_signature": null,
+            }),
+        )
+        .await?
+        .into_result()?;
+
+    Ok(json!({
+        "batch_root": batch_root,
+        "message_id": message_id,
+        "source_chain": chain::ETHEREUM,
+        "sequence": sequence,
+        "destination_chain": destination_chain,
+        "sender_address": sender,
+        "recipient": recipient.id(),
+        "payload": payload,
+        "leaf_index": 0,
+        "proof": [],
+    }))
+}
+
+#[tokio::test]
+async fn failed_batch_claim_can_be_retried() -> anyhow::Result<()> {
+    let bridge = funded_bridge(1_000).await?;
+    // Not registered with the token, so the transfer to it fails
+    let recipient = bridge.worker.dev_create_account().await?;
+    let message_id = [3; 32];
+    let args = single_transfer_batch(&bridge, message_id, chain::NEAR, &recipient, 400).await?;
+    let batch_root = args["batch_root"].clone();
+
+    let outcome = bridge.call(&recipient, "claim_from_batch", args.clone()).await?;
+    assert!(outcome.is_success(), "{outcome:?}");
+    assert!(!outcome.receipt_failures().is_empty());
+    match &events(&outcome)[..] {
+        [BridgeEvent::TokenUnlocked(_), BridgeEvent::UnlockFailed(event)] => {
+            assert_eq!(event.message_id, hex(&message_id));
+            assert_eq!(event.amount.0, 400);
+        }
+        events => panic!("unexpected events {events:?}"),
+    }
+    assert!(!outcome.json::<bool>()?);
+
+    assert!(!bridge.is_transfer_processed(chain::ETHEREUM, 3).await?);
+    assert_eq!(bridge.total_unlocked(bridge.token.id().as_str()).await?, 0);
+    let batch: BatchRoot =
+        bridge.view("get_batch_root", json!({ "batch_root": batch_root })).await?;
+    assert_eq!(batch.claimed_count, 0);
+
+    // The same proof goes through once the recipient can receive
+    bridge.register_with_token(&recipient).await?;
+    let outcome = bridge.call(&recipient, "claim_from_batch", args).await?;
+    assert!(outcome.receipt_failures().is_empty(), "{outcome:?}");
+    assert!(outcome.json::<bool>()?);
+    assert!(bridge.is_transfer_processed(chain::ETHEREUM, 3).await?);
+    assert_eq!(bridge.ft_balance(recipient.id()).await?, 400);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn batch_claim_rejects_other_destination() -> anyhow::Result<()> {
+    let bridge = funded_bridge(1_000).await?;
+    let recipient = bridge.user().await?;
+    let args = single_transfer_batch(&bridge, [4; 32], chain::SOLANA, &recipient, 400).await?;
+
+    // The proof is valid, but the message was batched for Solana
+    let outcome = bridge.call(&recipient, "claim_from_batch", args).await?;
+    assert_failure(outcome, "Message is not destined for this chain");
+    assert!(!bridge.is_transfer_processed(chain::ETHEREUM, 4).await?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn unlock_near_transfers_native_tokens() -> anyhow::Result<()> {
+    let bridge = Bridge::deploy().await?;
+    let locker = bridge.user().await?;
+    bridge
+        .call_with_deposit(
+            &locker,
+            "lock_near",
+            serde_json::json!({
+                "destination_chain": "solana",
+                "destination_address": "4Nd1mBQtrMJVYVfKf2PJy9NZUZdTAsp7D4xWLs4gDB4T",
+            }),
+            NearToken::from_near(5),
+        )
+        .await?
+        .into_result()?;
+
+    let recipient = bridge.worker.dev_create_account().await?;
+    let balance_before = recipient.view_account().await?.balance;
+    let unlock = Unlock::new(1, &recipient, NearToken::from_near(2).as_yoctonear());
+
+    // Submitted by a relayer so the recipient pays no gas
+    let outcome = bridge
+        .call(&locker, "unlock_near", bridge.unlock_near_args(&unlock, &[1, 2]))
+        .await?;
+    assert!(outcome.is_success(), "{outcome:?}");
+    match &events(&outcome)[..] {
+        [BridgeEvent::TokenUnlocked(event)] => {
+            assert_eq!(event.token.as_str(), NATIVE_TOKEN);
+            assert_eq!(event.amount.0, unlock.amount);
+        }
+        events => panic!("unexpected events {events:?}"),
+    }
+
+    let balance_after = recipient.view_account().await?.balance;
+    assert_eq!(balance_after.as_yoctonear() - balance_before.as_yoctonear(), unlock.amount);
+    assert_eq!(bridge.total_unlocked(NATIVE_TOKEN).await?, unlock.amount);
+
+    let outcome = bridge
+        .call(&locker, "unlock_near", bridge.unlock_near_args(&unlock, &[0, 1]))
+        .await?;
+    assert_failure(outcome, "Message already processed");
+
+    Ok(())
+}
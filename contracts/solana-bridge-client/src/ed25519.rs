@@ -0,0 +1,83 @@
+//! Ed25519 precompile instructions
+//!
+//! The bridge program does not verify signatures itself. It reads the
+//! Ed25519 precompile instruction placed immediately before it in the same
+//! transaction, which the runtime has already checked.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::instruction::Instruction;
+use crate::error::ClientError;
+
+const HEADER_LEN: usize = 2;
+const OFFSETS_LEN: usize = 14;
+const PUBLIC_KEY_LEN: usize = 32;
+const SIGNATURE_LEN: usize = 64;
+
+/// Instruction index referring to the precompile instruction itself
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+/// A validator's signature over an attestation hash
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ValidatorSignature {
+    pub public_key: Pubkey,
+    pub signature: [u8; 64],
+}
+
+/// Build an Ed25519 precompile instruction that verifies every signature
+/// over `message`.
+///
+/// All data lives in the instruction itself, as the bridge program requires.
+/// Public keys and signatures are laid out after the offsets table, followed
+/// by a single copy of the message shared by every entry.
+pub fn verify_instruction(
+    signatures: &[ValidatorSignature],
+    message: &[u8],
+) -> Result<Instruction, ClientError> {
+    if signatures.is_empty() {
+        return Err(ClientError::NoSignatures);
+    }
+    let count = u8::try_from(signatures.len())
+        .map_err(|_| ClientError::TooManySignatures(signatures.len()))?;
+
+    let entries_start = HEADER_LEN + OFFSETS_LEN * signatures.len();
+    let message_offset = entries_start + (PUBLIC_KEY_LEN + SIGNATURE_LEN) * signatures.len();
+    let message_offset = u16::try_from(message_offset)
+        .map_err(|_| ClientError::TooManySignatures(signatures.len()))?;
+    let message_size =
+        u16::try_from(message.len()).map_err(|_| ClientError::MessageTooLarge(message.len()))?;
+
+    let mut data = Vec::with_capacity(message_offset as usize + message.len());
+    data.push(count);
+    data.push(0);
+
+    for i in 0..signatures.len() {
+        // Both offsets fit since they are below `message_offset`
+        let public_key_offset = (entries_start + (PUBLIC_KEY_LEN + SIGNATURE_LEN) * i) as u16;
+        let signature_offset = public_key_offset + PUBLIC_KEY_LEN as u16;
+
+        for value in [
+            signature_offset,
+            CURRENT_INSTRUCTION,
+            public_key_offset,
+            CURRENT_INSTRUCTION,
+            message_offset,
+            message_size,
+            CURRENT_INSTRUCTION,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+
+    for entry in signatures {
+        data.extend_from_slice(entry.public_key.as_ref());
+        data.extend_from_slice(&entry.signature);
+    }
+    data.extend_from_slice(message);
+
+    Ok(Instruction {
+        program_id: ed25519_program::ID,
+        accounts: Vec::new(),
+        data,
+    })
+}
@@ -0,0 +1,22 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ClientError {
+    #[error("at least one signature is required")]
+    NoSignatures,
+
+    #[error("{0} signatures do not fit in a single Ed25519 instruction")]
+    TooManySignatures(usize),
+
+    #[error("message of {0} bytes is too large for the Ed25519 instruction")]
+    MessageTooLarge(usize),
+
+    #[error("invalid event encoding: {0}")]
+    InvalidEventEncoding(#[from] base64::DecodeError),
+
+    #[error("failed to deserialize {event}: {source}")]
+    InvalidEventData {
+        event: &'static str,
+        source: std::io::Error,
+    },
+}
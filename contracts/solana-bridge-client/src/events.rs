@@ -0,0 +1,134 @@
+//! Decoding of the events emitted by the bridge program
+//!
+//! `emit!` events are logged as `Program data: <base64>` lines. Admin and
+//! initialize events are emitted with `emit_cpi!` instead and arrive as the
+//! data of a self-invocation in the transaction's inner instructions.
+
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use solana_bridge::instructions::*;
+use solana_bridge::ID;
+use crate::error::ClientError;
+
+const PROGRAM_DATA: &str = "Program data: ";
+
+macro_rules! bridge_events {
+    ($($variant:ident($event:ident),)*) => {
+        /// Any event emitted by the bridge program
+        pub enum BridgeEvent {
+            $($variant($event),)*
+        }
+
+        impl BridgeEvent {
+            /// Decode discriminator-prefixed event data. Returns `None` for
+            /// data that does not belong to a known event.
+            pub fn decode(data: &[u8]) -> Result<Option<Self>, ClientError> {
+                if data.len() < 8 {
+                    return Ok(None);
+                }
+                let (discriminator, mut body) = data.split_at(8);
+                $(
+                    if discriminator == $event::discriminator() {
+                        return $event::deserialize(&mut body)
+                            .map(|event| Some(BridgeEvent::$variant(event)))
+                            .map_err(|source| ClientError::InvalidEventData {
+                                event: stringify!($event),
+                                source,
+                            });
+                    }
+                )*
+                Ok(None)
+            }
+
+            /// Name of the event struct, e.g. `TokenLockedEvent`
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(BridgeEvent::$variant(_) => stringify!($event),)*
+                }
+            }
+        }
+    };
+}
+
+bridge_events! {
+    BridgeInitialized(BridgeInitializedEvent),
+    TokenLocked(TokenLockedEvent),
+    LockCallAttached(LockCallAttachedEvent),
+    TokenUnlocked(TokenUnlockedEvent),
+    UnlockCallReceived(UnlockCallReceivedEvent),
+    UnlockCallExecuted(UnlockCallExecutedEvent),
+    UnlockCallFallback(UnlockCallFallbackEvent),
+    TokenRefunded(TokenRefundedEvent),
+    LockStatusChanged(LockStatusChangedEvent),
+    BatchRootSubmitted(BatchRootSubmittedEvent),
+    MessageSent(MessageSentEvent),
+    MessageReceived(MessageReceivedEvent),
+    MessageDelivered(MessageDeliveredEvent),
+    RecordClosed(RecordClosedEvent),
+    AccountMigrated(AccountMigratedEvent),
+    ValidatorAdded(ValidatorAddedEvent),
+    ValidatorRemoved(ValidatorRemovedEvent),
+    RequiredSignaturesUpdated(RequiredSignaturesUpdatedEvent),
+    SignatureModeUpdated(SignatureModeUpdatedEvent),
+    BridgePaused(BridgePausedEvent),
+    BridgeUnpaused(BridgeUnpausedEvent),
+    GroupKeyRotated(GroupKeyRotatedEvent),
+    UpgradeAuthorityHandover(UpgradeAuthorityHandoverEvent),
+}
+
+/// Decode the events the bridge program logged in a transaction.
+///
+/// Only `Program data:` lines written while the bridge program is the
+/// innermost executing program are considered, so data logged by other
+/// programs is never mistaken for a bridge event.
+pub fn parse_logs<S: AsRef<str>>(logs: &[S]) -> Result<Vec<BridgeEvent>, ClientError> {
+    let program_id = ID.to_string();
+    let mut stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+
+    for line in logs.iter().map(AsRef::as_ref) {
+        if let Some(data) = line.strip_prefix(PROGRAM_DATA) {
+            if stack.last() == Some(&program_id.as_str()) {
+                // One base64 field per logged slice; `emit!` logs a single slice
+                let field = data.split(' ').next().unwrap_or_default();
+                if let Some(event) = BridgeEvent::decode(&STANDARD.decode(field)?)? {
+                    events.push(event);
+                }
+            }
+        } else if let Some(rest) = line.strip_prefix("Program ") {
+            let mut words = rest.split(' ');
+            // `Program log:`, `Program return:` and similar lines carry no program ID
+            let (Some(program), Some(action)) = (words.next(), words.next()) else {
+                continue;
+            };
+            if program.ends_with(':') {
+                continue;
+            }
+            match action {
+                "invoke" => stack.push(program),
+                "success" | "failed:" => {
+                    stack.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(events)
+}
+
+/// Decode an event emitted with `emit_cpi!` from the data of an inner
+/// instruction. Returns `None` when the instruction is not a bridge
+/// self-invocation carrying an event.
+pub fn decode_cpi_event(program_id: &Pubkey, data: &[u8]) -> Result<Option<BridgeEvent>, ClientError> {
+    if program_id != &ID {
+        return Ok(None);
+    }
+    match data.strip_prefix(EVENT_IX_TAG_LE.as_slice()) {
+        Some(event) => BridgeEvent::decode(event),
+        None => Ok(None),
+    }
+}
@@ -0,0 +1,505 @@
+//! Typed builders for the bridge program instructions
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::{bpf_loader_upgradeable, keccak, system_program, sysvar};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::ID as ASSOCIATED_TOKEN_PROGRAM_ID;
+use anchor_spl::token::ID as TOKEN_PROGRAM_ID;
+use bridge_types::hash::Sha3Keccak;
+use bridge_types::{aggregate_signing_hash, chain, MessageId, TransferMessage, UnlockAttestation};
+use solana_bridge::state::{AggregateSignature, LockRecord, SignatureMode};
+use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use solana_bridge::{accounts, instruction, ID};
+use crate::ed25519::{verify_instruction, ValidatorSignature};
+use crate::error::ClientError;
+use crate::pda;
+
+/// `initialize`: create the bridge config with its validator set
+#[derive(Clone, Debug)]
+pub struct InitializeBridge {
+    pub admin: Pubkey,
+    pub validators: Vec<Pubkey>,
+    pub required_signatures: u8,
+}
+
+impl InitializeBridge {
+    pub fn instruction(&self) -> Instruction {
+        Instruction {
+            program_id: ID,
+            accounts: accounts::Initialize {
+                bridge_config: pda::bridge_config().0,
+                admin: self.admin,
+                system_program: system_program::ID,
+                event_authority: pda::event_authority().0,
+                program: ID,
+            }
+            .to_account_metas(None),
+            data: instruction::Initialize {
+                validators: self.validators.clone(),
+                required_signatures: self.required_signatures,
+            }
+            .data(),
+        }
+    }
+}
+
+/// Instructions signed by the bridge admin
+#[derive(Clone, Debug)]
+pub enum AdminInstruction {
+    AddValidator(Pubkey),
+    RemoveValidator(Pubkey),
+    UpdateRequiredSignatures(u8),
+    SetGroupKey {
+        group_key: Pubkey,
+        threshold: u8,
+        group_size: u8,
+    },
+    SetSignatureMode(SignatureMode),
+    Pause,
+    Unpause,
+    /// Move the program upgrade authority from the admin to `governance`
+    HandoverUpgradeAuthority { governance: Pubkey },
+}
+
+impl AdminInstruction {
+    pub fn instruction(&self, admin: Pubkey) -> Instruction {
+        let validators = || accounts::UpdateValidators {
+            bridge_config: pda::bridge_config().0,
+            admin,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        };
+        let config = || accounts::UpdateConfig {
+            bridge_config: pda::bridge_config().0,
+            admin,
+            event_authority: pda::event_authority().0,
+            program: ID,
+        };
+
+        let (accounts, data) = match *self {
+            AdminInstruction::AddValidator(validator) => (
+                validators().to_account_metas(None),
+                instruction::AddValidator { validator }.data(),
+            ),
+            AdminInstruction::RemoveValidator(validator) => (
+                validators().to_account_metas(None),
+                instruction::RemoveValidator { validator }.data(),
+            ),
+            AdminInstruction::UpdateRequiredSignatures(required_signatures) => (
+                config().to_account_metas(None),
+                instruction::UpdateRequiredSignatures { required_signatures }.data(),
+            ),
+            AdminInstruction::SetGroupKey {
+                group_key,
+                threshold,
+                group_size,
+            } => (
+                config().to_account_metas(None),
+                instruction::SetGroupKey {
+                    group_key,
+                    threshold,
+                    group_size,
+                }
+                .data(),
+            ),
+            AdminInstruction::SetSignatureMode(mode) => (
+                config().to_account_metas(None),
+                instruction::SetSignatureMode { mode }.data(),
+            ),
+            AdminInstruction::Pause => (config().to_account_metas(None), instruction::Pause.data()),
+            AdminInstruction::Unpause => (config().to_account_metas(None), instruction::Unpause.data()),
+            AdminInstruction::HandoverUpgradeAuthority { governance } => (
+                accounts::HandoverUpgradeAuthority {
+                    bridge_config: pda::bridge_config().0,
+                    admin,
+                    program_data: pda::program_data(),
+                    governance,
+                    bpf_loader_upgradeable: bpf_loader_upgradeable::ID,
+                    event_authority: pda::event_authority().0,
+                    program: ID,
+                }
+                .to_account_metas(None),
+                instruction::HandoverUpgradeAuthority.data(),
+            ),
+        };
+
+        Instruction {
+            program_id: ID,
+            accounts,
+            data,
+        }
+    }
+}
+
+/// Create the vault token account of `token_mint` if it does not exist yet.
+/// `unlock_token` releases from this account, so it must exist before the
+/// first inbound transfer of a token that was never locked on Solana.
+pub fn create_vault_token_account(payer: Pubkey, token_mint: Pubkey) -> Instruction {
+    create_associated_token_account_idempotent(
+        &payer,
+        &pda::bridge_config().0,
+        &token_mint,
+        &TOKEN_PROGRAM_ID,
+    )
+}
+
+/// `lock_token`: move `amount` of `token_mint` into the vault for delivery
+/// to `destination_address` on `destination_chain`
+#[derive(Clone, Debug)]
+pub struct LockToken {
+    pub sender: Pubkey,
+    pub sender_token_account: Pubkey,
+    pub token_mint: Pubkey,
+    pub amount: u64,
+    pub destination_chain: String,
+    pub destination_address: String,
+    /// Caller-chosen nonce, unique per sender
+    pub nonce: u64,
+}
+
+impl LockToken {
+    pub fn instruction(&self) -> Instruction {
+        Instruction {
+            program_id: ID,
+            accounts: accounts::LockToken {
+                bridge_config: pda::bridge_config().0,
+                token_vault: pda::token_vault(&self.token_mint).0,
+                lock_record: self.lock_record(),
+                sender: self.sender,
+                sender_token_account: self.sender_token_account,
+                vault_token_account: pda::vault_token_account(&self.token_mint),
+                token_mint: self.token_mint,
+                token_program: TOKEN_PROGRAM_ID,
+                associated_token_program: ASSOCIATED_TOKEN_PROGRAM_ID,
+                system_program: system_program::ID,
+                rent: sysvar::rent::ID,
+            }
+            .to_account_metas(None),
+            data: instruction::LockToken {
+                amount: self.amount,
+                destination_chain: self.destination_chain.clone(),
+                destination_address: self.destination_address.clone(),
+                nonce: self.nonce,
+            }
+            .data(),
+        }
+    }
+
+    /// Lock record the instruction creates
+    pub fn lock_record(&self) -> Pubkey {
+        pda::lock_record(&self.sender, self.nonce).0
+    }
+
+    /// Message ID the lock is assigned when the bridge config's
+    /// `message_count` equals `sequence` at execution time
+    pub fn message_id(&self, sequence: u64) -> MessageId {
+        TransferMessage {
+            source_chain: chain::SOLANA.to_string(),
+            source_bridge: ID.to_bytes().to_vec(),
+            sequence,
+            sender: self.sender.to_bytes().to_vec(),
+            token: self.token_mint.to_bytes().to_vec(),
+            amount: u128::from(self.amount),
+            destination_chain: self.destination_chain.clone(),
+            recipient: self.destination_address.clone(),
+        }
+        .id::<Sha3Keccak>()
+    }
+}
+
+/// `unlock_token`: release an inbound transfer attested by the validators
+#[derive(Clone, Debug)]
+pub struct UnlockToken {
+    pub payer: Pubkey,
+    pub recipient: Pubkey,
+    pub recipient_token_account: Pubkey,
+    pub token_mint: Pubkey,
+    pub message_id: [u8; 32],
+    pub source_chain: String,
+    /// Sequence of the transfer on the source bridge
+    pub sequence: u64,
+    /// Sender on the source chain, in that chain's native encoding
+    pub sender: String,
+    pub amount: u64,
+}
+
+impl UnlockToken {
+    /// Attestation hash validators sign for this unlock
+    pub fn message_hash(&self) -> [u8; 32] {
+        UnlockAttestation {
+            message_id: self.message_id,
+            source_chain: self.source_chain.clone(),
+            sequence: self.sequence,
+            sender: self.sender.clone(),
+            destination_chain: chain::SOLANA.to_string(),
+            destination_bridge: ID.to_bytes().to_vec(),
+            recipient: self.recipient.to_bytes().to_vec(),
+            token: self.token_mint.to_bytes().to_vec(),
+            amount: u128::from(self.amount),
+        }
+        .signing_hash::<Sha3Keccak>()
+    }
+
+    /// Ed25519 precompile instruction followed by `unlock_token`, in the
+    /// order they must appear in the transaction
+    pub fn instructions(
+        &self,
+        signatures: &[ValidatorSignature],
+    ) -> Result<Vec<Instruction>, ClientError> {
+        let unlock = Instruction {
+            program_id: ID,
+            accounts: self.accounts(),
+            data: instruction::UnlockToken {
+                message_id: self.message_id,
+                source_chain: self.source_chain.clone(),
+                sequence: self.sequence,
+                sender: self.sender.clone(),
+                amount: self.amount,
+                signatures: signatures.iter().map(|entry| entry.signature).collect(),
+            }
+            .data(),
+        };
+
+        Ok(vec![verify_instruction(signatures, &self.message_hash())?, unlock])
+    }
+
+    /// Ed25519 precompile instruction followed by `unlock_token_aggregate`,
+    /// for a threshold signature under `group_key`
+    pub fn aggregate_instructions(
+        &self,
+        group_key: Pubkey,
+        aggregate_signature: AggregateSignature,
+    ) -> Result<Vec<Instruction>, ClientError> {
+        let verify =
+            aggregate_verify_instruction(group_key, &aggregate_signature, &self.message_hash())?;
+        let unlock = Instruction {
+            program_id: ID,
+            accounts: self.accounts(),
+            data: instruction::UnlockTokenAggregate {
+                message_id: self.message_id,
+                source_chain: self.source_chain.clone(),
+                sequence: self.sequence,
+                sender: self.sender.clone(),
+                amount: self.amount,
+                aggregate_signature,
+            }
+            .data(),
+        };
+
+        Ok(vec![verify, unlock])
+    }
+
+    /// Message record the unlock creates
+    pub fn message_record(&self) -> Pubkey {
+        pda::message_record(&self.message_id).0
+    }
+
+    fn accounts(&self) -> Vec<AccountMeta> {
+        accounts::UnlockToken {
+            bridge_config: pda::bridge_config().0,
+            token_vault: pda::token_vault(&self.token_mint).0,
+            message_record: self.message_record(),
+            processed_sequences: pda::processed_sequences(&self.source_chain, self.sequence).0,
+            payer: self.payer,
+            recipient: self.recipient,
+            recipient_token_account: self.recipient_token_account,
+            vault_token_account: pda::vault_token_account(&self.token_mint),
+            token_mint: self.token_mint,
+            instructions_sysvar: sysvar::instructions::ID,
+            token_program: TOKEN_PROGRAM_ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None)
+    }
+}
+
+/// `refund`: return a pending lock to its sender after the validators attest
+/// that delivery failed
+#[derive(Clone, Debug)]
+pub struct Refund {
+    pub lock_record: Pubkey,
+    pub message_id: [u8; 32],
+    pub sender: Pubkey,
+    pub sender_token_account: Pubkey,
+    pub token_mint: Pubkey,
+    pub amount: u64,
+}
+
+impl Refund {
+    /// Refund of the lock stored at `lock_record` to `sender_token_account`
+    pub fn new(lock_record: Pubkey, record: &LockRecord, sender_token_account: Pubkey) -> Self {
+        Self {
+            lock_record,
+            message_id: record.message_id,
+            sender: record.sender,
+            sender_token_account,
+            token_mint: record.token_mint,
+            amount: record.amount,
+        }
+    }
+
+    /// Attestation hash validators sign for this refund
+    pub fn message_hash(&self) -> [u8; 32] {
+        lock_outcome_hash(
+            LockRecord::REFUND_DOMAIN,
+            &self.message_id,
+            &self.sender,
+            &self.token_mint,
+            self.amount,
+            &[],
+        )
+    }
+
+    /// Ed25519 precompile instruction followed by `refund`
+    pub fn instructions(
+        &self,
+        signatures: &[ValidatorSignature],
+    ) -> Result<Vec<Instruction>, ClientError> {
+        Ok(vec![verify_instruction(signatures, &self.message_hash())?, self.refund(None)])
+    }
+
+    /// Ed25519 precompile instruction followed by `refund`, for a threshold
+    /// signature under `group_key`
+    pub fn aggregate_instructions(
+        &self,
+        group_key: Pubkey,
+        aggregate_signature: AggregateSignature,
+    ) -> Result<Vec<Instruction>, ClientError> {
+        let verify =
+            aggregate_verify_instruction(group_key, &aggregate_signature, &self.message_hash())?;
+        Ok(vec![verify, self.refund(Some(aggregate_signature))])
+    }
+
+    fn refund(&self, aggregate_signature: Option<AggregateSignature>) -> Instruction {
+        Instruction {
+            program_id: ID,
+            accounts: accounts::Refund {
+                bridge_config: pda::bridge_config().0,
+                lock_record: self.lock_record,
+                token_vault: pda::token_vault(&self.token_mint).0,
+                vault_token_account: pda::vault_token_account(&self.token_mint),
+                sender_token_account: self.sender_token_account,
+                token_mint: self.token_mint,
+                instructions_sysvar: sysvar::instructions::ID,
+                token_program: TOKEN_PROGRAM_ID,
+            }
+            .to_account_metas(None),
+            data: instruction::Refund { aggregate_signature }.data(),
+        }
+    }
+}
+
+/// `acknowledge_delivery`: mark a pending lock as delivered by
+/// `destination_tx` on the destination chain
+#[derive(Clone, Debug)]
+pub struct AcknowledgeDelivery {
+    pub lock_record: Pubkey,
+    pub message_id: [u8; 32],
+    pub sender: Pubkey,
+    pub token_mint: Pubkey,
+    pub amount: u64,
+    pub destination_tx: String,
+}
+
+impl AcknowledgeDelivery {
+    /// Acknowledgement of the lock stored at `lock_record`
+    pub fn new(lock_record: Pubkey, record: &LockRecord, destination_tx: String) -> Self {
+        Self {
+            lock_record,
+            message_id: record.message_id,
+            sender: record.sender,
+            token_mint: record.token_mint,
+            amount: record.amount,
+            destination_tx,
+        }
+    }
+
+    /// Attestation hash validators sign for this acknowledgement
+    pub fn message_hash(&self) -> [u8; 32] {
+        lock_outcome_hash(
+            LockRecord::DELIVERED_DOMAIN,
+            &self.message_id,
+            &self.sender,
+            &self.token_mint,
+            self.amount,
+            self.destination_tx.as_bytes(),
+        )
+    }
+
+    /// Ed25519 precompile instruction followed by `acknowledge_delivery`
+    pub fn instructions(
+        &self,
+        signatures: &[ValidatorSignature],
+    ) -> Result<Vec<Instruction>, ClientError> {
+        Ok(vec![verify_instruction(signatures, &self.message_hash())?, self.acknowledge(None)])
+    }
+
+    /// Ed25519 precompile instruction followed by `acknowledge_delivery`, for
+    /// a threshold signature under `group_key`
+    pub fn aggregate_instructions(
+        &self,
+        group_key: Pubkey,
+        aggregate_signature: AggregateSignature,
+    ) -> Result<Vec<Instruction>, ClientError> {
+        let verify =
+            aggregate_verify_instruction(group_key, &aggregate_signature, &self.message_hash())?;
+        Ok(vec![verify, self.acknowledge(Some(aggregate_signature))])
+    }
+
+    fn acknowledge(&self, aggregate_signature: Option<AggregateSignature>) -> Instruction {
+        Instruction {
+            program_id: ID,
+            accounts: accounts::AcknowledgeDelivery {
+                bridge_config: pda::bridge_config().0,
+                lock_record: self.lock_record,
+                instructions_sysvar: sysvar::instructions::ID,
+            }
+            .to_account_metas(None),
+            data: instruction::AcknowledgeDelivery {
+                destination_tx: self.destination_tx.clone(),
+                aggregate_signature,
+            }
+            .data(),
+        }
+    }
+}
+
+/// Ed25519 precompile instruction for a threshold signature under
+/// `group_key`, which the group makes over the attestation hash together
+/// with the signer bitmap
+fn aggregate_verify_instruction(
+    group_key: Pubkey,
+    aggregate_signature: &AggregateSignature,
+    message_hash: &[u8; 32],
+) -> Result<Instruction, ClientError> {
+    verify_instruction(
+        &[ValidatorSignature {
+            public_key: group_key,
+            signature: aggregate_signature.signature,
+        }],
+        &aggregate_signing_hash::<Sha3Keccak>(message_hash, aggregate_signature.signer_bitmap),
+    )
+}
+
+/// Hash validators sign to attest the outcome of a lock on the destination
+/// chain. Mirrors the program's `create_lock_outcome_hash`.
+fn lock_outcome_hash(
+    domain: &[u8],
+    message_id: &[u8; 32],
+    sender: &Pubkey,
+    token_mint: &Pubkey,
+    amount: u64,
+    extra: &[u8],
+) -> [u8; 32] {
+    let mut data = Vec::new();
+    data.extend_from_slice(domain);
+    data.extend_from_slice(pda::bridge_config().0.as_ref());
+    data.extend_from_slice(message_id);
+    data.extend_from_slice(sender.as_ref());
+    data.extend_from_slice(token_mint.as_ref());
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.extend_from_slice(extra);
+
+    keccak::hash(&data).to_bytes()
+}
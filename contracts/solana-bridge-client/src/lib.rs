@@ -0,0 +1,19 @@
+//! Off-chain client for the Solana bridge program
+//!
+//! Derives the program's PDAs, builds `lock_token` and `unlock_token`
+//! instructions together with the Ed25519 precompile instruction unlocks are
+//! verified against, and decodes the events the program emits.
+
+pub mod ed25519;
+pub mod error;
+pub mod events;
+pub mod instruction;
+pub mod pda;
+
+pub use ed25519::ValidatorSignature;
+pub use error::ClientError;
+pub use events::BridgeEvent;
+pub use instruction::{
+    AcknowledgeDelivery, AdminInstruction, InitializeBridge, LockToken, Refund, UnlockToken,
+};
+pub use solana_bridge::ID as PROGRAM_ID;
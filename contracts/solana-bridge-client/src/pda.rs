@@ -0,0 +1,62 @@
+//! Addresses of the accounts owned or controlled by the bridge program
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use anchor_spl::associated_token::get_associated_token_address;
+use solana_bridge::state::ProcessedSequences;
+use solana_bridge::ID;
+
+pub const BRIDGE_CONFIG_SEED: &[u8] = b"bridge_config";
+pub const TOKEN_VAULT_SEED: &[u8] = b"token_vault";
+pub const LOCK_RECORD_SEED: &[u8] = b"lock_record";
+pub const MESSAGE_RECORD_SEED: &[u8] = b"message_record";
+pub const PROCESSED_SEQUENCES_SEED: &[u8] = b"processed_sequences";
+pub const EVENT_AUTHORITY_SEED: &[u8] = b"__event_authority";
+
+/// Bridge config, also the authority of every vault token account
+pub fn bridge_config() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[BRIDGE_CONFIG_SEED], &ID)
+}
+
+/// Per-mint vault statistics
+pub fn token_vault(token_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TOKEN_VAULT_SEED, token_mint.as_ref()], &ID)
+}
+
+/// Record of the lock `sender` made with `nonce`
+pub fn lock_record(sender: &Pubkey, nonce: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[LOCK_RECORD_SEED, sender.as_ref(), &nonce.to_le_bytes()], &ID)
+}
+
+/// Record of a processed inbound transfer
+pub fn message_record(message_id: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MESSAGE_RECORD_SEED, message_id.as_ref()], &ID)
+}
+
+/// Bitmap window recording whether the transfer at `sequence` from
+/// `source_chain` was released
+pub fn processed_sequences(source_chain: &str, sequence: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            PROCESSED_SEQUENCES_SEED,
+            &ProcessedSequences::chain_seed(source_chain),
+            &ProcessedSequences::window_of(sequence).to_le_bytes(),
+        ],
+        &ID,
+    )
+}
+
+/// Signer of the self-CPI events emitted by the admin instructions
+pub fn event_authority() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[EVENT_AUTHORITY_SEED], &ID)
+}
+
+/// Program data account of the upgradeable bridge program
+pub fn program_data() -> Pubkey {
+    Pubkey::find_program_address(&[ID.as_ref()], &bpf_loader_upgradeable::ID).0
+}
+
+/// Associated token account holding the locked tokens of `token_mint`
+pub fn vault_token_account(token_mint: &Pubkey) -> Pubkey {
+    get_associated_token_address(&bridge_config().0, token_mint)
+}
@@ -0,0 +1,495 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::{AccountDeserialize, Event};
+use anchor_spl::token::spl_token;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use solana_bridge::error::BridgeError;
+use solana_bridge::instructions::{BridgeInitializedEvent, TokenLockedEvent};
+use solana_bridge::state::{BridgeConfig, LockRecord, LockStatus, MessageRecord, TokenVault};
+use solana_bridge_client::events::{decode_cpi_event, parse_logs};
+use solana_bridge_client::instruction::create_vault_token_account;
+use solana_bridge_client::{
+    pda, AdminInstruction, BridgeEvent, InitializeBridge, LockToken, UnlockToken, ValidatorSignature,
+};
+use solana_program_test::{processor, ProgramTest, ProgramTestContext};
+use solana_sdk::instruction::{Instruction, InstructionError};
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::{Transaction, TransactionError};
+use spl_associated_token_account::get_associated_token_address;
+use spl_associated_token_account::instruction::create_associated_token_account;
+
+fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    // The Anchor entrypoint wants accounts that outlive the call
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    solana_bridge::entry(program_id, accounts, data)
+}
+
+/// Running bridge with two of three validators required and a funded sender
+struct Bridge {
+    context: ProgramTestContext,
+    validators: Vec<Keypair>,
+    mint: Pubkey,
+    sender: Keypair,
+    sender_token_account: Pubkey,
+}
+
+impl Bridge {
+    async fn start() -> Self {
+        let test = ProgramTest::new("solana_bridge", solana_bridge::ID, processor!(process_instruction));
+        let mut context = test.start_with_context().await;
+        let payer = context.payer.insecure_clone();
+
+        let validators: Vec<Keypair> = (0..3).map(|_| Keypair::new()).collect();
+        let initialize = InitializeBridge {
+            admin: payer.pubkey(),
+            validators: validators.iter().map(Keypair::pubkey).collect(),
+            required_signatures: 2,
+        };
+        send(&mut context, &[initialize.instruction()], &[&payer]).await.unwrap();
+
+        let mint = Keypair::new();
+        let rent = Rent::default().minimum_balance(spl_token::state::Mint::LEN);
+        send(
+            &mut context,
+            &[
+                system_instruction::create_account(
+                    &payer.pubkey(),
+                    &mint.pubkey(),
+                    rent,
+                    spl_token::state::Mint::LEN as u64,
+                    &spl_token::ID,
+                ),
+                spl_token::instruction::initialize_mint(&spl_token::ID, &mint.pubkey(), &payer.pubkey(), None, 6)
+                    .unwrap(),
+            ],
+            &[&payer, &mint],
+        )
+        .await
+        .unwrap();
+
+        let sender = Keypair::new();
+        let mut bridge = Bridge {
+            context,
+            validators,
+            mint: mint.pubkey(),
+            sender_token_account: Pubkey::default(),
+            sender,
+        };
+        bridge.sender_token_account = bridge.token_account(&bridge.sender.pubkey()).await;
+        bridge.mint_to(bridge.sender_token_account, 1_000_000).await;
+        bridge.fund(&bridge.sender.pubkey()).await;
+        bridge
+    }
+
+    fn payer(&self) -> Keypair {
+        self.context.payer.insecure_clone()
+    }
+
+    async fn fund(&mut self, account: &Pubkey) {
+        let payer = self.payer();
+        let transfer = system_instruction::transfer(&payer.pubkey(), account, 1_000_000_000);
+        send(&mut self.context, &[transfer], &[&payer]).await.unwrap();
+    }
+
+    async fn token_account(&mut self, owner: &Pubkey) -> Pubkey {
+        let payer = self.payer();
+        let create = create_associated_token_account(&payer.pubkey(), owner, &self.mint, &spl_token::ID);
+        send(&mut self.context, &[create], &[&payer]).await.unwrap();
+        get_associated_token_address(owner, &self.mint)
+    }
+
+    async fn mint_to(&mut self, account: Pubkey, amount: u64) {
+        let payer = self.payer();
+        let mint_to =
+            spl_token::instruction::mint_to(&spl_token::ID, &self.mint, &account, &payer.pubkey(), &[], amount)
+                .unwrap();
+        send(&mut self.context, &[mint_to], &[&payer]).await.unwrap();
+    }
+
+    async fn balance(&mut self, account: Pubkey) -> u64 {
+        let account = self.context.banks_client.get_account(account).await.unwrap().unwrap();
+        spl_token::state::Account::unpack(&account.data).unwrap().amount
+    }
+
+    async fn fetch<T: AccountDeserialize>(&mut self, address: Pubkey) -> T {
+        let account = self.context.banks_client.get_account(address).await.unwrap().unwrap();
+        T::try_deserialize(&mut &account.data[..]).unwrap()
+    }
+
+    fn lock(&self, amount: u64, nonce: u64) -> LockToken {
+        LockToken {
+            sender: self.sender.pubkey(),
+            sender_token_account: self.sender_token_account,
+            token_mint: self.mint,
+            amount,
+            destination_chain: "near".to_string(),
+            destination_address: "alice.near".to_string(),
+            nonce,
+        }
+    }
+
+    /// Lock `amount` and return the decoded events
+    async fn lock_tokens(&mut self, lock: &LockToken) -> Vec<BridgeEvent> {
+        let sender = self.sender.insecure_clone();
+        let payer = self.payer();
+        let blockhash = self.context.banks_client.get_latest_blockhash().await.unwrap();
+        let transaction = Transaction::new_signed_with_payer(
+            &[lock.instruction()],
+            Some(&payer.pubkey()),
+            &[&payer, &sender],
+            blockhash,
+        );
+        let outcome = self
+            .context
+            .banks_client
+            .process_transaction_with_metadata(transaction)
+            .await
+            .unwrap();
+        outcome.result.unwrap();
+        parse_logs(&outcome.metadata.unwrap().log_messages).unwrap()
+    }
+
+    fn sign(&self, unlock: &UnlockToken, signers: &[&Keypair]) -> Vec<ValidatorSignature> {
+        let hash = unlock.message_hash();
+        signers
+            .iter()
+            .map(|signer| ValidatorSignature {
+                public_key: signer.pubkey(),
+                signature: signer.sign_message(&hash).into(),
+            })
+            .collect()
+    }
+
+    async fn unlock(
+        &mut self,
+        unlock: &UnlockToken,
+        signatures: &[ValidatorSignature],
+    ) -> std::result::Result<(), TransactionError> {
+        let payer = self.payer();
+        let instructions = unlock.instructions(signatures).unwrap();
+        send(&mut self.context, &instructions, &[&payer])
+            .await
+            .map_err(|err| err.unwrap())
+    }
+}
+
+async fn send(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> std::result::Result<(), solana_program_test::BanksClientError> {
+    let blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    let transaction =
+        Transaction::new_signed_with_payer(instructions, Some(&context.payer.pubkey()), signers, blockhash);
+    context.banks_client.process_transaction(transaction).await
+}
+
+fn bridge_error(index: u8, error: BridgeError) -> TransactionError {
+    TransactionError::InstructionError(index, InstructionError::Custom(error.into()))
+}
+
+/// Check the events of a lock transaction. Natively run programs print event
+/// data to stdout instead of the transaction log, so events only show up when
+/// the tests run against the SBF build (`cargo test-sbf`).
+fn assert_locked(events: &[BridgeEvent], lock: &LockToken, sequence: u64) {
+    for event in events {
+        let BridgeEvent::TokenLocked(event) = event else {
+            panic!("expected TokenLockedEvent, got {}", event.name());
+        };
+        assert_eq!(event.message_id, lock.message_id(sequence));
+        assert_eq!(event.sender, lock.sender);
+        assert_eq!(event.token_mint, lock.token_mint);
+        assert_eq!(event.amount, lock.amount);
+        assert_eq!(event.destination_chain, lock.destination_chain);
+        assert_eq!(event.destination_address, lock.destination_address);
+        assert_eq!(event.nonce, lock.nonce);
+        assert_eq!(event.sequence, sequence);
+    }
+}
+
+fn inbound(bridge: &Bridge, recipient: Pubkey, recipient_token_account: Pubkey, amount: u64) -> UnlockToken {
+    UnlockToken {
+        payer: bridge.context.payer.pubkey(),
+        recipient,
+        recipient_token_account,
+        token_mint: bridge.mint,
+        message_id: [7u8; 32],
+        source_chain: "near".to_string(),
+        sequence: 7,
+        sender: "alice.near".to_string(),
+        amount,
+    }
+}
+
+#[tokio::test]
+async fn initialize_creates_config_at_pda() {
+    let mut bridge = Bridge::start().await;
+    let payer = bridge.payer().pubkey();
+
+    let config: BridgeConfig = bridge.fetch(pda::bridge_config().0).await;
+    assert_eq!(config.admin, payer);
+    assert_eq!(config.required_signatures, 2);
+    assert_eq!(
+        config.validators,
+        bridge.validators.iter().map(Keypair::pubkey).collect::<Vec<_>>()
+    );
+    assert_eq!(config.bump, pda::bridge_config().1);
+}
+
+#[tokio::test]
+async fn admin_instructions_update_config() {
+    let mut bridge = Bridge::start().await;
+    let payer = bridge.payer();
+    let validator = Keypair::new().pubkey();
+
+    let instructions: Vec<Instruction> = [
+        AdminInstruction::AddValidator(validator),
+        AdminInstruction::UpdateRequiredSignatures(3),
+        AdminInstruction::Pause,
+    ]
+    .iter()
+    .map(|admin| admin.instruction(payer.pubkey()))
+    .collect();
+    send(&mut bridge.context, &instructions, &[&payer]).await.unwrap();
+
+    let config: BridgeConfig = bridge.fetch(pda::bridge_config().0).await;
+    assert_eq!(config.validators.len(), 4);
+    assert!(config.is_validator(&validator));
+    assert_eq!(config.required_signatures, 3);
+    assert!(config.is_paused);
+
+    // Only the admin may sign
+    let outsider = Keypair::new();
+    bridge.fund(&outsider.pubkey()).await;
+    let unpause = AdminInstruction::Unpause.instruction(outsider.pubkey());
+    assert_eq!(
+        send(&mut bridge.context, &[unpause], &[&payer, &outsider])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        bridge_error(0, BridgeError::UnauthorizedValidator)
+    );
+
+    let unpause = AdminInstruction::Unpause.instruction(payer.pubkey());
+    send(&mut bridge.context, &[unpause], &[&payer]).await.unwrap();
+    let config: BridgeConfig = bridge.fetch(pda::bridge_config().0).await;
+    assert!(!config.is_paused);
+}
+
+#[tokio::test]
+async fn create_vault_token_account_is_idempotent() {
+    let mut bridge = Bridge::start().await;
+    let payer = bridge.payer();
+    let create = create_vault_token_account(payer.pubkey(), bridge.mint);
+
+    send(&mut bridge.context, std::slice::from_ref(&create), &[&payer]).await.unwrap();
+    assert_eq!(bridge.balance(pda::vault_token_account(&bridge.mint)).await, 0);
+
+    // Locking into an existing vault account, and creating it again, both succeed
+    bridge.lock_tokens(&bridge.lock(1_000, 1)).await;
+    bridge.context.warp_to_slot(2).unwrap();
+    send(&mut bridge.context, &[create], &[&payer]).await.unwrap();
+    assert_eq!(bridge.balance(pda::vault_token_account(&bridge.mint)).await, 1_000);
+}
+
+#[tokio::test]
+async fn lock_token_derives_accounts_and_decodes_event() {
+    let mut bridge = Bridge::start().await;
+    let lock = bridge.lock(250_000, 1);
+
+    let events = bridge.lock_tokens(&lock).await;
+    assert_locked(&events, &lock, 0);
+
+    let record: LockRecord = bridge.fetch(lock.lock_record()).await;
+    assert_eq!(record.message_id, lock.message_id(0));
+    assert_eq!(record.status, LockStatus::Pending);
+    assert_eq!(record.bump, pda::lock_record(&bridge.sender.pubkey(), 1).1);
+
+    let vault: TokenVault = bridge.fetch(pda::token_vault(&bridge.mint).0).await;
+    assert_eq!(vault.total_locked, 250_000);
+    assert_eq!(bridge.balance(pda::vault_token_account(&bridge.mint)).await, 250_000);
+    assert_eq!(bridge.balance(bridge.sender_token_account).await, 750_000);
+
+    // The next lock is assigned the next sequence
+    let lock = bridge.lock(1_000, 2);
+    let events = bridge.lock_tokens(&lock).await;
+    assert_locked(&events, &lock, 1);
+    let record: LockRecord = bridge.fetch(lock.lock_record()).await;
+    assert_eq!(record.message_id, lock.message_id(1));
+    assert_eq!(record.sequence, 1);
+}
+
+#[tokio::test]
+async fn unlock_token_with_validator_signatures() {
+    let mut bridge = Bridge::start().await;
+    bridge.lock_tokens(&bridge.lock(500_000, 1)).await;
+
+    let recipient = Keypair::new().pubkey();
+    let recipient_token_account = bridge.token_account(&recipient).await;
+    let unlock = inbound(&bridge, recipient, recipient_token_account, 200_000);
+    let signatures = bridge.sign(&unlock, &[&bridge.validators[0], &bridge.validators[2]]);
+
+    bridge.unlock(&unlock, &signatures).await.unwrap();
+
+    assert_eq!(bridge.balance(recipient_token_account).await, 200_000);
+    let record: MessageRecord = bridge.fetch(unlock.message_record()).await;
+    assert_eq!(record.message_id, unlock.message_id);
+    assert_eq!(record.source_chain, "near");
+    assert_eq!(record.sender, "alice.near");
+    assert_eq!(record.recipient, recipient);
+    assert!(record.processed);
+
+    // Replaying the same attestation is rejected
+    bridge.context.warp_to_slot(2).unwrap();
+    assert!(bridge.unlock(&unlock, &signatures).await.is_err());
+    assert_eq!(bridge.balance(recipient_token_account).await, 200_000);
+}
+
+#[tokio::test]
+async fn unlock_token_rejects_insufficient_signatures() {
+    let mut bridge = Bridge::start().await;
+    bridge.lock_tokens(&bridge.lock(500_000, 1)).await;
+
+    let recipient = Keypair::new().pubkey();
+    let recipient_token_account = bridge.token_account(&recipient).await;
+    let unlock = inbound(&bridge, recipient, recipient_token_account, 200_000);
+
+    // Below the threshold
+    let signatures = bridge.sign(&unlock, &[&bridge.validators[0]]);
+    assert_eq!(
+        bridge.unlock(&unlock, &signatures).await.unwrap_err(),
+        bridge_error(1, BridgeError::InsufficientSignatures)
+    );
+
+    // The same validator twice
+    let signatures = bridge.sign(&unlock, &[&bridge.validators[0], &bridge.validators[0]]);
+    assert_eq!(
+        bridge.unlock(&unlock, &signatures).await.unwrap_err(),
+        bridge_error(1, BridgeError::InsufficientSignatures)
+    );
+
+    // A valid signature from outside the validator set
+    let outsider = Keypair::new();
+    let signatures = bridge.sign(&unlock, &[&bridge.validators[0], &outsider]);
+    assert_eq!(
+        bridge.unlock(&unlock, &signatures).await.unwrap_err(),
+        bridge_error(1, BridgeError::InsufficientSignatures)
+    );
+
+    assert_eq!(bridge.balance(recipient_token_account).await, 0);
+}
+
+#[tokio::test]
+async fn unlock_token_rejects_signatures_over_other_terms() {
+    let mut bridge = Bridge::start().await;
+    bridge.lock_tokens(&bridge.lock(500_000, 1)).await;
+
+    let recipient = Keypair::new().pubkey();
+    let recipient_token_account = bridge.token_account(&recipient).await;
+    let signed = inbound(&bridge, recipient, recipient_token_account, 200_000);
+    let signatures = bridge.sign(&signed, &[&bridge.validators[0], &bridge.validators[1]]);
+
+    // Verified by the precompile, but over the hash of a smaller amount
+    let inflated = UnlockToken { amount: 400_000, ..signed.clone() };
+    let verify = solana_bridge_client::ed25519::verify_instruction(&signatures, &signed.message_hash()).unwrap();
+    let unlock = inflated.instructions(&signatures).unwrap().remove(1);
+    let payer = bridge.payer();
+    let err = send(&mut bridge.context, &[verify, unlock], &[&payer]).await.unwrap_err().unwrap();
+    assert_eq!(err, bridge_error(1, BridgeError::InvalidSignature));
+
+    // A forged signature is rejected by the precompile before the program runs
+    let mut forged = signatures.clone();
+    forged[1].signature[0] ^= 1;
+    assert!(bridge.unlock(&signed, &forged).await.is_err());
+
+    assert_eq!(bridge.balance(recipient_token_account).await, 0);
+}
+
+#[test]
+fn decode_cpi_event_from_inner_instruction() {
+    let admin = Pubkey::new_unique();
+    let event = BridgeInitializedEvent {
+        admin,
+        validators: vec![Pubkey::new_unique()],
+        required_signatures: 1,
+    };
+    let mut data = anchor_lang::event::EVENT_IX_TAG_LE.to_vec();
+    data.extend_from_slice(&event.data());
+
+    let Some(BridgeEvent::BridgeInitialized(decoded)) = decode_cpi_event(&solana_bridge::ID, &data).unwrap()
+    else {
+        panic!("expected BridgeInitializedEvent");
+    };
+    assert_eq!(decoded.admin, admin);
+    assert_eq!(decoded.required_signatures, 1);
+
+    assert!(decode_cpi_event(&Pubkey::new_unique(), &data).unwrap().is_none());
+    assert!(decode_cpi_event(&solana_bridge::ID, &event.data()).unwrap().is_none());
+}
+
+#[test]
+fn parse_logs_ignores_data_from_other_programs() {
+    let other = Pubkey::new_unique();
+    let event = BridgeInitializedEvent {
+        admin: Pubkey::new_unique(),
+        validators: Vec::new(),
+        required_signatures: 1,
+    };
+    let data = STANDARD.encode(event.data());
+    let logs = vec![
+        format!("Program {} invoke [1]", solana_bridge::ID),
+        "Program log: Instruction: Initialize".to_string(),
+        format!("Program {} invoke [2]", other),
+        format!("Program data: {}", data),
+        format!("Program {} success", other),
+        format!("Program data: {}", data),
+        format!("Program {} success", solana_bridge::ID),
+        format!("Program data: {}", data),
+    ];
+
+    let events = parse_logs(&logs).unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].name(), "BridgeInitializedEvent");
+}
+
+#[test]
+fn parse_logs_decodes_token_locked() {
+    let lock = LockToken {
+        sender: Pubkey::new_unique(),
+        sender_token_account: Pubkey::new_unique(),
+        token_mint: Pubkey::new_unique(),
+        amount: 42,
+        destination_chain: "ethereum".to_string(),
+        destination_address: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
+        nonce: 9,
+    };
+    let event = TokenLockedEvent {
+        message_id: lock.message_id(3),
+        sender: lock.sender,
+        token_mint: lock.token_mint,
+        amount: lock.amount,
+        destination_chain: lock.destination_chain.clone(),
+        destination_address: lock.destination_address.clone(),
+        nonce: lock.nonce,
+        sequence: 3,
+        timestamp: 1_700_000_000,
+    };
+    let logs = vec![
+        format!("Program {} invoke [1]", solana_bridge::ID),
+        "Program log: Instruction: LockToken".to_string(),
+        format!("Program data: {}", STANDARD.encode(event.data())),
+        format!("Program {} consumed 41234 of 200000 compute units", solana_bridge::ID),
+        format!("Program {} success", solana_bridge::ID),
+    ];
+
+    let events = parse_logs(&logs).unwrap();
+    assert_eq!(events.len(), 1);
+    assert_locked(&events, &lock, 3);
+}
@@ -0,0 +1,29 @@
+//! Any bytes can be passed where a bridge account is expected; decoding them
+//! fails instead of panicking, with or without a valid discriminator
+
+#![no_main]
+
+use anchor_lang::{AccountDeserialize, AnchorDeserialize, Discriminator};
+use libfuzzer_sys::fuzz_target;
+use solana_bridge::state::*;
+
+fn decode<T: AccountDeserialize + Discriminator>(data: &[u8]) {
+    let _ = T::try_deserialize(&mut &data[..]);
+    let prefixed = [&T::discriminator()[..], data].concat();
+    let _ = T::try_deserialize(&mut &prefixed[..]);
+}
+
+fuzz_target!(|data: &[u8]| {
+    decode::<BridgeConfig>(data);
+    decode::<TokenVault>(data);
+    decode::<MessageRecord>(data);
+    decode::<ProcessedSequences>(data);
+    decode::<LockRecord>(data);
+    decode::<BatchRoot>(data);
+    decode::<InboundMessage>(data);
+    decode::<PendingCall>(data);
+
+    // Pre-versioning layouts read by the migrations
+    let _ = BridgeConfigV0::deserialize(&mut &data[..]);
+    let _ = LockRecordV0::deserialize(&mut &data[..]);
+});
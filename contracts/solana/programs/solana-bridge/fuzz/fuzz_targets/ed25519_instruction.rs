@@ -0,0 +1,15 @@
+//! Ed25519 precompile instructions are built by the transaction sender.
+//! Parsing one never panics, and parsed messages lie within the data.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use solana_bridge::ed25519::parse_ed25519_instruction;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(signatures) = parse_ed25519_instruction(data) {
+        for signature in signatures {
+            assert!(signature.message.len() <= data.len());
+        }
+    }
+});
@@ -0,0 +1,31 @@
+//! Instruction arguments are chosen by the caller; decoding them fails
+//! instead of panicking
+
+#![no_main]
+
+use anchor_lang::AnchorDeserialize;
+use libfuzzer_sys::fuzz_target;
+use solana_bridge::instruction::*;
+
+fn decode<T: AnchorDeserialize>(data: &[u8]) {
+    let _ = T::try_from_slice(data);
+}
+
+fuzz_target!(|data: &[u8]| {
+    decode::<Initialize>(data);
+    decode::<LockToken>(data);
+    decode::<LockTokenAndCall>(data);
+    decode::<UnlockToken>(data);
+    decode::<UnlockTokenAggregate>(data);
+    decode::<SubmitBatchRoot>(data);
+    decode::<ClaimFromBatch>(data);
+    decode::<UnlockTokenAndCall>(data);
+    decode::<AcknowledgeDelivery>(data);
+    decode::<SendMessage>(data);
+    decode::<ReceiveMessage>(data);
+    decode::<AddValidator>(data);
+    decode::<RemoveValidator>(data);
+    decode::<UpdateRequiredSignatures>(data);
+    decode::<SetGroupKey>(data);
+    decode::<SetSignatureMode>(data);
+});
@@ -0,0 +1,225 @@
+use anchor_lang::prelude::*;
+use bridge_types::aggregate_signing_hash;
+use bridge_types::hash::SolanaKeccak;
+use solana_program::ed25519_program;
+use solana_program::sysvar::instructions as sysvar_instructions;
+use crate::state::*;
+use crate::error::*;
+
+/// A signature checked by the Ed25519 precompile
+pub struct VerifiedSignature {
+    pub public_key: Pubkey,
+    pub signature: [u8; 64],
+    pub message: Vec<u8>,
+}
+
+/// Load the signatures checked by the Ed25519 precompile instruction that
+/// immediately precedes the current instruction. The runtime rejects the
+/// whole transaction if any of them is invalid, so every returned entry is
+/// a verified signature.
+pub fn load_verified_signatures(instructions_sysvar: &AccountInfo) -> Result<Vec<VerifiedSignature>> {
+    let current_index = sysvar_instructions::load_current_index_checked(instructions_sysvar)?;
+    require!(current_index > 0, BridgeError::MissingEd25519Instruction);
+
+    let ix = sysvar_instructions::load_instruction_at_checked(
+        (current_index - 1) as usize,
+        instructions_sysvar,
+    )?;
+    require!(
+        ix.program_id == ed25519_program::ID && ix.accounts.is_empty(),
+        BridgeError::MissingEd25519Instruction
+    );
+
+    parse_ed25519_instruction(&ix.data)
+}
+
+/// Signatures listed in the data of an Ed25519 precompile instruction. Only
+/// instructions holding all their data inline are accepted.
+pub fn parse_ed25519_instruction(data: &[u8]) -> Result<Vec<VerifiedSignature>> {
+    // Layout: num_signatures (1), padding (1), then per signature 7 little-endian u16 offsets
+    require!(data.len() >= 2, BridgeError::MissingEd25519Instruction);
+    let count = data[0] as usize;
+    require!(
+        count > 0 && data.len() >= 2 + count * 14,
+        BridgeError::MissingEd25519Instruction
+    );
+
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    let slice = |offset: usize, len: usize| data.get(offset..offset.saturating_add(len));
+
+    let mut verified = Vec::with_capacity(count);
+    for i in 0..count {
+        let base = 2 + i * 14;
+        let signature_offset = read_u16(base) as usize;
+        let signature_ix_index = read_u16(base + 2);
+        let public_key_offset = read_u16(base + 4) as usize;
+        let public_key_ix_index = read_u16(base + 6);
+        let message_offset = read_u16(base + 8) as usize;
+        let message_size = read_u16(base + 10) as usize;
+        let message_ix_index = read_u16(base + 12);
+
+        // All data must live in the precompile instruction itself
+        require!(
+            signature_ix_index == u16::MAX
+                && public_key_ix_index == u16::MAX
+                && message_ix_index == u16::MAX,
+            BridgeError::MissingEd25519Instruction
+        );
+
+        let public_key = slice(public_key_offset, 32)
+            .ok_or(BridgeError::MissingEd25519Instruction)?;
+        let signature = slice(signature_offset, 64)
+            .ok_or(BridgeError::MissingEd25519Instruction)?;
+        let message = slice(message_offset, message_size)
+            .ok_or(BridgeError::MissingEd25519Instruction)?;
+
+        verified.push(VerifiedSignature {
+            public_key: Pubkey::try_from(public_key).map_err(|_| BridgeError::MissingEd25519Instruction)?,
+            signature: signature.try_into().map_err(|_| BridgeError::MissingEd25519Instruction)?,
+            message: message.to_vec(),
+        });
+    }
+
+    Ok(verified)
+}
+
+/// Require that the preceding Ed25519 instruction verified `signature` by
+/// `public_key` over `message`
+pub fn verify_signature_instruction(
+    instructions_sysvar: &AccountInfo,
+    public_key: &Pubkey,
+    message: &[u8],
+    signature: &[u8; 64],
+) -> Result<()> {
+    let verified = load_verified_signatures(instructions_sysvar)?;
+    require!(
+        verified.iter().any(|entry| {
+            &entry.public_key == public_key
+                && &entry.signature == signature
+                && entry.message == message
+        }),
+        BridgeError::InvalidSignature
+    );
+
+    Ok(())
+}
+
+/// Require that the preceding Ed25519 instruction attests `message`, either
+/// with `required_signatures` distinct validator signatures or, in threshold
+/// mode, with `aggregate_signature` under the group key
+pub fn verify_attestation_instruction(
+    instructions_sysvar: &AccountInfo,
+    bridge_config: &BridgeConfig,
+    message: &[u8; 32],
+    aggregate_signature: Option<&AggregateSignature>,
+) -> Result<()> {
+    let verified = load_verified_signatures(instructions_sysvar)?;
+    check_attestation(bridge_config, &verified, message, aggregate_signature)
+}
+
+/// Require that `verified` attests `message`, as for
+/// [`verify_attestation_instruction`]. A group signature only counts over
+/// the `aggregate_signing_hash` of `message` and its signer bitmap.
+pub fn check_attestation(
+    bridge_config: &BridgeConfig,
+    verified: &[VerifiedSignature],
+    message: &[u8; 32],
+    aggregate_signature: Option<&AggregateSignature>,
+) -> Result<()> {
+    if let Some(aggregate_signature) = aggregate_signature {
+        check_aggregate_signature(bridge_config, aggregate_signature)?;
+        let signed_hash =
+            aggregate_signing_hash::<SolanaKeccak>(message, aggregate_signature.signer_bitmap);
+        require!(
+            verified.iter().any(|entry| {
+                entry.public_key == bridge_config.group_key
+                    && entry.signature == aggregate_signature.signature
+                    && entry.message == signed_hash
+            }),
+            BridgeError::InvalidSignature
+        );
+        return Ok(());
+    }
+
+    let mut signers: Vec<Pubkey> = Vec::new();
+    for entry in verified.iter().filter(|entry| entry.message == message) {
+        if bridge_config.is_validator(&entry.public_key) && !signers.contains(&entry.public_key) {
+            signers.push(entry.public_key);
+        }
+    }
+
+    require!(
+        signers.len() >= bridge_config.required_signatures as usize,
+        BridgeError::InsufficientSignatures
+    );
+
+    Ok(())
+}
+
+/// Require that threshold mode is enabled with a group key, and that the
+/// signer bitmap of `aggregate_signature` only names group participants and
+/// reaches the group threshold
+pub fn check_aggregate_signature(
+    bridge_config: &BridgeConfig,
+    aggregate_signature: &AggregateSignature,
+) -> Result<()> {
+    require!(
+        bridge_config.signature_mode == SignatureMode::Threshold,
+        BridgeError::ThresholdModeDisabled
+    );
+    require!(bridge_config.has_group_key(), BridgeError::GroupKeyNotSet);
+
+    let bitmap = aggregate_signature.signer_bitmap;
+    require!(
+        bridge_config.group_size >= BridgeConfig::MAX_GROUP_SIZE
+            || bitmap >> bridge_config.group_size == 0,
+        BridgeError::InvalidSignerBitmap
+    );
+    require!(
+        bitmap.count_ones() >= bridge_config.group_threshold as u32,
+        BridgeError::InsufficientSignatures
+    );
+
+    Ok(())
+}
+
+/// Require that at least `required_signatures` of `signatures` are validator
+/// signatures over `message` checked by the preceding Ed25519 instruction,
+/// each from a distinct validator
+pub fn verify_validator_signatures(
+    instructions_sysvar: &AccountInfo,
+    bridge_config: &BridgeConfig,
+    message: &[u8],
+    signatures: &[[u8; 64]],
+) -> Result<()> {
+    let verified = load_verified_signatures(instructions_sysvar)?;
+    check_validator_signatures(bridge_config, &verified, message, signatures)
+}
+
+/// Require that `signatures` are backed by `verified` entries over `message`
+/// from at least `required_signatures` distinct validators, as for
+/// [`verify_validator_signatures`]
+pub fn check_validator_signatures(
+    bridge_config: &BridgeConfig,
+    verified: &[VerifiedSignature],
+    message: &[u8],
+    signatures: &[[u8; 64]],
+) -> Result<()> {
+    let mut signers: Vec<Pubkey> = Vec::new();
+    for signature in signatures.iter() {
+        let entry = verified
+            .iter()
+            .find(|entry| &entry.signature == signature && entry.message == message)
+            .ok_or(BridgeError::InvalidSignature)?;
+        if bridge_config.is_validator(&entry.public_key) && !signers.contains(&entry.public_key) {
+            signers.push(entry.public_key);
+        }
+    }
+
+    require!(
+        signers.len() >= bridge_config.required_signatures as usize,
+        BridgeError::InsufficientSignatures
+    );
+
+    Ok(())
+}
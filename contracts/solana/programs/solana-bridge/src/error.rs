@@ -49,4 +49,67 @@ pub enum BridgeError {
 
     #[msg("Arithmetic overflow")]
     ArithmeticOverflow,
+
+    #[msg("Threshold signature mode is disabled")]
+    ThresholdModeDisabled,
+
+    #[msg("Group key not set")]
+    GroupKeyNotSet,
+
+    #[msg("Invalid group key configuration")]
+    InvalidGroupConfig,
+
+    #[msg("Invalid signer bitmap")]
+    InvalidSignerBitmap,
+
+    #[msg("Missing or malformed Ed25519 verification instruction")]
+    MissingEd25519Instruction,
+
+    #[msg("Invalid batch")]
+    InvalidBatch,
+
+    #[msg("Invalid Merkle proof")]
+    InvalidMerkleProof,
+
+    #[msg("Invalid batch payload")]
+    InvalidBatchPayload,
+
+    #[msg("Message is not destined for this chain")]
+    WrongDestinationChain,
+
+    #[msg("Payload too large")]
+    PayloadTooLarge,
+
+    #[msg("Invalid message target")]
+    InvalidMessageTarget,
+
+    #[msg("Message already delivered")]
+    MessageAlreadyDelivered,
+
+    #[msg("Call is not pending")]
+    CallNotPending,
+
+    #[msg("Call fallback delay has not elapsed")]
+    FallbackTooEarly,
+
+    #[msg("Lock is not pending")]
+    LockNotPending,
+
+    #[msg("Lock has not expired")]
+    LockNotExpired,
+
+    #[msg("Destination transaction reference too long")]
+    DestinationTxTooLong,
+
+    #[msg("Record is not finalized")]
+    RecordNotFinalized,
+
+    #[msg("Account is already in the current layout")]
+    AlreadyMigrated,
+
+    #[msg("Unrecognized account layout")]
+    UnknownAccountLayout,
+
+    #[msg("Invalid governance account")]
+    InvalidGovernance,
 }
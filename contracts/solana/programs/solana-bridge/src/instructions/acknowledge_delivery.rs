@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+use solana_program::sysvar::instructions as sysvar_instructions;
+use crate::state::*;
+use crate::error::*;
+use crate::ed25519::verify_attestation_instruction;
+use super::refund::{create_lock_outcome_hash, LockStatusChangedEvent};
+
+#[derive(Accounts)]
+pub struct AcknowledgeDelivery<'info> {
+    #[account(
+        seeds = [b"bridge_config"],
+        bump = bridge_config.bump,
+    )]
+    pub bridge_config: Account<'info, BridgeConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"lock_record", lock_record.sender.as_ref(), &lock_record.nonce.to_le_bytes()],
+        bump = lock_record.bump,
+    )]
+    pub lock_record: Account<'info, LockRecord>,
+
+    /// CHECK: Instructions sysvar, used to inspect the Ed25519 precompile instruction
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions_sysvar: AccountInfo<'info>,
+}
+
+/// Mark a lock as completed on the destination chain. `destination_tx`
+/// references the transaction that delivered it.
+pub fn handler(
+    ctx: Context<AcknowledgeDelivery>,
+    destination_tx: String,
+    aggregate_signature: Option<AggregateSignature>,
+) -> Result<()> {
+    let bridge_config = &ctx.accounts.bridge_config;
+    let lock_record = &ctx.accounts.lock_record;
+
+    // Check bridge is not paused
+    require!(!bridge_config.is_paused, BridgeError::BridgePaused);
+
+    require!(
+        destination_tx.len() <= LockRecord::MAX_TX_REF_LEN,
+        BridgeError::DestinationTxTooLong
+    );
+    require!(lock_record.status == LockStatus::Pending, BridgeError::LockNotPending);
+
+    // Verify validator attestation
+    let message_hash = create_lock_outcome_hash(
+        LockRecord::DELIVERED_DOMAIN,
+        &bridge_config.key(),
+        lock_record,
+        destination_tx.as_bytes(),
+    );
+    verify_attestation_instruction(
+        &ctx.accounts.instructions_sysvar,
+        bridge_config,
+        &message_hash,
+        aggregate_signature.as_ref(),
+    )?;
+
+    let lock_record = &mut ctx.accounts.lock_record;
+    lock_record.status = LockStatus::Delivered { destination_tx };
+
+    msg!("Lock delivered: amount={}, sender={}", lock_record.amount, lock_record.sender);
+
+    emit!(LockStatusChangedEvent {
+        message_id: lock_record.message_id,
+        status: lock_record.status.clone(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
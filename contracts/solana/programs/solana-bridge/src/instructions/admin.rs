@@ -1,9 +1,12 @@
 use anchor_lang::prelude::*;
+use solana_program::bpf_loader_upgradeable;
+use solana_program::program::invoke;
 use crate::state::*;
 use crate::error::*;
 
 // ===== Add Validator =====
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct UpdateValidators<'info> {
     #[account(
@@ -33,10 +36,19 @@ pub fn add_validator(ctx: Context<UpdateValidators>, validator: Pubkey) -> Resul
     );
 
     // Add validator
+    let old_validator_count = bridge_config.validators.len() as u8;
     bridge_config.validators.push(validator);
+    let new_validator_count = bridge_config.validators.len() as u8;
 
     msg!("Validator added: {}", validator);
 
+    emit_cpi!(ValidatorAddedEvent {
+        admin: ctx.accounts.admin.key(),
+        validator,
+        old_validator_count,
+        new_validator_count,
+    });
+
     Ok(())
 }
 
@@ -47,7 +59,9 @@ pub fn remove_validator(ctx: Context<UpdateValidators>, validator: Pubkey) -> Re
     let position = bridge_config.validators.iter().position(|v| v == &validator);
     require!(position.is_some(), BridgeError::ValidatorNotFound);
 
+    let old_validator_count = bridge_config.validators.len() as u8;
     bridge_config.validators.remove(position.unwrap());
+    let new_validator_count = bridge_config.validators.len() as u8;
 
     // Ensure required signatures is still valid
     require!(
@@ -57,11 +71,19 @@ pub fn remove_validator(ctx: Context<UpdateValidators>, validator: Pubkey) -> Re
 
     msg!("Validator removed: {}", validator);
 
+    emit_cpi!(ValidatorRemovedEvent {
+        admin: ctx.accounts.admin.key(),
+        validator,
+        old_validator_count,
+        new_validator_count,
+    });
+
     Ok(())
 }
 
 // ===== Update Config =====
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct UpdateConfig<'info> {
     #[account(
@@ -87,29 +109,247 @@ pub fn update_required_signatures(
         BridgeError::InvalidRequiredSignatures
     );
 
+    let old_required_signatures = bridge_config.required_signatures;
     bridge_config.required_signatures = required_signatures;
 
     msg!("Required signatures updated to: {}", required_signatures);
 
+    emit_cpi!(RequiredSignaturesUpdatedEvent {
+        admin: ctx.accounts.admin.key(),
+        old_required_signatures,
+        new_required_signatures: required_signatures,
+    });
+
+    Ok(())
+}
+
+pub fn set_group_key(
+    ctx: Context<UpdateConfig>,
+    group_key: Pubkey,
+    threshold: u8,
+    group_size: u8,
+) -> Result<()> {
+    let bridge_config = &mut ctx.accounts.bridge_config;
+
+    require!(group_key != Pubkey::default(), BridgeError::InvalidGroupConfig);
+    require!(
+        group_size > 0 && group_size <= BridgeConfig::MAX_GROUP_SIZE,
+        BridgeError::InvalidGroupConfig
+    );
+    require!(
+        threshold > 0 && threshold <= group_size,
+        BridgeError::InvalidGroupConfig
+    );
+
+    let old_key = bridge_config.group_key;
+    let epoch = if bridge_config.has_group_key() {
+        bridge_config.group_key_epoch
+            .checked_add(1)
+            .ok_or(BridgeError::ArithmeticOverflow)?
+    } else {
+        0
+    };
+
+    bridge_config.group_key = group_key;
+    bridge_config.group_threshold = threshold;
+    bridge_config.group_size = group_size;
+    bridge_config.group_key_epoch = epoch;
+
+    msg!("Group key rotated: epoch={}, threshold={}/{}", epoch, threshold, group_size);
+
+    emit_cpi!(GroupKeyRotatedEvent {
+        admin: ctx.accounts.admin.key(),
+        old_key,
+        new_key: group_key,
+        threshold,
+        group_size,
+        epoch,
+    });
+
+    Ok(())
+}
+
+pub fn set_signature_mode(ctx: Context<UpdateConfig>, mode: SignatureMode) -> Result<()> {
+    let bridge_config = &mut ctx.accounts.bridge_config;
+
+    if mode == SignatureMode::Threshold {
+        require!(bridge_config.has_group_key(), BridgeError::GroupKeyNotSet);
+    }
+
+    let old_mode = bridge_config.signature_mode;
+    bridge_config.signature_mode = mode;
+
+    msg!("Signature mode updated to: {:?}", mode);
+
+    emit_cpi!(SignatureModeUpdatedEvent {
+        admin: ctx.accounts.admin.key(),
+        old_mode,
+        new_mode: mode,
+    });
+
     Ok(())
 }
 
 pub fn pause(ctx: Context<UpdateConfig>) -> Result<()> {
     let bridge_config = &mut ctx.accounts.bridge_config;
 
+    let was_paused = bridge_config.is_paused;
     bridge_config.is_paused = true;
 
     msg!("Bridge paused");
 
+    emit_cpi!(BridgePausedEvent {
+        admin: ctx.accounts.admin.key(),
+        was_paused,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
     Ok(())
 }
 
 pub fn unpause(ctx: Context<UpdateConfig>) -> Result<()> {
     let bridge_config = &mut ctx.accounts.bridge_config;
 
+    let was_paused = bridge_config.is_paused;
     bridge_config.is_paused = false;
 
     msg!("Bridge unpaused");
 
+    emit_cpi!(BridgeUnpausedEvent {
+        admin: ctx.accounts.admin.key(),
+        was_paused,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
     Ok(())
 }
+
+// ===== Upgrade Authority =====
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct HandoverUpgradeAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_config"],
+        bump = bridge_config.bump,
+        constraint = bridge_config.admin == admin.key() @ BridgeError::UnauthorizedValidator
+    )]
+    pub bridge_config: Account<'info, BridgeConfig>,
+
+    /// Bridge admin, who must also be the current upgrade authority
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(admin.key())
+            @ BridgeError::UnauthorizedValidator
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    /// CHECK: New upgrade authority, typically a governance PDA
+    pub governance: UncheckedAccount<'info>,
+
+    /// CHECK: BPF upgradeable loader
+    #[account(address = bpf_loader_upgradeable::ID)]
+    pub bpf_loader_upgradeable: UncheckedAccount<'info>,
+}
+
+/// Move the program upgrade authority from the admin to governance and record
+/// it in the config
+pub fn handover_upgrade_authority(ctx: Context<HandoverUpgradeAuthority>) -> Result<()> {
+    let governance = ctx.accounts.governance.key();
+    require!(governance != Pubkey::default(), BridgeError::InvalidGovernance);
+
+    let instruction = bpf_loader_upgradeable::set_upgrade_authority(
+        &crate::ID,
+        &ctx.accounts.admin.key(),
+        Some(&governance),
+    );
+    invoke(
+        &instruction,
+        &[
+            ctx.accounts.program_data.to_account_info(),
+            ctx.accounts.admin.to_account_info(),
+            ctx.accounts.governance.to_account_info(),
+        ],
+    )?;
+
+    let bridge_config = &mut ctx.accounts.bridge_config;
+    let old_governance = bridge_config.governance;
+    bridge_config.governance = governance;
+
+    msg!("Upgrade authority handed over to: {}", governance);
+
+    emit_cpi!(UpgradeAuthorityHandoverEvent {
+        previous_authority: ctx.accounts.admin.key(),
+        old_governance,
+        governance,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct ValidatorAddedEvent {
+    pub admin: Pubkey,
+    pub validator: Pubkey,
+    pub old_validator_count: u8,
+    pub new_validator_count: u8,
+}
+
+#[event]
+pub struct ValidatorRemovedEvent {
+    pub admin: Pubkey,
+    pub validator: Pubkey,
+    pub old_validator_count: u8,
+    pub new_validator_count: u8,
+}
+
+#[event]
+pub struct RequiredSignaturesUpdatedEvent {
+    pub admin: Pubkey,
+    pub old_required_signatures: u8,
+    pub new_required_signatures: u8,
+}
+
+#[event]
+pub struct SignatureModeUpdatedEvent {
+    pub admin: Pubkey,
+    pub old_mode: SignatureMode,
+    pub new_mode: SignatureMode,
+}
+
+#[event]
+pub struct BridgePausedEvent {
+    pub admin: Pubkey,
+    pub was_paused: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BridgeUnpausedEvent {
+    pub admin: Pubkey,
+    pub was_paused: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct UpgradeAuthorityHandoverEvent {
+    pub previous_authority: Pubkey,
+    pub old_governance: Pubkey,
+    pub governance: Pubkey,
+}
+
+#[event]
+pub struct GroupKeyRotatedEvent {
+    pub admin: Pubkey,
+    pub old_key: Pubkey,
+    pub new_key: Pubkey,
+    pub threshold: u8,
+    pub group_size: u8,
+    pub epoch: u64,
+}
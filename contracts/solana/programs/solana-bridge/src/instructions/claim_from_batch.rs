@@ -0,0 +1,242 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use serde::Deserialize;
+use bridge_types::chain;
+use crate::state::*;
+use crate::error::*;
+use crate::merkle;
+use super::unlock_token::TokenUnlockedEvent;
+
+#[derive(Accounts)]
+#[instruction(
+    batch_root: [u8; 32],
+    message_id: [u8; 32],
+    source_chain: String,
+    sequence: u64,
+)]
+pub struct ClaimFromBatch<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_config"],
+        bump = bridge_config.bump,
+    )]
+    pub bridge_config: Account<'info, BridgeConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"batch_root", batch_root.as_ref()],
+        bump = batch_root_account.bump,
+    )]
+    pub batch_root_account: Account<'info, BatchRoot>,
+
+    #[account(
+        mut,
+        seeds = [b"token_vault", token_mint.key().as_ref()],
+        bump = token_vault.bump,
+    )]
+    pub token_vault: Account<'info, TokenVault>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = MessageRecord::LEN,
+        seeds = [b"message_record", message_id.as_ref()],
+        bump
+    )]
+    pub message_record: Account<'info, MessageRecord>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = ProcessedSequences::LEN,
+        seeds = [
+            b"processed_sequences".as_ref(),
+            &ProcessedSequences::chain_seed(&source_chain),
+            &ProcessedSequences::window_of(sequence).to_le_bytes(),
+        ],
+        bump
+    )]
+    pub processed_sequences: Box<Account<'info, ProcessedSequences>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Recipient can be any account
+    pub recipient: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = recipient_token_account.owner == recipient.key(),
+        constraint = recipient_token_account.mint == token_mint.key(),
+    )]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.owner == bridge_config.key(),
+        constraint = vault_token_account.mint == token_mint.key(),
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Token mint account
+    pub token_mint: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Token transfer payload as serialized by the Go relayer
+/// (`types.TokenTransferPayload`); unknown fields are ignored
+#[derive(Deserialize)]
+struct BatchTokenPayload {
+    token_address: BatchPayloadAddress,
+    amount: String,
+}
+
+/// Address as serialized by the Go relayer (`types.Address`)
+#[derive(Deserialize)]
+struct BatchPayloadAddress {
+    raw: String,
+}
+
+/// Unlock a single message from an attested batch using its Merkle proof.
+/// Leaf fields must be exactly those hashed by `internal/batching`; the token
+/// and amount are taken from the attested JSON payload.
+#[allow(clippy::too_many_arguments)]
+pub fn handler(
+    ctx: Context<ClaimFromBatch>,
+    batch_root: [u8; 32],
+    message_id: [u8; 32],
+    source_chain: String,
+    sequence: u64,
+    destination_chain: String,
+    sender: String,
+    payload: Vec<u8>,
+    leaf_index: u32,
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    let bridge_config = &ctx.accounts.bridge_config;
+    let batch_root_account = &mut ctx.accounts.batch_root_account;
+
+    // Check bridge is not paused
+    require!(!bridge_config.is_paused, BridgeError::BridgePaused);
+
+    // Check the transfer was not released before, even if its record was closed
+    require!(
+        !ctx.accounts.processed_sequences.contains(sequence),
+        BridgeError::MessageAlreadyProcessed
+    );
+
+    // Only messages bound for Solana can be claimed here
+    require!(
+        destination_chain == chain::SOLANA,
+        BridgeError::WrongDestinationChain
+    );
+
+    // Validate string lengths
+    require!(
+        source_chain.len() <= MessageRecord::MAX_CHAIN_LEN,
+        BridgeError::SourceChainTooLong
+    );
+    require!(
+        sender.len() <= MessageRecord::MAX_SENDER_LEN,
+        BridgeError::SenderAddressTooLong
+    );
+
+    // Verify inclusion in the attested batch
+    require!(
+        leaf_index < batch_root_account.message_count,
+        BridgeError::InvalidMerkleProof
+    );
+    let leaf = merkle::hash_leaf(
+        &message_id,
+        &source_chain,
+        sequence,
+        &destination_chain,
+        &sender,
+        &ctx.accounts.recipient.key().to_string(),
+        &payload,
+    );
+    require!(
+        merkle::verify_proof(&leaf, leaf_index, &proof, &batch_root),
+        BridgeError::InvalidMerkleProof
+    );
+
+    // The token and amount come from the attested payload only
+    let transfer: BatchTokenPayload = serde_json::from_slice(&payload)
+        .map_err(|_| BridgeError::InvalidBatchPayload)?;
+    require!(
+        transfer.token_address.raw == ctx.accounts.token_mint.key().to_string(),
+        BridgeError::InvalidBatchPayload
+    );
+    let amount: u64 = transfer.amount.parse()
+        .map_err(|_| BridgeError::InvalidBatchPayload)?;
+    require!(amount > 0, BridgeError::InvalidAmount);
+
+    batch_root_account.claimed_count = batch_root_account.claimed_count
+        .checked_add(1)
+        .ok_or(BridgeError::ArithmeticOverflow)?;
+
+    // Transfer tokens from vault to recipient
+    let seeds = &[
+        b"bridge_config".as_ref(),
+        &[bridge_config.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.vault_token_account.to_account_info(),
+        to: ctx.accounts.recipient_token_account.to_account_info(),
+        authority: ctx.accounts.bridge_config.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+    token::transfer(cpi_ctx, amount)?;
+
+    // Update vault stats
+    let token_vault = &mut ctx.accounts.token_vault;
+    token_vault.total_locked = token_vault.total_locked
+        .checked_sub(amount)
+        .ok_or(BridgeError::ArithmeticOverflow)?;
+
+    // Update bridge stats
+    let bridge_config = &mut ctx.accounts.bridge_config;
+    bridge_config.total_unlocked = bridge_config.total_unlocked
+        .checked_add(amount)
+        .ok_or(BridgeError::ArithmeticOverflow)?;
+
+    // Create message record
+    let message_record = &mut ctx.accounts.message_record;
+    message_record.message_id = message_id;
+    message_record.source_chain = source_chain;
+    message_record.sender = sender;
+    message_record.recipient = ctx.accounts.recipient.key();
+    message_record.token_mint = ctx.accounts.token_mint.key();
+    message_record.amount = amount;
+    message_record.timestamp = Clock::get()?.unix_timestamp;
+    message_record.processed = true;
+    message_record.payer = ctx.accounts.payer.key();
+    message_record.bump = ctx.bumps.message_record;
+
+    let processed_sequences = &mut ctx.accounts.processed_sequences;
+    processed_sequences.window = ProcessedSequences::window_of(sequence);
+    processed_sequences.insert(sequence);
+    processed_sequences.bump = ctx.bumps.processed_sequences;
+
+    msg!("Batch message claimed: amount={}, recipient={}, leaf={}",
+        amount,
+        ctx.accounts.recipient.key(),
+        leaf_index
+    );
+
+    // Emit event
+    emit!(TokenUnlockedEvent {
+        message_id,
+        recipient: ctx.accounts.recipient.key(),
+        token_mint: ctx.accounts.token_mint.key(),
+        amount,
+        timestamp: message_record.timestamp,
+    });
+
+    Ok(())
+}
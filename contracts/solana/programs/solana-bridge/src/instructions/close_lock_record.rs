@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use super::close_message_record::RecordClosedEvent;
+
+#[derive(Accounts)]
+pub struct CloseLockRecord<'info> {
+    #[account(
+        mut,
+        close = sender,
+        has_one = sender,
+        seeds = [b"lock_record", sender.key().as_ref(), &lock_record.nonce.to_le_bytes()],
+        bump = lock_record.bump,
+    )]
+    pub lock_record: Account<'info, LockRecord>,
+
+    /// Sender of the lock, who paid for the record
+    #[account(mut)]
+    pub sender: Signer<'info>,
+}
+
+/// Close a delivered, refunded or expired `LockRecord`, returning its rent to
+/// the sender. The nonce only names the record's address and may be reused:
+/// the message ID is derived from the bridge-wide sequence, so a new lock at
+/// the same address gets a new ID that no earlier attestation covers.
+pub fn handler(ctx: Context<CloseLockRecord>) -> Result<()> {
+    let lock_record = &ctx.accounts.lock_record;
+
+    // Only finalized records can be closed
+    require!(lock_record.status != LockStatus::Pending, BridgeError::RecordNotFinalized);
+
+    msg!("Lock record closed: sender={}, nonce={}", lock_record.sender, lock_record.nonce);
+
+    emit!(RecordClosedEvent {
+        record: lock_record.key(),
+        message_id: lock_record.message_id,
+        rent_recipient: lock_record.sender,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct CloseMessageRecord<'info> {
+    #[account(
+        mut,
+        close = payer,
+        has_one = payer,
+        seeds = [b"message_record", message_record.message_id.as_ref()],
+        bump = message_record.bump,
+    )]
+    pub message_record: Account<'info, MessageRecord>,
+
+    /// Original payer of the record; receives its rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+/// Close a processed `MessageRecord`, returning its rent to the payer. The
+/// transfer's bit in `ProcessedSequences`, set when it was released, keeps it
+/// from being replayed.
+pub fn handler(ctx: Context<CloseMessageRecord>) -> Result<()> {
+    let message_id = ctx.accounts.message_record.message_id;
+
+    // Only finalized records can be closed
+    require!(ctx.accounts.message_record.processed, BridgeError::RecordNotFinalized);
+
+    msg!("Message record closed: payer={}", ctx.accounts.payer.key());
+
+    emit!(RecordClosedEvent {
+        record: ctx.accounts.message_record.key(),
+        message_id,
+        rent_recipient: ctx.accounts.payer.key(),
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct RecordClosedEvent {
+    pub record: Pubkey,
+    pub message_id: [u8; 32],
+    pub rent_recipient: Pubkey,
+}
@@ -0,0 +1,179 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::program::invoke_signed;
+use crate::state::*;
+use crate::error::*;
+use super::unlock_token::{transfer_from_vault, TokenUnlockedEvent};
+
+#[derive(Accounts)]
+pub struct ExecuteCall<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_config"],
+        bump = bridge_config.bump,
+    )]
+    pub bridge_config: Account<'info, BridgeConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"message_record", message_record.message_id.as_ref()],
+        bump = message_record.bump,
+    )]
+    pub message_record: Account<'info, MessageRecord>,
+
+    #[account(
+        mut,
+        seeds = [b"pending_call", message_record.message_id.as_ref()],
+        bump = pending_call.bump,
+    )]
+    pub pending_call: Account<'info, PendingCall>,
+
+    #[account(
+        mut,
+        seeds = [b"token_vault", token_mint.key().as_ref()],
+        bump = token_vault.bump,
+    )]
+    pub token_vault: Account<'info, TokenVault>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.owner == bridge_config.key(),
+        constraint = vault_token_account.mint == token_mint.key(),
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = receiver_token_account.owner == pending_call.receiver_authority,
+        constraint = receiver_token_account.mint == token_mint.key(),
+    )]
+    pub receiver_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Must be the mint recorded for the message
+    #[account(address = message_record.token_mint)]
+    pub token_mint: AccountInfo<'info>,
+
+    /// CHECK: PDA that signs deliveries so receivers can authenticate the bridge.
+    /// It holds no funds and has no authority elsewhere.
+    #[account(
+        seeds = [b"messenger"],
+        bump
+    )]
+    pub messenger: AccountInfo<'info>,
+
+    /// CHECK: Must be the receiver recorded in the pending call
+    #[account(
+        executable,
+        address = pending_call.receiver_program @ BridgeError::InvalidMessageTarget
+    )]
+    pub receiver_program: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Deliver the tokens of a pending unlock-and-call to the receiver and invoke
+/// it. Any accounts the receiver needs are passed as remaining accounts.
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, ExecuteCall<'info>>) -> Result<()> {
+    require!(!ctx.accounts.bridge_config.is_paused, BridgeError::BridgePaused);
+    require!(
+        ctx.accounts.pending_call.status == CallStatus::Pending,
+        BridgeError::CallNotPending
+    );
+
+    let amount = ctx.accounts.message_record.amount;
+
+    // Deliver tokens to the receiver
+    transfer_from_vault(
+        &ctx.accounts.bridge_config,
+        &ctx.accounts.vault_token_account,
+        &ctx.accounts.receiver_token_account,
+        &ctx.accounts.token_program,
+        amount,
+    )?;
+
+    // Invoke the receiver
+    let message_record = &ctx.accounts.message_record;
+    let pending_call = &ctx.accounts.pending_call;
+    let transfer = BridgeTransfer {
+        message_id: message_record.message_id,
+        source_chain: message_record.source_chain.clone(),
+        sender: message_record.sender.clone(),
+        token_mint: message_record.token_mint,
+        amount,
+        payload: pending_call.payload.clone(),
+    };
+    let mut data = BridgeTransfer::discriminator().to_vec();
+    transfer.serialize(&mut data)?;
+
+    let messenger = ctx.accounts.messenger.to_account_info();
+    let receiver_token_account = ctx.accounts.receiver_token_account.to_account_info();
+    let mut accounts = vec![
+        AccountMeta::new_readonly(messenger.key(), true),
+        AccountMeta::new(receiver_token_account.key(), false),
+    ];
+    let mut account_infos = vec![messenger, receiver_token_account];
+    for account in ctx.remaining_accounts.iter() {
+        accounts.push(if account.is_writable {
+            AccountMeta::new(account.key(), account.is_signer)
+        } else {
+            AccountMeta::new_readonly(account.key(), account.is_signer)
+        });
+        account_infos.push(account.clone());
+    }
+    account_infos.push(ctx.accounts.receiver_program.to_account_info());
+
+    let instruction = Instruction {
+        program_id: pending_call.receiver_program,
+        accounts,
+        data,
+    };
+
+    let bump = [ctx.bumps.messenger];
+    let seeds: &[&[u8]] = &[b"messenger", &bump];
+    invoke_signed(&instruction, &account_infos, &[seeds])?;
+
+    // Update vault stats
+    let token_vault = &mut ctx.accounts.token_vault;
+    token_vault.total_locked = token_vault.total_locked
+        .checked_sub(amount)
+        .ok_or(BridgeError::ArithmeticOverflow)?;
+
+    // Update bridge stats
+    let bridge_config = &mut ctx.accounts.bridge_config;
+    bridge_config.total_unlocked = bridge_config.total_unlocked
+        .checked_add(amount)
+        .ok_or(BridgeError::ArithmeticOverflow)?;
+
+    let pending_call = &mut ctx.accounts.pending_call;
+    pending_call.status = CallStatus::Executed;
+    let message_record = &mut ctx.accounts.message_record;
+    message_record.processed = true;
+
+    let timestamp = Clock::get()?.unix_timestamp;
+
+    msg!("Unlock call executed: amount={}, receiver={}", amount, pending_call.receiver_program);
+
+    emit!(TokenUnlockedEvent {
+        message_id: message_record.message_id,
+        recipient: pending_call.receiver_authority,
+        token_mint: message_record.token_mint,
+        amount,
+        timestamp,
+    });
+
+    emit!(UnlockCallExecutedEvent {
+        message_id: message_record.message_id,
+        receiver_program: pending_call.receiver_program,
+        timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct UnlockCallExecutedEvent {
+    pub message_id: [u8; 32],
+    pub receiver_program: Pubkey,
+    pub timestamp: i64,
+}
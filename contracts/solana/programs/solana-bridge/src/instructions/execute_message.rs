@@ -0,0 +1,103 @@
+use anchor_lang::prelude::*;
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::program::invoke_signed;
+use crate::state::*;
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct ExecuteMessage<'info> {
+    #[account(
+        seeds = [b"bridge_config"],
+        bump = bridge_config.bump,
+    )]
+    pub bridge_config: Account<'info, BridgeConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"inbound_message", inbound_message.message_id.as_ref()],
+        bump = inbound_message.bump,
+    )]
+    pub inbound_message: Account<'info, InboundMessage>,
+
+    /// CHECK: PDA that signs deliveries so receivers can authenticate the bridge.
+    /// It holds no funds and has no authority elsewhere.
+    #[account(
+        seeds = [b"messenger"],
+        bump
+    )]
+    pub messenger: AccountInfo<'info>,
+
+    /// CHECK: Must be the program recorded in the inbound message
+    #[account(
+        executable,
+        address = inbound_message.target_program @ BridgeError::InvalidMessageTarget
+    )]
+    pub target_program: AccountInfo<'info>,
+}
+
+/// Deliver a received message to its target program. Any additional accounts
+/// the receiver needs are passed as remaining accounts and forwarded as-is.
+/// A failed CPI reverts the whole transaction, leaving the message pending,
+/// so delivery can simply be retried.
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, ExecuteMessage<'info>>) -> Result<()> {
+    require!(!ctx.accounts.bridge_config.is_paused, BridgeError::BridgePaused);
+
+    let inbound_message = &ctx.accounts.inbound_message;
+    require!(
+        inbound_message.status == DeliveryStatus::Pending,
+        BridgeError::MessageAlreadyDelivered
+    );
+
+    let message = BridgeMessage {
+        message_id: inbound_message.message_id,
+        source_chain: inbound_message.source_chain.clone(),
+        sender: inbound_message.sender.clone(),
+        payload: inbound_message.payload.clone(),
+    };
+    let mut data = BridgeMessage::discriminator().to_vec();
+    message.serialize(&mut data)?;
+
+    let messenger = ctx.accounts.messenger.to_account_info();
+    let mut accounts = vec![AccountMeta::new_readonly(messenger.key(), true)];
+    let mut account_infos = vec![messenger];
+    for account in ctx.remaining_accounts.iter() {
+        accounts.push(if account.is_writable {
+            AccountMeta::new(account.key(), account.is_signer)
+        } else {
+            AccountMeta::new_readonly(account.key(), account.is_signer)
+        });
+        account_infos.push(account.clone());
+    }
+    account_infos.push(ctx.accounts.target_program.to_account_info());
+
+    let instruction = Instruction {
+        program_id: inbound_message.target_program,
+        accounts,
+        data,
+    };
+
+    let bump = [ctx.bumps.messenger];
+    let seeds: &[&[u8]] = &[b"messenger", &bump];
+    invoke_signed(&instruction, &account_infos, &[seeds])?;
+
+    let inbound_message = &mut ctx.accounts.inbound_message;
+    inbound_message.status = DeliveryStatus::Delivered;
+    inbound_message.delivered_at = Clock::get()?.unix_timestamp;
+
+    msg!("Message delivered: target={}", inbound_message.target_program);
+
+    emit!(MessageDeliveredEvent {
+        message_id: inbound_message.message_id,
+        target_program: inbound_message.target_program,
+        timestamp: inbound_message.delivered_at,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct MessageDeliveredEvent {
+    pub message_id: [u8; 32],
+    pub target_program: Pubkey,
+    pub timestamp: i64,
+}
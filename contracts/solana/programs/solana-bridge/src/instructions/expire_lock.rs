@@ -0,0 +1,19 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use super::refund::{return_to_sender, Refund};
+
+/// Return locked tokens to the sender once `LockRecord::EXPIRY_PERIOD` has
+/// passed and the validators attest the transfer was never delivered.
+/// Callable by anyone.
+pub fn handler(ctx: Context<Refund>, aggregate_signature: Option<AggregateSignature>) -> Result<()> {
+    let expires_at = ctx.accounts.lock_record.timestamp
+        .checked_add(LockRecord::EXPIRY_PERIOD)
+        .ok_or(BridgeError::ArithmeticOverflow)?;
+    require!(
+        Clock::get()?.unix_timestamp >= expires_at,
+        BridgeError::LockNotExpired
+    );
+
+    return_to_sender(ctx, LockStatus::Expired, aggregate_signature)
+}
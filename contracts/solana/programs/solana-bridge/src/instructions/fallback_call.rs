@@ -0,0 +1,126 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+use crate::state::*;
+use crate::error::*;
+use super::unlock_token::{transfer_from_vault, TokenUnlockedEvent};
+
+#[derive(Accounts)]
+pub struct FallbackCall<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_config"],
+        bump = bridge_config.bump,
+    )]
+    pub bridge_config: Account<'info, BridgeConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"message_record", message_record.message_id.as_ref()],
+        bump = message_record.bump,
+    )]
+    pub message_record: Account<'info, MessageRecord>,
+
+    #[account(
+        mut,
+        seeds = [b"pending_call", message_record.message_id.as_ref()],
+        bump = pending_call.bump,
+    )]
+    pub pending_call: Account<'info, PendingCall>,
+
+    #[account(
+        mut,
+        seeds = [b"token_vault", token_mint.key().as_ref()],
+        bump = token_vault.bump,
+    )]
+    pub token_vault: Account<'info, TokenVault>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.owner == bridge_config.key(),
+        constraint = vault_token_account.mint == token_mint.key(),
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = recipient_token_account.owner == message_record.recipient,
+        constraint = recipient_token_account.mint == token_mint.key(),
+    )]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Must be the mint recorded for the message
+    #[account(address = message_record.token_mint)]
+    pub token_mint: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Send the tokens of an unlock-and-call that was not executed within
+/// `PendingCall::FALLBACK_DELAY` to the original recipient. Callable by anyone.
+pub fn handler(ctx: Context<FallbackCall>) -> Result<()> {
+    require!(!ctx.accounts.bridge_config.is_paused, BridgeError::BridgePaused);
+    require!(
+        ctx.accounts.pending_call.status == CallStatus::Pending,
+        BridgeError::CallNotPending
+    );
+
+    let timestamp = Clock::get()?.unix_timestamp;
+    let fallback_at = ctx.accounts.pending_call.received_at
+        .checked_add(PendingCall::FALLBACK_DELAY)
+        .ok_or(BridgeError::ArithmeticOverflow)?;
+    require!(timestamp >= fallback_at, BridgeError::FallbackTooEarly);
+
+    let amount = ctx.accounts.message_record.amount;
+
+    transfer_from_vault(
+        &ctx.accounts.bridge_config,
+        &ctx.accounts.vault_token_account,
+        &ctx.accounts.recipient_token_account,
+        &ctx.accounts.token_program,
+        amount,
+    )?;
+
+    // Update vault stats
+    let token_vault = &mut ctx.accounts.token_vault;
+    token_vault.total_locked = token_vault.total_locked
+        .checked_sub(amount)
+        .ok_or(BridgeError::ArithmeticOverflow)?;
+
+    // Update bridge stats
+    let bridge_config = &mut ctx.accounts.bridge_config;
+    bridge_config.total_unlocked = bridge_config.total_unlocked
+        .checked_add(amount)
+        .ok_or(BridgeError::ArithmeticOverflow)?;
+
+    let pending_call = &mut ctx.accounts.pending_call;
+    pending_call.status = CallStatus::FellBack;
+    let message_record = &mut ctx.accounts.message_record;
+    message_record.processed = true;
+
+    msg!("Unlock call fell back: amount={}, recipient={}", amount, message_record.recipient);
+
+    emit!(TokenUnlockedEvent {
+        message_id: message_record.message_id,
+        recipient: message_record.recipient,
+        token_mint: message_record.token_mint,
+        amount,
+        timestamp,
+    });
+
+    emit!(UnlockCallFallbackEvent {
+        message_id: message_record.message_id,
+        recipient: message_record.recipient,
+        amount,
+        timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct UnlockCallFallbackEvent {
+    pub message_id: [u8; 32],
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
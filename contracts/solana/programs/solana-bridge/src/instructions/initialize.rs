@@ -2,6 +2,7 @@ use anchor_lang::prelude::*;
 use crate::state::*;
 use crate::error::*;
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct Initialize<'info> {
     #[account(
@@ -38,6 +39,7 @@ pub fn handler(
     );
 
     // Initialize bridge config
+    bridge_config.version = BridgeConfig::VERSION;
     bridge_config.admin = ctx.accounts.admin.key();
     bridge_config.validators = validators;
     bridge_config.required_signatures = required_signatures;
@@ -46,11 +48,30 @@ pub fn handler(
     bridge_config.total_unlocked = 0;
     bridge_config.message_count = 0;
     bridge_config.bump = ctx.bumps.bridge_config;
+    bridge_config.signature_mode = SignatureMode::Individual;
+    bridge_config.group_key = Pubkey::default();
+    bridge_config.group_threshold = 0;
+    bridge_config.group_size = 0;
+    bridge_config.group_key_epoch = 0;
+    bridge_config.governance = Pubkey::default();
 
     msg!("Bridge initialized with {} validators, requiring {} signatures",
         bridge_config.validators.len(),
         bridge_config.required_signatures
     );
 
+    emit_cpi!(BridgeInitializedEvent {
+        admin: ctx.accounts.admin.key(),
+        validators: bridge_config.validators.clone(),
+        required_signatures,
+    });
+
     Ok(())
 }
+
+#[event]
+pub struct BridgeInitializedEvent {
+    pub admin: Pubkey,
+    pub validators: Vec<Pubkey>,
+    pub required_signatures: u8,
+}
@@ -3,6 +3,8 @@ use anchor_spl::token::{self, Token, TokenAccount, Transfer};
 use solana_program::keccak;
 use crate::state::*;
 use crate::error::*;
+use bridge_types::hash::SolanaKeccak;
+use bridge_types::{chain, TransferMessage};
 
 #[derive(Accounts)]
 #[instruction(amount: u64, destination_chain: String, destination_address: String, nonce: u64)]
@@ -65,6 +67,19 @@ pub fn handler(
     destination_chain: String,
     destination_address: String,
     nonce: u64,
+) -> Result<()> {
+    execute_lock(ctx, amount, destination_chain, destination_address, nonce, None)
+}
+
+/// Lock `amount` into the vault and record the outgoing transfer, optionally
+/// with a call to execute on the destination chain
+pub(crate) fn execute_lock(
+    ctx: Context<LockToken>,
+    amount: u64,
+    destination_chain: String,
+    destination_address: String,
+    nonce: u64,
+    call: Option<LockCall>,
 ) -> Result<()> {
     let bridge_config = &mut ctx.accounts.bridge_config;
     let token_vault = &mut ctx.accounts.token_vault;
@@ -85,17 +100,36 @@ pub fn handler(
         destination_address.len() <= LockRecord::MAX_ADDRESS_LEN,
         BridgeError::DestinationAddressTooLong
     );
+    if let Some(call) = &call {
+        require!(
+            call.payload.len() <= LockCall::MAX_PAYLOAD_LEN,
+            BridgeError::PayloadTooLarge
+        );
+    }
 
-    // Generate message ID from hash of lock parameters
-    let message_data = format!(
-        "{}{}{}{}{}",
-        ctx.accounts.sender.key(),
-        ctx.accounts.token_mint.key(),
-        amount,
-        destination_chain,
-        nonce
-    );
-    let message_id = keccak::hash(message_data.as_bytes()).to_bytes();
+    // Generate message ID from the canonical transfer encoding
+    let sequence = bridge_config.message_count;
+    let mut message_id = TransferMessage {
+        source_chain: chain::SOLANA.to_string(),
+        source_bridge: crate::ID.to_bytes().to_vec(),
+        sequence,
+        sender: ctx.accounts.sender.key().to_bytes().to_vec(),
+        token: ctx.accounts.token_mint.key().to_bytes().to_vec(),
+        amount: u128::from(amount),
+        destination_chain: destination_chain.clone(),
+        recipient: destination_address.clone(),
+    }
+    .id::<SolanaKeccak>();
+
+    // Commit to the attached call so it cannot be altered
+    if let Some(call) = &call {
+        message_id = keccak::hashv(&[
+            &message_id,
+            &keccak::hash(&call.payload).to_bytes(),
+            &call.compute_budget.to_le_bytes(),
+        ])
+        .to_bytes();
+    }
 
     // Transfer tokens to vault
     let cpi_accounts = Transfer {
@@ -129,6 +163,7 @@ pub fn handler(
         .ok_or(BridgeError::ArithmeticOverflow)?;
 
     // Create lock record
+    lock_record.version = LockRecord::VERSION;
     lock_record.message_id = message_id;
     lock_record.sender = ctx.accounts.sender.key();
     lock_record.destination_chain = destination_chain.clone();
@@ -136,7 +171,9 @@ pub fn handler(
     lock_record.token_mint = ctx.accounts.token_mint.key();
     lock_record.amount = amount;
     lock_record.nonce = nonce;
+    lock_record.sequence = sequence;
     lock_record.timestamp = Clock::get()?.unix_timestamp;
+    lock_record.status = LockStatus::Pending;
     lock_record.bump = ctx.bumps.lock_record;
 
     msg!("Token locked: amount={}, destination={}, address={}",
@@ -154,9 +191,18 @@ pub fn handler(
         destination_chain,
         destination_address,
         nonce,
+        sequence,
         timestamp: lock_record.timestamp,
     });
 
+    if let Some(call) = call {
+        emit!(LockCallAttachedEvent {
+            message_id,
+            payload: call.payload,
+            compute_budget: call.compute_budget,
+        });
+    }
+
     Ok(())
 }
 
@@ -169,5 +215,13 @@ pub struct TokenLockedEvent {
     pub destination_chain: String,
     pub destination_address: String,
     pub nonce: u64,
+    pub sequence: u64,
     pub timestamp: i64,
 }
+
+#[event]
+pub struct LockCallAttachedEvent {
+    pub message_id: [u8; 32],
+    pub payload: Vec<u8>,
+    pub compute_budget: u32,
+}
@@ -0,0 +1,16 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use super::lock_token::{execute_lock, LockToken};
+
+/// Lock tokens and attach a call for the destination bridge to execute once
+/// the tokens are delivered to the receiver
+pub fn handler(
+    ctx: Context<LockToken>,
+    amount: u64,
+    destination_chain: String,
+    destination_address: String,
+    nonce: u64,
+    call: LockCall,
+) -> Result<()> {
+    execute_lock(ctx, amount, destination_chain, destination_address, nonce, Some(call))
+}
@@ -0,0 +1,149 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_lang::Discriminator;
+use crate::state::*;
+use crate::error::*;
+
+// ===== Migrate Config =====
+
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    /// CHECK: May still be in an older layout; owner, discriminator and
+    /// admin are checked by the handler
+    #[account(
+        mut,
+        seeds = [b"bridge_config"],
+        bump,
+        owner = crate::ID,
+    )]
+    pub bridge_config: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Convert the bridge config to the current layout, growing the account and
+/// topping up its rent from the admin. Run right after upgrading the program;
+/// instructions that load the config fail until then.
+pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+    let info = ctx.accounts.bridge_config.to_account_info();
+
+    let old = {
+        let data = info.try_borrow_data()?;
+        require!(
+            data.len() >= 8 && data[..8] == BridgeConfig::DISCRIMINATOR,
+            BridgeError::UnknownAccountLayout
+        );
+        require!(data.len() != BridgeConfig::LEN, BridgeError::AlreadyMigrated);
+        require!(data.len() == BridgeConfigV0::LEN, BridgeError::UnknownAccountLayout);
+        BridgeConfigV0::deserialize(&mut &data[8..])?
+    };
+    require!(
+        old.admin == ctx.accounts.admin.key(),
+        BridgeError::UnauthorizedValidator
+    );
+
+    resize_account(
+        &info,
+        &ctx.accounts.admin.to_account_info(),
+        &ctx.accounts.system_program,
+        BridgeConfig::LEN,
+    )?;
+
+    let config = old.into_current();
+    config.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+    msg!("Bridge config migrated to version {}", BridgeConfig::VERSION);
+
+    emit!(AccountMigratedEvent {
+        account: info.key(),
+        version: BridgeConfig::VERSION,
+    });
+
+    Ok(())
+}
+
+// ===== Migrate Lock Record =====
+
+#[derive(Accounts)]
+pub struct MigrateLockRecord<'info> {
+    /// CHECK: May still be in an older layout; owner and discriminator are
+    /// checked by the handler
+    #[account(mut, owner = crate::ID)]
+    pub lock_record: UncheckedAccount<'info>,
+
+    /// Pays for the additional space; anyone may migrate a record
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Convert a lock record to the current layout
+pub fn migrate_lock_record(ctx: Context<MigrateLockRecord>) -> Result<()> {
+    let info = ctx.accounts.lock_record.to_account_info();
+
+    let old = {
+        let data = info.try_borrow_data()?;
+        require!(
+            data.len() >= 8 && data[..8] == LockRecord::DISCRIMINATOR,
+            BridgeError::UnknownAccountLayout
+        );
+        require!(data.len() != LockRecord::LEN, BridgeError::AlreadyMigrated);
+        require!(data.len() == LockRecordV0::LEN, BridgeError::UnknownAccountLayout);
+        LockRecordV0::deserialize(&mut &data[8..])?
+    };
+
+    resize_account(
+        &info,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program,
+        LockRecord::LEN,
+    )?;
+
+    let record = old.into_current();
+    record.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+    msg!("Lock record migrated to version {}", LockRecord::VERSION);
+
+    emit!(AccountMigratedEvent {
+        account: info.key(),
+        version: LockRecord::VERSION,
+    });
+
+    Ok(())
+}
+
+// Helper function to reallocate an account, topping up rent from `payer`
+fn resize_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+    new_len: usize,
+) -> Result<()> {
+    let required = Rent::get()?.minimum_balance(new_len);
+    let shortfall = required.saturating_sub(account.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    account.realloc(new_len, true)?;
+
+    Ok(())
+}
+
+#[event]
+pub struct AccountMigratedEvent {
+    pub account: Pubkey,
+    pub version: u8,
+}
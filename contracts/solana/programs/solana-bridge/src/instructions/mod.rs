@@ -1,9 +1,41 @@
+// Every instruction module exports a `handler`; lib.rs calls them by path
+#![allow(ambiguous_glob_reexports)]
+
 pub mod initialize;
 pub mod lock_token;
 pub mod unlock_token;
+pub mod unlock_token_aggregate;
 pub mod admin;
+pub mod submit_batch_root;
+pub mod claim_from_batch;
+pub mod send_message;
+pub mod receive_message;
+pub mod execute_message;
+pub mod lock_token_and_call;
+pub mod unlock_token_and_call;
+pub mod execute_call;
+pub mod fallback_call;
+pub mod refund;
+pub mod expire_lock;
+pub mod acknowledge_delivery;
+pub mod close_message_record;
+pub mod close_lock_record;
+pub mod migrate;
 
 pub use initialize::*;
 pub use lock_token::*;
 pub use unlock_token::*;
 pub use admin::*;
+pub use submit_batch_root::*;
+pub use claim_from_batch::*;
+pub use send_message::*;
+pub use receive_message::*;
+pub use execute_message::*;
+pub use unlock_token_and_call::*;
+pub use execute_call::*;
+pub use fallback_call::*;
+pub use refund::*;
+pub use acknowledge_delivery::*;
+pub use close_message_record::*;
+pub use close_lock_record::*;
+pub use migrate::*;
@@ -0,0 +1,133 @@
+use anchor_lang::prelude::*;
+use bridge_types::hash::SolanaKeccak;
+use bridge_types::{chain, MessageDeliveryAttestation};
+use solana_program::sysvar::instructions as sysvar_instructions;
+use crate::state::*;
+use crate::error::*;
+use crate::ed25519::verify_attestation_instruction;
+
+#[derive(Accounts)]
+#[instruction(message_id: [u8; 32])]
+pub struct ReceiveMessage<'info> {
+    #[account(
+        seeds = [b"bridge_config"],
+        bump = bridge_config.bump,
+    )]
+    pub bridge_config: Account<'info, BridgeConfig>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = InboundMessage::LEN,
+        seeds = [b"inbound_message", message_id.as_ref()],
+        bump
+    )]
+    pub inbound_message: Account<'info, InboundMessage>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Instructions sysvar, used to inspect the Ed25519 precompile instruction
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions_sysvar: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Record a validator-attested message for delivery. The attestation is
+/// checked by the Ed25519 precompile instruction that must immediately precede
+/// this one; delivery happens separately through `execute_message`.
+pub fn handler(
+    ctx: Context<ReceiveMessage>,
+    message_id: [u8; 32],
+    source_chain: String,
+    sender: String,
+    target_program: Pubkey,
+    payload: Vec<u8>,
+    aggregate_signature: Option<AggregateSignature>,
+) -> Result<()> {
+    let bridge_config = &ctx.accounts.bridge_config;
+
+    // Check bridge is not paused
+    require!(!bridge_config.is_paused, BridgeError::BridgePaused);
+
+    // Validate lengths
+    require!(
+        source_chain.len() <= InboundMessage::MAX_CHAIN_LEN,
+        BridgeError::SourceChainTooLong
+    );
+    require!(
+        sender.len() <= InboundMessage::MAX_SENDER_LEN,
+        BridgeError::SenderAddressTooLong
+    );
+    require!(
+        payload.len() <= InboundMessage::MAX_PAYLOAD_LEN,
+        BridgeError::PayloadTooLarge
+    );
+    require!(target_program != crate::ID, BridgeError::InvalidMessageTarget);
+
+    // Verify validator attestation
+    let message_hash = create_message_hash(
+        &message_id,
+        &source_chain,
+        &sender,
+        &target_program,
+        &payload,
+    );
+    verify_attestation_instruction(
+        &ctx.accounts.instructions_sysvar,
+        bridge_config,
+        &message_hash,
+        aggregate_signature.as_ref(),
+    )?;
+
+    let inbound_message = &mut ctx.accounts.inbound_message;
+    inbound_message.message_id = message_id;
+    inbound_message.source_chain = source_chain.clone();
+    inbound_message.sender = sender;
+    inbound_message.target_program = target_program;
+    inbound_message.payload = payload;
+    inbound_message.status = DeliveryStatus::Pending;
+    inbound_message.received_at = Clock::get()?.unix_timestamp;
+    inbound_message.delivered_at = 0;
+    inbound_message.bump = ctx.bumps.inbound_message;
+
+    msg!("Message received: source={}, target={}", source_chain, target_program);
+
+    emit!(MessageReceivedEvent {
+        message_id,
+        source_chain,
+        target_program,
+        timestamp: inbound_message.received_at,
+    });
+
+    Ok(())
+}
+
+// Helper function to create the message hash validators sign
+pub(crate) fn create_message_hash(
+    message_id: &[u8; 32],
+    source_chain: &str,
+    sender: &str,
+    target_program: &Pubkey,
+    payload: &[u8],
+) -> [u8; 32] {
+    MessageDeliveryAttestation {
+        message_id: *message_id,
+        source_chain: source_chain.to_string(),
+        sender: sender.to_string(),
+        destination_chain: chain::SOLANA.to_string(),
+        destination_bridge: crate::ID.to_bytes().to_vec(),
+        target: target_program.to_bytes().to_vec(),
+        payload: payload.to_vec(),
+    }
+    .signing_hash::<SolanaKeccak>()
+}
+
+#[event]
+pub struct MessageReceivedEvent {
+    pub message_id: [u8; 32],
+    pub source_chain: String,
+    pub target_program: Pubkey,
+    pub timestamp: i64,
+}
@@ -0,0 +1,175 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+use solana_program::keccak;
+use solana_program::sysvar::instructions as sysvar_instructions;
+use crate::state::*;
+use crate::error::*;
+use crate::ed25519::verify_attestation_instruction;
+use super::unlock_token::transfer_from_vault;
+
+#[derive(Accounts)]
+pub struct Refund<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_config"],
+        bump = bridge_config.bump,
+    )]
+    pub bridge_config: Account<'info, BridgeConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"lock_record", lock_record.sender.as_ref(), &lock_record.nonce.to_le_bytes()],
+        bump = lock_record.bump,
+    )]
+    pub lock_record: Account<'info, LockRecord>,
+
+    #[account(
+        mut,
+        seeds = [b"token_vault", token_mint.key().as_ref()],
+        bump = token_vault.bump,
+    )]
+    pub token_vault: Account<'info, TokenVault>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.owner == bridge_config.key(),
+        constraint = vault_token_account.mint == token_mint.key(),
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = sender_token_account.owner == lock_record.sender,
+        constraint = sender_token_account.mint == token_mint.key(),
+    )]
+    pub sender_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Must be the mint recorded for the lock
+    #[account(address = lock_record.token_mint)]
+    pub token_mint: AccountInfo<'info>,
+
+    /// CHECK: Instructions sysvar, used to inspect the Ed25519 precompile instruction
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions_sysvar: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Return locked tokens to the sender after the validators attest that the
+/// transfer failed on the destination chain. Callable by anyone.
+pub fn handler(ctx: Context<Refund>, aggregate_signature: Option<AggregateSignature>) -> Result<()> {
+    return_to_sender(ctx, LockStatus::Refunded, aggregate_signature)
+}
+
+/// Move a pending lock to `Refunded` or `Expired` on a validator attestation
+/// and send the tokens back to the sender
+pub(crate) fn return_to_sender(
+    ctx: Context<Refund>,
+    status: LockStatus,
+    aggregate_signature: Option<AggregateSignature>,
+) -> Result<()> {
+    let bridge_config = &ctx.accounts.bridge_config;
+    let lock_record = &ctx.accounts.lock_record;
+
+    // Check bridge is not paused
+    require!(!bridge_config.is_paused, BridgeError::BridgePaused);
+
+    // Only pending locks can be returned
+    require!(lock_record.status == LockStatus::Pending, BridgeError::LockNotPending);
+
+    // Verify validator attestation
+    let domain = match status {
+        LockStatus::Refunded => LockRecord::REFUND_DOMAIN,
+        LockStatus::Expired => LockRecord::EXPIRED_DOMAIN,
+        _ => return err!(BridgeError::LockNotPending),
+    };
+    let message_hash = create_lock_outcome_hash(domain, &bridge_config.key(), lock_record, &[]);
+    verify_attestation_instruction(
+        &ctx.accounts.instructions_sysvar,
+        bridge_config,
+        &message_hash,
+        aggregate_signature.as_ref(),
+    )?;
+
+    let amount = lock_record.amount;
+
+    transfer_from_vault(
+        &ctx.accounts.bridge_config,
+        &ctx.accounts.vault_token_account,
+        &ctx.accounts.sender_token_account,
+        &ctx.accounts.token_program,
+        amount,
+    )?;
+
+    // Update vault stats
+    let token_vault = &mut ctx.accounts.token_vault;
+    token_vault.total_locked = token_vault.total_locked
+        .checked_sub(amount)
+        .ok_or(BridgeError::ArithmeticOverflow)?;
+
+    // Update bridge stats
+    let bridge_config = &mut ctx.accounts.bridge_config;
+    bridge_config.total_locked = bridge_config.total_locked
+        .checked_sub(amount)
+        .ok_or(BridgeError::ArithmeticOverflow)?;
+
+    let lock_record = &mut ctx.accounts.lock_record;
+    lock_record.status = status;
+
+    let timestamp = Clock::get()?.unix_timestamp;
+
+    msg!("Lock refunded: amount={}, sender={}", amount, lock_record.sender);
+
+    emit!(LockStatusChangedEvent {
+        message_id: lock_record.message_id,
+        status: lock_record.status.clone(),
+        timestamp,
+    });
+
+    emit!(TokenRefundedEvent {
+        message_id: lock_record.message_id,
+        sender: lock_record.sender,
+        token_mint: lock_record.token_mint,
+        amount,
+        timestamp,
+    });
+
+    Ok(())
+}
+
+// Helper function to create the hash validators sign to attest the outcome of
+// an outbound transfer. `domain` selects the outcome; `extra` carries
+// outcome-specific data such as the delivering transaction.
+pub(crate) fn create_lock_outcome_hash(
+    domain: &[u8],
+    bridge_config: &Pubkey,
+    lock_record: &LockRecord,
+    extra: &[u8],
+) -> [u8; 32] {
+    let mut data = Vec::new();
+    data.extend_from_slice(domain);
+    data.extend_from_slice(bridge_config.as_ref());
+    data.extend_from_slice(&lock_record.message_id);
+    data.extend_from_slice(lock_record.sender.as_ref());
+    data.extend_from_slice(lock_record.token_mint.as_ref());
+    data.extend_from_slice(&lock_record.amount.to_le_bytes());
+    data.extend_from_slice(extra);
+
+    keccak::hash(&data).to_bytes()
+}
+
+#[event]
+pub struct TokenRefundedEvent {
+    pub message_id: [u8; 32],
+    pub sender: Pubkey,
+    pub token_mint: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LockStatusChangedEvent {
+    pub message_id: [u8; 32],
+    pub status: LockStatus,
+    pub timestamp: i64,
+}
@@ -0,0 +1,85 @@
+use anchor_lang::prelude::*;
+use bridge_types::hash::SolanaKeccak;
+use bridge_types::{chain, GenericMessage};
+use crate::state::*;
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct SendMessage<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_config"],
+        bump = bridge_config.bump,
+    )]
+    pub bridge_config: Account<'info, BridgeConfig>,
+
+    pub sender: Signer<'info>,
+}
+
+pub fn handler(
+    ctx: Context<SendMessage>,
+    destination_chain: String,
+    target: String,
+    payload: Vec<u8>,
+) -> Result<()> {
+    let bridge_config = &mut ctx.accounts.bridge_config;
+
+    // Check bridge is not paused
+    require!(!bridge_config.is_paused, BridgeError::BridgePaused);
+
+    // Validate lengths
+    require!(
+        destination_chain.len() <= LockRecord::MAX_CHAIN_LEN,
+        BridgeError::DestinationChainTooLong
+    );
+    require!(
+        target.len() <= LockRecord::MAX_ADDRESS_LEN,
+        BridgeError::DestinationAddressTooLong
+    );
+    require!(
+        payload.len() <= InboundMessage::MAX_PAYLOAD_LEN,
+        BridgeError::PayloadTooLarge
+    );
+
+    let nonce = bridge_config.message_count;
+    bridge_config.message_count = bridge_config.message_count
+        .checked_add(1)
+        .ok_or(BridgeError::ArithmeticOverflow)?;
+
+    let sender = ctx.accounts.sender.key();
+    let message_id = GenericMessage {
+        source_chain: chain::SOLANA.to_string(),
+        source_bridge: crate::ID.to_bytes().to_vec(),
+        sequence: nonce,
+        sender: sender.to_bytes().to_vec(),
+        destination_chain: destination_chain.clone(),
+        target: target.clone(),
+        payload: payload.clone(),
+    }
+    .id::<SolanaKeccak>();
+
+    msg!("Message sent: destination={}, target={}", destination_chain, target);
+
+    emit!(MessageSentEvent {
+        message_id,
+        sender,
+        destination_chain,
+        target,
+        payload,
+        nonce,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct MessageSentEvent {
+    pub message_id: [u8; 32],
+    pub sender: Pubkey,
+    pub destination_chain: String,
+    pub target: String,
+    pub payload: Vec<u8>,
+    pub nonce: u64,
+    pub timestamp: i64,
+}
@@ -0,0 +1,102 @@
+use anchor_lang::prelude::*;
+use solana_program::keccak;
+use solana_program::sysvar::instructions as sysvar_instructions;
+use crate::state::*;
+use crate::error::*;
+use crate::ed25519::verify_attestation_instruction;
+
+#[derive(Accounts)]
+#[instruction(batch_root: [u8; 32])]
+pub struct SubmitBatchRoot<'info> {
+    #[account(
+        seeds = [b"bridge_config"],
+        bump = bridge_config.bump,
+    )]
+    pub bridge_config: Account<'info, BridgeConfig>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = BatchRoot::LEN,
+        seeds = [b"batch_root", batch_root.as_ref()],
+        bump
+    )]
+    pub batch_root_account: Account<'info, BatchRoot>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Instructions sysvar, used to inspect the Ed25519 precompile instruction
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions_sysvar: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Record a batch Merkle root signed once by the validators. The validator
+/// signatures are checked by the Ed25519 precompile instruction that must
+/// immediately precede this one.
+pub fn handler(
+    ctx: Context<SubmitBatchRoot>,
+    batch_root: [u8; 32],
+    message_count: u32,
+    aggregate_signature: Option<AggregateSignature>,
+) -> Result<()> {
+    let bridge_config = &ctx.accounts.bridge_config;
+
+    // Check bridge is not paused
+    require!(!bridge_config.is_paused, BridgeError::BridgePaused);
+
+    // Validate batch size
+    require!(message_count > 0, BridgeError::InvalidBatch);
+
+    // Verify validator attestation
+    let root_hash = create_batch_root_hash(&bridge_config.key(), &batch_root, message_count);
+    verify_attestation_instruction(
+        &ctx.accounts.instructions_sysvar,
+        bridge_config,
+        &root_hash,
+        aggregate_signature.as_ref(),
+    )?;
+
+    let batch_root_account = &mut ctx.accounts.batch_root_account;
+    batch_root_account.root = batch_root;
+    batch_root_account.message_count = message_count;
+    batch_root_account.claimed_count = 0;
+    batch_root_account.timestamp = Clock::get()?.unix_timestamp;
+    batch_root_account.bump = ctx.bumps.batch_root_account;
+
+    msg!("Batch root submitted: messages={}", message_count);
+
+    emit!(BatchRootSubmittedEvent {
+        batch_root,
+        message_count,
+        timestamp: batch_root_account.timestamp,
+    });
+
+    Ok(())
+}
+
+// Helper function to create the hash validators sign to attest a batch root.
+// Binding the bridge config address keeps a root attested for one deployment
+// from being replayed on another.
+pub(crate) fn create_batch_root_hash(
+    bridge_config: &Pubkey,
+    batch_root: &[u8; 32],
+    message_count: u32,
+) -> [u8; 32] {
+    let mut data = Vec::new();
+    data.extend_from_slice(BatchRoot::DOMAIN);
+    data.extend_from_slice(bridge_config.as_ref());
+    data.extend_from_slice(batch_root);
+    data.extend_from_slice(&message_count.to_le_bytes());
+
+    keccak::hash(&data).to_bytes()
+}
+
+#[event]
+pub struct BatchRootSubmittedEvent {
+    pub batch_root: [u8; 32],
+    pub message_count: u32,
+    pub timestamp: i64,
+}
@@ -1,11 +1,14 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
-use solana_program::ed25519_program;
+use solana_program::sysvar::instructions as sysvar_instructions;
+use bridge_types::hash::SolanaKeccak;
+use bridge_types::{chain, UnlockAttestation};
 use crate::state::*;
 use crate::error::*;
+use crate::ed25519::verify_validator_signatures;
 
 #[derive(Accounts)]
-#[instruction(message_id: [u8; 32])]
+#[instruction(message_id: [u8; 32], source_chain: String, sequence: u64)]
 pub struct UnlockToken<'info> {
     #[account(
         mut,
@@ -25,11 +28,24 @@ pub struct UnlockToken<'info> {
         init,
         payer = payer,
         space = MessageRecord::LEN,
-        seeds = [b"message_record", &message_id],
+        seeds = [b"message_record", message_id.as_ref()],
         bump
     )]
     pub message_record: Account<'info, MessageRecord>,
 
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = ProcessedSequences::LEN,
+        seeds = [
+            b"processed_sequences".as_ref(),
+            &ProcessedSequences::chain_seed(&source_chain),
+            &ProcessedSequences::window_of(sequence).to_le_bytes(),
+        ],
+        bump
+    )]
+    pub processed_sequences: Box<Account<'info, ProcessedSequences>>,
+
     #[account(mut)]
     pub payer: Signer<'info>,
 
@@ -53,6 +69,10 @@ pub struct UnlockToken<'info> {
     /// CHECK: Token mint account
     pub token_mint: AccountInfo<'info>,
 
+    /// CHECK: Instructions sysvar, used to inspect the Ed25519 precompile instruction
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions_sysvar: AccountInfo<'info>,
+
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
@@ -60,18 +80,19 @@ pub struct UnlockToken<'info> {
 pub fn handler(
     ctx: Context<UnlockToken>,
     message_id: [u8; 32],
+    source_chain: String,
+    sequence: u64,
+    sender: String,
     amount: u64,
     signatures: Vec<[u8; 64]>,
 ) -> Result<()> {
     let bridge_config = &ctx.accounts.bridge_config;
-    let token_vault = &mut ctx.accounts.token_vault;
-    let message_record = &mut ctx.accounts.message_record;
 
     // Check bridge is not paused
     require!(!bridge_config.is_paused, BridgeError::BridgePaused);
 
-    // Validate amount
-    require!(amount > 0, BridgeError::InvalidAmount);
+    // Validate inputs
+    validate_unlock(&source_chain, &sender, amount)?;
 
     // Check sufficient signatures
     require!(
@@ -79,31 +100,66 @@ pub fn handler(
         BridgeError::InsufficientSignatures
     );
 
-    // Verify signatures
+    // Verify signatures against the preceding Ed25519 precompile instruction
     let message_hash = create_unlock_message_hash(
         &message_id,
+        &source_chain,
+        sequence,
+        &sender,
         &ctx.accounts.recipient.key(),
         &ctx.accounts.token_mint.key(),
         amount,
     );
+    verify_validator_signatures(
+        &ctx.accounts.instructions_sysvar,
+        bridge_config,
+        &message_hash,
+        &signatures,
+    )?;
 
-    let mut valid_signatures = 0;
-    for signature in signatures.iter() {
-        for validator in bridge_config.validators.iter() {
-            if verify_ed25519_signature(&message_hash, signature, validator)? {
-                valid_signatures += 1;
-                break;
-            }
-        }
-    }
+    execute_unlock(ctx, message_id, source_chain, sequence, sender, amount)
+}
 
+/// Check the unlock parameters fit the message record
+pub(crate) fn validate_unlock(source_chain: &str, sender: &str, amount: u64) -> Result<()> {
+    require!(amount > 0, BridgeError::InvalidAmount);
     require!(
-        valid_signatures >= bridge_config.required_signatures as usize,
-        BridgeError::InsufficientSignatures
+        source_chain.len() <= MessageRecord::MAX_CHAIN_LEN,
+        BridgeError::SourceChainTooLong
+    );
+    require!(
+        sender.len() <= MessageRecord::MAX_SENDER_LEN,
+        BridgeError::SenderAddressTooLong
+    );
+
+    Ok(())
+}
+
+/// Release `amount` from the vault to the recipient and record the message.
+/// Callers must have verified the attestation for `message_id` beforehand.
+pub(crate) fn execute_unlock(
+    ctx: Context<UnlockToken>,
+    message_id: [u8; 32],
+    source_chain: String,
+    sequence: u64,
+    sender: String,
+    amount: u64,
+) -> Result<()> {
+    // Check the transfer was not released before, even if its record was closed
+    let processed_sequences = &mut ctx.accounts.processed_sequences;
+    require!(
+        !processed_sequences.contains(sequence),
+        BridgeError::MessageAlreadyProcessed
     );
+    processed_sequences.window = ProcessedSequences::window_of(sequence);
+    processed_sequences.insert(sequence);
+    processed_sequences.bump = ctx.bumps.processed_sequences;
+
+    let bridge_config = &ctx.accounts.bridge_config;
+    let token_vault = &mut ctx.accounts.token_vault;
+    let message_record = &mut ctx.accounts.message_record;
 
     // Transfer tokens from vault to recipient
-    let bridge_config_key = bridge_config.key();
     let seeds = &[
         b"bridge_config".as_ref(),
         &[bridge_config.bump],
@@ -132,13 +188,14 @@ pub fn handler(
 
     // Create message record
     message_record.message_id = message_id;
-    message_record.source_chain = String::from("external"); // Would come from signature data
-    message_record.sender = String::from("unknown"); // Would come from signature data
+    message_record.source_chain = source_chain;
+    message_record.sender = sender;
     message_record.recipient = ctx.accounts.recipient.key();
     message_record.token_mint = ctx.accounts.token_mint.key();
     message_record.amount = amount;
     message_record.timestamp = Clock::get()?.unix_timestamp;
     message_record.processed = true;
+    message_record.payer = ctx.accounts.payer.key();
     message_record.bump = ctx.bumps.message_record;
 
     msg!("Token unlocked: amount={}, recipient={}",
@@ -158,37 +215,51 @@ pub fn handler(
     Ok(())
 }
 
-// Helper function to create message hash for signing
-fn create_unlock_message_hash(
+/// Hash validators sign to release an inbound transfer
+pub(crate) fn create_unlock_message_hash(
     message_id: &[u8; 32],
+    source_chain: &str,
+    sequence: u64,
+    sender: &str,
     recipient: &Pubkey,
     token_mint: &Pubkey,
     amount: u64,
 ) -> [u8; 32] {
-    use solana_program::keccak;
-
-    let mut data = Vec::new();
-    data.extend_from_slice(message_id);
-    data.extend_from_slice(recipient.as_ref());
-    data.extend_from_slice(token_mint.as_ref());
-    data.extend_from_slice(&amount.to_le_bytes());
-
-    keccak::hash(&data).to_bytes()
+    UnlockAttestation {
+        message_id: *message_id,
+        source_chain: source_chain.to_string(),
+        sequence,
+        sender: sender.to_string(),
+        destination_chain: chain::SOLANA.to_string(),
+        destination_bridge: crate::ID.to_bytes().to_vec(),
+        recipient: recipient.to_bytes().to_vec(),
+        token: token_mint.to_bytes().to_vec(),
+        amount: u128::from(amount),
+    }
+    .signing_hash::<SolanaKeccak>()
 }
 
-// Helper function to verify Ed25519 signature
-fn verify_ed25519_signature(
-    message: &[u8; 32],
-    signature: &[u8; 64],
-    public_key: &Pubkey,
-) -> Result<bool> {
-    // In production, you would use ed25519_program for verification
-    // For now, we'll do a simplified check
-    // Note: Actual implementation would use ed25519_program::ID and verify instruction
-
-    // This is a placeholder - in production use proper Ed25519 verification
-    // via the ed25519_program
-    Ok(true) // Replace with actual verification
+// Helper function to transfer tokens out of the vault, signed by the bridge config PDA
+pub(crate) fn transfer_from_vault<'info>(
+    bridge_config: &Account<'info, BridgeConfig>,
+    vault_token_account: &Account<'info, TokenAccount>,
+    to: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    amount: u64,
+) -> Result<()> {
+    let seeds = &[
+        b"bridge_config".as_ref(),
+        &[bridge_config.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let cpi_accounts = Transfer {
+        from: vault_token_account.to_account_info(),
+        to: to.to_account_info(),
+        authority: bridge_config.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer);
+    token::transfer(cpi_ctx, amount)
 }
 
 #[event]
@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+use bridge_types::aggregate_signing_hash;
+use bridge_types::hash::SolanaKeccak;
+use crate::state::*;
+use crate::error::*;
+use crate::ed25519::{check_aggregate_signature, verify_signature_instruction};
+use super::unlock_token::{create_unlock_message_hash, execute_unlock, validate_unlock, UnlockToken};
+
+/// Unlock using a single threshold signature (e.g. FROST Ed25519) under the
+/// bridge group key, over the unlock hash and the signer bitmap. The signature
+/// itself is checked by the Ed25519 precompile instruction that must
+/// immediately precede this one.
+pub fn handler(
+    ctx: Context<UnlockToken>,
+    message_id: [u8; 32],
+    source_chain: String,
+    sequence: u64,
+    sender: String,
+    amount: u64,
+    aggregate_signature: AggregateSignature,
+) -> Result<()> {
+    let bridge_config = &ctx.accounts.bridge_config;
+
+    // Check bridge is not paused
+    require!(!bridge_config.is_paused, BridgeError::BridgePaused);
+
+    // Validate inputs
+    validate_unlock(&source_chain, &sender, amount)?;
+
+    // Threshold mode must be enabled and the bitmap must reach the threshold
+    check_aggregate_signature(bridge_config, &aggregate_signature)?;
+    let bitmap = aggregate_signature.signer_bitmap;
+
+    // Verify the group signature, which also covers the bitmap
+    let message_hash = create_unlock_message_hash(
+        &message_id,
+        &source_chain,
+        sequence,
+        &sender,
+        &ctx.accounts.recipient.key(),
+        &ctx.accounts.token_mint.key(),
+        amount,
+    );
+    let signed_hash = aggregate_signing_hash::<SolanaKeccak>(&message_hash, bitmap);
+    verify_signature_instruction(
+        &ctx.accounts.instructions_sysvar,
+        &bridge_config.group_key,
+        &signed_hash,
+        &aggregate_signature.signature,
+    )?;
+
+    msg!("Threshold signature verified: epoch={}, signers={:#010x}",
+        bridge_config.group_key_epoch,
+        bitmap
+    );
+
+    execute_unlock(ctx, message_id, source_chain, sequence, sender, amount)
+}
@@ -0,0 +1,199 @@
+use anchor_lang::prelude::*;
+use solana_program::keccak;
+use solana_program::sysvar::instructions as sysvar_instructions;
+use crate::state::*;
+use crate::error::*;
+use crate::ed25519::verify_attestation_instruction;
+use super::unlock_token::create_unlock_message_hash;
+
+#[derive(Accounts)]
+#[instruction(message_id: [u8; 32], source_chain: String, sequence: u64)]
+pub struct UnlockTokenAndCall<'info> {
+    #[account(
+        seeds = [b"bridge_config"],
+        bump = bridge_config.bump,
+    )]
+    pub bridge_config: Account<'info, BridgeConfig>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = MessageRecord::LEN,
+        seeds = [b"message_record", message_id.as_ref()],
+        bump
+    )]
+    pub message_record: Account<'info, MessageRecord>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = ProcessedSequences::LEN,
+        seeds = [
+            b"processed_sequences".as_ref(),
+            &ProcessedSequences::chain_seed(&source_chain),
+            &ProcessedSequences::window_of(sequence).to_le_bytes(),
+        ],
+        bump
+    )]
+    pub processed_sequences: Box<Account<'info, ProcessedSequences>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = PendingCall::LEN,
+        seeds = [b"pending_call", message_id.as_ref()],
+        bump
+    )]
+    pub pending_call: Account<'info, PendingCall>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Instructions sysvar, used to inspect the Ed25519 precompile instruction
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions_sysvar: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Verify an unlock-and-call and park it until `execute_call` delivers the
+/// tokens to the receiver and invokes it. A failed CPI reverts the whole
+/// transaction on Solana, so the fallback to `recipient` is a separate
+/// instruction available after `PendingCall::FALLBACK_DELAY`.
+#[allow(clippy::too_many_arguments)]
+pub fn handler(
+    ctx: Context<UnlockTokenAndCall>,
+    message_id: [u8; 32],
+    source_chain: String,
+    sequence: u64,
+    sender: String,
+    recipient: Pubkey,
+    token_mint: Pubkey,
+    amount: u64,
+    receiver_program: Pubkey,
+    receiver_authority: Pubkey,
+    call: LockCall,
+    aggregate_signature: Option<AggregateSignature>,
+) -> Result<()> {
+    let bridge_config = &ctx.accounts.bridge_config;
+
+    // Check bridge is not paused
+    require!(!bridge_config.is_paused, BridgeError::BridgePaused);
+
+    // Check the transfer was not released before, even if its record was closed
+    require!(
+        !ctx.accounts.processed_sequences.contains(sequence),
+        BridgeError::MessageAlreadyProcessed
+    );
+
+    // Validate inputs
+    require!(amount > 0, BridgeError::InvalidAmount);
+    require!(
+        source_chain.len() <= MessageRecord::MAX_CHAIN_LEN,
+        BridgeError::SourceChainTooLong
+    );
+    require!(
+        sender.len() <= MessageRecord::MAX_SENDER_LEN,
+        BridgeError::SenderAddressTooLong
+    );
+    require!(
+        call.payload.len() <= LockCall::MAX_PAYLOAD_LEN,
+        BridgeError::PayloadTooLarge
+    );
+    require!(receiver_program != crate::ID, BridgeError::InvalidMessageTarget);
+
+    // Verify validator attestation
+    let unlock_hash = create_unlock_message_hash(
+        &message_id,
+        &source_chain,
+        sequence,
+        &sender,
+        &recipient,
+        &token_mint,
+        amount,
+    );
+    let message_hash = create_unlock_call_message_hash(
+        &unlock_hash,
+        &receiver_program,
+        &receiver_authority,
+        &call,
+    );
+    verify_attestation_instruction(
+        &ctx.accounts.instructions_sysvar,
+        bridge_config,
+        &message_hash,
+        aggregate_signature.as_ref(),
+    )?;
+
+    let timestamp = Clock::get()?.unix_timestamp;
+
+    // Record the message as seen but not yet processed
+    let message_record = &mut ctx.accounts.message_record;
+    message_record.message_id = message_id;
+    message_record.source_chain = source_chain;
+    message_record.sender = sender;
+    message_record.recipient = recipient;
+    message_record.token_mint = token_mint;
+    message_record.amount = amount;
+    message_record.timestamp = timestamp;
+    message_record.processed = false;
+    message_record.payer = ctx.accounts.payer.key();
+    message_record.bump = ctx.bumps.message_record;
+
+    // The sequence is spent now; the call or its fallback releases the tokens
+    let processed_sequences = &mut ctx.accounts.processed_sequences;
+    processed_sequences.window = ProcessedSequences::window_of(sequence);
+    processed_sequences.insert(sequence);
+    processed_sequences.bump = ctx.bumps.processed_sequences;
+
+    let pending_call = &mut ctx.accounts.pending_call;
+    pending_call.message_id = message_id;
+    pending_call.receiver_program = receiver_program;
+    pending_call.receiver_authority = receiver_authority;
+    pending_call.payload = call.payload;
+    pending_call.compute_budget = call.compute_budget;
+    pending_call.status = CallStatus::Pending;
+    pending_call.received_at = timestamp;
+    pending_call.bump = ctx.bumps.pending_call;
+
+    msg!("Unlock call received: amount={}, receiver={}", amount, receiver_program);
+
+    emit!(UnlockCallReceivedEvent {
+        message_id,
+        receiver_program,
+        fallback_recipient: recipient,
+        token_mint,
+        amount,
+        compute_budget: call.compute_budget,
+    });
+
+    Ok(())
+}
+
+// Helper function to create the hash validators sign for an unlock-and-call:
+// the plain unlock hash (with the fallback recipient) extended with the call
+pub(crate) fn create_unlock_call_message_hash(
+    unlock_hash: &[u8; 32],
+    receiver_program: &Pubkey,
+    receiver_authority: &Pubkey,
+    call: &LockCall,
+) -> [u8; 32] {
+    keccak::hashv(&[
+        unlock_hash,
+        receiver_program.as_ref(),
+        receiver_authority.as_ref(),
+        &keccak::hash(&call.payload).to_bytes(),
+        &call.compute_budget.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+#[event]
+pub struct UnlockCallReceivedEvent {
+    pub message_id: [u8; 32],
+    pub receiver_program: Pubkey,
+    pub fallback_recipient: Pubkey,
+    pub token_mint: Pubkey,
+    pub amount: u64,
+    pub compute_budget: u32,
+}
@@ -1,15 +1,18 @@
+// Instruction handlers take the attested fields as separate arguments
+#![allow(clippy::too_many_arguments)]
+
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
 
 pub mod state;
 pub mod error;
 pub mod instructions;
+pub mod ed25519;
+pub mod merkle;
 
 use state::*;
-use error::*;
 use instructions::*;
 
-declare_id!("BrdgE1111111111111111111111111111111111111111");
+declare_id!("Brdg111111111111111111111111111111111111111");
 
 #[program]
 pub mod solana_bridge {
@@ -35,14 +38,228 @@ pub mod solana_bridge {
         instructions::lock_token::handler(ctx, amount, destination_chain, destination_address, nonce)
     }
 
+    /// Lock tokens and attach a call to execute on the destination chain
+    pub fn lock_token_and_call(
+        ctx: Context<LockToken>,
+        amount: u64,
+        destination_chain: String,
+        destination_address: String,
+        nonce: u64,
+        call: LockCall,
+    ) -> Result<()> {
+        instructions::lock_token_and_call::handler(ctx, amount, destination_chain, destination_address, nonce, call)
+    }
+
     /// Unlock tokens after cross-chain transfer
     pub fn unlock_token(
         ctx: Context<UnlockToken>,
         message_id: [u8; 32],
+        source_chain: String,
+        sequence: u64,
+        sender: String,
         amount: u64,
         signatures: Vec<[u8; 64]>,
     ) -> Result<()> {
-        instructions::unlock_token::handler(ctx, message_id, amount, signatures)
+        instructions::unlock_token::handler(
+            ctx,
+            message_id,
+            source_chain,
+            sequence,
+            sender,
+            amount,
+            signatures,
+        )
+    }
+
+    /// Unlock tokens with a single threshold signature under the group key
+    pub fn unlock_token_aggregate(
+        ctx: Context<UnlockToken>,
+        message_id: [u8; 32],
+        source_chain: String,
+        sequence: u64,
+        sender: String,
+        amount: u64,
+        aggregate_signature: AggregateSignature,
+    ) -> Result<()> {
+        instructions::unlock_token_aggregate::handler(
+            ctx,
+            message_id,
+            source_chain,
+            sequence,
+            sender,
+            amount,
+            aggregate_signature,
+        )
+    }
+
+    /// Record a validator-attested batch Merkle root
+    pub fn submit_batch_root(
+        ctx: Context<SubmitBatchRoot>,
+        batch_root: [u8; 32],
+        message_count: u32,
+        aggregate_signature: Option<AggregateSignature>,
+    ) -> Result<()> {
+        instructions::submit_batch_root::handler(ctx, batch_root, message_count, aggregate_signature)
+    }
+
+    /// Unlock a single message from an attested batch with a Merkle proof
+    #[allow(clippy::too_many_arguments)]
+    pub fn claim_from_batch(
+        ctx: Context<ClaimFromBatch>,
+        batch_root: [u8; 32],
+        message_id: [u8; 32],
+        source_chain: String,
+        sequence: u64,
+        destination_chain: String,
+        sender: String,
+        payload: Vec<u8>,
+        leaf_index: u32,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::claim_from_batch::handler(
+            ctx,
+            batch_root,
+            message_id,
+            source_chain,
+            sequence,
+            destination_chain,
+            sender,
+            payload,
+            leaf_index,
+            proof,
+        )
+    }
+
+    /// Verify an unlock-and-call and park it for execution
+    #[allow(clippy::too_many_arguments)]
+    pub fn unlock_token_and_call(
+        ctx: Context<UnlockTokenAndCall>,
+        message_id: [u8; 32],
+        source_chain: String,
+        sequence: u64,
+        sender: String,
+        recipient: Pubkey,
+        token_mint: Pubkey,
+        amount: u64,
+        receiver_program: Pubkey,
+        receiver_authority: Pubkey,
+        call: LockCall,
+        aggregate_signature: Option<AggregateSignature>,
+    ) -> Result<()> {
+        instructions::unlock_token_and_call::handler(
+            ctx,
+            message_id,
+            source_chain,
+            sequence,
+            sender,
+            recipient,
+            token_mint,
+            amount,
+            receiver_program,
+            receiver_authority,
+            call,
+            aggregate_signature,
+        )
+    }
+
+    /// Deliver tokens to the receiver and invoke it with the attached call
+    pub fn execute_call<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteCall<'info>>,
+    ) -> Result<()> {
+        instructions::execute_call::handler(ctx)
+    }
+
+    /// Send an unexecuted unlock-and-call to the original recipient after the delay
+    pub fn fallback_call(ctx: Context<FallbackCall>) -> Result<()> {
+        instructions::fallback_call::handler(ctx)
+    }
+
+    /// Refund a lock the validators attested as failed on the destination chain
+    pub fn refund(
+        ctx: Context<Refund>,
+        aggregate_signature: Option<AggregateSignature>,
+    ) -> Result<()> {
+        instructions::refund::handler(ctx, aggregate_signature)
+    }
+
+    /// Refund a lock that was not delivered within the expiry period
+    pub fn expire_lock(
+        ctx: Context<Refund>,
+        aggregate_signature: Option<AggregateSignature>,
+    ) -> Result<()> {
+        instructions::expire_lock::handler(ctx, aggregate_signature)
+    }
+
+    /// Mark a lock as delivered on the destination chain
+    pub fn acknowledge_delivery(
+        ctx: Context<AcknowledgeDelivery>,
+        destination_tx: String,
+        aggregate_signature: Option<AggregateSignature>,
+    ) -> Result<()> {
+        instructions::acknowledge_delivery::handler(ctx, destination_tx, aggregate_signature)
+    }
+
+    /// Convert the bridge config to the current account layout
+    pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+        instructions::migrate::migrate_config(ctx)
+    }
+
+    /// Convert a lock record to the current account layout
+    pub fn migrate_lock_record(ctx: Context<MigrateLockRecord>) -> Result<()> {
+        instructions::migrate::migrate_lock_record(ctx)
+    }
+
+    /// Hand the program upgrade authority over to governance
+    pub fn handover_upgrade_authority(ctx: Context<HandoverUpgradeAuthority>) -> Result<()> {
+        instructions::admin::handover_upgrade_authority(ctx)
+    }
+
+    /// Close a finalized lock record and return its rent to the sender
+    pub fn close_lock_record(ctx: Context<CloseLockRecord>) -> Result<()> {
+        instructions::close_lock_record::handler(ctx)
+    }
+
+    /// Close a processed message record and return its rent to the payer
+    pub fn close_message_record(ctx: Context<CloseMessageRecord>) -> Result<()> {
+        instructions::close_message_record::handler(ctx)
+    }
+
+    /// Send an arbitrary payload to a target on another chain
+    pub fn send_message(
+        ctx: Context<SendMessage>,
+        destination_chain: String,
+        target: String,
+        payload: Vec<u8>,
+    ) -> Result<()> {
+        instructions::send_message::handler(ctx, destination_chain, target, payload)
+    }
+
+    /// Record a validator-attested inbound message for delivery
+    pub fn receive_message(
+        ctx: Context<ReceiveMessage>,
+        message_id: [u8; 32],
+        source_chain: String,
+        sender: String,
+        target_program: Pubkey,
+        payload: Vec<u8>,
+        aggregate_signature: Option<AggregateSignature>,
+    ) -> Result<()> {
+        instructions::receive_message::handler(
+            ctx,
+            message_id,
+            source_chain,
+            sender,
+            target_program,
+            payload,
+            aggregate_signature,
+        )
+    }
+
+    /// Deliver a received message to its target program via CPI (retryable)
+    pub fn execute_message<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteMessage<'info>>,
+    ) -> Result<()> {
+        instructions::execute_message::handler(ctx)
     }
 
     /// Add a new validator (admin only)
@@ -50,7 +267,7 @@ pub mod solana_bridge {
         ctx: Context<UpdateValidators>,
         validator: Pubkey,
     ) -> Result<()> {
-        instructions::add_validator::handler(ctx, validator)
+        instructions::admin::add_validator(ctx, validator)
     }
 
     /// Remove a validator (admin only)
@@ -58,7 +275,7 @@ pub mod solana_bridge {
         ctx: Context<UpdateValidators>,
         validator: Pubkey,
     ) -> Result<()> {
-        instructions::remove_validator::handler(ctx, validator)
+        instructions::admin::remove_validator(ctx, validator)
     }
 
     /// Update required signatures (admin only)
@@ -66,16 +283,34 @@ pub mod solana_bridge {
         ctx: Context<UpdateConfig>,
         required_signatures: u8,
     ) -> Result<()> {
-        instructions::update_required_signatures::handler(ctx, required_signatures)
+        instructions::admin::update_required_signatures(ctx, required_signatures)
+    }
+
+    /// Rotate the threshold group key (admin only)
+    pub fn set_group_key(
+        ctx: Context<UpdateConfig>,
+        group_key: Pubkey,
+        threshold: u8,
+        group_size: u8,
+    ) -> Result<()> {
+        instructions::admin::set_group_key(ctx, group_key, threshold, group_size)
+    }
+
+    /// Switch between individual and threshold signature verification (admin only)
+    pub fn set_signature_mode(
+        ctx: Context<UpdateConfig>,
+        mode: SignatureMode,
+    ) -> Result<()> {
+        instructions::admin::set_signature_mode(ctx, mode)
     }
 
     /// Pause the bridge (admin only)
     pub fn pause(ctx: Context<UpdateConfig>) -> Result<()> {
-        instructions::pause::handler(ctx)
+        instructions::admin::pause(ctx)
     }
 
     /// Unpause the bridge (admin only)
     pub fn unpause(ctx: Context<UpdateConfig>) -> Result<()> {
-        instructions::unpause::handler(ctx)
+        instructions::admin::unpause(ctx)
     }
 }
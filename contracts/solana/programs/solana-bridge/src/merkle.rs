@@ -0,0 +1,82 @@
+//! Batch Merkle proofs compatible with `internal/batching/merkle.go`.
+//!
+//! The Go pipeline works on lowercase hex strings rather than raw digests,
+//! so the on-chain verifier reproduces that exactly:
+//!
+//! - Leaf: `sha256(hex(message_id) || lp(source_chain) || u64_le(sequence) || lp(destination_chain) || lp(sender) || lp(recipient) || lp(payload))`,
+//!   where `lp(x) = u32_le(len(x)) || x` and `recipient` is the base58
+//!   recipient pubkey. The prefixes keep bytes from moving between
+//!   neighbouring fields, such as from the sender into the recipient.
+//! - Parent: `sha256(hex(left) || hex(right))`
+//! - Sibling order: while walking up, an even index means the running hash is
+//!   the left input and the sibling the right one; an odd index swaps them.
+//!   The index is halved after every level.
+//! - A layer with an odd number of nodes duplicates its last node, so a proof
+//!   may contain the node's own hash as its sibling.
+//!
+//! `message_id` is the 32-byte on-chain ID (`CrossChainMessage.OnChainID`)
+//! and the chains are the `bridge_types::chain` names
+//! (`ChainInfo.BridgeName`), whatever the relayer config calls them.
+//! `sequence` is the transfer's sequence on the source bridge
+//! (`CrossChainMessage.Nonce`), by which the claim is recorded.
+//!
+//! Roots and siblings are passed as the raw 32-byte digests of those hex strings.
+
+use bridge_types::encoding::{push_prefixed, push_u64};
+use solana_program::hash::hashv;
+
+/// Lowercase hex encoding of a 32-byte digest
+pub fn to_hex(bytes: &[u8; 32]) -> [u8; 64] {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+    let mut out = [0u8; 64];
+    for (i, byte) in bytes.iter().enumerate() {
+        out[2 * i] = HEX[(byte >> 4) as usize];
+        out[2 * i + 1] = HEX[(byte & 0x0f) as usize];
+    }
+    out
+}
+
+/// Hash a batch leaf the same way as `hashMessage` in merkle.go
+pub fn hash_leaf(
+    message_id: &[u8; 32],
+    source_chain: &str,
+    sequence: u64,
+    destination_chain: &str,
+    sender: &str,
+    recipient: &str,
+    payload: &[u8],
+) -> [u8; 32] {
+    let mut data = Vec::new();
+    data.extend_from_slice(&to_hex(message_id));
+    push_prefixed(&mut data, source_chain.as_bytes());
+    push_u64(&mut data, sequence);
+    push_prefixed(&mut data, destination_chain.as_bytes());
+    push_prefixed(&mut data, sender.as_bytes());
+    push_prefixed(&mut data, recipient.as_bytes());
+    push_prefixed(&mut data, payload);
+
+    hashv(&[&data]).to_bytes()
+}
+
+/// Hash two child nodes the same way as `hashPair` in merkle.go
+pub fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    hashv(&[&to_hex(left), &to_hex(right)]).to_bytes()
+}
+
+/// Verify a proof the same way as `VerifyProof` in merkle.go
+pub fn verify_proof(leaf: &[u8; 32], index: u32, proof: &[[u8; 32]], root: &[u8; 32]) -> bool {
+    let mut current = *leaf;
+    let mut index = index;
+
+    for sibling in proof.iter() {
+        // Even index: `is_multiple_of` is newer than the SBF toolchain's rustc
+        current = if index & 1 == 0 {
+            hash_pair(&current, sibling)
+        } else {
+            hash_pair(sibling, &current)
+        };
+        index /= 2;
+    }
+
+    &current == root
+}
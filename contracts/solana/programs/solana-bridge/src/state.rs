@@ -3,6 +3,9 @@ use anchor_lang::prelude::*;
 /// Bridge configuration and state
 #[account]
 pub struct BridgeConfig {
+    /// Layout version, see `BridgeConfig::VERSION`
+    pub version: u8,
+
     /// Authority that can manage the bridge
     pub admin: Pubkey,
 
@@ -26,12 +29,37 @@ pub struct BridgeConfig {
 
     /// Bump seed for PDA
     pub bump: u8,
+
+    /// How unlock attestations are verified
+    pub signature_mode: SignatureMode,
+
+    /// Threshold group key (default pubkey when unset)
+    pub group_key: Pubkey,
+
+    /// Minimum number of group participants that must sign
+    pub group_threshold: u8,
+
+    /// Number of participants holding key shares
+    pub group_size: u8,
+
+    /// Incremented on every group key rotation
+    pub group_key_epoch: u64,
+
+    /// Program upgrade authority after handover (default pubkey until then)
+    pub governance: Pubkey,
+
+    /// Space for future fields without reallocation
+    pub reserved: [u8; 128],
 }
 
 impl BridgeConfig {
+    /// Current layout version
+    pub const VERSION: u8 = 1;
+
     pub const MAX_VALIDATORS: usize = 10;
 
     pub const LEN: usize = 8 + // discriminator
+        1 + // version
         32 + // admin
         (4 + 32 * Self::MAX_VALIDATORS) + // validators vec
         1 + // required_signatures
@@ -39,11 +67,45 @@ impl BridgeConfig {
         8 + // total_locked
         8 + // total_unlocked
         8 + // message_count
-        1; // bump
+        1 + // bump
+        1 + // signature_mode
+        32 + // group_key
+        1 + // group_threshold
+        1 + // group_size
+        8 + // group_key_epoch
+        32 + // governance
+        128; // reserved
+
+    /// Maximum participants addressable by the signer bitmap
+    pub const MAX_GROUP_SIZE: u8 = 32;
 
     pub fn is_validator(&self, pubkey: &Pubkey) -> bool {
         self.validators.contains(pubkey)
     }
+
+    pub fn has_group_key(&self) -> bool {
+        self.group_key != Pubkey::default()
+    }
+}
+
+/// How unlock attestations are verified
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SignatureMode {
+    /// One Ed25519 signature per validator
+    Individual,
+    /// A single threshold signature under the group key; individual
+    /// signatures remain accepted as a fallback
+    Threshold,
+}
+
+/// Aggregated threshold signature (e.g. FROST Ed25519) over an unlock hash
+/// and the signer bitmap, see `bridge_types::aggregate_signing_hash`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct AggregateSignature {
+    /// 64-byte Ed25519 signature valid under the group key
+    pub signature: [u8; 64],
+    /// Bit `i` is set when group participant `i` contributed a share
+    pub signer_bitmap: u32,
 }
 
 /// Token vault for holding locked tokens
@@ -97,6 +159,9 @@ pub struct MessageRecord {
     /// Whether this message has been processed
     pub processed: bool,
 
+    /// Account that paid for this record and receives the rent when closed
+    pub payer: Pubkey,
+
     /// Bump seed for PDA
     pub bump: u8,
 }
@@ -114,12 +179,72 @@ impl MessageRecord {
         8 + // amount
         8 + // timestamp
         1 + // processed
+        32 + // payer
         1; // bump
 }
 
+/// Sequences of the transfers released from one source chain, a bit per
+/// sequence. Each account covers `WINDOW_BITS` consecutive sequences and is
+/// created by the first release that falls in it. Every instruction that
+/// creates a `MessageRecord` checks and sets the bit, so a transfer stays
+/// released after its record is closed.
+#[account]
+pub struct ProcessedSequences {
+    /// Index of the window: the account covers sequences
+    /// `window * WINDOW_BITS..(window + 1) * WINDOW_BITS`
+    pub window: u64,
+
+    /// Bit `sequence % 64` of word `sequence % WINDOW_BITS / 64` is set once
+    /// the transfer at `sequence` is released
+    pub bits: [u64; ProcessedSequences::WORDS],
+
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl ProcessedSequences {
+    pub const WORDS: usize = 64;
+    pub const WINDOW_BITS: u64 = 64 * Self::WORDS as u64;
+
+    pub const LEN: usize = 8 + // discriminator
+        8 + // window
+        8 * Self::WORDS + // bits
+        1; // bump
+
+    /// Window holding `sequence`, as used in the PDA seeds
+    pub fn window_of(sequence: u64) -> u64 {
+        sequence / Self::WINDOW_BITS
+    }
+
+    /// Seed standing for `source_chain`, whose name may be longer than a seed
+    pub fn chain_seed(source_chain: &str) -> [u8; 32] {
+        solana_program::hash::hash(source_chain.as_bytes()).to_bytes()
+    }
+
+    fn position(sequence: u64) -> (usize, u64) {
+        let offset = sequence % Self::WINDOW_BITS;
+        ((offset / 64) as usize, 1 << (offset % 64))
+    }
+
+    /// Whether the transfer at `sequence` was released
+    pub fn contains(&self, sequence: u64) -> bool {
+        let (word, bit) = Self::position(sequence);
+        self.bits[word] & bit != 0
+    }
+
+    /// Record the transfer at `sequence` as released
+    pub fn insert(&mut self, sequence: u64) {
+        let (word, bit) = Self::position(sequence);
+        self.bits[word] |= bit;
+    }
+}
+
 /// Record of a lock event for outgoing transfers
 #[account]
 pub struct LockRecord {
+    /// Layout version, see `LockRecord::VERSION`
+    pub version: u8,
+
     /// Message ID
     pub message_id: [u8; 32],
 
@@ -144,15 +269,39 @@ pub struct LockRecord {
     /// Timestamp when locked
     pub timestamp: i64,
 
+    /// Lifecycle state, advanced by validator acknowledgements
+    pub status: LockStatus,
+
     /// Bump seed for PDA
     pub bump: u8,
+
+    /// Bridge sequence number committed to by `message_id`; zero for locks
+    /// made before the length-prefixed message ID scheme
+    pub sequence: u64,
+
+    /// Space for future fields without reallocation
+    pub reserved: [u8; 24],
 }
 
 impl LockRecord {
+    /// Current layout version
+    pub const VERSION: u8 = 1;
+
     pub const MAX_CHAIN_LEN: usize = 32;
     pub const MAX_ADDRESS_LEN: usize = 128;
 
+    pub const MAX_TX_REF_LEN: usize = 128;
+
+    /// Domain separators for destination outcome attestations
+    pub const REFUND_DOMAIN: &'static [u8] = b"articium:refund";
+    pub const EXPIRED_DOMAIN: &'static [u8] = b"articium:expired";
+    pub const DELIVERED_DOMAIN: &'static [u8] = b"articium:delivered";
+
+    /// Time after which an undelivered lock may be expired
+    pub const EXPIRY_PERIOD: i64 = 7 * 24 * 60 * 60;
+
     pub const LEN: usize = 8 + // discriminator
+        1 + // version
         32 + // message_id
         32 + // sender
         (4 + Self::MAX_CHAIN_LEN) + // destination_chain
@@ -161,5 +310,326 @@ impl LockRecord {
         8 + // amount
         8 + // nonce
         8 + // timestamp
+        (1 + 4 + Self::MAX_TX_REF_LEN) + // status
+        1 + // bump
+        8 + // sequence
+        24; // reserved
+}
+
+/// Lifecycle state of an outbound lock
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum LockStatus {
+    /// Locked, outcome on the destination chain not yet acknowledged
+    Pending,
+    /// Completed on the destination chain
+    Delivered { destination_tx: String },
+    /// Rejected on the destination chain and returned to the sender
+    Refunded,
+    /// Not delivered within the expiry period and returned to the sender
+    Expired,
+}
+
+/// Batch Merkle root attested by the validators
+#[account]
+pub struct BatchRoot {
+    /// Merkle root built by `internal/batching`
+    pub root: [u8; 32],
+
+    /// Number of leaves in the batch
+    pub message_count: u32,
+
+    /// Number of leaves claimed so far
+    pub claimed_count: u32,
+
+    /// Timestamp when submitted
+    pub timestamp: i64,
+
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl BatchRoot {
+    /// Domain separator for batch root attestations
+    pub const DOMAIN: &'static [u8] = b"articium:batch_root";
+
+    pub const LEN: usize = 8 + // discriminator
+        32 + // root
+        4 + // message_count
+        4 + // claimed_count
+        8 + // timestamp
         1; // bump
 }
+
+/// Delivery state of an inbound cross-chain message
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeliveryStatus {
+    /// Verified, awaiting a successful `execute_message`
+    Pending,
+    /// Target program accepted the message
+    Delivered,
+}
+
+/// Validated inbound generic message
+#[account]
+pub struct InboundMessage {
+    /// Unique message ID
+    pub message_id: [u8; 32],
+
+    /// Source chain identifier
+    pub source_chain: String,
+
+    /// Sender address on source chain
+    pub sender: String,
+
+    /// Program the message is delivered to
+    pub target_program: Pubkey,
+
+    /// Opaque payload for the target program
+    pub payload: Vec<u8>,
+
+    /// Delivery state
+    pub status: DeliveryStatus,
+
+    /// Timestamp when received
+    pub received_at: i64,
+
+    /// Timestamp when delivered (0 while pending)
+    pub delivered_at: i64,
+
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl InboundMessage {
+    pub const MAX_CHAIN_LEN: usize = 32;
+    pub const MAX_SENDER_LEN: usize = 128;
+    pub const MAX_PAYLOAD_LEN: usize = 512;
+
+    pub const LEN: usize = 8 + // discriminator
+        32 + // message_id
+        (4 + Self::MAX_CHAIN_LEN) + // source_chain
+        (4 + Self::MAX_SENDER_LEN) + // sender
+        32 + // target_program
+        (4 + Self::MAX_PAYLOAD_LEN) + // payload
+        1 + // status
+        8 + // received_at
+        8 + // delivered_at
+        1; // bump
+}
+
+/// Instruction data delivered to receiver programs, prefixed by
+/// `BridgeMessage::discriminator()`. The first account of the instruction is
+/// the bridge messenger PDA (seeds `[b"messenger"]`) as a signer, followed by
+/// the remaining accounts passed to `execute_message`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct BridgeMessage {
+    pub message_id: [u8; 32],
+    pub source_chain: String,
+    pub sender: String,
+    pub payload: Vec<u8>,
+}
+
+impl BridgeMessage {
+    /// Anchor-compatible sighash of `on_bridge_message`, so Anchor receivers
+    /// can declare a plain `on_bridge_message` instruction
+    pub fn discriminator() -> [u8; 8] {
+        let hash = solana_program::hash::hash(b"global:on_bridge_message");
+        let mut discriminator = [0u8; 8];
+        discriminator.copy_from_slice(&hash.to_bytes()[..8]);
+        discriminator
+    }
+}
+
+/// Call attached to a lock, executed by the destination bridge after the
+/// tokens are delivered to the receiver
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct LockCall {
+    /// Calldata for the receiver
+    pub payload: Vec<u8>,
+    /// Gas (or compute units) the receiver call should be given
+    pub compute_budget: u32,
+}
+
+impl LockCall {
+    pub const MAX_PAYLOAD_LEN: usize = 512;
+}
+
+/// State of an inbound unlock-and-call
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CallStatus {
+    /// Verified, tokens still in the vault
+    Pending,
+    /// Tokens delivered to the receiver and the call succeeded
+    Executed,
+    /// Tokens sent to the fallback recipient
+    FellBack,
+}
+
+/// Verified unlock-and-call awaiting execution. The matching `MessageRecord`
+/// stays unprocessed until the call executes or falls back.
+#[account]
+pub struct PendingCall {
+    /// Message ID
+    pub message_id: [u8; 32],
+
+    /// Program invoked with the call
+    pub receiver_program: Pubkey,
+
+    /// Owner of the token account the tokens are delivered to
+    pub receiver_authority: Pubkey,
+
+    /// Calldata for the receiver
+    pub payload: Vec<u8>,
+
+    /// Compute units requested by the sender (hint for executors)
+    pub compute_budget: u32,
+
+    /// Execution state
+    pub status: CallStatus,
+
+    /// Timestamp when received
+    pub received_at: i64,
+
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl PendingCall {
+    /// Time the call can be executed before anyone may trigger the fallback
+    pub const FALLBACK_DELAY: i64 = 24 * 60 * 60;
+
+    pub const LEN: usize = 8 + // discriminator
+        32 + // message_id
+        32 + // receiver_program
+        32 + // receiver_authority
+        (4 + LockCall::MAX_PAYLOAD_LEN) + // payload
+        4 + // compute_budget
+        1 + // status
+        8 + // received_at
+        1; // bump
+}
+
+/// Instruction data passed to receiver programs on unlock-and-call, prefixed
+/// by `BridgeTransfer::discriminator()`. Accounts are the messenger PDA as a
+/// signer, the receiver token account holding the delivered tokens, then the
+/// remaining accounts passed to `execute_call`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct BridgeTransfer {
+    pub message_id: [u8; 32],
+    pub source_chain: String,
+    pub sender: String,
+    pub token_mint: Pubkey,
+    pub amount: u64,
+    pub payload: Vec<u8>,
+}
+
+impl BridgeTransfer {
+    /// Anchor-compatible sighash of `on_bridge_transfer`
+    pub fn discriminator() -> [u8; 8] {
+        let hash = solana_program::hash::hash(b"global:on_bridge_transfer");
+        let mut discriminator = [0u8; 8];
+        discriminator.copy_from_slice(&hash.to_bytes()[..8]);
+        discriminator
+    }
+}
+
+// ===== Previous layouts =====
+//
+// Accounts written before layouts were versioned. They share the discriminator
+// of the current type and are told apart by their size. `migrate_config` and
+// `migrate_lock_record` convert them.
+
+/// `BridgeConfig` as deployed before versioning, without threshold
+/// signatures or governance
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct BridgeConfigV0 {
+    pub admin: Pubkey,
+    pub validators: Vec<Pubkey>,
+    pub required_signatures: u8,
+    pub is_paused: bool,
+    pub total_locked: u64,
+    pub total_unlocked: u64,
+    pub message_count: u64,
+    pub bump: u8,
+}
+
+impl BridgeConfigV0 {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // admin
+        (4 + 32 * BridgeConfig::MAX_VALIDATORS) + // validators vec
+        1 + // required_signatures
+        1 + // is_paused
+        8 + // total_locked
+        8 + // total_unlocked
+        8 + // message_count
+        1; // bump
+
+    /// Migrated configs keep individual signatures until a group key is set
+    pub fn into_current(self) -> BridgeConfig {
+        BridgeConfig {
+            version: BridgeConfig::VERSION,
+            admin: self.admin,
+            validators: self.validators,
+            required_signatures: self.required_signatures,
+            is_paused: self.is_paused,
+            total_locked: self.total_locked,
+            total_unlocked: self.total_unlocked,
+            message_count: self.message_count,
+            bump: self.bump,
+            signature_mode: SignatureMode::Individual,
+            group_key: Pubkey::default(),
+            group_threshold: 0,
+            group_size: 0,
+            group_key_epoch: 0,
+            governance: Pubkey::default(),
+            reserved: [0u8; 128],
+        }
+    }
+}
+
+/// `LockRecord` as deployed before versioning, without an outcome
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct LockRecordV0 {
+    pub message_id: [u8; 32],
+    pub sender: Pubkey,
+    pub destination_chain: String,
+    pub destination_address: String,
+    pub token_mint: Pubkey,
+    pub amount: u64,
+    pub nonce: u64,
+    pub timestamp: i64,
+    pub bump: u8,
+}
+
+impl LockRecordV0 {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // message_id
+        32 + // sender
+        (4 + LockRecord::MAX_CHAIN_LEN) + // destination_chain
+        (4 + LockRecord::MAX_ADDRESS_LEN) + // destination_address
+        32 + // token_mint
+        8 + // amount
+        8 + // nonce
+        8 + // timestamp
+        1; // bump
+
+    /// Migrated records start out pending, so their outcome can still be
+    /// acknowledged, refunded or expired
+    pub fn into_current(self) -> LockRecord {
+        LockRecord {
+            version: LockRecord::VERSION,
+            message_id: self.message_id,
+            sender: self.sender,
+            destination_chain: self.destination_chain,
+            destination_address: self.destination_address,
+            token_mint: self.token_mint,
+            amount: self.amount,
+            nonce: self.nonce,
+            timestamp: self.timestamp,
+            status: LockStatus::Pending,
+            bump: self.bump,
+            sequence: 0,
+            reserved: [0u8; 24],
+        }
+    }
+}
@@ -0,0 +1,223 @@
+//! Admin instructions: validator set, signature threshold, pausing and the
+//! threshold group key
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use common::*;
+use solana_bridge::error::BridgeError;
+use solana_bridge::instruction::{
+    AddValidator, Pause, RemoveValidator, SetGroupKey, SetSignatureMode, Unpause,
+    UpdateRequiredSignatures,
+};
+use solana_bridge::state::{BridgeConfig, SignatureMode};
+use solana_sdk::signature::{Keypair, Signer};
+
+#[tokio::test]
+async fn admin_instructions_are_admin_only() {
+    let mut bridge = Bridge::start().await;
+    let intruder = Keypair::new();
+    let payer = bridge.payer();
+    let validator = bridge.validators[0].pubkey();
+
+    let instructions = [
+        admin_instruction(intruder.pubkey(), Pause {}),
+        admin_instruction(intruder.pubkey(), Unpause {}),
+        admin_instruction(intruder.pubkey(), AddValidator { validator: Pubkey::new_unique() }),
+        admin_instruction(intruder.pubkey(), RemoveValidator { validator }),
+        admin_instruction(
+            intruder.pubkey(),
+            UpdateRequiredSignatures { required_signatures: 1 },
+        ),
+        admin_instruction(
+            intruder.pubkey(),
+            SetGroupKey { group_key: Pubkey::new_unique(), threshold: 2, group_size: 3 },
+        ),
+    ];
+    for instruction in instructions {
+        assert_eq!(
+            bridge.send(&[instruction], &[&payer, &intruder]).await.unwrap_err(),
+            bridge_error(0, BridgeError::UnauthorizedValidator)
+        );
+    }
+
+    let config = bridge.config().await;
+    assert!(!config.is_paused);
+    assert_eq!(config.validators.len(), 3);
+    assert_eq!(config.required_signatures, 2);
+    assert!(!config.has_group_key());
+}
+
+#[tokio::test]
+async fn pause_and_unpause_toggle_the_bridge() {
+    let mut bridge = Bridge::start().await;
+
+    bridge.send_admin(Pause {}).await.unwrap();
+    assert!(bridge.config().await.is_paused);
+    // Pausing twice is harmless
+    bridge.send_admin(Pause {}).await.unwrap();
+    assert!(bridge.config().await.is_paused);
+
+    bridge.send_admin(Unpause {}).await.unwrap();
+    assert!(!bridge.config().await.is_paused);
+    bridge.send_lock(&bridge.lock(1_000, 1)).await.unwrap();
+}
+
+#[tokio::test]
+async fn add_validator_grows_the_set_up_to_the_maximum() {
+    let mut bridge = Bridge::start().await;
+
+    let validator = bridge.validators[0].pubkey();
+    assert_eq!(
+        bridge.send_admin(AddValidator { validator }).await.unwrap_err(),
+        bridge_error(0, BridgeError::ValidatorAlreadyExists)
+    );
+
+    for _ in 3..BridgeConfig::MAX_VALIDATORS {
+        let validator = Pubkey::new_unique();
+        bridge.send_admin(AddValidator { validator }).await.unwrap();
+        assert_eq!(bridge.config().await.validators.last(), Some(&validator));
+    }
+
+    let validator = Pubkey::new_unique();
+    assert_eq!(
+        bridge.send_admin(AddValidator { validator }).await.unwrap_err(),
+        bridge_error(0, BridgeError::MaxValidatorsReached)
+    );
+    assert_eq!(bridge.config().await.validators.len(), BridgeConfig::MAX_VALIDATORS);
+}
+
+#[tokio::test]
+async fn added_validator_signatures_count() {
+    let mut bridge = Bridge::start().await;
+    bridge.send_lock(&bridge.lock(1_000, 1)).await.unwrap();
+
+    let validator = Keypair::new();
+    bridge.send_admin(AddValidator { validator: validator.pubkey() }).await.unwrap();
+    bridge.validators.push(validator);
+
+    let unlock = bridge.unlock(1, 100).await;
+    bridge.send_unlock(&unlock, &[0, 3]).await.unwrap();
+    assert_eq!(bridge.balance(unlock.recipient_token_account).await, 100);
+}
+
+#[tokio::test]
+async fn removed_validator_signatures_no_longer_count() {
+    let mut bridge = Bridge::start().await;
+    bridge.send_lock(&bridge.lock(1_000, 1)).await.unwrap();
+
+    let removed = bridge.validators[2].pubkey();
+    bridge.send_admin(RemoveValidator { validator: removed }).await.unwrap();
+    assert_eq!(bridge.config().await.validators.len(), 2);
+
+    let unlock = bridge.unlock(1, 100).await;
+    assert_eq!(
+        bridge.send_unlock(&unlock, &[0, 2]).await.unwrap_err(),
+        bridge_error(1, BridgeError::InsufficientSignatures)
+    );
+    bridge.send_unlock(&unlock, &[0, 1]).await.unwrap();
+
+    assert_eq!(
+        bridge.send_admin(RemoveValidator { validator: removed }).await.unwrap_err(),
+        bridge_error(0, BridgeError::ValidatorNotFound)
+    );
+
+    // Two validators are left and two signatures are required
+    let validator = bridge.validators[0].pubkey();
+    assert_eq!(
+        bridge.send_admin(RemoveValidator { validator }).await.unwrap_err(),
+        bridge_error(0, BridgeError::InvalidRequiredSignatures)
+    );
+    assert_eq!(bridge.config().await.validators.len(), 2);
+}
+
+#[tokio::test]
+async fn update_required_signatures_stays_within_the_validator_set() {
+    let mut bridge = Bridge::start().await;
+
+    for required_signatures in [0, 4] {
+        assert_eq!(
+            bridge
+                .send_admin(UpdateRequiredSignatures { required_signatures })
+                .await
+                .unwrap_err(),
+            bridge_error(0, BridgeError::InvalidRequiredSignatures)
+        );
+    }
+
+    bridge.send_admin(UpdateRequiredSignatures { required_signatures: 3 }).await.unwrap();
+    assert_eq!(bridge.config().await.required_signatures, 3);
+
+    // Two signatures no longer make a quorum
+    bridge.send_lock(&bridge.lock(1_000, 1)).await.unwrap();
+    let unlock = bridge.unlock(1, 100).await;
+    assert_eq!(
+        bridge.send_unlock(&unlock, &[0, 1]).await.unwrap_err(),
+        bridge_error(1, BridgeError::InsufficientSignatures)
+    );
+    bridge.send_unlock(&unlock, &[0, 1, 2]).await.unwrap();
+}
+
+#[tokio::test]
+async fn set_group_key_validates_the_group() {
+    let mut bridge = Bridge::start().await;
+    let group_key = Pubkey::new_unique();
+    let max = BridgeConfig::MAX_GROUP_SIZE;
+
+    let cases = [
+        (Pubkey::default(), 2, 3),
+        (group_key, 1, 0),
+        (group_key, 1, max + 1),
+        (group_key, 0, 3),
+        (group_key, 4, 3),
+    ];
+    for (group_key, threshold, group_size) in cases {
+        assert_eq!(
+            bridge
+                .send_admin(SetGroupKey { group_key, threshold, group_size })
+                .await
+                .unwrap_err(),
+            bridge_error(0, BridgeError::InvalidGroupConfig)
+        );
+    }
+    assert!(!bridge.config().await.has_group_key());
+}
+
+#[tokio::test]
+async fn set_group_key_rotates_epochs() {
+    let mut bridge = Bridge::start().await;
+
+    for epoch in 0..3 {
+        let group_key = Pubkey::new_unique();
+        bridge
+            .send_admin(SetGroupKey { group_key, threshold: 2, group_size: 3 })
+            .await
+            .unwrap();
+        let config = bridge.config().await;
+        assert_eq!(config.group_key, group_key);
+        assert_eq!(config.group_threshold, 2);
+        assert_eq!(config.group_size, 3);
+        assert_eq!(config.group_key_epoch, epoch);
+    }
+}
+
+#[tokio::test]
+async fn set_signature_mode_requires_a_group_key_for_threshold() {
+    let mut bridge = Bridge::start().await;
+
+    assert_eq!(
+        bridge
+            .send_admin(SetSignatureMode { mode: SignatureMode::Threshold })
+            .await
+            .unwrap_err(),
+        bridge_error(0, BridgeError::GroupKeyNotSet)
+    );
+
+    let group_key = Pubkey::new_unique();
+    bridge.send_admin(SetGroupKey { group_key, threshold: 2, group_size: 3 }).await.unwrap();
+    bridge.send_admin(SetSignatureMode { mode: SignatureMode::Threshold }).await.unwrap();
+    assert_eq!(bridge.config().await.signature_mode, SignatureMode::Threshold);
+
+    bridge.send_admin(SetSignatureMode { mode: SignatureMode::Individual }).await.unwrap();
+    assert_eq!(bridge.config().await.signature_mode, SignatureMode::Individual);
+}
@@ -0,0 +1,491 @@
+//! Batched settlement: validator-attested Merkle roots and claims of single
+//! messages by proof
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use common::*;
+use solana_bridge::error::BridgeError;
+use solana_bridge::instruction::Pause;
+use solana_bridge::merkle;
+use solana_bridge::state::{BatchRoot, MessageRecord, ProcessedSequences};
+use solana_program::{system_program, sysvar};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::{Keypair, Signer};
+
+/// One message of a batch, bound for a new recipient
+#[derive(Clone, Debug)]
+struct Claim {
+    message_id: [u8; 32],
+    source_chain: String,
+    sequence: u64,
+    destination_chain: String,
+    sender: String,
+    recipient: Pubkey,
+    recipient_token_account: Pubkey,
+    mint: Pubkey,
+    payload: Vec<u8>,
+}
+
+impl Claim {
+    fn leaf(&self) -> [u8; 32] {
+        merkle::hash_leaf(
+            &self.message_id,
+            &self.source_chain,
+            self.sequence,
+            &self.destination_chain,
+            &self.sender,
+            &self.recipient.to_string(),
+            &self.payload,
+        )
+    }
+
+    fn instruction(
+        &self,
+        payer: Pubkey,
+        batch_root: [u8; 32],
+        leaf_index: u32,
+        proof: &[[u8; 32]],
+    ) -> Instruction {
+        Instruction {
+            program_id: solana_bridge::ID,
+            accounts: solana_bridge::accounts::ClaimFromBatch {
+                bridge_config: bridge_config_address(),
+                batch_root_account: batch_root_address(&batch_root),
+                token_vault: token_vault_address(&self.mint),
+                message_record: message_record_address(&self.message_id),
+                processed_sequences: processed_sequences_address(
+                    &self.source_chain,
+                    self.sequence,
+                ),
+                payer,
+                recipient: self.recipient,
+                recipient_token_account: self.recipient_token_account,
+                vault_token_account: vault_token_account(&self.mint),
+                token_mint: self.mint,
+                token_program: spl_token::ID,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: solana_bridge::instruction::ClaimFromBatch {
+                batch_root,
+                message_id: self.message_id,
+                source_chain: self.source_chain.clone(),
+                sequence: self.sequence,
+                destination_chain: self.destination_chain.clone(),
+                sender: self.sender.clone(),
+                payload: self.payload.clone(),
+                leaf_index,
+                proof: proof.to_vec(),
+            }
+            .data(),
+        }
+    }
+}
+
+/// Token transfer payload as the Go relayer serializes it
+fn token_payload(mint: &Pubkey, amount: &str) -> Vec<u8> {
+    format!(r#"{{"token_address":{{"raw":"{}"}},"amount":"{}"}}"#, mint, amount).into_bytes()
+}
+
+fn batch_root_address(batch_root: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(&[b"batch_root", batch_root.as_ref()], &solana_bridge::ID).0
+}
+
+fn submit_instruction(payer: Pubkey, batch_root: [u8; 32], message_count: u32) -> Instruction {
+    Instruction {
+        program_id: solana_bridge::ID,
+        accounts: solana_bridge::accounts::SubmitBatchRoot {
+            bridge_config: bridge_config_address(),
+            batch_root_account: batch_root_address(&batch_root),
+            payer,
+            instructions_sysvar: sysvar::instructions::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: solana_bridge::instruction::SubmitBatchRoot {
+            batch_root,
+            message_count,
+            aggregate_signature: None,
+        }
+        .data(),
+    }
+}
+
+/// Hash the validators sign to attest a batch root
+fn batch_root_hash(batch_root: &[u8; 32], message_count: u32) -> [u8; 32] {
+    solana_program::keccak::hashv(&[
+        BatchRoot::DOMAIN,
+        bridge_config_address().as_ref(),
+        batch_root,
+        &message_count.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// Merkle root over `leaves` and the proof of every leaf, built the same way
+/// as `internal/batching/merkle.go`
+fn merkle_tree(leaves: &[[u8; 32]]) -> ([u8; 32], Vec<Vec<[u8; 32]>>) {
+    let mut layer = leaves.to_vec();
+    let mut positions: Vec<usize> = (0..leaves.len()).collect();
+    let mut proofs = vec![Vec::new(); leaves.len()];
+
+    while layer.len() > 1 {
+        if !layer.len().is_multiple_of(2) {
+            layer.push(*layer.last().unwrap());
+        }
+        for (proof, position) in proofs.iter_mut().zip(positions.iter_mut()) {
+            proof.push(layer[*position ^ 1]);
+            *position /= 2;
+        }
+        layer = layer.chunks(2).map(|pair| merkle::hash_pair(&pair[0], &pair[1])).collect();
+    }
+
+    (layer[0], proofs)
+}
+
+/// Bridge holding 1 000 of the sender's tokens in the vault
+async fn funded_bridge() -> Bridge {
+    let mut bridge = Bridge::start().await;
+    bridge.send_lock(&bridge.lock(1_000, 1)).await.unwrap();
+    bridge
+}
+
+async fn claim(bridge: &mut Bridge, message_id: u8, amount: u64) -> Claim {
+    let recipient = Keypair::new().pubkey();
+    let recipient_token_account = bridge.token_account(&recipient).await;
+    Claim {
+        message_id: [message_id; 32],
+        source_chain: "near".to_string(),
+        sequence: u64::from(message_id),
+        destination_chain: "solana".to_string(),
+        sender: "alice.near".to_string(),
+        recipient,
+        recipient_token_account,
+        mint: bridge.mint,
+        payload: token_payload(&bridge.mint, &amount.to_string()),
+    }
+}
+
+impl Bridge {
+    async fn submit_batch(
+        &mut self,
+        batch_root: [u8; 32],
+        message_count: u32,
+        signers: &[usize],
+    ) -> Result<(), solana_sdk::transaction::TransactionError> {
+        let payer = self.payer();
+        let submit = submit_instruction(payer.pubkey(), batch_root, message_count);
+        let attestation = self.attestation(&batch_root_hash(&batch_root, message_count), signers);
+        self.send(&[attestation, submit], &[&payer]).await
+    }
+
+    async fn send_claim(
+        &mut self,
+        claim: &Claim,
+        batch_root: [u8; 32],
+        leaf_index: u32,
+        proof: &[[u8; 32]],
+    ) -> Result<(), solana_sdk::transaction::TransactionError> {
+        let payer = self.payer();
+        let claim = claim.instruction(payer.pubkey(), batch_root, leaf_index, proof);
+        self.send(&[claim], &[&payer]).await
+    }
+}
+
+#[tokio::test]
+async fn submit_batch_root_records_the_root() {
+    let mut bridge = Bridge::start().await;
+    let batch_root = [9; 32];
+
+    bridge.submit_batch(batch_root, 3, &[0, 2]).await.unwrap();
+
+    let batch: BatchRoot = bridge.fetch(batch_root_address(&batch_root)).await;
+    assert_eq!(batch.root, batch_root);
+    assert_eq!(batch.message_count, 3);
+    assert_eq!(batch.claimed_count, 0);
+
+    // A root is submitted once
+    assert_eq!(bridge.submit_batch(batch_root, 3, &[0, 1]).await.unwrap_err(), already_in_use(1));
+}
+
+#[tokio::test]
+async fn submit_batch_root_requires_an_attestation() {
+    let mut bridge = Bridge::start().await;
+    let batch_root = [9; 32];
+
+    assert_eq!(
+        bridge.submit_batch(batch_root, 3, &[0]).await.unwrap_err(),
+        bridge_error(1, BridgeError::InsufficientSignatures)
+    );
+
+    // Signed for another message count
+    let payer = bridge.payer();
+    let attestation = bridge.attestation(&batch_root_hash(&batch_root, 4), &[0, 1]);
+    let submit = submit_instruction(payer.pubkey(), batch_root, 3);
+    assert_eq!(
+        bridge.send(&[attestation, submit], &[&payer]).await.unwrap_err(),
+        bridge_error(1, BridgeError::InsufficientSignatures)
+    );
+
+    assert_eq!(
+        bridge.submit_batch(batch_root, 0, &[0, 1]).await.unwrap_err(),
+        bridge_error(1, BridgeError::InvalidBatch)
+    );
+
+    bridge.send_admin(Pause {}).await.unwrap();
+    assert_eq!(
+        bridge.submit_batch(batch_root, 3, &[0, 1]).await.unwrap_err(),
+        bridge_error(1, BridgeError::BridgePaused)
+    );
+    assert!(!bridge.exists(batch_root_address(&batch_root)).await);
+}
+
+#[tokio::test]
+async fn claim_from_batch_releases_every_message() {
+    let mut bridge = funded_bridge().await;
+    let claims = [
+        claim(&mut bridge, 1, 100).await,
+        claim(&mut bridge, 2, 200).await,
+        claim(&mut bridge, 3, 300).await,
+    ];
+    let leaves: Vec<[u8; 32]> = claims.iter().map(Claim::leaf).collect();
+    let (batch_root, proofs) = merkle_tree(&leaves);
+    bridge.submit_batch(batch_root, 3, &[0, 1]).await.unwrap();
+
+    for (index, claim) in claims.iter().enumerate() {
+        bridge.send_claim(claim, batch_root, index as u32, &proofs[index]).await.unwrap();
+    }
+
+    for (claim, amount) in claims.iter().zip([100, 200, 300]) {
+        assert_eq!(bridge.balance(claim.recipient_token_account).await, amount);
+        let record: MessageRecord = bridge.fetch(message_record_address(&claim.message_id)).await;
+        assert_eq!(record.recipient, claim.recipient);
+        assert_eq!(record.amount, amount);
+        assert!(record.processed);
+    }
+    let window: ProcessedSequences =
+        bridge.fetch(processed_sequences_address("near", 1)).await;
+    assert!((1..=3).all(|sequence| window.contains(sequence)));
+
+    let batch: BatchRoot = bridge.fetch(batch_root_address(&batch_root)).await;
+    assert_eq!(batch.claimed_count, 3);
+    assert_eq!(bridge.balance(vault_token_account(&bridge.mint)).await, 400);
+    assert_eq!(bridge.config().await.total_unlocked, 600);
+}
+
+#[tokio::test]
+async fn claim_from_batch_rejects_invalid_proofs() {
+    let mut bridge = funded_bridge().await;
+    let claims = [claim(&mut bridge, 1, 100).await, claim(&mut bridge, 2, 200).await];
+    let leaves: Vec<[u8; 32]> = claims.iter().map(Claim::leaf).collect();
+    let (batch_root, proofs) = merkle_tree(&leaves);
+    bridge.submit_batch(batch_root, 2, &[0, 1]).await.unwrap();
+
+    // Proof of another leaf
+    assert_eq!(
+        bridge.send_claim(&claims[0], batch_root, 0, &proofs[1]).await.unwrap_err(),
+        bridge_error(0, BridgeError::InvalidMerkleProof)
+    );
+
+    // Wrong position
+    assert_eq!(
+        bridge.send_claim(&claims[0], batch_root, 1, &proofs[0]).await.unwrap_err(),
+        bridge_error(0, BridgeError::InvalidMerkleProof)
+    );
+
+    // Position past the attested message count
+    assert_eq!(
+        bridge.send_claim(&claims[0], batch_root, 2, &proofs[0]).await.unwrap_err(),
+        bridge_error(0, BridgeError::InvalidMerkleProof)
+    );
+
+    // Terms that differ from the leaf
+    let inflated = Claim { payload: token_payload(&bridge.mint, "1000"), ..claims[0].clone() };
+    assert_eq!(
+        bridge.send_claim(&inflated, batch_root, 0, &proofs[0]).await.unwrap_err(),
+        bridge_error(0, BridgeError::InvalidMerkleProof)
+    );
+    let mut redirected = claims[0].clone();
+    redirected.recipient = bridge.sender.pubkey();
+    redirected.recipient_token_account = bridge.sender_token_account;
+    assert_eq!(
+        bridge.send_claim(&redirected, batch_root, 0, &proofs[0]).await.unwrap_err(),
+        bridge_error(0, BridgeError::InvalidMerkleProof)
+    );
+
+    // Root that was never attested
+    assert_eq!(
+        bridge.send_claim(&claims[0], [1; 32], 0, &proofs[0]).await.unwrap_err(),
+        anchor_error(0, anchor_lang::error::ErrorCode::AccountNotInitialized)
+    );
+
+    assert_eq!(bridge.balance(claims[0].recipient_token_account).await, 0);
+    assert_eq!(bridge.balance(vault_token_account(&bridge.mint)).await, 1_000);
+}
+
+#[tokio::test]
+async fn claim_from_batch_rejects_replay() {
+    let mut bridge = funded_bridge().await;
+    let claims = [claim(&mut bridge, 1, 100).await, claim(&mut bridge, 2, 200).await];
+    let leaves: Vec<[u8; 32]> = claims.iter().map(Claim::leaf).collect();
+    let (batch_root, proofs) = merkle_tree(&leaves);
+    bridge.submit_batch(batch_root, 2, &[0, 1]).await.unwrap();
+    bridge.send_claim(&claims[0], batch_root, 0, &proofs[0]).await.unwrap();
+
+    assert_eq!(
+        bridge.send_claim(&claims[0], batch_root, 0, &proofs[0]).await.unwrap_err(),
+        already_in_use(0)
+    );
+
+    // The sequence stays spent once the record is closed
+    let payer = bridge.payer();
+    let close = Unlock {
+        payer: payer.pubkey(),
+        recipient: claims[0].recipient,
+        recipient_token_account: claims[0].recipient_token_account,
+        mint: bridge.mint,
+        message_id: claims[0].message_id,
+        source_chain: claims[0].source_chain.clone(),
+        sequence: claims[0].sequence,
+        sender: claims[0].sender.clone(),
+        amount: 100,
+    }
+    .close_instruction();
+    bridge.send(&[close], &[&payer]).await.unwrap();
+    assert_eq!(
+        bridge.send_claim(&claims[0], batch_root, 0, &proofs[0]).await.unwrap_err(),
+        bridge_error(0, BridgeError::MessageAlreadyProcessed)
+    );
+
+    // A transfer released by a direct unlock cannot be claimed again
+    let unlock = Unlock {
+        recipient: claims[1].recipient,
+        recipient_token_account: claims[1].recipient_token_account,
+        message_id: [20; 32],
+        sequence: claims[1].sequence,
+        ..bridge.unlock(20, 200).await
+    };
+    bridge.send_unlock(&unlock, &[0, 1]).await.unwrap();
+    assert_eq!(
+        bridge.send_claim(&claims[1], batch_root, 1, &proofs[1]).await.unwrap_err(),
+        bridge_error(0, BridgeError::MessageAlreadyProcessed)
+    );
+
+    assert_eq!(bridge.balance(claims[0].recipient_token_account).await, 100);
+    assert_eq!(bridge.balance(claims[1].recipient_token_account).await, 200);
+    let batch: BatchRoot = bridge.fetch(batch_root_address(&batch_root)).await;
+    assert_eq!(batch.claimed_count, 1);
+}
+
+#[tokio::test]
+async fn claim_from_batch_only_releases_messages_for_solana() {
+    let mut bridge = funded_bridge().await;
+    let mut claim = claim(&mut bridge, 1, 100).await;
+    claim.destination_chain = "ethereum".to_string();
+    let (batch_root, proofs) = merkle_tree(&[claim.leaf()]);
+    bridge.submit_batch(batch_root, 1, &[0, 1]).await.unwrap();
+
+    assert_eq!(
+        bridge.send_claim(&claim, batch_root, 0, &proofs[0]).await.unwrap_err(),
+        bridge_error(0, BridgeError::WrongDestinationChain)
+    );
+    assert_eq!(bridge.balance(claim.recipient_token_account).await, 0);
+}
+
+#[tokio::test]
+async fn claim_from_batch_validates_the_payload() {
+    let mut bridge = funded_bridge().await;
+    let base = claim(&mut bridge, 1, 100).await;
+    let cases = [
+        (token_payload(&Pubkey::new_unique(), "100"), BridgeError::InvalidBatchPayload),
+        (token_payload(&bridge.mint, "0"), BridgeError::InvalidAmount),
+        (token_payload(&bridge.mint, "-1"), BridgeError::InvalidBatchPayload),
+        (b"not json".to_vec(), BridgeError::InvalidBatchPayload),
+    ];
+    let claims: Vec<Claim> = cases
+        .iter()
+        .enumerate()
+        .map(|(index, (payload, _))| Claim {
+            message_id: [index as u8 + 1; 32],
+            sequence: index as u64 + 1,
+            payload: payload.clone(),
+            ..base.clone()
+        })
+        .collect();
+    let leaves: Vec<[u8; 32]> = claims.iter().map(Claim::leaf).collect();
+    let (batch_root, proofs) = merkle_tree(&leaves);
+    bridge.submit_batch(batch_root, claims.len() as u32, &[0, 1]).await.unwrap();
+
+    for (index, (claim, (_, error))) in claims.iter().zip(cases).enumerate() {
+        assert_eq!(
+            bridge.send_claim(claim, batch_root, index as u32, &proofs[index]).await.unwrap_err(),
+            bridge_error(0, error)
+        );
+    }
+    assert_eq!(bridge.balance(vault_token_account(&bridge.mint)).await, 1_000);
+}
+
+#[tokio::test]
+async fn claim_from_batch_is_rejected_while_paused() {
+    let mut bridge = funded_bridge().await;
+    let claim = claim(&mut bridge, 1, 100).await;
+    let (batch_root, proofs) = merkle_tree(&[claim.leaf()]);
+    bridge.submit_batch(batch_root, 1, &[0, 1]).await.unwrap();
+    bridge.send_admin(Pause {}).await.unwrap();
+
+    assert_eq!(
+        bridge.send_claim(&claim, batch_root, 0, &proofs[0]).await.unwrap_err(),
+        bridge_error(0, BridgeError::BridgePaused)
+    );
+}
+
+#[test]
+fn batch_leaf_matches_shared_vector() {
+    // Same vector as merkle_test.go and the NEAR contract's merkle.rs
+    let leaf = merkle::hash_leaf(
+        &[0xab; 32],
+        "ethereum",
+        7,
+        "near",
+        "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb",
+        "alice.near",
+        br#"{"token_address":{"raw":"usdc.near"},"amount":"1000000"}"#,
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&merkle::to_hex(&leaf)),
+        "d8d73f0976fa70a6ab263270bf019c4ef4218e3885763800885bbcaf17bb4347"
+    );
+}
+
+#[test]
+fn batch_proof_rejects_bytes_moved_between_sender_and_recipient() {
+    let claim = Claim {
+        message_id: [1; 32],
+        source_chain: "ethereum".to_string(),
+        sequence: 1,
+        destination_chain: "solana".to_string(),
+        sender: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
+        recipient: Pubkey::new_unique(),
+        recipient_token_account: Pubkey::new_unique(),
+        mint: Pubkey::new_unique(),
+        payload: token_payload(&Pubkey::new_unique(), "100"),
+    };
+    let (batch_root, proofs) = merkle_tree(&[claim.leaf(), [0xcd; 32]]);
+    assert!(merkle::verify_proof(&claim.leaf(), 0, &proofs[0], &batch_root));
+
+    // The same bytes, with the first recipient character moved to the sender
+    let recipient = claim.recipient.to_string();
+    let shifted = merkle::hash_leaf(
+        &claim.message_id,
+        &claim.source_chain,
+        claim.sequence,
+        &claim.destination_chain,
+        &format!("{}{}", claim.sender, &recipient[..1]),
+        &recipient[1..],
+        &claim.payload,
+    );
+    assert!(!merkle::verify_proof(&shifted, 0, &proofs[0], &batch_root));
+}
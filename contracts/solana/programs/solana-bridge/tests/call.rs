@@ -0,0 +1,466 @@
+//! Transfers with calls: outbound locks committing to a call, and inbound
+//! unlock-and-calls executed on the receiver or falling back to the recipient
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AnchorSerialize, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use common::*;
+use solana_bridge::error::BridgeError;
+use solana_bridge::instruction::{Pause, Unpause};
+use solana_bridge::state::{
+    BridgeTransfer, CallStatus, LockCall, LockRecord, MessageRecord, PendingCall,
+};
+use solana_program::{keccak, system_program, sysvar};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::TransactionError;
+
+/// Inbound transfer to the receiver program, falling back to the recipient
+#[derive(Clone, Debug)]
+struct Call {
+    unlock: Unlock,
+    receiver_program: Pubkey,
+    receiver_authority: Pubkey,
+    receiver_token_account: Pubkey,
+    call: LockCall,
+}
+
+impl Call {
+    fn pending_call(&self) -> Pubkey {
+        Pubkey::find_program_address(
+            &[b"pending_call", self.unlock.message_id.as_ref()],
+            &solana_bridge::ID,
+        )
+        .0
+    }
+
+    /// Hash the validators sign to release the transfer through the call
+    fn message_hash(&self) -> [u8; 32] {
+        keccak::hashv(&[
+            &self.unlock.message_hash(),
+            self.receiver_program.as_ref(),
+            self.receiver_authority.as_ref(),
+            &keccak::hash(&self.call.payload).to_bytes(),
+            &self.call.compute_budget.to_le_bytes(),
+        ])
+        .to_bytes()
+    }
+
+    fn instruction(&self) -> Instruction {
+        let unlock = &self.unlock;
+        Instruction {
+            program_id: solana_bridge::ID,
+            accounts: solana_bridge::accounts::UnlockTokenAndCall {
+                bridge_config: bridge_config_address(),
+                message_record: unlock.message_record(),
+                processed_sequences: unlock.processed_sequences(),
+                pending_call: self.pending_call(),
+                payer: unlock.payer,
+                instructions_sysvar: sysvar::instructions::ID,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: solana_bridge::instruction::UnlockTokenAndCall {
+                message_id: unlock.message_id,
+                source_chain: unlock.source_chain.clone(),
+                sequence: unlock.sequence,
+                sender: unlock.sender.clone(),
+                recipient: unlock.recipient,
+                token_mint: unlock.mint,
+                amount: unlock.amount,
+                receiver_program: self.receiver_program,
+                receiver_authority: self.receiver_authority,
+                call: self.call.clone(),
+                aggregate_signature: None,
+            }
+            .data(),
+        }
+    }
+
+    /// Execution on `receiver_program` with `remaining` accounts
+    fn execute_instruction(
+        &self,
+        receiver_program: Pubkey,
+        remaining: &[AccountMeta],
+    ) -> Instruction {
+        let mint = self.unlock.mint;
+        let mut accounts = solana_bridge::accounts::ExecuteCall {
+            bridge_config: bridge_config_address(),
+            message_record: self.unlock.message_record(),
+            pending_call: self.pending_call(),
+            token_vault: token_vault_address(&mint),
+            vault_token_account: vault_token_account(&mint),
+            receiver_token_account: self.receiver_token_account,
+            token_mint: mint,
+            messenger: messenger_address(),
+            receiver_program,
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend_from_slice(remaining);
+        Instruction {
+            program_id: solana_bridge::ID,
+            accounts,
+            data: solana_bridge::instruction::ExecuteCall {}.data(),
+        }
+    }
+
+    fn fallback_instruction(&self) -> Instruction {
+        let mint = self.unlock.mint;
+        Instruction {
+            program_id: solana_bridge::ID,
+            accounts: solana_bridge::accounts::FallbackCall {
+                bridge_config: bridge_config_address(),
+                message_record: self.unlock.message_record(),
+                pending_call: self.pending_call(),
+                token_vault: token_vault_address(&mint),
+                vault_token_account: vault_token_account(&mint),
+                recipient_token_account: self.unlock.recipient_token_account,
+                token_mint: mint,
+                token_program: spl_token::ID,
+            }
+            .to_account_metas(None),
+            data: solana_bridge::instruction::FallbackCall {}.data(),
+        }
+    }
+}
+
+/// Bridge holding 1 000 of the sender's tokens in the vault
+async fn funded_bridge() -> Bridge {
+    let mut bridge = Bridge::start().await;
+    bridge.send_lock(&bridge.lock(1_000, 1)).await.unwrap();
+    bridge
+}
+
+impl Bridge {
+    /// Inbound transfer of `amount` from NEAR to the receiver program
+    async fn call(&mut self, message_id: u8, amount: u64) -> Call {
+        let unlock = self.unlock(message_id, amount).await;
+        let receiver_authority = Keypair::new().pubkey();
+        let receiver_token_account = self.token_account(&receiver_authority).await;
+        Call {
+            unlock,
+            receiver_program: RECEIVER_ID,
+            receiver_authority,
+            receiver_token_account,
+            call: LockCall { payload: b"deposit".to_vec(), compute_budget: 200_000 },
+        }
+    }
+
+    /// Submit `call`, attested by `signers` over `signed`
+    async fn send_call(
+        &mut self,
+        call: &Call,
+        signed: &Call,
+        signers: &[usize],
+    ) -> Result<(), TransactionError> {
+        let attestation = self.attestation(&signed.message_hash(), signers);
+        let payer = self.payer();
+        self.send(&[attestation, call.instruction()], &[&payer]).await
+    }
+
+    async fn send_execute(&mut self, instruction: Instruction) -> Result<(), TransactionError> {
+        let payer = self.payer();
+        self.send(&[instruction], &[&payer]).await
+    }
+
+    async fn send_lock_and_call(
+        &mut self,
+        lock: &Lock,
+        call: &LockCall,
+    ) -> Result<(), TransactionError> {
+        let mut instruction = lock.instruction();
+        instruction.data = solana_bridge::instruction::LockTokenAndCall {
+            amount: lock.amount,
+            destination_chain: lock.destination_chain.clone(),
+            destination_address: lock.destination_address.clone(),
+            nonce: lock.nonce,
+            call: call.clone(),
+        }
+        .data();
+        let sender = self.sender.insecure_clone();
+        let payer = self.payer();
+        self.send(&[instruction], &[&payer, &sender]).await
+    }
+}
+
+#[tokio::test]
+async fn lock_token_and_call_commits_to_the_call() {
+    let mut bridge = Bridge::start().await;
+    let lock = bridge.lock(1_000, 1);
+    let call = LockCall { payload: b"deposit".to_vec(), compute_budget: 200_000 };
+
+    bridge.send_lock_and_call(&lock, &call).await.unwrap();
+
+    let record: LockRecord = bridge.fetch(lock.lock_record()).await;
+    let expected = keccak::hashv(&[
+        &lock.message_id(0),
+        &keccak::hash(&call.payload).to_bytes(),
+        &call.compute_budget.to_le_bytes(),
+    ])
+    .to_bytes();
+    assert_eq!(record.message_id, expected);
+    assert_eq!(record.amount, 1_000);
+    assert_eq!(bridge.balance(vault_token_account(&bridge.mint)).await, 1_000);
+
+    let large_call = LockCall { payload: vec![0; LockCall::MAX_PAYLOAD_LEN + 1], ..call };
+    assert_eq!(
+        bridge.send_lock_and_call(&bridge.lock(1_000, 2), &large_call).await.unwrap_err(),
+        bridge_error(0, BridgeError::PayloadTooLarge)
+    );
+}
+
+#[tokio::test]
+async fn unlock_token_and_call_parks_the_call() {
+    let mut bridge = funded_bridge().await;
+    let call = bridge.call(1, 600).await;
+
+    bridge.send_call(&call, &call, &[0, 1]).await.unwrap();
+
+    let pending: PendingCall = bridge.fetch(call.pending_call()).await;
+    assert_eq!(pending.message_id, call.unlock.message_id);
+    assert_eq!(pending.receiver_program, RECEIVER_ID);
+    assert_eq!(pending.receiver_authority, call.receiver_authority);
+    assert_eq!(pending.payload, b"deposit");
+    assert_eq!(pending.compute_budget, 200_000);
+    assert_eq!(pending.status, CallStatus::Pending);
+    let record: MessageRecord = bridge.fetch(call.unlock.message_record()).await;
+    assert!(!record.processed);
+
+    // Tokens stay in the vault until the call executes or falls back
+    assert_eq!(bridge.balance(vault_token_account(&bridge.mint)).await, 1_000);
+    assert_eq!(bridge.balance(call.receiver_token_account).await, 0);
+
+    // The sequence is spent
+    let mut replay = call.clone();
+    replay.unlock.message_id = [2; 32];
+    assert_eq!(
+        bridge.send_call(&replay, &replay, &[0, 1]).await.unwrap_err(),
+        bridge_error(1, BridgeError::MessageAlreadyProcessed)
+    );
+    assert_eq!(
+        bridge.send_unlock(&replay.unlock, &[0, 1]).await.unwrap_err(),
+        bridge_error(1, BridgeError::MessageAlreadyProcessed)
+    );
+}
+
+#[tokio::test]
+async fn unlock_token_and_call_requires_an_attestation_of_the_call() {
+    let mut bridge = funded_bridge().await;
+    let call = bridge.call(1, 600).await;
+
+    assert_eq!(
+        bridge.send_call(&call, &call, &[0]).await.unwrap_err(),
+        bridge_error(1, BridgeError::InsufficientSignatures)
+    );
+
+    let other_authority = Call { receiver_authority: Pubkey::new_unique(), ..call.clone() };
+    let other_payload = Call {
+        call: LockCall { payload: b"withdraw".to_vec(), ..call.call.clone() },
+        ..call.clone()
+    };
+    let other_budget =
+        Call { call: LockCall { compute_budget: 1, ..call.call.clone() }, ..call.clone() };
+    for signed in [other_authority, other_payload, other_budget] {
+        assert_eq!(
+            bridge.send_call(&call, &signed, &[0, 1]).await.unwrap_err(),
+            bridge_error(1, BridgeError::InsufficientSignatures)
+        );
+    }
+
+    // An attestation of the plain unlock does not cover the call
+    let attestation = bridge.attestation(&call.unlock.message_hash(), &[0, 1]);
+    let payer = bridge.payer();
+    assert_eq!(
+        bridge.send(&[attestation, call.instruction()], &[&payer]).await.unwrap_err(),
+        bridge_error(1, BridgeError::InsufficientSignatures)
+    );
+
+    let bridge_target = Call { receiver_program: solana_bridge::ID, ..call.clone() };
+    assert_eq!(
+        bridge.send_call(&bridge_target, &bridge_target, &[0, 1]).await.unwrap_err(),
+        bridge_error(1, BridgeError::InvalidMessageTarget)
+    );
+    assert!(!bridge.exists(call.pending_call()).await);
+}
+
+#[tokio::test]
+async fn execute_call_delivers_the_tokens_to_the_receiver() {
+    let mut bridge = funded_bridge().await;
+    let call = bridge.call(1, 600).await;
+    bridge.send_call(&call, &call, &[0, 1]).await.unwrap();
+
+    let inbox = [AccountMeta::new(INBOX, false)];
+    bridge.send_execute(call.execute_instruction(RECEIVER_ID, &inbox)).await.unwrap();
+
+    assert_eq!(bridge.balance(call.receiver_token_account).await, 600);
+    assert_eq!(bridge.balance(call.unlock.recipient_token_account).await, 0);
+    assert_eq!(bridge.balance(vault_token_account(&bridge.mint)).await, 400);
+    assert_eq!(bridge.config().await.total_unlocked, 600);
+
+    let mut expected = BridgeTransfer::discriminator().to_vec();
+    BridgeTransfer {
+        message_id: call.unlock.message_id,
+        source_chain: "near".to_string(),
+        sender: "alice.near".to_string(),
+        token_mint: bridge.mint,
+        amount: 600,
+        payload: b"deposit".to_vec(),
+    }
+    .serialize(&mut expected)
+    .unwrap();
+    assert_eq!(bridge.delivery().await, expected);
+
+    let pending: PendingCall = bridge.fetch(call.pending_call()).await;
+    assert_eq!(pending.status, CallStatus::Executed);
+    let record: MessageRecord = bridge.fetch(call.unlock.message_record()).await;
+    assert!(record.processed);
+
+    // A call executes once and then cannot fall back
+    assert_eq!(
+        bridge.send_execute(call.execute_instruction(RECEIVER_ID, &inbox)).await.unwrap_err(),
+        bridge_error(0, BridgeError::CallNotPending)
+    );
+    bridge.advance_clock(PendingCall::FALLBACK_DELAY).await;
+    assert_eq!(
+        bridge.send_execute(call.fallback_instruction()).await.unwrap_err(),
+        bridge_error(0, BridgeError::CallNotPending)
+    );
+}
+
+#[tokio::test]
+async fn execute_call_can_be_retried_after_a_failed_call() {
+    let mut bridge = funded_bridge().await;
+    let call = bridge.call(1, 600).await;
+    bridge.send_call(&call, &call, &[0, 1]).await.unwrap();
+
+    // The receiver fails without its inbox, reverting the token delivery
+    assert!(bridge.send_execute(call.execute_instruction(RECEIVER_ID, &[])).await.is_err());
+    assert_eq!(bridge.balance(call.receiver_token_account).await, 0);
+    let pending: PendingCall = bridge.fetch(call.pending_call()).await;
+    assert_eq!(pending.status, CallStatus::Pending);
+
+    let inbox = [AccountMeta::new(INBOX, false)];
+    bridge.send_execute(call.execute_instruction(RECEIVER_ID, &inbox)).await.unwrap();
+    assert_eq!(bridge.balance(call.receiver_token_account).await, 600);
+}
+
+#[tokio::test]
+async fn execute_call_only_delivers_to_the_recorded_receiver() {
+    let mut bridge = funded_bridge().await;
+    let call = bridge.call(1, 600).await;
+    bridge.send_call(&call, &call, &[0, 1]).await.unwrap();
+    let inbox = [AccountMeta::new(INBOX, false)];
+
+    assert_eq!(
+        bridge.send_execute(call.execute_instruction(spl_token::ID, &inbox)).await.unwrap_err(),
+        bridge_error(0, BridgeError::InvalidMessageTarget)
+    );
+
+    let other = Keypair::new().pubkey();
+    let other_account = Call { receiver_token_account: bridge.token_account(&other).await, ..call };
+    assert_eq!(
+        bridge
+            .send_execute(other_account.execute_instruction(RECEIVER_ID, &inbox))
+            .await
+            .unwrap_err(),
+        anchor_error(0, ErrorCode::ConstraintRaw)
+    );
+    assert_eq!(bridge.balance(vault_token_account(&bridge.mint)).await, 1_000);
+}
+
+#[tokio::test]
+async fn fallback_call_waits_for_the_fallback_delay() {
+    let mut bridge = funded_bridge().await;
+    let call = bridge.call(1, 600).await;
+    bridge.send_call(&call, &call, &[0, 1]).await.unwrap();
+
+    assert_eq!(
+        bridge.send_execute(call.fallback_instruction()).await.unwrap_err(),
+        bridge_error(0, BridgeError::FallbackTooEarly)
+    );
+
+    bridge.advance_clock(PendingCall::FALLBACK_DELAY).await;
+    bridge.send_execute(call.fallback_instruction()).await.unwrap();
+
+    assert_eq!(bridge.balance(call.unlock.recipient_token_account).await, 600);
+    assert_eq!(bridge.balance(call.receiver_token_account).await, 0);
+    assert_eq!(bridge.config().await.total_unlocked, 600);
+    let pending: PendingCall = bridge.fetch(call.pending_call()).await;
+    assert_eq!(pending.status, CallStatus::FellBack);
+    let record: MessageRecord = bridge.fetch(call.unlock.message_record()).await;
+    assert!(record.processed);
+
+    // The receiver can no longer be called
+    let inbox = [AccountMeta::new(INBOX, false)];
+    assert_eq!(
+        bridge.send_execute(call.execute_instruction(RECEIVER_ID, &inbox)).await.unwrap_err(),
+        bridge_error(0, BridgeError::CallNotPending)
+    );
+}
+
+#[tokio::test]
+async fn calls_are_rejected_while_paused() {
+    let mut bridge = funded_bridge().await;
+    let call = bridge.call(1, 600).await;
+    bridge.send_call(&call, &call, &[0, 1]).await.unwrap();
+    bridge.send_admin(Pause {}).await.unwrap();
+
+    let paused = bridge.call(2, 100).await;
+    assert_eq!(
+        bridge.send_call(&paused, &paused, &[0, 1]).await.unwrap_err(),
+        bridge_error(1, BridgeError::BridgePaused)
+    );
+    let inbox = [AccountMeta::new(INBOX, false)];
+    assert_eq!(
+        bridge.send_execute(call.execute_instruction(RECEIVER_ID, &inbox)).await.unwrap_err(),
+        bridge_error(0, BridgeError::BridgePaused)
+    );
+    bridge.advance_clock(PendingCall::FALLBACK_DELAY).await;
+    assert_eq!(
+        bridge.send_execute(call.fallback_instruction()).await.unwrap_err(),
+        bridge_error(0, BridgeError::BridgePaused)
+    );
+
+    bridge.send_admin(Unpause {}).await.unwrap();
+    bridge.send_execute(call.execute_instruction(RECEIVER_ID, &inbox)).await.unwrap();
+}
+
+#[tokio::test]
+async fn close_message_record_waits_for_the_call() {
+    let mut bridge = funded_bridge().await;
+    let call = bridge.call(1, 600).await;
+    bridge.send_call(&call, &call, &[0, 1]).await.unwrap();
+
+    let payer = bridge.payer();
+    assert_eq!(
+        bridge.send(&[call.unlock.close_instruction()], &[&payer]).await.unwrap_err(),
+        bridge_error(0, BridgeError::RecordNotFinalized)
+    );
+
+    let inbox = [AccountMeta::new(INBOX, false)];
+    bridge.send_execute(call.execute_instruction(RECEIVER_ID, &inbox)).await.unwrap();
+
+    // Only the payer of the record can close it
+    let intruder = Keypair::new();
+    let intruder_close = Unlock { payer: intruder.pubkey(), ..call.unlock.clone() };
+    assert_eq!(
+        bridge
+            .send(&[intruder_close.close_instruction()], &[&payer, &intruder])
+            .await
+            .unwrap_err(),
+        anchor_error(0, ErrorCode::ConstraintHasOne)
+    );
+
+    bridge.send(&[call.unlock.close_instruction()], &[&payer]).await.unwrap();
+    assert!(!bridge.exists(call.unlock.message_record()).await);
+
+    // The spent sequence still blocks a replay
+    assert_eq!(
+        bridge.send_unlock(&call.unlock, &[0, 1]).await.unwrap_err(),
+        bridge_error(1, BridgeError::MessageAlreadyProcessed)
+    );
+}
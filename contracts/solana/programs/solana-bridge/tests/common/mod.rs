@@ -0,0 +1,786 @@
+//! In-process bridge shared by the instruction tests: the program running
+//! natively under `solana-program-test`, three validators of which two are
+//! required, a mint, a funded sender and a receiver program for deliveries.
+
+#![allow(dead_code)]
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::{AccountDeserialize, AccountSerialize, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use solana_bridge::error::BridgeError;
+use solana_bridge::state::*;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::{ed25519_program, system_program, sysvar};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::account::Account as SolanaAccount;
+use solana_sdk::instruction::{Instruction, InstructionError};
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::{Transaction, TransactionError};
+use spl_associated_token_account::get_associated_token_address;
+use spl_associated_token_account::instruction::create_associated_token_account;
+
+/// Tokens minted to the sender
+pub const SENDER_TOKENS: u64 = 1_000_000;
+
+fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    // The Anchor entrypoint wants accounts that outlive the call
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    solana_bridge::entry(program_id, accounts, data)
+}
+
+/// Program the bridge delivers messages and calls to
+pub const RECEIVER_ID: Pubkey = Pubkey::new_from_array([7; 32]);
+
+/// Receiver-owned account holding the last delivery
+pub const INBOX: Pubkey = Pubkey::new_from_array([8; 32]);
+
+const INBOX_LEN: usize = 1024;
+
+/// Receiver accepting deliveries signed by the bridge messenger. The
+/// instruction data is written to the inbox, which must be passed as a
+/// remaining account; without it the delivery fails and can be retried.
+fn process_receiver(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let messenger = accounts.first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+    if messenger.key != &messenger_address() || !messenger.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let inbox = accounts
+        .iter()
+        .find(|account| account.key == &INBOX && account.owner == program_id)
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+    let mut stored = inbox.try_borrow_mut_data()?;
+    stored[..4].copy_from_slice(&(data.len() as u32).to_le_bytes());
+    stored[4..4 + data.len()].copy_from_slice(data);
+    Ok(())
+}
+
+/// Program test with a funded admin account and the receiver program
+pub fn program_test(admin: &Keypair) -> ProgramTest {
+    let mut test =
+        ProgramTest::new("solana_bridge", solana_bridge::ID, processor!(process_instruction));
+    test.add_account(
+        admin.pubkey(),
+        SolanaAccount::new(10_000_000_000, 0, &system_program::ID),
+    );
+    test.add_program("message_receiver", RECEIVER_ID, processor!(process_receiver));
+    test.add_account(
+        INBOX,
+        SolanaAccount {
+            lamports: Rent::default().minimum_balance(INBOX_LEN),
+            data: vec![0; INBOX_LEN],
+            owner: RECEIVER_ID,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    test
+}
+
+pub fn bridge_config_address() -> Pubkey {
+    Pubkey::find_program_address(&[b"bridge_config"], &solana_bridge::ID).0
+}
+
+pub fn token_vault_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"token_vault", mint.as_ref()], &solana_bridge::ID).0
+}
+
+pub fn lock_record_address(sender: &Pubkey, nonce: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"lock_record", sender.as_ref(), &nonce.to_le_bytes()],
+        &solana_bridge::ID,
+    )
+    .0
+}
+
+pub fn message_record_address(message_id: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(&[b"message_record", message_id.as_ref()], &solana_bridge::ID).0
+}
+
+pub fn processed_sequences_address(source_chain: &str, sequence: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"processed_sequences",
+            &ProcessedSequences::chain_seed(source_chain),
+            &ProcessedSequences::window_of(sequence).to_le_bytes(),
+        ],
+        &solana_bridge::ID,
+    )
+    .0
+}
+
+pub fn messenger_address() -> Pubkey {
+    Pubkey::find_program_address(&[b"messenger"], &solana_bridge::ID).0
+}
+
+pub fn event_authority_address() -> Pubkey {
+    Pubkey::find_program_address(&[b"__event_authority"], &solana_bridge::ID).0
+}
+
+pub fn vault_token_account(mint: &Pubkey) -> Pubkey {
+    get_associated_token_address(&bridge_config_address(), mint)
+}
+
+pub fn initialize_instruction(
+    admin: Pubkey,
+    validators: Vec<Pubkey>,
+    required_signatures: u8,
+) -> Instruction {
+    Instruction {
+        program_id: solana_bridge::ID,
+        accounts: solana_bridge::accounts::Initialize {
+            bridge_config: bridge_config_address(),
+            admin,
+            system_program: system_program::ID,
+            event_authority: event_authority_address(),
+            program: solana_bridge::ID,
+        }
+        .to_account_metas(None),
+        data: solana_bridge::instruction::Initialize {
+            validators,
+            required_signatures,
+        }
+        .data(),
+    }
+}
+
+/// Admin instruction of the bridge program, signed by `admin`
+pub fn admin_instruction(admin: Pubkey, data: impl InstructionData) -> Instruction {
+    // `UpdateValidators` and `UpdateConfig` take the same accounts
+    Instruction {
+        program_id: solana_bridge::ID,
+        accounts: solana_bridge::accounts::UpdateConfig {
+            bridge_config: bridge_config_address(),
+            admin,
+            event_authority: event_authority_address(),
+            program: solana_bridge::ID,
+        }
+        .to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// Outbound transfer of the sender's tokens
+#[derive(Clone, Debug)]
+pub struct Lock {
+    pub sender: Pubkey,
+    pub sender_token_account: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub destination_chain: String,
+    pub destination_address: String,
+    pub nonce: u64,
+}
+
+impl Lock {
+    pub fn instruction(&self) -> Instruction {
+        Instruction {
+            program_id: solana_bridge::ID,
+            accounts: solana_bridge::accounts::LockToken {
+                bridge_config: bridge_config_address(),
+                token_vault: token_vault_address(&self.mint),
+                lock_record: self.lock_record(),
+                sender: self.sender,
+                sender_token_account: self.sender_token_account,
+                vault_token_account: vault_token_account(&self.mint),
+                token_mint: self.mint,
+                token_program: spl_token::ID,
+                associated_token_program: spl_associated_token_account::ID,
+                system_program: system_program::ID,
+                rent: sysvar::rent::ID,
+            }
+            .to_account_metas(None),
+            data: solana_bridge::instruction::LockToken {
+                amount: self.amount,
+                destination_chain: self.destination_chain.clone(),
+                destination_address: self.destination_address.clone(),
+                nonce: self.nonce,
+            }
+            .data(),
+        }
+    }
+
+    pub fn lock_record(&self) -> Pubkey {
+        lock_record_address(&self.sender, self.nonce)
+    }
+
+    /// Return of the locked tokens, a refund or an expiry by `data`
+    pub fn refund_instruction(&self, data: impl InstructionData) -> Instruction {
+        Instruction {
+            program_id: solana_bridge::ID,
+            accounts: solana_bridge::accounts::Refund {
+                bridge_config: bridge_config_address(),
+                lock_record: self.lock_record(),
+                token_vault: token_vault_address(&self.mint),
+                vault_token_account: vault_token_account(&self.mint),
+                sender_token_account: self.sender_token_account,
+                token_mint: self.mint,
+                instructions_sysvar: sysvar::instructions::ID,
+                token_program: spl_token::ID,
+            }
+            .to_account_metas(None),
+            data: data.data(),
+        }
+    }
+
+    pub fn acknowledge_instruction(&self, destination_tx: &str) -> Instruction {
+        self.acknowledge_aggregate_instruction(destination_tx, None)
+    }
+
+    /// Acknowledgement attested by a threshold signature, or by individual
+    /// signatures when `aggregate_signature` is `None`
+    pub fn acknowledge_aggregate_instruction(
+        &self,
+        destination_tx: &str,
+        aggregate_signature: Option<AggregateSignature>,
+    ) -> Instruction {
+        Instruction {
+            program_id: solana_bridge::ID,
+            accounts: solana_bridge::accounts::AcknowledgeDelivery {
+                bridge_config: bridge_config_address(),
+                lock_record: self.lock_record(),
+                instructions_sysvar: sysvar::instructions::ID,
+            }
+            .to_account_metas(None),
+            data: solana_bridge::instruction::AcknowledgeDelivery {
+                destination_tx: destination_tx.to_string(),
+                aggregate_signature,
+            }
+            .data(),
+        }
+    }
+
+    /// Closing of the lock record, signed by `sender`
+    pub fn close_instruction(&self, sender: Pubkey) -> Instruction {
+        Instruction {
+            program_id: solana_bridge::ID,
+            accounts: solana_bridge::accounts::CloseLockRecord {
+                lock_record: self.lock_record(),
+                sender,
+            }
+            .to_account_metas(None),
+            data: solana_bridge::instruction::CloseLockRecord {}.data(),
+        }
+    }
+
+    /// Message ID of the lock made at `sequence`
+    pub fn message_id(&self, sequence: u64) -> [u8; 32] {
+        bridge_types::TransferMessage {
+            source_chain: bridge_types::chain::SOLANA.to_string(),
+            source_bridge: solana_bridge::ID.to_bytes().to_vec(),
+            sequence,
+            sender: self.sender.to_bytes().to_vec(),
+            token: self.mint.to_bytes().to_vec(),
+            amount: u128::from(self.amount),
+            destination_chain: self.destination_chain.clone(),
+            recipient: self.destination_address.clone(),
+        }
+        .id::<bridge_types::hash::Sha3Keccak>()
+    }
+}
+
+/// Inbound transfer to `recipient`
+#[derive(Clone, Debug)]
+pub struct Unlock {
+    pub payer: Pubkey,
+    pub recipient: Pubkey,
+    pub recipient_token_account: Pubkey,
+    pub mint: Pubkey,
+    pub message_id: [u8; 32],
+    pub source_chain: String,
+    pub sequence: u64,
+    pub sender: String,
+    pub amount: u64,
+}
+
+impl Unlock {
+    pub fn instruction(&self, signatures: &[[u8; 64]]) -> Instruction {
+        Instruction {
+            program_id: solana_bridge::ID,
+            accounts: solana_bridge::accounts::UnlockToken {
+                bridge_config: bridge_config_address(),
+                token_vault: token_vault_address(&self.mint),
+                message_record: message_record_address(&self.message_id),
+                processed_sequences: self.processed_sequences(),
+                payer: self.payer,
+                recipient: self.recipient,
+                recipient_token_account: self.recipient_token_account,
+                vault_token_account: vault_token_account(&self.mint),
+                token_mint: self.mint,
+                instructions_sysvar: sysvar::instructions::ID,
+                token_program: spl_token::ID,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: solana_bridge::instruction::UnlockToken {
+                message_id: self.message_id,
+                source_chain: self.source_chain.clone(),
+                sequence: self.sequence,
+                sender: self.sender.clone(),
+                amount: self.amount,
+                signatures: signatures.to_vec(),
+            }
+            .data(),
+        }
+    }
+
+    /// Unlock under a single threshold signature
+    pub fn aggregate_instruction(&self, aggregate_signature: AggregateSignature) -> Instruction {
+        let mut instruction = self.instruction(&[]);
+        instruction.data = solana_bridge::instruction::UnlockTokenAggregate {
+            message_id: self.message_id,
+            source_chain: self.source_chain.clone(),
+            sequence: self.sequence,
+            sender: self.sender.clone(),
+            amount: self.amount,
+            aggregate_signature,
+        }
+        .data();
+        instruction
+    }
+
+    /// Ed25519 verification of the group signature over the unlock hash and
+    /// `signer_bitmap`, followed by the threshold unlock naming the
+    /// participants in `signer_bitmap`
+    pub fn aggregate_instructions(&self, group: &Keypair, signer_bitmap: u32) -> Vec<Instruction> {
+        let hash = self.aggregate_hash(signer_bitmap);
+        let signature: [u8; 64] = group.sign_message(&hash).into();
+        vec![
+            ed25519_instruction(&[(group.pubkey(), signature)], &hash),
+            self.aggregate_instruction(AggregateSignature { signature, signer_bitmap }),
+        ]
+    }
+
+    /// Hash the threshold group signs to release the transfer with the
+    /// participants in `signer_bitmap`
+    pub fn aggregate_hash(&self, signer_bitmap: u32) -> [u8; 32] {
+        bridge_types::aggregate_signing_hash::<bridge_types::hash::Sha3Keccak>(
+            &self.message_hash(),
+            signer_bitmap,
+        )
+    }
+
+    /// Hash the validators sign to release the transfer
+    pub fn message_hash(&self) -> [u8; 32] {
+        bridge_types::UnlockAttestation {
+            message_id: self.message_id,
+            source_chain: self.source_chain.clone(),
+            sequence: self.sequence,
+            sender: self.sender.clone(),
+            destination_chain: bridge_types::chain::SOLANA.to_string(),
+            destination_bridge: solana_bridge::ID.to_bytes().to_vec(),
+            recipient: self.recipient.to_bytes().to_vec(),
+            token: self.mint.to_bytes().to_vec(),
+            amount: u128::from(self.amount),
+        }
+        .signing_hash::<bridge_types::hash::Sha3Keccak>()
+    }
+
+    /// Ed25519 verification of `signers` over the unlock hash, followed by
+    /// the unlock itself
+    pub fn instructions(&self, signers: &[&Keypair]) -> Vec<Instruction> {
+        let hash = self.message_hash();
+        let signatures: Vec<(Pubkey, [u8; 64])> = signers
+            .iter()
+            .map(|signer| (signer.pubkey(), signer.sign_message(&hash).into()))
+            .collect();
+        let unlock_signatures: Vec<[u8; 64]> =
+            signatures.iter().map(|(_, signature)| *signature).collect();
+        vec![
+            ed25519_instruction(&signatures, &hash),
+            self.instruction(&unlock_signatures),
+        ]
+    }
+
+    pub fn message_record(&self) -> Pubkey {
+        message_record_address(&self.message_id)
+    }
+
+    pub fn processed_sequences(&self) -> Pubkey {
+        processed_sequences_address(&self.source_chain, self.sequence)
+    }
+
+    pub fn close_instruction(&self) -> Instruction {
+        Instruction {
+            program_id: solana_bridge::ID,
+            accounts: solana_bridge::accounts::CloseMessageRecord {
+                message_record: self.message_record(),
+                payer: self.payer,
+            }
+            .to_account_metas(None),
+            data: solana_bridge::instruction::CloseMessageRecord {}.data(),
+        }
+    }
+}
+
+/// Hash the validators sign to attest the outcome of the lock in `record`.
+/// `extra` is the delivering transaction for deliveries and empty otherwise.
+pub fn lock_outcome_hash(domain: &[u8], record: &LockRecord, extra: &[u8]) -> [u8; 32] {
+    solana_program::keccak::hashv(&[
+        domain,
+        bridge_config_address().as_ref(),
+        &record.message_id,
+        record.sender.as_ref(),
+        record.token_mint.as_ref(),
+        &record.amount.to_le_bytes(),
+        extra,
+    ])
+    .to_bytes()
+}
+
+/// Ed25519 precompile instruction verifying every signature over `message`,
+/// with all data held in the instruction itself
+pub fn ed25519_instruction(signatures: &[(Pubkey, [u8; 64])], message: &[u8]) -> Instruction {
+    let entries_start = 2 + 14 * signatures.len();
+    let message_offset = (entries_start + 96 * signatures.len()) as u16;
+
+    let mut data = vec![signatures.len() as u8, 0];
+    for i in 0..signatures.len() {
+        let public_key_offset = (entries_start + 96 * i) as u16;
+        for value in [
+            public_key_offset + 32,
+            u16::MAX,
+            public_key_offset,
+            u16::MAX,
+            message_offset,
+            message.len() as u16,
+            u16::MAX,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+    for (public_key, signature) in signatures {
+        data.extend_from_slice(public_key.as_ref());
+        data.extend_from_slice(signature);
+    }
+    data.extend_from_slice(message);
+
+    Instruction {
+        program_id: ed25519_program::ID,
+        accounts: Vec::new(),
+        data,
+    }
+}
+
+/// Running bridge with two of three validators required and a funded sender
+pub struct Bridge {
+    pub context: ProgramTestContext,
+    pub admin: Keypair,
+    pub validators: Vec<Keypair>,
+    pub mint: Pubkey,
+    pub sender: Keypair,
+    pub sender_token_account: Pubkey,
+}
+
+impl Bridge {
+    /// Bridge initialized through the `initialize` instruction
+    pub async fn start() -> Self {
+        let admin = Keypair::new();
+        let validators: Vec<Keypair> = (0..3).map(|_| Keypair::new()).collect();
+        let mut context = program_test(&admin).start_with_context().await;
+
+        let initialize = initialize_instruction(
+            admin.pubkey(),
+            validators.iter().map(Keypair::pubkey).collect(),
+            2,
+        );
+        let payer = context.payer.insecure_clone();
+        send(&mut context, &[initialize], &[&payer, &admin]).await.unwrap();
+
+        Self::with_mint(context, admin, validators).await
+    }
+
+    /// Bridge whose config is written directly, after `edit`, instead of
+    /// through `initialize`. Used to reach states such as counters near
+    /// their limits.
+    pub async fn start_with_config(edit: impl FnOnce(&mut BridgeConfig)) -> Self {
+        let admin = Keypair::new();
+        let validators: Vec<Keypair> = (0..3).map(|_| Keypair::new()).collect();
+        let (_, bump) = Pubkey::find_program_address(&[b"bridge_config"], &solana_bridge::ID);
+        let mut config = BridgeConfig {
+            version: BridgeConfig::VERSION,
+            admin: admin.pubkey(),
+            validators: validators.iter().map(Keypair::pubkey).collect(),
+            required_signatures: 2,
+            is_paused: false,
+            total_locked: 0,
+            total_unlocked: 0,
+            message_count: 0,
+            bump,
+            signature_mode: SignatureMode::Individual,
+            group_key: Pubkey::default(),
+            group_threshold: 0,
+            group_size: 0,
+            group_key_epoch: 0,
+            governance: Pubkey::default(),
+            reserved: [0; 128],
+        };
+        edit(&mut config);
+
+        let mut data = Vec::with_capacity(BridgeConfig::LEN);
+        config.try_serialize(&mut data).unwrap();
+        data.resize(BridgeConfig::LEN, 0);
+
+        let mut test = program_test(&admin);
+        test.add_account(
+            bridge_config_address(),
+            SolanaAccount {
+                lamports: Rent::default().minimum_balance(BridgeConfig::LEN),
+                data,
+                owner: solana_bridge::ID,
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+        let context = test.start_with_context().await;
+
+        Self::with_mint(context, admin, validators).await
+    }
+
+    async fn with_mint(
+        mut context: ProgramTestContext,
+        admin: Keypair,
+        validators: Vec<Keypair>,
+    ) -> Self {
+        let payer = context.payer.insecure_clone();
+        let mint = Keypair::new();
+        let rent = Rent::default().minimum_balance(spl_token::state::Mint::LEN);
+        send(
+            &mut context,
+            &[
+                system_instruction::create_account(
+                    &payer.pubkey(),
+                    &mint.pubkey(),
+                    rent,
+                    spl_token::state::Mint::LEN as u64,
+                    &spl_token::ID,
+                ),
+                spl_token::instruction::initialize_mint(
+                    &spl_token::ID,
+                    &mint.pubkey(),
+                    &payer.pubkey(),
+                    None,
+                    6,
+                )
+                .unwrap(),
+            ],
+            &[&payer, &mint],
+        )
+        .await
+        .unwrap();
+
+        let mut bridge = Self {
+            context,
+            admin,
+            validators,
+            mint: mint.pubkey(),
+            sender: Keypair::new(),
+            sender_token_account: Pubkey::default(),
+        };
+        let sender = bridge.sender.pubkey();
+        bridge.sender_token_account = bridge.token_account(&sender).await;
+        bridge.mint_to(bridge.sender_token_account, SENDER_TOKENS).await;
+        bridge.fund(&sender).await;
+        bridge
+    }
+
+    pub fn payer(&self) -> Keypair {
+        self.context.payer.insecure_clone()
+    }
+
+    pub async fn fund(&mut self, account: &Pubkey) {
+        let payer = self.payer();
+        let transfer = system_instruction::transfer(&payer.pubkey(), account, 1_000_000_000);
+        send(&mut self.context, &[transfer], &[&payer]).await.unwrap();
+    }
+
+    /// Associated token account of `owner` for the bridge mint
+    pub async fn token_account(&mut self, owner: &Pubkey) -> Pubkey {
+        let payer = self.payer();
+        let create =
+            create_associated_token_account(&payer.pubkey(), owner, &self.mint, &spl_token::ID);
+        send(&mut self.context, &[create], &[&payer]).await.unwrap();
+        get_associated_token_address(owner, &self.mint)
+    }
+
+    pub async fn mint_to(&mut self, account: Pubkey, amount: u64) {
+        let payer = self.payer();
+        let mint_to = spl_token::instruction::mint_to(
+            &spl_token::ID,
+            &self.mint,
+            &account,
+            &payer.pubkey(),
+            &[],
+            amount,
+        )
+        .unwrap();
+        send(&mut self.context, &[mint_to], &[&payer]).await.unwrap();
+    }
+
+    pub async fn balance(&mut self, account: Pubkey) -> u64 {
+        let account = self.context.banks_client.get_account(account).await.unwrap().unwrap();
+        spl_token::state::Account::unpack(&account.data).unwrap().amount
+    }
+
+    pub async fn fetch<T: AccountDeserialize>(&mut self, address: Pubkey) -> T {
+        let account = self.context.banks_client.get_account(address).await.unwrap().unwrap();
+        T::try_deserialize(&mut &account.data[..]).unwrap()
+    }
+
+    pub async fn exists(&mut self, address: Pubkey) -> bool {
+        self.context.banks_client.get_account(address).await.unwrap().is_some()
+    }
+
+    pub async fn config(&mut self) -> BridgeConfig {
+        self.fetch(bridge_config_address()).await
+    }
+
+    /// Lock of `amount` of the sender's tokens to NEAR
+    pub fn lock(&self, amount: u64, nonce: u64) -> Lock {
+        Lock {
+            sender: self.sender.pubkey(),
+            sender_token_account: self.sender_token_account,
+            mint: self.mint,
+            amount,
+            destination_chain: "near".to_string(),
+            destination_address: "alice.near".to_string(),
+            nonce,
+        }
+    }
+
+    pub async fn send_lock(&mut self, lock: &Lock) -> std::result::Result<(), TransactionError> {
+        let sender = self.sender.insecure_clone();
+        let payer = self.payer();
+        self.send(&[lock.instruction()], &[&payer, &sender]).await
+    }
+
+    /// Inbound transfer of `amount` from NEAR to a new recipient
+    pub async fn unlock(&mut self, message_id: u8, amount: u64) -> Unlock {
+        let recipient = Keypair::new().pubkey();
+        let recipient_token_account = self.token_account(&recipient).await;
+        Unlock {
+            payer: self.context.payer.pubkey(),
+            recipient,
+            recipient_token_account,
+            mint: self.mint,
+            message_id: [message_id; 32],
+            source_chain: "near".to_string(),
+            sequence: u64::from(message_id),
+            sender: "alice.near".to_string(),
+            amount,
+        }
+    }
+
+    /// Submit `unlock` attested by the validators at `signers`
+    pub async fn send_unlock(
+        &mut self,
+        unlock: &Unlock,
+        signers: &[usize],
+    ) -> std::result::Result<(), TransactionError> {
+        let signers: Vec<&Keypair> = signers.iter().map(|&index| &self.validators[index]).collect();
+        let instructions = unlock.instructions(&signers);
+        let payer = self.payer();
+        self.send(&instructions, &[&payer]).await
+    }
+
+    /// Ed25519 precompile instruction with the signatures of the validators
+    /// at `signers` over `message`
+    pub fn attestation(&self, message: &[u8], signers: &[usize]) -> Instruction {
+        let signatures: Vec<(Pubkey, [u8; 64])> = signers
+            .iter()
+            .map(|&index| {
+                let validator = &self.validators[index];
+                (validator.pubkey(), validator.sign_message(message).into())
+            })
+            .collect();
+        ed25519_instruction(&signatures, message)
+    }
+
+    /// Move the cluster clock `seconds` forward
+    pub async fn advance_clock(&mut self, seconds: i64) {
+        let mut clock: Clock = self.context.banks_client.get_sysvar().await.unwrap();
+        clock.unix_timestamp += seconds;
+        self.context.set_sysvar(&clock);
+    }
+
+    /// Lamports held by `address`
+    pub async fn lamports(&mut self, address: Pubkey) -> u64 {
+        self.context.banks_client.get_balance(address).await.unwrap()
+    }
+
+    /// Instruction data of the last delivery to the receiver program
+    pub async fn delivery(&mut self) -> Vec<u8> {
+        let account = self.context.banks_client.get_account(INBOX).await.unwrap().unwrap();
+        let len = u32::from_le_bytes(account.data[..4].try_into().unwrap()) as usize;
+        account.data[4..4 + len].to_vec()
+    }
+
+    /// Admin instruction signed by the admin
+    pub async fn send_admin(
+        &mut self,
+        data: impl InstructionData,
+    ) -> std::result::Result<(), TransactionError> {
+        let admin = self.admin.insecure_clone();
+        let payer = self.payer();
+        self.send(&[admin_instruction(admin.pubkey(), data)], &[&payer, &admin]).await
+    }
+
+    /// Send `instructions` in a fresh transaction, so identical retries are
+    /// not deduplicated
+    pub async fn send(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> std::result::Result<(), TransactionError> {
+        self.next_slot().await;
+        send(&mut self.context, instructions, signers).await.map_err(|err| err.unwrap())
+    }
+
+    async fn next_slot(&mut self) {
+        let clock: Clock = self.context.banks_client.get_sysvar().await.unwrap();
+        self.context.warp_to_slot(clock.slot + 2).unwrap();
+    }
+}
+
+pub async fn send(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> std::result::Result<(), BanksClientError> {
+    let blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        signers,
+        blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await
+}
+
+/// Failure of instruction `index` with a bridge error
+pub fn bridge_error(index: u8, error: BridgeError) -> TransactionError {
+    TransactionError::InstructionError(index, InstructionError::Custom(error.into()))
+}
+
+/// Failure of instruction `index` with an Anchor framework error, such as a
+/// violated account constraint
+pub fn anchor_error(index: u8, error: anchor_lang::error::ErrorCode) -> TransactionError {
+    TransactionError::InstructionError(index, InstructionError::Custom(error.into()))
+}
+
+/// Failure of instruction `index` creating an account that already exists
+pub fn already_in_use(index: u8) -> TransactionError {
+    TransactionError::InstructionError(
+        index,
+        InstructionError::Custom(
+            solana_sdk::system_instruction::SystemError::AccountAlreadyInUse as u32,
+        ),
+    )
+}
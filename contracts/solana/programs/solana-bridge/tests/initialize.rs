@@ -0,0 +1,99 @@
+//! Bridge initialization and validator set checks
+
+mod common;
+
+use anchor_lang::prelude::*;
+use common::*;
+use solana_bridge::error::BridgeError;
+use solana_bridge::state::{BridgeConfig, SignatureMode};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::TransactionError;
+
+/// Initialize a fresh bridge
+async fn initialize(
+    validators: Vec<Pubkey>,
+    required_signatures: u8,
+) -> (ProgramTestContext, std::result::Result<(), TransactionError>) {
+    let admin = Keypair::new();
+    let mut context = program_test(&admin).start_with_context().await;
+    let payer = context.payer.insecure_clone();
+    let result = send(
+        &mut context,
+        &[initialize_instruction(admin.pubkey(), validators, required_signatures)],
+        &[&payer, &admin],
+    )
+    .await
+    .map_err(|err| err.unwrap());
+    (context, result)
+}
+
+#[tokio::test]
+async fn initialize_writes_config() {
+    let mut bridge = Bridge::start().await;
+    let admin = bridge.admin.pubkey();
+    let validators: Vec<Pubkey> = bridge.validators.iter().map(Keypair::pubkey).collect();
+
+    let config = bridge.config().await;
+    assert_eq!(config.version, BridgeConfig::VERSION);
+    assert_eq!(config.admin, admin);
+    assert_eq!(config.validators, validators);
+    assert_eq!(config.required_signatures, 2);
+    assert!(!config.is_paused);
+    assert_eq!(config.total_locked, 0);
+    assert_eq!(config.total_unlocked, 0);
+    assert_eq!(config.message_count, 0);
+    assert_eq!(
+        config.bump,
+        Pubkey::find_program_address(&[b"bridge_config"], &solana_bridge::ID).1
+    );
+    assert_eq!(config.signature_mode, SignatureMode::Individual);
+    assert_eq!(config.group_key, Pubkey::default());
+    assert_eq!(config.governance, Pubkey::default());
+}
+
+#[tokio::test]
+async fn initialize_accepts_the_maximum_validator_set() {
+    let validators: Vec<Pubkey> =
+        (0..BridgeConfig::MAX_VALIDATORS).map(|_| Pubkey::new_unique()).collect();
+    let (mut context, result) = initialize(validators.clone(), 10).await;
+    result.unwrap();
+
+    let account = context.banks_client.get_account(bridge_config_address()).await.unwrap();
+    let config = BridgeConfig::try_deserialize(&mut &account.unwrap().data[..]).unwrap();
+    assert_eq!(config.validators, validators);
+}
+
+#[tokio::test]
+async fn initialize_rejects_too_many_validators() {
+    let validators = (0..=BridgeConfig::MAX_VALIDATORS).map(|_| Pubkey::new_unique()).collect();
+    let (_, result) = initialize(validators, 2).await;
+    assert_eq!(result.unwrap_err(), bridge_error(0, BridgeError::MaxValidatorsReached));
+}
+
+#[tokio::test]
+async fn initialize_rejects_invalid_required_signatures() {
+    let validators: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+    for required_signatures in [0, 4] {
+        let (_, result) = initialize(validators.clone(), required_signatures).await;
+        assert_eq!(result.unwrap_err(), bridge_error(0, BridgeError::InvalidRequiredSignatures));
+    }
+
+    // An empty set cannot meet any threshold
+    let (_, result) = initialize(Vec::new(), 1).await;
+    assert_eq!(result.unwrap_err(), bridge_error(0, BridgeError::InvalidRequiredSignatures));
+}
+
+#[tokio::test]
+async fn initialize_runs_once() {
+    let mut bridge = Bridge::start().await;
+    let admin = bridge.admin.insecure_clone();
+    let payer = bridge.payer();
+
+    let initialize = initialize_instruction(admin.pubkey(), vec![Pubkey::new_unique()], 1);
+    assert_eq!(
+        bridge.send(&[initialize], &[&payer, &admin]).await.unwrap_err(),
+        already_in_use(0)
+    );
+    assert_eq!(bridge.config().await.validators.len(), 3);
+}
@@ -0,0 +1,162 @@
+//! Outbound transfers: escrow in the vault, lock records and the bridge
+//! counters
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use common::*;
+use solana_bridge::error::BridgeError;
+use solana_bridge::instruction::Pause;
+use solana_bridge::state::{LockRecord, LockStatus, TokenVault};
+use solana_sdk::signature::{Keypair, Signer};
+
+#[tokio::test]
+async fn lock_token_escrows_tokens_and_records_the_lock() {
+    let mut bridge = Bridge::start().await;
+    let lock = bridge.lock(250_000, 1);
+
+    bridge.send_lock(&lock).await.unwrap();
+
+    assert_eq!(bridge.balance(bridge.sender_token_account).await, SENDER_TOKENS - 250_000);
+    assert_eq!(bridge.balance(vault_token_account(&bridge.mint)).await, 250_000);
+
+    let record: LockRecord = bridge.fetch(lock.lock_record()).await;
+    assert_eq!(record.version, LockRecord::VERSION);
+    assert_eq!(record.message_id, lock.message_id(0));
+    assert_eq!(record.sender, lock.sender);
+    assert_eq!(record.token_mint, bridge.mint);
+    assert_eq!(record.amount, 250_000);
+    assert_eq!(record.destination_chain, "near");
+    assert_eq!(record.destination_address, "alice.near");
+    assert_eq!(record.nonce, 1);
+    assert_eq!(record.sequence, 0);
+    assert_eq!(record.status, LockStatus::Pending);
+
+    let vault: TokenVault = bridge.fetch(token_vault_address(&bridge.mint)).await;
+    assert_eq!(vault.bridge_config, bridge_config_address());
+    assert_eq!(vault.token_mint, bridge.mint);
+    assert_eq!(vault.total_locked, 250_000);
+
+    let config = bridge.config().await;
+    assert_eq!(config.total_locked, 250_000);
+    assert_eq!(config.message_count, 1);
+}
+
+#[tokio::test]
+async fn lock_token_assigns_sequences_across_nonces() {
+    let mut bridge = Bridge::start().await;
+
+    for (sequence, nonce) in [(0, 7), (1, 3), (2, 100)] {
+        let lock = bridge.lock(1_000, nonce);
+        bridge.send_lock(&lock).await.unwrap();
+        let record: LockRecord = bridge.fetch(lock.lock_record()).await;
+        assert_eq!(record.sequence, sequence);
+        assert_eq!(record.message_id, lock.message_id(sequence));
+    }
+
+    let vault: TokenVault = bridge.fetch(token_vault_address(&bridge.mint)).await;
+    assert_eq!(vault.total_locked, 3_000);
+    assert_eq!(bridge.config().await.message_count, 3);
+}
+
+#[tokio::test]
+async fn lock_token_rejects_a_reused_nonce() {
+    let mut bridge = Bridge::start().await;
+    bridge.send_lock(&bridge.lock(1_000, 1)).await.unwrap();
+
+    let lock = bridge.lock(2_000, 1);
+    assert_eq!(bridge.send_lock(&lock).await.unwrap_err(), already_in_use(0));
+
+    let record: LockRecord = bridge.fetch(lock.lock_record()).await;
+    assert_eq!(record.amount, 1_000);
+    assert_eq!(bridge.config().await.message_count, 1);
+}
+
+#[tokio::test]
+async fn lock_token_validates_the_transfer() {
+    let mut bridge = Bridge::start().await;
+
+    let lock = bridge.lock(0, 1);
+    assert_eq!(
+        bridge.send_lock(&lock).await.unwrap_err(),
+        bridge_error(0, BridgeError::InvalidAmount)
+    );
+
+    let mut lock = bridge.lock(1_000, 1);
+    lock.destination_chain = "c".repeat(LockRecord::MAX_CHAIN_LEN + 1);
+    assert_eq!(
+        bridge.send_lock(&lock).await.unwrap_err(),
+        bridge_error(0, BridgeError::DestinationChainTooLong)
+    );
+
+    let mut lock = bridge.lock(1_000, 1);
+    lock.destination_address = "a".repeat(LockRecord::MAX_ADDRESS_LEN + 1);
+    assert_eq!(
+        bridge.send_lock(&lock).await.unwrap_err(),
+        bridge_error(0, BridgeError::DestinationAddressTooLong)
+    );
+
+    // More than the sender holds fails in the token program
+    let lock = bridge.lock(SENDER_TOKENS + 1, 1);
+    assert!(bridge.send_lock(&lock).await.is_err());
+
+    assert_eq!(bridge.balance(bridge.sender_token_account).await, SENDER_TOKENS);
+    assert_eq!(bridge.config().await.message_count, 0);
+}
+
+#[tokio::test]
+async fn lock_token_rejects_foreign_token_accounts() {
+    let mut bridge = Bridge::start().await;
+    let victim = Keypair::new().pubkey();
+    let victim_token_account = bridge.token_account(&victim).await;
+    bridge.mint_to(victim_token_account, 1_000).await;
+
+    // The sender cannot lock from an account it does not own
+    let mut lock = bridge.lock(1_000, 1);
+    lock.sender_token_account = victim_token_account;
+    assert_eq!(
+        bridge.send_lock(&lock).await.unwrap_err(),
+        anchor_error(0, ErrorCode::ConstraintRaw)
+    );
+
+    // Nor lock one mint while recording another
+    let mut lock = bridge.lock(1_000, 1);
+    lock.mint = Keypair::new().pubkey();
+    assert!(bridge.send_lock(&lock).await.is_err());
+
+    assert_eq!(bridge.balance(victim_token_account).await, 1_000);
+}
+
+#[tokio::test]
+async fn lock_token_is_rejected_while_paused() {
+    let mut bridge = Bridge::start().await;
+    bridge.send_admin(Pause {}).await.unwrap();
+
+    let lock = bridge.lock(1_000, 1);
+    assert_eq!(
+        bridge.send_lock(&lock).await.unwrap_err(),
+        bridge_error(0, BridgeError::BridgePaused)
+    );
+    assert!(!bridge.exists(lock.lock_record()).await);
+    assert_eq!(bridge.balance(bridge.sender_token_account).await, SENDER_TOKENS);
+}
+
+#[tokio::test]
+async fn lock_token_reports_counter_overflow() {
+    let mut bridge =
+        Bridge::start_with_config(|config| config.total_locked = u64::MAX - 10).await;
+    let lock = bridge.lock(11, 1);
+    assert_eq!(
+        bridge.send_lock(&lock).await.unwrap_err(),
+        bridge_error(0, BridgeError::ArithmeticOverflow)
+    );
+    bridge.send_lock(&bridge.lock(10, 2)).await.unwrap();
+
+    let mut bridge = Bridge::start_with_config(|config| config.message_count = u64::MAX).await;
+    let lock = bridge.lock(1, 1);
+    assert_eq!(
+        bridge.send_lock(&lock).await.unwrap_err(),
+        bridge_error(0, BridgeError::ArithmeticOverflow)
+    );
+    assert!(!bridge.exists(lock.lock_record()).await);
+}
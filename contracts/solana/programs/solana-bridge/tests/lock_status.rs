@@ -0,0 +1,268 @@
+//! Outbound transfer outcomes: attested delivery, refund and expiry, and
+//! closing finalized lock records
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use common::*;
+use solana_bridge::error::BridgeError;
+use solana_bridge::instruction::{ExpireLock, Pause, Refund};
+use solana_bridge::state::{LockRecord, LockStatus, TokenVault};
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::TransactionError;
+
+/// Bridge with a pending lock of 1 000 of the sender's tokens
+async fn locked_bridge() -> (Bridge, Lock) {
+    let mut bridge = Bridge::start().await;
+    let lock = bridge.lock(1_000, 1);
+    bridge.send_lock(&lock).await.unwrap();
+    (bridge, lock)
+}
+
+impl Bridge {
+    async fn lock_record(&mut self, lock: &Lock) -> LockRecord {
+        self.fetch(lock.lock_record()).await
+    }
+
+    /// Refund `lock`, attested by `signers` under `domain`
+    async fn send_refund(
+        &mut self,
+        lock: &Lock,
+        domain: &[u8],
+        signers: &[usize],
+    ) -> Result<(), TransactionError> {
+        let record = self.lock_record(lock).await;
+        let attestation = self.attestation(&lock_outcome_hash(domain, &record, &[]), signers);
+        let payer = self.payer();
+        let refund = lock.refund_instruction(Refund { aggregate_signature: None });
+        self.send(&[attestation, refund], &[&payer]).await
+    }
+
+    /// Expire `lock`, attested by `signers` under `domain`
+    async fn send_expire(
+        &mut self,
+        lock: &Lock,
+        domain: &[u8],
+        signers: &[usize],
+    ) -> Result<(), TransactionError> {
+        let record = self.lock_record(lock).await;
+        let attestation = self.attestation(&lock_outcome_hash(domain, &record, &[]), signers);
+        let payer = self.payer();
+        let expire = lock.refund_instruction(ExpireLock { aggregate_signature: None });
+        self.send(&[attestation, expire], &[&payer]).await
+    }
+
+    /// Acknowledge delivery of `lock` in `destination_tx`, attested by
+    /// `signers` for `signed_tx`
+    async fn send_acknowledge(
+        &mut self,
+        lock: &Lock,
+        destination_tx: &str,
+        signed_tx: &str,
+        signers: &[usize],
+    ) -> Result<(), TransactionError> {
+        let record = self.lock_record(lock).await;
+        let hash =
+            lock_outcome_hash(LockRecord::DELIVERED_DOMAIN, &record, signed_tx.as_bytes());
+        let attestation = self.attestation(&hash, signers);
+        let payer = self.payer();
+        self.send(&[attestation, lock.acknowledge_instruction(destination_tx)], &[&payer]).await
+    }
+
+    async fn send_close(&mut self, lock: &Lock) -> Result<(), TransactionError> {
+        let sender = self.sender.insecure_clone();
+        let payer = self.payer();
+        self.send(&[lock.close_instruction(sender.pubkey())], &[&payer, &sender]).await
+    }
+}
+
+#[tokio::test]
+async fn acknowledge_delivery_marks_the_lock_delivered() {
+    let (mut bridge, lock) = locked_bridge().await;
+
+    bridge.send_acknowledge(&lock, "near-tx", "near-tx", &[0, 1]).await.unwrap();
+
+    let record = bridge.lock_record(&lock).await;
+    assert_eq!(record.status, LockStatus::Delivered { destination_tx: "near-tx".to_string() });
+
+    // A delivered lock is final
+    assert_eq!(
+        bridge.send_acknowledge(&lock, "near-tx", "near-tx", &[1, 2]).await.unwrap_err(),
+        bridge_error(1, BridgeError::LockNotPending)
+    );
+    assert_eq!(
+        bridge.send_refund(&lock, LockRecord::REFUND_DOMAIN, &[0, 1]).await.unwrap_err(),
+        bridge_error(1, BridgeError::LockNotPending)
+    );
+    assert_eq!(bridge.balance(vault_token_account(&bridge.mint)).await, 1_000);
+}
+
+#[tokio::test]
+async fn acknowledge_delivery_requires_an_attestation_of_the_transaction() {
+    let (mut bridge, lock) = locked_bridge().await;
+
+    assert_eq!(
+        bridge.send_acknowledge(&lock, "near-tx", "near-tx", &[0]).await.unwrap_err(),
+        bridge_error(1, BridgeError::InsufficientSignatures)
+    );
+    assert_eq!(
+        bridge.send_acknowledge(&lock, "other-tx", "near-tx", &[0, 1]).await.unwrap_err(),
+        bridge_error(1, BridgeError::InsufficientSignatures)
+    );
+
+    let long_tx = "t".repeat(LockRecord::MAX_TX_REF_LEN + 1);
+    assert_eq!(
+        bridge.send_acknowledge(&lock, &long_tx, &long_tx, &[0, 1]).await.unwrap_err(),
+        bridge_error(1, BridgeError::DestinationTxTooLong)
+    );
+
+    bridge.send_admin(Pause {}).await.unwrap();
+    assert_eq!(
+        bridge.send_acknowledge(&lock, "near-tx", "near-tx", &[0, 1]).await.unwrap_err(),
+        bridge_error(1, BridgeError::BridgePaused)
+    );
+
+    assert_eq!(bridge.lock_record(&lock).await.status, LockStatus::Pending);
+}
+
+#[tokio::test]
+async fn refund_returns_the_tokens_to_the_sender() {
+    let (mut bridge, lock) = locked_bridge().await;
+
+    bridge.send_refund(&lock, LockRecord::REFUND_DOMAIN, &[0, 2]).await.unwrap();
+
+    assert_eq!(bridge.balance(bridge.sender_token_account).await, SENDER_TOKENS);
+    assert_eq!(bridge.balance(vault_token_account(&bridge.mint)).await, 0);
+    assert_eq!(bridge.lock_record(&lock).await.status, LockStatus::Refunded);
+    let vault: TokenVault = bridge.fetch(token_vault_address(&bridge.mint)).await;
+    assert_eq!(vault.total_locked, 0);
+    assert_eq!(bridge.config().await.total_locked, 0);
+
+    // Tokens are returned once
+    assert_eq!(
+        bridge.send_refund(&lock, LockRecord::REFUND_DOMAIN, &[0, 1]).await.unwrap_err(),
+        bridge_error(1, BridgeError::LockNotPending)
+    );
+    assert_eq!(bridge.balance(bridge.sender_token_account).await, SENDER_TOKENS);
+}
+
+#[tokio::test]
+async fn refund_requires_a_refund_attestation() {
+    let (mut bridge, lock) = locked_bridge().await;
+
+    assert_eq!(
+        bridge.send_refund(&lock, LockRecord::REFUND_DOMAIN, &[0]).await.unwrap_err(),
+        bridge_error(1, BridgeError::InsufficientSignatures)
+    );
+
+    // Attestations of other outcomes do not count
+    for domain in [LockRecord::EXPIRED_DOMAIN, LockRecord::DELIVERED_DOMAIN] {
+        assert_eq!(
+            bridge.send_refund(&lock, domain, &[0, 1]).await.unwrap_err(),
+            bridge_error(1, BridgeError::InsufficientSignatures)
+        );
+    }
+
+    assert_eq!(bridge.lock_record(&lock).await.status, LockStatus::Pending);
+    assert_eq!(bridge.balance(vault_token_account(&bridge.mint)).await, 1_000);
+}
+
+#[tokio::test]
+async fn refund_pays_only_the_sender() {
+    let (mut bridge, mut lock) = locked_bridge().await;
+    let other = Keypair::new().pubkey();
+    lock.sender_token_account = bridge.token_account(&other).await;
+
+    assert_eq!(
+        bridge.send_refund(&lock, LockRecord::REFUND_DOMAIN, &[0, 1]).await.unwrap_err(),
+        anchor_error(1, ErrorCode::ConstraintRaw)
+    );
+    assert_eq!(bridge.balance(lock.sender_token_account).await, 0);
+}
+
+#[tokio::test]
+async fn refund_is_rejected_while_paused() {
+    let (mut bridge, lock) = locked_bridge().await;
+    bridge.send_admin(Pause {}).await.unwrap();
+
+    assert_eq!(
+        bridge.send_refund(&lock, LockRecord::REFUND_DOMAIN, &[0, 1]).await.unwrap_err(),
+        bridge_error(1, BridgeError::BridgePaused)
+    );
+}
+
+#[tokio::test]
+async fn expire_lock_waits_for_the_expiry_period() {
+    let (mut bridge, lock) = locked_bridge().await;
+
+    assert_eq!(
+        bridge.send_expire(&lock, LockRecord::EXPIRED_DOMAIN, &[0, 1]).await.unwrap_err(),
+        bridge_error(1, BridgeError::LockNotExpired)
+    );
+
+    bridge.advance_clock(LockRecord::EXPIRY_PERIOD).await;
+    assert_eq!(
+        bridge.send_expire(&lock, LockRecord::REFUND_DOMAIN, &[0, 1]).await.unwrap_err(),
+        bridge_error(1, BridgeError::InsufficientSignatures)
+    );
+    bridge.send_expire(&lock, LockRecord::EXPIRED_DOMAIN, &[0, 1]).await.unwrap();
+
+    assert_eq!(bridge.lock_record(&lock).await.status, LockStatus::Expired);
+    assert_eq!(bridge.balance(bridge.sender_token_account).await, SENDER_TOKENS);
+    assert_eq!(bridge.config().await.total_locked, 0);
+    assert_eq!(
+        bridge.send_expire(&lock, LockRecord::EXPIRED_DOMAIN, &[0, 1]).await.unwrap_err(),
+        bridge_error(1, BridgeError::LockNotPending)
+    );
+}
+
+#[tokio::test]
+async fn close_lock_record_requires_a_final_status() {
+    let (mut bridge, lock) = locked_bridge().await;
+
+    assert_eq!(
+        bridge.send_close(&lock).await.unwrap_err(),
+        bridge_error(0, BridgeError::RecordNotFinalized)
+    );
+    assert!(bridge.exists(lock.lock_record()).await);
+}
+
+#[tokio::test]
+async fn close_lock_record_returns_the_rent_to_the_sender() {
+    let (mut bridge, lock) = locked_bridge().await;
+    bridge.send_acknowledge(&lock, "near-tx", "near-tx", &[0, 1]).await.unwrap();
+    let rent = bridge.lamports(lock.lock_record()).await;
+    let before = bridge.lamports(lock.sender).await;
+
+    // Only the sender can close it
+    let intruder = Keypair::new();
+    let payer = bridge.payer();
+    assert_eq!(
+        bridge
+            .send(&[lock.close_instruction(intruder.pubkey())], &[&payer, &intruder])
+            .await
+            .unwrap_err(),
+        anchor_error(0, ErrorCode::ConstraintSeeds)
+    );
+
+    bridge.send_close(&lock).await.unwrap();
+
+    assert!(!bridge.exists(lock.lock_record()).await);
+    assert_eq!(bridge.lamports(lock.sender).await, before + rent);
+}
+
+#[tokio::test]
+async fn close_lock_record_frees_the_nonce_under_a_new_message_id() {
+    let (mut bridge, lock) = locked_bridge().await;
+    bridge.send_refund(&lock, LockRecord::REFUND_DOMAIN, &[0, 1]).await.unwrap();
+    let refunded = bridge.lock_record(&lock).await;
+    bridge.send_close(&lock).await.unwrap();
+
+    bridge.send_lock(&lock).await.unwrap();
+
+    let record = bridge.lock_record(&lock).await;
+    assert_eq!(record.status, LockStatus::Pending);
+    assert_eq!(record.sequence, 1);
+    assert_eq!(record.message_id, lock.message_id(1));
+    assert_ne!(record.message_id, refunded.message_id);
+}
@@ -0,0 +1,365 @@
+//! Generic messages: outbound message IDs, attested inbound messages and
+//! their delivery to the target program
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AnchorSerialize, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use common::*;
+use solana_bridge::error::BridgeError;
+use solana_bridge::instruction::{Pause, Unpause};
+use solana_bridge::state::{BridgeMessage, DeliveryStatus, InboundMessage, LockRecord};
+use solana_program::{system_program, sysvar};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::TransactionError;
+
+/// Message from NEAR to the receiver program
+#[derive(Clone, Debug)]
+struct Inbound {
+    message_id: [u8; 32],
+    source_chain: String,
+    sender: String,
+    target_program: Pubkey,
+    payload: Vec<u8>,
+}
+
+impl Inbound {
+    fn new(message_id: u8) -> Self {
+        Self {
+            message_id: [message_id; 32],
+            source_chain: "near".to_string(),
+            sender: "alice.near".to_string(),
+            target_program: RECEIVER_ID,
+            payload: b"ping".to_vec(),
+        }
+    }
+
+    fn address(&self) -> Pubkey {
+        Pubkey::find_program_address(
+            &[b"inbound_message", self.message_id.as_ref()],
+            &solana_bridge::ID,
+        )
+        .0
+    }
+
+    /// Hash the validators sign to deliver the message
+    fn message_hash(&self) -> [u8; 32] {
+        bridge_types::MessageDeliveryAttestation {
+            message_id: self.message_id,
+            source_chain: self.source_chain.clone(),
+            sender: self.sender.clone(),
+            destination_chain: bridge_types::chain::SOLANA.to_string(),
+            destination_bridge: solana_bridge::ID.to_bytes().to_vec(),
+            target: self.target_program.to_bytes().to_vec(),
+            payload: self.payload.clone(),
+        }
+        .signing_hash::<bridge_types::hash::Sha3Keccak>()
+    }
+
+    fn receive_instruction(&self, payer: Pubkey) -> Instruction {
+        Instruction {
+            program_id: solana_bridge::ID,
+            accounts: solana_bridge::accounts::ReceiveMessage {
+                bridge_config: bridge_config_address(),
+                inbound_message: self.address(),
+                payer,
+                instructions_sysvar: sysvar::instructions::ID,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: solana_bridge::instruction::ReceiveMessage {
+                message_id: self.message_id,
+                source_chain: self.source_chain.clone(),
+                sender: self.sender.clone(),
+                target_program: self.target_program,
+                payload: self.payload.clone(),
+                aggregate_signature: None,
+            }
+            .data(),
+        }
+    }
+
+    /// Delivery to `target_program` with `remaining` accounts
+    fn execute_instruction(
+        &self,
+        target_program: Pubkey,
+        remaining: &[AccountMeta],
+    ) -> Instruction {
+        let mut accounts = solana_bridge::accounts::ExecuteMessage {
+            bridge_config: bridge_config_address(),
+            inbound_message: self.address(),
+            messenger: messenger_address(),
+            target_program,
+        }
+        .to_account_metas(None);
+        accounts.extend_from_slice(remaining);
+        Instruction {
+            program_id: solana_bridge::ID,
+            accounts,
+            data: solana_bridge::instruction::ExecuteMessage {}.data(),
+        }
+    }
+}
+
+impl Bridge {
+    async fn send_message(
+        &mut self,
+        destination_chain: &str,
+        target: &str,
+        payload: &[u8],
+    ) -> Result<(), TransactionError> {
+        let sender = self.sender.insecure_clone();
+        let instruction = Instruction {
+            program_id: solana_bridge::ID,
+            accounts: solana_bridge::accounts::SendMessage {
+                bridge_config: bridge_config_address(),
+                sender: sender.pubkey(),
+            }
+            .to_account_metas(None),
+            data: solana_bridge::instruction::SendMessage {
+                destination_chain: destination_chain.to_string(),
+                target: target.to_string(),
+                payload: payload.to_vec(),
+            }
+            .data(),
+        };
+        let payer = self.payer();
+        self.send(&[instruction], &[&payer, &sender]).await
+    }
+
+    /// Receive `inbound`, attested by `signers` over `signed`
+    async fn receive_message(
+        &mut self,
+        inbound: &Inbound,
+        signed: &Inbound,
+        signers: &[usize],
+    ) -> Result<(), TransactionError> {
+        let payer = self.payer();
+        let attestation = self.attestation(&signed.message_hash(), signers);
+        self.send(&[attestation, inbound.receive_instruction(payer.pubkey())], &[&payer]).await
+    }
+
+    async fn execute_message(&mut self, instruction: Instruction) -> Result<(), TransactionError> {
+        let payer = self.payer();
+        self.send(&[instruction], &[&payer]).await
+    }
+}
+
+#[tokio::test]
+async fn send_message_takes_the_next_bridge_sequence() {
+    let mut bridge = Bridge::start().await;
+
+    bridge.send_message("near", "receiver.near", b"ping").await.unwrap();
+    assert_eq!(bridge.config().await.message_count, 1);
+
+    // Locks and messages share the sequence their IDs commit to
+    bridge.send_lock(&bridge.lock(1_000, 1)).await.unwrap();
+    bridge.send_message("near", "receiver.near", b"pong").await.unwrap();
+    let record: LockRecord = bridge.fetch(bridge.lock(1_000, 1).lock_record()).await;
+    assert_eq!(record.sequence, 1);
+    assert_eq!(bridge.config().await.message_count, 3);
+}
+
+#[tokio::test]
+async fn send_message_validates_the_message() {
+    let mut bridge = Bridge::start().await;
+    let long_chain = "c".repeat(33);
+    let long_target = "t".repeat(129);
+    let large_payload = vec![0; InboundMessage::MAX_PAYLOAD_LEN + 1];
+
+    let cases = [
+        (long_chain.as_str(), "receiver.near", &b"ping"[..], BridgeError::DestinationChainTooLong),
+        ("near", long_target.as_str(), b"ping", BridgeError::DestinationAddressTooLong),
+        ("near", "receiver.near", large_payload.as_slice(), BridgeError::PayloadTooLarge),
+    ];
+    for (destination_chain, target, payload, error) in cases {
+        assert_eq!(
+            bridge.send_message(destination_chain, target, payload).await.unwrap_err(),
+            bridge_error(0, error)
+        );
+    }
+
+    bridge.send_admin(Pause {}).await.unwrap();
+    assert_eq!(
+        bridge.send_message("near", "receiver.near", b"ping").await.unwrap_err(),
+        bridge_error(0, BridgeError::BridgePaused)
+    );
+    assert_eq!(bridge.config().await.message_count, 0);
+}
+
+#[tokio::test]
+async fn receive_message_records_the_attested_message() {
+    let mut bridge = Bridge::start().await;
+    let inbound = Inbound::new(1);
+
+    bridge.receive_message(&inbound, &inbound, &[0, 1]).await.unwrap();
+
+    let message: InboundMessage = bridge.fetch(inbound.address()).await;
+    assert_eq!(message.message_id, inbound.message_id);
+    assert_eq!(message.source_chain, "near");
+    assert_eq!(message.sender, "alice.near");
+    assert_eq!(message.target_program, RECEIVER_ID);
+    assert_eq!(message.payload, b"ping");
+    assert_eq!(message.status, DeliveryStatus::Pending);
+    assert_eq!(message.delivered_at, 0);
+
+    // A message is received once
+    assert_eq!(
+        bridge.receive_message(&inbound, &inbound, &[1, 2]).await.unwrap_err(),
+        already_in_use(1)
+    );
+}
+
+#[tokio::test]
+async fn receive_message_requires_an_attestation_of_target_and_payload() {
+    let mut bridge = Bridge::start().await;
+    let inbound = Inbound::new(1);
+
+    assert_eq!(
+        bridge.receive_message(&inbound, &inbound, &[0]).await.unwrap_err(),
+        bridge_error(1, BridgeError::InsufficientSignatures)
+    );
+
+    let other_target = Inbound { target_program: Pubkey::new_unique(), ..inbound.clone() };
+    let other_payload = Inbound { payload: b"pong".to_vec(), ..inbound.clone() };
+    let other_sender = Inbound { sender: "bob.near".to_string(), ..inbound.clone() };
+    for signed in [other_target, other_payload, other_sender] {
+        assert_eq!(
+            bridge.receive_message(&inbound, &signed, &[0, 1]).await.unwrap_err(),
+            bridge_error(1, BridgeError::InsufficientSignatures)
+        );
+    }
+    assert!(!bridge.exists(inbound.address()).await);
+}
+
+#[tokio::test]
+async fn receive_message_validates_the_message() {
+    let mut bridge = Bridge::start().await;
+
+    let cases = [
+        (
+            Inbound {
+                source_chain: "c".repeat(InboundMessage::MAX_CHAIN_LEN + 1),
+                ..Inbound::new(1)
+            },
+            BridgeError::SourceChainTooLong,
+        ),
+        (
+            Inbound { sender: "s".repeat(InboundMessage::MAX_SENDER_LEN + 1), ..Inbound::new(2) },
+            BridgeError::SenderAddressTooLong,
+        ),
+        (
+            Inbound { payload: vec![0; InboundMessage::MAX_PAYLOAD_LEN + 1], ..Inbound::new(3) },
+            BridgeError::PayloadTooLarge,
+        ),
+        (
+            Inbound { target_program: solana_bridge::ID, ..Inbound::new(4) },
+            BridgeError::InvalidMessageTarget,
+        ),
+    ];
+    for (inbound, error) in cases {
+        assert_eq!(
+            bridge.receive_message(&inbound, &inbound, &[0, 1]).await.unwrap_err(),
+            bridge_error(1, error)
+        );
+    }
+
+    bridge.send_admin(Pause {}).await.unwrap();
+    let inbound = Inbound::new(5);
+    assert_eq!(
+        bridge.receive_message(&inbound, &inbound, &[0, 1]).await.unwrap_err(),
+        bridge_error(1, BridgeError::BridgePaused)
+    );
+}
+
+#[tokio::test]
+async fn execute_message_delivers_to_the_target_program() {
+    let mut bridge = Bridge::start().await;
+    let inbound = Inbound::new(1);
+    bridge.receive_message(&inbound, &inbound, &[0, 1]).await.unwrap();
+
+    let inbox = [AccountMeta::new(INBOX, false)];
+    bridge.execute_message(inbound.execute_instruction(RECEIVER_ID, &inbox)).await.unwrap();
+
+    let mut expected = BridgeMessage::discriminator().to_vec();
+    BridgeMessage {
+        message_id: inbound.message_id,
+        source_chain: inbound.source_chain.clone(),
+        sender: inbound.sender.clone(),
+        payload: inbound.payload.clone(),
+    }
+    .serialize(&mut expected)
+    .unwrap();
+    assert_eq!(bridge.delivery().await, expected);
+
+    let message: InboundMessage = bridge.fetch(inbound.address()).await;
+    assert_eq!(message.status, DeliveryStatus::Delivered);
+    assert!(message.delivered_at > 0);
+
+    // A message is delivered once
+    assert_eq!(
+        bridge.execute_message(inbound.execute_instruction(RECEIVER_ID, &inbox)).await.unwrap_err(),
+        bridge_error(0, BridgeError::MessageAlreadyDelivered)
+    );
+}
+
+#[tokio::test]
+async fn execute_message_can_be_retried_after_a_failed_delivery() {
+    let mut bridge = Bridge::start().await;
+    let inbound = Inbound::new(1);
+    bridge.receive_message(&inbound, &inbound, &[0, 1]).await.unwrap();
+
+    // The receiver fails without its inbox
+    assert!(bridge.execute_message(inbound.execute_instruction(RECEIVER_ID, &[])).await.is_err());
+    let message: InboundMessage = bridge.fetch(inbound.address()).await;
+    assert_eq!(message.status, DeliveryStatus::Pending);
+
+    let inbox = [AccountMeta::new(INBOX, false)];
+    bridge.execute_message(inbound.execute_instruction(RECEIVER_ID, &inbox)).await.unwrap();
+    let message: InboundMessage = bridge.fetch(inbound.address()).await;
+    assert_eq!(message.status, DeliveryStatus::Delivered);
+}
+
+#[tokio::test]
+async fn execute_message_only_invokes_the_recorded_target() {
+    let mut bridge = Bridge::start().await;
+    let inbound = Inbound::new(1);
+    bridge.receive_message(&inbound, &inbound, &[0, 1]).await.unwrap();
+
+    let inbox = [AccountMeta::new(INBOX, false)];
+    let instruction = inbound.execute_instruction(spl_token::ID, &inbox);
+    assert_eq!(
+        bridge.execute_message(instruction).await.unwrap_err(),
+        bridge_error(0, BridgeError::InvalidMessageTarget)
+    );
+
+    // The target must be a program
+    let mut inbound = Inbound::new(2);
+    inbound.target_program = Keypair::new().pubkey();
+    bridge.receive_message(&inbound, &inbound, &[0, 1]).await.unwrap();
+    let target = inbound.target_program;
+    assert_eq!(
+        bridge.execute_message(inbound.execute_instruction(target, &inbox)).await.unwrap_err(),
+        anchor_error(0, ErrorCode::ConstraintExecutable)
+    );
+}
+
+#[tokio::test]
+async fn execute_message_is_rejected_while_paused() {
+    let mut bridge = Bridge::start().await;
+    let inbound = Inbound::new(1);
+    bridge.receive_message(&inbound, &inbound, &[0, 1]).await.unwrap();
+    bridge.send_admin(Pause {}).await.unwrap();
+
+    let inbox = [AccountMeta::new(INBOX, false)];
+    assert_eq!(
+        bridge.execute_message(inbound.execute_instruction(RECEIVER_ID, &inbox)).await.unwrap_err(),
+        bridge_error(0, BridgeError::BridgePaused)
+    );
+
+    bridge.send_admin(Unpause {}).await.unwrap();
+    bridge.execute_message(inbound.execute_instruction(RECEIVER_ID, &inbox)).await.unwrap();
+}
@@ -0,0 +1,268 @@
+use anchor_lang::prelude::*;
+use anchor_lang::{AccountDeserialize, Discriminator, InstructionData, ToAccountMetas};
+use solana_bridge::state::*;
+use solana_program::entrypoint::ProgramResult;
+use solana_program_test::{processor, ProgramTest, ProgramTestContext};
+use solana_sdk::account::Account as SolanaAccount;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+
+fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    // The Anchor entrypoint wants accounts that outlive the call
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    solana_bridge::entry(program_id, accounts, data)
+}
+
+fn program_test() -> ProgramTest {
+    ProgramTest::new("solana_bridge", solana_bridge::ID, processor!(process_instruction))
+}
+
+/// Account holding `data` under the discriminator of `T`, padded to `len`
+fn legacy_account<T: Discriminator>(data: &[u8], len: usize) -> SolanaAccount {
+    let mut bytes = T::discriminator().to_vec();
+    bytes.extend_from_slice(data);
+    bytes.resize(len, 0);
+
+    SolanaAccount {
+        lamports: Rent::default().minimum_balance(len),
+        data: bytes,
+        owner: solana_bridge::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn bridge_config_address() -> Pubkey {
+    Pubkey::find_program_address(&[b"bridge_config"], &solana_bridge::ID).0
+}
+
+fn legacy_config(admin: Pubkey) -> BridgeConfigV0 {
+    let (_, bump) = Pubkey::find_program_address(&[b"bridge_config"], &solana_bridge::ID);
+    BridgeConfigV0 {
+        admin,
+        validators: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+        required_signatures: 2,
+        is_paused: true,
+        total_locked: 5_000,
+        total_unlocked: 1_000,
+        message_count: 7,
+        bump,
+    }
+}
+
+/// `config` written field by field as the program before versioning did
+fn baseline_config_data(config: &BridgeConfigV0) -> Vec<u8> {
+    let mut data = config.admin.to_bytes().to_vec();
+    data.extend_from_slice(&(config.validators.len() as u32).to_le_bytes());
+    for validator in &config.validators {
+        data.extend_from_slice(validator.as_ref());
+    }
+    data.push(config.required_signatures);
+    data.push(config.is_paused as u8);
+    data.extend_from_slice(&config.total_locked.to_le_bytes());
+    data.extend_from_slice(&config.total_unlocked.to_le_bytes());
+    data.extend_from_slice(&config.message_count.to_le_bytes());
+    data.push(config.bump);
+    data
+}
+
+/// `record` written field by field as the program before versioning did
+fn baseline_lock_record_data(record: &LockRecordV0) -> Vec<u8> {
+    let mut data = record.message_id.to_vec();
+    data.extend_from_slice(record.sender.as_ref());
+    for field in [&record.destination_chain, &record.destination_address] {
+        data.extend_from_slice(&(field.len() as u32).to_le_bytes());
+        data.extend_from_slice(field.as_bytes());
+    }
+    data.extend_from_slice(record.token_mint.as_ref());
+    data.extend_from_slice(&record.amount.to_le_bytes());
+    data.extend_from_slice(&record.nonce.to_le_bytes());
+    data.extend_from_slice(&record.timestamp.to_le_bytes());
+    data.push(record.bump);
+    data
+}
+
+fn migrate_config_instruction(admin: Pubkey) -> Instruction {
+    Instruction {
+        program_id: solana_bridge::ID,
+        accounts: solana_bridge::accounts::MigrateConfig {
+            bridge_config: bridge_config_address(),
+            admin,
+            system_program: anchor_lang::system_program::ID,
+        }
+        .to_account_metas(None),
+        data: solana_bridge::instruction::MigrateConfig {}.data(),
+    }
+}
+
+fn migrate_lock_record_instruction(lock_record: Pubkey, payer: Pubkey) -> Instruction {
+    Instruction {
+        program_id: solana_bridge::ID,
+        accounts: solana_bridge::accounts::MigrateLockRecord {
+            lock_record,
+            payer,
+            system_program: anchor_lang::system_program::ID,
+        }
+        .to_account_metas(None),
+        data: solana_bridge::instruction::MigrateLockRecord {}.data(),
+    }
+}
+
+async fn send(
+    context: &mut ProgramTestContext,
+    instruction: Instruction,
+    signers: &[&Keypair],
+) -> std::result::Result<(), solana_program_test::BanksClientError> {
+    let blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&context.payer.pubkey()),
+        signers,
+        blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await
+}
+
+async fn fetch<T: AccountDeserialize>(context: &mut ProgramTestContext, address: Pubkey) -> (usize, T) {
+    let account = context.banks_client.get_account(address).await.unwrap().unwrap();
+    let value = T::try_deserialize(&mut &account.data[..]).unwrap();
+    (account.data.len(), value)
+}
+
+#[tokio::test]
+async fn migrate_config_from_v0() {
+    let admin = Keypair::new();
+    let old = legacy_config(admin.pubkey());
+
+    let mut test = program_test();
+    test.add_account(
+        bridge_config_address(),
+        legacy_account::<BridgeConfig>(&baseline_config_data(&old), BridgeConfigV0::LEN),
+    );
+    test.add_account(
+        admin.pubkey(),
+        SolanaAccount::new(1_000_000_000, 0, &anchor_lang::system_program::ID),
+    );
+    let mut context = test.start_with_context().await;
+
+    let payer = context.payer.insecure_clone();
+    send(&mut context, migrate_config_instruction(admin.pubkey()), &[&payer, &admin])
+        .await
+        .unwrap();
+
+    let (len, config): (usize, BridgeConfig) = fetch(&mut context, bridge_config_address()).await;
+    assert_eq!(len, BridgeConfig::LEN);
+    assert_eq!(config.version, BridgeConfig::VERSION);
+    assert_eq!(config.admin, old.admin);
+    assert_eq!(config.validators, old.validators);
+    assert_eq!(config.required_signatures, old.required_signatures);
+    assert!(config.is_paused);
+    assert_eq!(config.total_locked, old.total_locked);
+    assert_eq!(config.total_unlocked, old.total_unlocked);
+    assert_eq!(config.message_count, old.message_count);
+    assert_eq!(config.bump, old.bump);
+    assert_eq!(config.signature_mode, SignatureMode::Individual);
+    assert_eq!(config.group_key, Pubkey::default());
+    assert_eq!(config.group_threshold, 0);
+    assert_eq!(config.group_size, 0);
+    assert_eq!(config.group_key_epoch, 0);
+    assert_eq!(config.governance, Pubkey::default());
+    assert_eq!(config.reserved, [0u8; 128]);
+
+    // A second migration is rejected
+    context.warp_to_slot(2).unwrap();
+    assert!(send(&mut context, migrate_config_instruction(admin.pubkey()), &[&payer, &admin])
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn migrate_config_requires_admin() {
+    let admin = Keypair::new();
+    let impostor = Keypair::new();
+
+    let mut test = program_test();
+    test.add_account(
+        bridge_config_address(),
+        legacy_account::<BridgeConfig>(
+            &baseline_config_data(&legacy_config(admin.pubkey())),
+            BridgeConfigV0::LEN,
+        ),
+    );
+    let mut context = test.start_with_context().await;
+
+    let payer = context.payer.insecure_clone();
+    assert!(send(&mut context, migrate_config_instruction(impostor.pubkey()), &[&payer, &impostor])
+        .await
+        .is_err());
+
+    let account = context.banks_client.get_account(bridge_config_address()).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), BridgeConfigV0::LEN);
+}
+
+#[tokio::test]
+async fn migrate_lock_record_from_v0() {
+    let sender = Pubkey::new_unique();
+    let nonce = 3u64;
+    let (address, bump) = Pubkey::find_program_address(
+        &[b"lock_record", sender.as_ref(), &nonce.to_le_bytes()],
+        &solana_bridge::ID,
+    );
+    let old = LockRecordV0 {
+        message_id: [9u8; 32],
+        sender,
+        destination_chain: "ethereum".to_string(),
+        destination_address: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
+        token_mint: Pubkey::new_unique(),
+        amount: 1_000,
+        nonce,
+        timestamp: 1_700_000_000,
+        bump,
+    };
+
+    let mut test = program_test();
+    test.add_account(address, legacy_account::<LockRecord>(&baseline_lock_record_data(&old), LockRecordV0::LEN));
+    let mut context = test.start_with_context().await;
+
+    let payer = context.payer.insecure_clone();
+    send(&mut context, migrate_lock_record_instruction(address, payer.pubkey()), &[&payer])
+        .await
+        .unwrap();
+
+    let (len, record): (usize, LockRecord) = fetch(&mut context, address).await;
+    assert_eq!(len, LockRecord::LEN);
+    assert_eq!(record.version, LockRecord::VERSION);
+    assert_eq!(record.message_id, old.message_id);
+    assert_eq!(record.sender, old.sender);
+    assert_eq!(record.destination_chain, old.destination_chain);
+    assert_eq!(record.destination_address, old.destination_address);
+    assert_eq!(record.token_mint, old.token_mint);
+    assert_eq!(record.amount, old.amount);
+    assert_eq!(record.nonce, old.nonce);
+    assert_eq!(record.timestamp, old.timestamp);
+    assert_eq!(record.status, LockStatus::Pending);
+    assert_eq!(record.sequence, 0);
+    assert_eq!(record.bump, old.bump);
+
+    // Rent covers the larger account
+    let account = context.banks_client.get_account(address).await.unwrap().unwrap();
+    assert!(account.lamports >= Rent::default().minimum_balance(LockRecord::LEN));
+}
+
+#[test]
+fn previous_layouts_match_the_deployed_program() {
+    // Sizes allocated by the program before versioning
+    assert_eq!(BridgeConfigV0::LEN, 391);
+    assert_eq!(LockRecordV0::LEN, 297);
+
+    let config = legacy_config(Pubkey::new_unique());
+    let data = baseline_config_data(&config);
+    let decoded = BridgeConfigV0::deserialize(&mut &data[..]).unwrap();
+    assert_eq!(decoded.validators, config.validators);
+    assert_eq!(decoded.bump, config.bump);
+}
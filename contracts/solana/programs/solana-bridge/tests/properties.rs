@@ -0,0 +1,285 @@
+//! Property tests of Ed25519 instruction parsing, signature-set validation,
+//! account decoding and the lock/unlock counters
+
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use common::*;
+use proptest::prelude::*;
+use solana_bridge::ed25519::{
+    check_attestation, check_validator_signatures, parse_ed25519_instruction, VerifiedSignature,
+};
+use solana_bridge::error::BridgeError;
+use solana_bridge::state::*;
+use std::collections::HashSet;
+
+/// Bridge error code of a failed check, if any
+fn error_code(result: Result<()>) -> Option<u32> {
+    match result {
+        Ok(()) => None,
+        Err(Error::AnchorError(error)) => Some(error.error_code_number),
+        Err(error) => panic!("unexpected error {error:?}"),
+    }
+}
+
+fn code(error: BridgeError) -> Option<u32> {
+    Some(error.into())
+}
+
+/// Validator `i` of every generated config
+fn validator(index: u8) -> Pubkey {
+    Pubkey::new_from_array([index + 1; 32])
+}
+
+fn config(validators: u8, required_signatures: u8) -> BridgeConfig {
+    BridgeConfig {
+        version: BridgeConfig::VERSION,
+        admin: Pubkey::default(),
+        validators: (0..validators).map(validator).collect(),
+        required_signatures,
+        is_paused: false,
+        total_locked: 0,
+        total_unlocked: 0,
+        message_count: 0,
+        bump: 0,
+        signature_mode: SignatureMode::Individual,
+        group_key: Pubkey::default(),
+        group_threshold: 0,
+        group_size: 0,
+        group_key_epoch: 0,
+        governance: Pubkey::default(),
+        reserved: [0; 128],
+    }
+}
+
+/// Signature of key `signer` over `message`, as checked by the precompile.
+/// Only the pairing matters here, so signatures are derived, not computed.
+fn verified(signer: u8, message: &[u8]) -> VerifiedSignature {
+    let mut signature = [signer; 64];
+    signature[1..1 + message.len().min(63)].copy_from_slice(&message[..message.len().min(63)]);
+    VerifiedSignature { public_key: validator(signer), signature, message: message.to_vec() }
+}
+
+/// An entry of the precompile instruction, and whether the unlock
+/// instruction lists its signature
+#[derive(Clone, Debug)]
+enum Entry {
+    /// Key `i` over the attested message
+    Attested(u8),
+    /// Key `i` over another message
+    OtherMessage(u8),
+}
+
+fn entry() -> impl Strategy<Value = (Entry, bool)> {
+    let entry = prop_oneof![
+        3 => (0..16u8).prop_map(Entry::Attested),
+        1 => (0..16u8).prop_map(Entry::OtherMessage),
+    ];
+    (entry, any::<bool>())
+}
+
+/// A state change of the lock/unlock counters
+#[derive(Clone, Debug)]
+enum CounterOp {
+    Lock(u64),
+    Unlock(u64),
+    /// Tokens sent straight to the vault, outside any lock
+    Donate(u64),
+}
+
+fn counter_op() -> impl Strategy<Value = CounterOp> {
+    let amount = 1..=SENDER_TOKENS / 16;
+    prop_oneof![
+        2 => amount.clone().prop_map(CounterOp::Lock),
+        2 => amount.clone().prop_map(CounterOp::Unlock),
+        1 => amount.prop_map(CounterOp::Donate),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn ed25519_instructions_parse_back(
+        entries in proptest::collection::vec((any::<[u8; 32]>(), any::<[u8; 32]>()), 1..8),
+        message in proptest::collection::vec(any::<u8>(), 0..96),
+    ) {
+        let signatures: Vec<(Pubkey, [u8; 64])> = entries
+            .iter()
+            .map(|(key, half)| {
+                let signature = [*half, *half].concat().try_into().unwrap();
+                (Pubkey::new_from_array(*key), signature)
+            })
+            .collect();
+        let instruction = ed25519_instruction(&signatures, &message);
+
+        let parsed = parse_ed25519_instruction(&instruction.data).unwrap();
+        prop_assert_eq!(parsed.len(), signatures.len());
+        for (entry, (public_key, signature)) in parsed.iter().zip(&signatures) {
+            prop_assert_eq!(&entry.public_key, public_key);
+            prop_assert_eq!(&entry.signature, signature);
+            prop_assert_eq!(&entry.message, &message);
+        }
+    }
+
+    #[test]
+    fn ed25519_parsing_never_panics(data in proptest::collection::vec(any::<u8>(), 0..512)) {
+        if let Err(error) = parse_ed25519_instruction(&data) {
+            prop_assert_eq!(
+                error_code(Err(error)),
+                code(BridgeError::MissingEd25519Instruction)
+            );
+        }
+    }
+
+    #[test]
+    fn validator_signatures_count_each_validator_once(
+        validators in 1..=10u8,
+        required in 1..=10u8,
+        entries in proptest::collection::vec(entry(), 0..12),
+    ) {
+        prop_assume!(required <= validators);
+        let config = config(validators, required);
+        let message = b"attested";
+
+        let mut verified_entries = Vec::new();
+        let mut listed = Vec::new();
+        let mut signers = HashSet::new();
+        let mut unbacked = false;
+        for (entry, is_listed) in entries {
+            let verified_entry = match entry {
+                Entry::Attested(signer) => verified(signer, message),
+                Entry::OtherMessage(signer) => verified(signer, b"other"),
+            };
+            if is_listed {
+                listed.push(verified_entry.signature);
+                match entry {
+                    Entry::Attested(signer) if signer < validators => {
+                        signers.insert(signer);
+                    }
+                    Entry::Attested(_) => {}
+                    Entry::OtherMessage(_) => unbacked = true,
+                }
+            }
+            verified_entries.push(verified_entry);
+        }
+
+        let expected = if unbacked {
+            code(BridgeError::InvalidSignature)
+        } else if signers.len() < required as usize {
+            code(BridgeError::InsufficientSignatures)
+        } else {
+            None
+        };
+        let result = check_validator_signatures(&config, &verified_entries, message, &listed);
+        prop_assert_eq!(error_code(result), expected);
+    }
+
+    #[test]
+    fn attestations_count_each_validator_once(
+        validators in 1..=10u8,
+        required in 1..=10u8,
+        entries in proptest::collection::vec(entry(), 0..12),
+    ) {
+        prop_assume!(required <= validators);
+        let config = config(validators, required);
+        let message = &[0xaa; 32];
+
+        let mut signers = HashSet::new();
+        let verified_entries: Vec<VerifiedSignature> = entries
+            .into_iter()
+            .map(|(entry, _)| match entry {
+                Entry::Attested(signer) => {
+                    if signer < validators {
+                        signers.insert(signer);
+                    }
+                    verified(signer, message)
+                }
+                Entry::OtherMessage(signer) => verified(signer, b"other"),
+            })
+            .collect();
+
+        let expected = (signers.len() < required as usize)
+            .then(|| BridgeError::InsufficientSignatures.into());
+        let result = check_attestation(&config, &verified_entries, message, None);
+        prop_assert_eq!(error_code(result), expected);
+    }
+
+    /// Account data is attacker-controlled in the sense that any bytes can be
+    /// passed where an account is expected; decoding must fail, not panic
+    #[test]
+    fn account_decoding_never_panics(
+        data in proptest::collection::vec(any::<u8>(), 0..1024),
+        discriminator in 0..7usize,
+    ) {
+        let discriminators = [
+            BridgeConfig::discriminator(),
+            TokenVault::discriminator(),
+            LockRecord::discriminator(),
+            MessageRecord::discriminator(),
+            ProcessedSequences::discriminator(),
+            InboundMessage::discriminator(),
+            BatchRoot::discriminator(),
+        ];
+        let data = [&discriminators[discriminator][..], &data].concat();
+
+        let _ = BridgeConfig::try_deserialize(&mut &data[..]);
+        let _ = TokenVault::try_deserialize(&mut &data[..]);
+        let _ = LockRecord::try_deserialize(&mut &data[..]);
+        let _ = MessageRecord::try_deserialize(&mut &data[..]);
+        let _ = ProcessedSequences::try_deserialize(&mut &data[..]);
+        let _ = InboundMessage::try_deserialize(&mut &data[..]);
+        let _ = BatchRoot::try_deserialize(&mut &data[..]);
+    }
+}
+
+proptest! {
+    // Every case starts a bank, so fewer cases than the default
+    #![proptest_config(ProptestConfig::with_cases(12))]
+
+    #[test]
+    fn unlocks_never_exceed_locks(ops in proptest::collection::vec(counter_op(), 1..12)) {
+        let runtime =
+            tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        runtime.block_on(async {
+            let mut bridge = Bridge::start().await;
+            let vault = vault_token_account(&bridge.mint);
+            let (mut locked, mut unlocked, mut locks) = (0u64, 0u64, 0u64);
+
+            for (index, op) in ops.into_iter().enumerate() {
+                match op {
+                    CounterOp::Lock(amount) => {
+                        bridge.send_lock(&bridge.lock(amount, index as u64)).await.unwrap();
+                        locked += amount;
+                        locks += 1;
+                    }
+                    CounterOp::Unlock(amount) => {
+                        let unlock = bridge.unlock(index as u8, amount).await;
+                        let result = bridge.send_unlock(&unlock, &[0, 1]).await;
+                        prop_assert_eq!(result.is_ok(), amount <= locked - unlocked);
+                        if result.is_ok() {
+                            unlocked += amount;
+                        }
+                    }
+                    CounterOp::Donate(amount) => {
+                        if locks > 0 {
+                            bridge.mint_to(vault, amount).await;
+                        }
+                    }
+                }
+
+                let config = bridge.config().await;
+                prop_assert_eq!(config.total_locked, locked);
+                prop_assert_eq!(config.total_unlocked, unlocked);
+                prop_assert!(config.total_unlocked <= config.total_locked);
+                prop_assert_eq!(config.message_count, locks);
+                if locks > 0 {
+                    let token_vault: TokenVault =
+                        bridge.fetch(token_vault_address(&bridge.mint)).await;
+                    prop_assert_eq!(token_vault.total_locked, locked - unlocked);
+                    prop_assert!(bridge.balance(vault).await >= token_vault.total_locked);
+                }
+            }
+            Ok(())
+        })?;
+    }
+}
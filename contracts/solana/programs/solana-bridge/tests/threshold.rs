@@ -0,0 +1,298 @@
+//! Threshold mode: unlocks under a single group signature and the group key
+//! as an attestation signer
+
+mod common;
+
+use bridge_types::aggregate_signing_hash;
+use bridge_types::hash::Sha3Keccak;
+use common::*;
+use solana_bridge::error::BridgeError;
+use solana_bridge::instruction::Pause;
+use solana_bridge::state::{
+    AggregateSignature, LockRecord, LockStatus, MessageRecord, SignatureMode,
+};
+use solana_sdk::signature::{Keypair, Signer};
+
+/// Bridge in threshold mode under `group` with two of three participants
+/// required, holding 1 000 of the sender's tokens in the vault
+async fn threshold_bridge(group: &Keypair) -> Bridge {
+    let group_key = group.pubkey();
+    let mut bridge = Bridge::start_with_config(|config| {
+        config.signature_mode = SignatureMode::Threshold;
+        config.group_key = group_key;
+        config.group_threshold = 2;
+        config.group_size = 3;
+        config.group_key_epoch = 1;
+    })
+    .await;
+    bridge.send_lock(&bridge.lock(1_000, 1)).await.unwrap();
+    bridge
+}
+
+#[tokio::test]
+async fn unlock_token_aggregate_releases_tokens() {
+    let group = Keypair::new();
+    let mut bridge = threshold_bridge(&group).await;
+    let unlock = bridge.unlock(1, 600).await;
+
+    let payer = bridge.payer();
+    bridge.send(&unlock.aggregate_instructions(&group, 0b011), &[&payer]).await.unwrap();
+
+    assert_eq!(bridge.balance(unlock.recipient_token_account).await, 600);
+    let record: MessageRecord = bridge.fetch(unlock.message_record()).await;
+    assert_eq!(record.amount, 600);
+    assert!(record.processed);
+    assert_eq!(bridge.config().await.total_unlocked, 600);
+
+    // The same transfer cannot be released twice
+    let unlock = Unlock { message_id: [2; 32], ..unlock };
+    assert_eq!(
+        bridge.send(&unlock.aggregate_instructions(&group, 0b110), &[&payer]).await.unwrap_err(),
+        bridge_error(1, BridgeError::MessageAlreadyProcessed)
+    );
+}
+
+#[tokio::test]
+async fn unlock_token_aggregate_checks_the_signer_bitmap() {
+    let group = Keypair::new();
+    let mut bridge = threshold_bridge(&group).await;
+    let unlock = bridge.unlock(1, 600).await;
+    let payer = bridge.payer();
+
+    // Below the group threshold
+    assert_eq!(
+        bridge.send(&unlock.aggregate_instructions(&group, 0b100), &[&payer]).await.unwrap_err(),
+        bridge_error(1, BridgeError::InsufficientSignatures)
+    );
+
+    // A participant outside the group
+    assert_eq!(
+        bridge.send(&unlock.aggregate_instructions(&group, 0b1001), &[&payer]).await.unwrap_err(),
+        bridge_error(1, BridgeError::InvalidSignerBitmap)
+    );
+
+    // A signature from one participant claimed for the whole group
+    let mut instructions = unlock.aggregate_instructions(&group, 0b001);
+    let signature = group.sign_message(&unlock.aggregate_hash(0b001)).into();
+    instructions[1] =
+        unlock.aggregate_instruction(AggregateSignature { signature, signer_bitmap: 0b111 });
+    assert_eq!(
+        bridge.send(&instructions, &[&payer]).await.unwrap_err(),
+        bridge_error(1, BridgeError::InvalidSignature)
+    );
+
+    // The group signature over the unlock hash alone does not name the signers
+    let signature: [u8; 64] = group.sign_message(&unlock.message_hash()).into();
+    let instructions = [
+        ed25519_instruction(&[(group.pubkey(), signature)], &unlock.message_hash()),
+        unlock.aggregate_instruction(AggregateSignature { signature, signer_bitmap: 0b011 }),
+    ];
+    assert_eq!(
+        bridge.send(&instructions, &[&payer]).await.unwrap_err(),
+        bridge_error(1, BridgeError::InvalidSignature)
+    );
+
+    assert_eq!(bridge.balance(unlock.recipient_token_account).await, 0);
+}
+
+#[tokio::test]
+async fn unlock_token_aggregate_requires_the_group_signature() {
+    let group = Keypair::new();
+    let mut bridge = threshold_bridge(&group).await;
+    let unlock = bridge.unlock(1, 600).await;
+    let payer = bridge.payer();
+
+    // Signed by a validator instead of the group
+    let instructions = unlock.aggregate_instructions(&bridge.validators[0], 0b011);
+    assert_eq!(
+        bridge.send(&instructions, &[&payer]).await.unwrap_err(),
+        bridge_error(1, BridgeError::InvalidSignature)
+    );
+
+    // Signed for 6 tokens, claimed for 600
+    let signed = Unlock { amount: 6, ..unlock.clone() };
+    let mut instructions = signed.aggregate_instructions(&group, 0b011);
+    let signature = group.sign_message(&signed.aggregate_hash(0b011)).into();
+    instructions[1] =
+        unlock.aggregate_instruction(AggregateSignature { signature, signer_bitmap: 0b011 });
+    assert_eq!(
+        bridge.send(&instructions, &[&payer]).await.unwrap_err(),
+        bridge_error(1, BridgeError::InvalidSignature)
+    );
+
+    // Without the precompile instruction
+    let instruction = instructions.remove(1);
+    assert_eq!(
+        bridge.send(&[instruction], &[&payer]).await.unwrap_err(),
+        bridge_error(0, BridgeError::MissingEd25519Instruction)
+    );
+
+    assert_eq!(bridge.balance(unlock.recipient_token_account).await, 0);
+}
+
+#[tokio::test]
+async fn unlock_token_aggregate_requires_threshold_mode() {
+    let group = Keypair::new();
+    let group_key = group.pubkey();
+    let mut bridge = Bridge::start_with_config(|config| {
+        config.group_key = group_key;
+        config.group_threshold = 2;
+        config.group_size = 3;
+    })
+    .await;
+    bridge.send_lock(&bridge.lock(1_000, 1)).await.unwrap();
+    let unlock = bridge.unlock(1, 600).await;
+    let payer = bridge.payer();
+    assert_eq!(
+        bridge.send(&unlock.aggregate_instructions(&group, 0b011), &[&payer]).await.unwrap_err(),
+        bridge_error(1, BridgeError::ThresholdModeDisabled)
+    );
+
+    let mut bridge = Bridge::start_with_config(|config| {
+        config.signature_mode = SignatureMode::Threshold;
+    })
+    .await;
+    bridge.send_lock(&bridge.lock(1_000, 1)).await.unwrap();
+    let unlock = bridge.unlock(1, 600).await;
+    let payer = bridge.payer();
+    assert_eq!(
+        bridge.send(&unlock.aggregate_instructions(&group, 0b011), &[&payer]).await.unwrap_err(),
+        bridge_error(1, BridgeError::GroupKeyNotSet)
+    );
+}
+
+#[tokio::test]
+async fn unlock_token_aggregate_is_rejected_while_paused() {
+    let group = Keypair::new();
+    let mut bridge = threshold_bridge(&group).await;
+    bridge.send_admin(Pause {}).await.unwrap();
+
+    let unlock = bridge.unlock(1, 600).await;
+    let payer = bridge.payer();
+    assert_eq!(
+        bridge.send(&unlock.aggregate_instructions(&group, 0b011), &[&payer]).await.unwrap_err(),
+        bridge_error(1, BridgeError::BridgePaused)
+    );
+}
+
+#[tokio::test]
+async fn threshold_mode_accepts_individual_signatures() {
+    let group = Keypair::new();
+    let mut bridge = threshold_bridge(&group).await;
+    let unlock = bridge.unlock(1, 600).await;
+
+    bridge.send_unlock(&unlock, &[0, 1]).await.unwrap();
+
+    assert_eq!(bridge.balance(unlock.recipient_token_account).await, 600);
+}
+
+#[tokio::test]
+async fn group_key_attests_lock_outcomes_in_threshold_mode() {
+    let group = Keypair::new();
+    let mut bridge = threshold_bridge(&group).await;
+    let lock = bridge.lock(1_000, 1);
+    let record: LockRecord = bridge.fetch(lock.lock_record()).await;
+    let hash = lock_outcome_hash(LockRecord::DELIVERED_DOMAIN, &record, b"tx");
+    let signed_hash = aggregate_signing_hash::<Sha3Keccak>(&hash, 0b011);
+    let signature = group.sign_message(&signed_hash).into();
+
+    let payer = bridge.payer();
+    let instructions = [
+        ed25519_instruction(&[(group.pubkey(), signature)], &signed_hash),
+        lock.acknowledge_aggregate_instruction(
+            "tx",
+            Some(AggregateSignature { signature, signer_bitmap: 0b011 }),
+        ),
+    ];
+    bridge.send(&instructions, &[&payer]).await.unwrap();
+
+    let record: LockRecord = bridge.fetch(lock.lock_record()).await;
+    assert_eq!(record.status, LockStatus::Delivered { destination_tx: "tx".to_string() });
+}
+
+#[tokio::test]
+async fn group_key_attestations_check_the_signer_bitmap() {
+    let group = Keypair::new();
+    let mut bridge = threshold_bridge(&group).await;
+    let lock = bridge.lock(1_000, 1);
+    let record: LockRecord = bridge.fetch(lock.lock_record()).await;
+    let hash = lock_outcome_hash(LockRecord::DELIVERED_DOMAIN, &record, b"tx");
+    let payer = bridge.payer();
+
+    // The group signature over the outcome hash alone, with or without a
+    // bitmap, does not name the signers
+    let signature: [u8; 64] = group.sign_message(&hash).into();
+    let instructions = [
+        ed25519_instruction(&[(group.pubkey(), signature)], &hash),
+        lock.acknowledge_instruction("tx"),
+    ];
+    assert_eq!(
+        bridge.send(&instructions, &[&payer]).await.unwrap_err(),
+        bridge_error(1, BridgeError::InsufficientSignatures)
+    );
+    let instructions = [
+        ed25519_instruction(&[(group.pubkey(), signature)], &hash),
+        lock.acknowledge_aggregate_instruction(
+            "tx",
+            Some(AggregateSignature { signature, signer_bitmap: 0b011 }),
+        ),
+    ];
+    assert_eq!(
+        bridge.send(&instructions, &[&payer]).await.unwrap_err(),
+        bridge_error(1, BridgeError::InvalidSignature)
+    );
+
+    // Below the group threshold, and outside the group
+    for (signer_bitmap, error) in [
+        (0b100, BridgeError::InsufficientSignatures),
+        (0b1001, BridgeError::InvalidSignerBitmap),
+    ] {
+        let signed_hash = aggregate_signing_hash::<Sha3Keccak>(&hash, signer_bitmap);
+        let signature = group.sign_message(&signed_hash).into();
+        let instructions = [
+            ed25519_instruction(&[(group.pubkey(), signature)], &signed_hash),
+            lock.acknowledge_aggregate_instruction(
+                "tx",
+                Some(AggregateSignature { signature, signer_bitmap }),
+            ),
+        ];
+        assert_eq!(
+            bridge.send(&instructions, &[&payer]).await.unwrap_err(),
+            bridge_error(1, error)
+        );
+    }
+
+    let record: LockRecord = bridge.fetch(lock.lock_record()).await;
+    assert_eq!(record.status, LockStatus::Pending);
+}
+
+#[tokio::test]
+async fn group_key_attestations_require_threshold_mode() {
+    let group = Keypair::new();
+    let group_key = group.pubkey();
+    let mut bridge = Bridge::start_with_config(|config| {
+        config.group_key = group_key;
+        config.group_threshold = 2;
+        config.group_size = 3;
+    })
+    .await;
+    let lock = bridge.lock(1_000, 1);
+    bridge.send_lock(&lock).await.unwrap();
+    let record: LockRecord = bridge.fetch(lock.lock_record()).await;
+    let hash = lock_outcome_hash(LockRecord::DELIVERED_DOMAIN, &record, b"tx");
+    let signed_hash = aggregate_signing_hash::<Sha3Keccak>(&hash, 0b011);
+    let signature = group.sign_message(&signed_hash).into();
+
+    let payer = bridge.payer();
+    let instructions = [
+        ed25519_instruction(&[(group.pubkey(), signature)], &signed_hash),
+        lock.acknowledge_aggregate_instruction(
+            "tx",
+            Some(AggregateSignature { signature, signer_bitmap: 0b011 }),
+        ),
+    ];
+    assert_eq!(
+        bridge.send(&instructions, &[&payer]).await.unwrap_err(),
+        bridge_error(1, BridgeError::ThresholdModeDisabled)
+    );
+}
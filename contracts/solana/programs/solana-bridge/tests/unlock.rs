@@ -0,0 +1,326 @@
+//! Inbound transfers: validator-attested unlocks checked against the Ed25519
+//! precompile, replay protection and vault accounting
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use common::*;
+use solana_bridge::error::BridgeError;
+use solana_bridge::instruction::Pause;
+use solana_bridge::state::{MessageRecord, ProcessedSequences, TokenVault};
+use solana_program::sysvar;
+use solana_sdk::signature::{Keypair, Signer};
+
+/// Bridge holding `amount` of the sender's tokens in the vault
+async fn funded_bridge(amount: u64) -> Bridge {
+    let mut bridge = Bridge::start().await;
+    bridge.send_lock(&bridge.lock(amount, 1)).await.unwrap();
+    bridge
+}
+
+#[tokio::test]
+async fn unlock_token_releases_tokens() {
+    let mut bridge = funded_bridge(1_000).await;
+    let unlock = bridge.unlock(1, 600).await;
+
+    bridge.send_unlock(&unlock, &[0, 1]).await.unwrap();
+
+    assert_eq!(bridge.balance(unlock.recipient_token_account).await, 600);
+    assert_eq!(bridge.balance(vault_token_account(&bridge.mint)).await, 400);
+
+    let record: MessageRecord = bridge.fetch(unlock.message_record()).await;
+    assert_eq!(record.message_id, unlock.message_id);
+    assert_eq!(record.source_chain, "near");
+    assert_eq!(record.sender, "alice.near");
+    assert_eq!(record.recipient, unlock.recipient);
+    assert_eq!(record.token_mint, bridge.mint);
+    assert_eq!(record.amount, 600);
+    assert!(record.processed);
+    assert_eq!(record.payer, unlock.payer);
+
+    let vault: TokenVault = bridge.fetch(token_vault_address(&bridge.mint)).await;
+    assert_eq!(vault.total_locked, 400);
+    assert_eq!(bridge.config().await.total_unlocked, 600);
+}
+
+#[tokio::test]
+async fn unlock_token_accepts_any_quorum() {
+    let mut bridge = funded_bridge(1_000).await;
+
+    for (message_id, signers) in [(1, &[0, 2][..]), (2, &[1, 2]), (3, &[2, 0, 1])] {
+        let unlock = bridge.unlock(message_id, 100).await;
+        bridge.send_unlock(&unlock, signers).await.unwrap();
+        assert_eq!(bridge.balance(unlock.recipient_token_account).await, 100);
+    }
+    assert_eq!(bridge.config().await.total_unlocked, 300);
+}
+
+#[tokio::test]
+async fn unlock_token_rejects_too_few_signatures() {
+    let mut bridge = funded_bridge(1_000).await;
+    let unlock = bridge.unlock(1, 600).await;
+
+    assert_eq!(
+        bridge.send_unlock(&unlock, &[0]).await.unwrap_err(),
+        bridge_error(1, BridgeError::InsufficientSignatures)
+    );
+    assert!(!bridge.exists(unlock.message_record()).await);
+}
+
+#[tokio::test]
+async fn unlock_token_counts_each_validator_once() {
+    let mut bridge = funded_bridge(1_000).await;
+    let unlock = bridge.unlock(1, 600).await;
+
+    assert_eq!(
+        bridge.send_unlock(&unlock, &[0, 0]).await.unwrap_err(),
+        bridge_error(1, BridgeError::InsufficientSignatures)
+    );
+    assert_eq!(bridge.balance(unlock.recipient_token_account).await, 0);
+}
+
+#[tokio::test]
+async fn unlock_token_rejects_non_validator_signatures() {
+    let mut bridge = funded_bridge(1_000).await;
+    let unlock = bridge.unlock(1, 600).await;
+    let outsider = Keypair::new();
+
+    // The precompile verifies the outsider's signature, but it does not count
+    let instructions = unlock.instructions(&[&bridge.validators[0], &outsider]);
+    let payer = bridge.payer();
+    assert_eq!(
+        bridge.send(&instructions, &[&payer]).await.unwrap_err(),
+        bridge_error(1, BridgeError::InsufficientSignatures)
+    );
+    assert_eq!(bridge.balance(unlock.recipient_token_account).await, 0);
+}
+
+#[tokio::test]
+async fn unlock_token_rejects_signatures_over_other_terms() {
+    let mut bridge = funded_bridge(1_000).await;
+    let unlock = bridge.unlock(1, 600).await;
+
+    // Signed for 6 tokens, claimed for 600
+    let signed = Unlock { amount: 6, ..unlock.clone() };
+    let mut instructions = signed.instructions(&[&bridge.validators[0], &bridge.validators[1]]);
+    let signature = |index: usize| -> [u8; 64] {
+        bridge.validators[index].sign_message(&signed.message_hash()).into()
+    };
+    instructions[1] = unlock.instruction(&[signature(0), signature(1)]);
+    let payer = bridge.payer();
+    assert_eq!(
+        bridge.send(&instructions, &[&payer]).await.unwrap_err(),
+        bridge_error(1, BridgeError::InvalidSignature)
+    );
+
+    // Signed for another sequence
+    let signed = Unlock { sequence: unlock.sequence + 1, ..unlock.clone() };
+    let mut instructions = signed.instructions(&[&bridge.validators[0], &bridge.validators[1]]);
+    let signature = |index: usize| -> [u8; 64] {
+        bridge.validators[index].sign_message(&signed.message_hash()).into()
+    };
+    instructions[1] = unlock.instruction(&[signature(0), signature(1)]);
+    assert_eq!(
+        bridge.send(&instructions, &[&payer]).await.unwrap_err(),
+        bridge_error(1, BridgeError::InvalidSignature)
+    );
+
+    // Signed for another recipient
+    let other = bridge.unlock(1, 600).await;
+    let mut instructions = other.instructions(&[&bridge.validators[0], &bridge.validators[1]]);
+    let signatures: Vec<[u8; 64]> = [0, 1]
+        .iter()
+        .map(|&index| bridge.validators[index].sign_message(&other.message_hash()).into())
+        .collect();
+    instructions[1] = unlock.instruction(&signatures);
+    assert_eq!(
+        bridge.send(&instructions, &[&payer]).await.unwrap_err(),
+        bridge_error(1, BridgeError::InvalidSignature)
+    );
+
+    assert_eq!(bridge.balance(unlock.recipient_token_account).await, 0);
+    assert_eq!(bridge.balance(other.recipient_token_account).await, 0);
+}
+
+#[tokio::test]
+async fn unlock_token_requires_the_ed25519_instruction() {
+    let mut bridge = funded_bridge(1_000).await;
+    let unlock = bridge.unlock(1, 600).await;
+    let hash = unlock.message_hash();
+    let signatures: Vec<[u8; 64]> = bridge.validators[..2]
+        .iter()
+        .map(|validator| validator.sign_message(&hash).into())
+        .collect();
+
+    let payer = bridge.payer();
+    assert_eq!(
+        bridge.send(&[unlock.instruction(&signatures)], &[&payer]).await.unwrap_err(),
+        bridge_error(0, BridgeError::MissingEd25519Instruction)
+    );
+}
+
+#[tokio::test]
+async fn unlock_token_rejects_a_spoofed_instructions_sysvar() {
+    let mut bridge = funded_bridge(1_000).await;
+    let unlock = bridge.unlock(1, 600).await;
+    let mut instructions = unlock.instructions(&[&bridge.validators[0], &bridge.validators[1]]);
+
+    let sysvar_meta = instructions[1]
+        .accounts
+        .iter_mut()
+        .find(|meta| meta.pubkey == sysvar::instructions::ID)
+        .unwrap();
+    sysvar_meta.pubkey = Keypair::new().pubkey();
+    let payer = bridge.payer();
+    assert_eq!(
+        bridge.send(&instructions, &[&payer]).await.unwrap_err(),
+        anchor_error(1, ErrorCode::ConstraintAddress)
+    );
+}
+
+#[tokio::test]
+async fn unlock_token_pays_only_the_attested_recipient() {
+    let mut bridge = funded_bridge(1_000).await;
+    let mut unlock = bridge.unlock(1, 600).await;
+
+    // The sender's token account with the attested recipient
+    unlock.recipient_token_account = bridge.sender_token_account;
+    assert_eq!(
+        bridge.send_unlock(&unlock, &[0, 1]).await.unwrap_err(),
+        anchor_error(1, ErrorCode::ConstraintRaw)
+    );
+    assert_eq!(bridge.balance(bridge.sender_token_account).await, SENDER_TOKENS - 1_000);
+}
+
+#[tokio::test]
+async fn unlock_token_validates_the_transfer() {
+    let mut bridge = funded_bridge(1_000).await;
+
+    let unlock = bridge.unlock(1, 0).await;
+    assert_eq!(
+        bridge.send_unlock(&unlock, &[0, 1]).await.unwrap_err(),
+        bridge_error(1, BridgeError::InvalidAmount)
+    );
+
+    let mut unlock = bridge.unlock(1, 600).await;
+    unlock.source_chain = "c".repeat(MessageRecord::MAX_CHAIN_LEN + 1);
+    assert_eq!(
+        bridge.send_unlock(&unlock, &[0, 1]).await.unwrap_err(),
+        bridge_error(1, BridgeError::SourceChainTooLong)
+    );
+
+    let mut unlock = bridge.unlock(1, 600).await;
+    unlock.sender = "a".repeat(MessageRecord::MAX_SENDER_LEN + 1);
+    assert_eq!(
+        bridge.send_unlock(&unlock, &[0, 1]).await.unwrap_err(),
+        bridge_error(1, BridgeError::SenderAddressTooLong)
+    );
+}
+
+#[tokio::test]
+async fn unlock_token_is_rejected_while_paused() {
+    let mut bridge = funded_bridge(1_000).await;
+    bridge.send_admin(Pause {}).await.unwrap();
+
+    let unlock = bridge.unlock(1, 600).await;
+    assert_eq!(
+        bridge.send_unlock(&unlock, &[0, 1]).await.unwrap_err(),
+        bridge_error(1, BridgeError::BridgePaused)
+    );
+    assert!(!bridge.exists(unlock.message_record()).await);
+}
+
+#[tokio::test]
+async fn unlock_token_rejects_replay() {
+    let mut bridge = funded_bridge(1_000).await;
+    let unlock = bridge.unlock(1, 300).await;
+    bridge.send_unlock(&unlock, &[0, 1]).await.unwrap();
+
+    // The message record already exists
+    assert_eq!(bridge.send_unlock(&unlock, &[0, 1]).await.unwrap_err(), already_in_use(1));
+    assert_eq!(bridge.send_unlock(&unlock, &[1, 2]).await.unwrap_err(), already_in_use(1));
+
+    // Closing the record leaves the sequence marked, which is checked instead
+    let payer = bridge.payer();
+    bridge.send(&[unlock.close_instruction()], &[&payer]).await.unwrap();
+    assert!(!bridge.exists(unlock.message_record()).await);
+    assert_eq!(
+        bridge.send_unlock(&unlock, &[0, 1]).await.unwrap_err(),
+        bridge_error(1, BridgeError::MessageAlreadyProcessed)
+    );
+
+    assert_eq!(bridge.balance(unlock.recipient_token_account).await, 300);
+    assert_eq!(bridge.config().await.total_unlocked, 300);
+}
+
+#[tokio::test]
+async fn unlock_token_records_sequences_per_source_chain() {
+    let mut bridge = funded_bridge(1_000).await;
+    let unlock = bridge.unlock(1, 100).await;
+    bridge.send_unlock(&unlock, &[0, 1]).await.unwrap();
+
+    let window: ProcessedSequences = bridge.fetch(unlock.processed_sequences()).await;
+    assert_eq!(window.window, 0);
+    assert!(window.contains(unlock.sequence));
+    assert!(!window.contains(unlock.sequence + 1));
+
+    // Another message claiming a spent sequence is rejected
+    let mut reused = bridge.unlock(2, 100).await;
+    reused.sequence = unlock.sequence;
+    assert_eq!(
+        bridge.send_unlock(&reused, &[0, 1]).await.unwrap_err(),
+        bridge_error(1, BridgeError::MessageAlreadyProcessed)
+    );
+
+    // Sequences are counted per source chain
+    let mut other_chain = bridge.unlock(3, 100).await;
+    other_chain.source_chain = "ethereum".to_string();
+    other_chain.sequence = unlock.sequence;
+    bridge.send_unlock(&other_chain, &[0, 1]).await.unwrap();
+    assert_ne!(other_chain.processed_sequences(), unlock.processed_sequences());
+
+    // A sequence past the window goes to the next account
+    let mut later = bridge.unlock(4, 100).await;
+    later.sequence = unlock.sequence + ProcessedSequences::WINDOW_BITS;
+    bridge.send_unlock(&later, &[0, 1]).await.unwrap();
+    let window: ProcessedSequences = bridge.fetch(later.processed_sequences()).await;
+    assert_eq!(window.window, 1);
+    assert!(window.contains(later.sequence));
+    let first: ProcessedSequences = bridge.fetch(unlock.processed_sequences()).await;
+    assert_eq!(first.bits, window.bits);
+
+    assert_eq!(bridge.config().await.total_unlocked, 300);
+}
+
+#[tokio::test]
+async fn unlock_token_reports_counter_overflow() {
+    let mut bridge =
+        Bridge::start_with_config(|config| config.total_unlocked = u64::MAX - 10).await;
+    bridge.send_lock(&bridge.lock(1_000, 1)).await.unwrap();
+    let unlock = bridge.unlock(1, 11).await;
+    assert_eq!(
+        bridge.send_unlock(&unlock, &[0, 1]).await.unwrap_err(),
+        bridge_error(1, BridgeError::ArithmeticOverflow)
+    );
+    let unlock = bridge.unlock(2, 10).await;
+    bridge.send_unlock(&unlock, &[0, 1]).await.unwrap();
+}
+
+#[tokio::test]
+async fn unlock_token_cannot_release_more_than_the_vault_recorded() {
+    let mut bridge = funded_bridge(100).await;
+    // Tokens sent to the vault without a lock are not backed by the vault record
+    bridge.mint_to(vault_token_account(&bridge.mint), 1_000).await;
+
+    let unlock = bridge.unlock(1, 101).await;
+    assert_eq!(
+        bridge.send_unlock(&unlock, &[0, 1]).await.unwrap_err(),
+        bridge_error(1, BridgeError::ArithmeticOverflow)
+    );
+    assert_eq!(bridge.balance(vault_token_account(&bridge.mint)).await, 1_100);
+
+    let unlock = bridge.unlock(2, 100).await;
+    bridge.send_unlock(&unlock, &[0, 1]).await.unwrap();
+    let vault: TokenVault = bridge.fetch(token_vault_address(&bridge.mint)).await;
+    assert_eq!(vault.total_locked, 0);
+}
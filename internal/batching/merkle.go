@@ -2,8 +2,10 @@ package batching
 
 import (
 	"crypto/sha256"
+	"encoding/binary"
 	"encoding/hex"
 	"fmt"
+	"io"
 
 	"github.com/EmekaIwuagwu/articium-hub/internal/types"
 )
@@ -181,17 +183,29 @@ func (t *MerkleTree) GetRoot() string {
 func hashMessage(msg *types.CrossChainMessage) string {
 	hasher := sha256.New()
 
-	// Hash all message fields
-	hasher.Write([]byte(msg.ID))
-	hasher.Write([]byte(msg.SourceChain.Name))
-	hasher.Write([]byte(msg.DestinationChain.Name))
-	hasher.Write([]byte(msg.Sender.Raw))
-	hasher.Write([]byte(msg.Recipient.Raw))
-	hasher.Write(msg.Payload)
+	// Hash all message fields. The ID and chains are written in the form the
+	// contracts know them by, whatever encoding the listener or config used,
+	// so a claim can rebuild the leaf from its on-chain arguments. Every
+	// variable-length field is length-prefixed, as push_prefixed does in
+	// bridge-types, so no bytes can move between neighbouring fields.
+	id := msg.OnChainID()
+	hasher.Write([]byte(hex.EncodeToString(id[:])))
+	writePrefixed(hasher, []byte(msg.SourceChain.BridgeName()))
+	hasher.Write(binary.LittleEndian.AppendUint64(nil, msg.Nonce))
+	writePrefixed(hasher, []byte(msg.DestinationChain.BridgeName()))
+	writePrefixed(hasher, []byte(msg.Sender.Raw))
+	writePrefixed(hasher, []byte(msg.Recipient.Raw))
+	writePrefixed(hasher, msg.Payload)
 
 	return hex.EncodeToString(hasher.Sum(nil))
 }
 
+// writePrefixed writes u32_le(len(field)) || field
+func writePrefixed(w io.Writer, field []byte) {
+	w.Write(binary.LittleEndian.AppendUint32(nil, uint32(len(field))))
+	w.Write(field)
+}
+
 func hashPair(left, right string) string {
 	hasher := sha256.New()
 	hasher.Write([]byte(left))
@@ -0,0 +1,55 @@
+package batching
+
+import (
+	"encoding/json"
+	"strings"
+	"testing"
+
+	"github.com/EmekaIwuagwu/articium-hub/internal/types"
+)
+
+// leafVectorMessage is the shared batch leaf vector, also checked by
+// contracts/near/src/merkle.rs and the Solana program's tests/batch.rs
+func leafVectorMessage() *types.CrossChainMessage {
+	return &types.CrossChainMessage{
+		ID:               strings.Repeat("ab", 32),
+		Nonce:            7,
+		SourceChain:      types.ChainInfo{Name: "ethereum", Type: types.ChainTypeEVM},
+		DestinationChain: types.ChainInfo{Name: "near-testnet", Type: types.ChainTypeNEAR},
+		Sender:           types.Address{Raw: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb"},
+		Recipient:        types.Address{Raw: "alice.near"},
+		Payload:          json.RawMessage(`{"token_address":{"raw":"usdc.near"},"amount":"1000000"}`),
+	}
+}
+
+func TestHashMessage_SharedVector(t *testing.T) {
+	expected := "d8d73f0976fa70a6ab263270bf019c4ef4218e3885763800885bbcaf17bb4347"
+	if got := hashMessage(leafVectorMessage()); got != expected {
+		t.Errorf("Leaf hash = %s, want %s", got, expected)
+	}
+}
+
+func TestVerifyProof_RejectsBytesMovedBetweenSenderAndRecipient(t *testing.T) {
+	other := leafVectorMessage()
+	other.ID = strings.Repeat("cd", 32)
+	tree, err := BuildMerkleTree([]*types.CrossChainMessage{leafVectorMessage(), other})
+	if err != nil {
+		t.Fatalf("Failed to build tree: %v", err)
+	}
+	proof, err := tree.GetProof(0)
+	if err != nil {
+		t.Fatalf("Failed to get proof: %v", err)
+	}
+
+	if !VerifyProof(proof, hashMessage(leafVectorMessage())) {
+		t.Fatal("Proof should verify for the original leaf")
+	}
+
+	// The same bytes split differently between sender and recipient
+	shifted := leafVectorMessage()
+	shifted.Sender.Raw += "a"
+	shifted.Recipient.Raw = "lice.near"
+	if VerifyProof(proof, hashMessage(shifted)) {
+		t.Error("Proof should not verify once bytes move from the recipient to the sender")
+	}
+}
@@ -331,6 +331,12 @@ func (l *Listener) parseTokenLockedEvent(data map[string]interface{}) (*types.Cr
 		return nil, fmt.Errorf("missing or invalid destination_chain")
 	}
 
+	// Sequence of the lock on the bridge, by which the claim is recorded
+	nonce, ok := data["nonce"].(float64)
+	if !ok {
+		return nil, fmt.Errorf("missing or invalid nonce")
+	}
+
 	// Build payload
 	tokenAddr, err := types.NewAddress(token, types.ChainTypeNEAR)
 	if err != nil {
@@ -373,7 +379,7 @@ func (l *Listener) parseTokenLockedEvent(data map[string]interface{}) (*types.Cr
 		Sender:    senderAddr,
 		Recipient: recipientAddr,
 		Payload:   payloadBytes,
-		Nonce:     0, // Would extract from event
+		Nonce:     uint64(nonce),
 		Status:    types.MessageStatusPending,
 		CreatedAt: time.Now(),
 		UpdatedAt: time.Now(),
@@ -430,9 +436,13 @@ func (l *Listener) parseNFTLockedEvent(data map[string]interface{}) (*types.Cros
 		return nil, fmt.Errorf("invalid NFT contract address: %w", err)
 	}
 
+	// Metadata URI, empty when the token has none
+	tokenURI, _ := data["token_uri"].(string)
+
 	payload := types.NFTTransferPayload{
 		ContractAddress: contractAddr,
 		TokenID:         tokenID,
+		TokenURI:        tokenURI,
 		Standard:        "NEP171",
 	}
 
@@ -35,6 +35,22 @@ type ChainInfo struct {
 	Environment Environment `json:"environment"`
 }
 
+// BridgeName returns the chain name the bridge contracts use in message IDs,
+// attestations and batch leaves ("near", "solana", ...). NEAR and Solana
+// networks map to their contract name whatever they are called in the
+// config; other chains, such as destinations read from a lock event, already
+// carry it.
+func (c ChainInfo) BridgeName() string {
+	switch c.Type {
+	case ChainTypeNEAR:
+		return "near"
+	case ChainTypeSolana:
+		return "solana"
+	default:
+		return c.Name
+	}
+}
+
 // TransactionStatus represents universal transaction status
 type TransactionStatus struct {
 	Hash        string    `json:"hash"`
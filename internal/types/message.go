@@ -1,6 +1,9 @@
 package types
 
 import (
+	"crypto/sha256"
+	"encoding/base64"
+	"encoding/hex"
 	"encoding/json"
 	"time"
 
@@ -69,6 +72,23 @@ type CrossChainMessage struct {
 	ProcessedAt *time.Time `json:"processed_at,omitempty" db:"processed_at"`
 }
 
+// OnChainID returns the 32-byte ID the bridge contracts know the message by.
+// Listener IDs already are one, in lowercase hex (NEAR events) or standard
+// base64 (Solana events). Any other ID, such as the UUID of a message
+// submitted through the API, maps to the SHA-256 of the ID string.
+func (m *CrossChainMessage) OnChainID() [32]byte {
+	var id [32]byte
+	if decoded, err := hex.DecodeString(m.ID); err == nil && len(decoded) == len(id) {
+		copy(id[:], decoded)
+		return id
+	}
+	if decoded, err := base64.StdEncoding.DecodeString(m.ID); err == nil && len(decoded) == len(id) {
+		copy(id[:], decoded)
+		return id
+	}
+	return sha256.Sum256([]byte(m.ID))
+}
+
 // NewCrossChainMessage creates a new cross-chain message
 func NewCrossChainMessage(
 	msgType MessageType,